@@ -7,6 +7,8 @@
 // 2. Full Matching - End-to-end order matching through the engine
 // 3. Algorithm Comparison - Different matching algorithms
 // 4. Order Book Operations - Snapshot and other operations
+// 5. Batch Submission - submit_orders vs. a submit_order loop
+// 6. Trade Buffers - match_order vs. match_order_into a reused Vec
 //
 // Architecture Notes:
 // - x86_64: Uses AVX2 (256-bit, 4x i64 parallel)
@@ -14,10 +16,11 @@
 // - Other: Scalar fallback
 // ============================================================================
 
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use matching_engine::numeric::{Price, Quantity};
 use matching_engine::prelude::*;
 use matching_engine::simd::{create_simd_matcher, ScalarMatcher, SimdMatcher};
+use std::hint::black_box;
 use std::sync::Arc;
 
 // ============================================================================
@@ -50,6 +53,15 @@ fn benchmark_simd_price_matcher(c: &mut Criterion) {
             },
         );
 
+        // SIMD count (no allocation)
+        group.bench_with_input(
+            BenchmarkId::new("SIMD-count", num_prices),
+            &(&prices, buy_price),
+            |b, (prices, buy_price)| {
+                b.iter(|| black_box(simd_matcher.count_crossing_buy_prices(*buy_price, prices)));
+            },
+        );
+
         // Scalar comparison
         let scalar_matcher = ScalarMatcher;
         group.bench_with_input(
@@ -215,6 +227,94 @@ fn benchmark_simd_no_match(c: &mut Criterion) {
     group.finish();
 }
 
+// Crossover between the scalar path and the SIMD pre-check by book depth;
+// the pre-check only pays off once the book is deep enough
+// (see `PriceTimePriority::with_simd_min_levels`)
+fn benchmark_simd_threshold(c: &mut Criterion) {
+    let mut group = c.benchmark_group("simd_threshold_crossover");
+
+    for levels in [2, 4, 8, 16, 32, 64] {
+        for use_simd in [false, true] {
+            let label = if use_simd { "SIMD" } else { "Scalar" };
+            group.bench_with_input(BenchmarkId::new(label, levels), &levels, |b, &levels| {
+                // Threshold 0 forces the pre-check at every depth
+                let algo = PriceTimePriority::new(use_simd).with_simd_min_levels(0);
+                let side = OrderBookSide::new(Side::Sell);
+                for i in 0..levels {
+                    side.add_order(Arc::new(Order::new(
+                        format!("user{}", i),
+                        "BTC-USD".to_string(),
+                        Side::Sell,
+                        OrderType::Limit,
+                        Some(Price::from_integer(60000 + i).unwrap()),
+                        Quantity::from_integer(1).unwrap(),
+                        TimeInForce::GoodTillCancel,
+                    )));
+                }
+
+                b.iter(|| {
+                    // Below every ask: measures only the crossing check
+                    let buy = Arc::new(Order::new(
+                        "benchmark_user".to_string(),
+                        "BTC-USD".to_string(),
+                        Side::Buy,
+                        OrderType::Limit,
+                        Some(Price::from_integer(50000).unwrap()),
+                        Quantity::from_integer(1).unwrap(),
+                        TimeInForce::ImmediateOrCancel,
+                    ));
+                    black_box(algo.match_order(buy, &side));
+                });
+            });
+        }
+    }
+
+    group.finish();
+}
+
+// Gathering level prices for a SIMD scan: a fresh Vec per call versus
+// `best_prices_raw` into a buffer whose capacity is reused
+fn benchmark_price_gather(c: &mut Criterion) {
+    const LEVELS: usize = 1000;
+    let mut group = c.benchmark_group("price_gather_1000_levels");
+
+    let side = OrderBookSide::new(Side::Sell);
+    for i in 0..LEVELS as i64 {
+        side.add_order(Arc::new(Order::new(
+            format!("user{}", i),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000 + i).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+    }
+
+    group.bench_function("allocating", |b| {
+        b.iter(|| {
+            // Same keys as `best_prices_raw` (asks are best-first in key order)
+            let prices: Vec<i64> = side
+                .levels
+                .iter()
+                .map(|entry| *entry.key())
+                .take(LEVELS)
+                .collect();
+            black_box(prices);
+        });
+    });
+
+    group.bench_function("reused_buffer", |b| {
+        let mut buffer = vec![0i64; LEVELS];
+        b.iter(|| {
+            let len = side.best_prices_raw(&mut buffer);
+            black_box(&buffer[..len]);
+        });
+    });
+
+    group.finish();
+}
+
 // ============================================================================
 // Algorithm Comparison Benchmarks
 // ============================================================================
@@ -323,14 +423,137 @@ fn benchmark_order_submission_no_match(c: &mut Criterion) {
     });
 }
 
+fn benchmark_batch_submission(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch_submission");
+
+    let make_orders = || -> Vec<Arc<Order>> {
+        (0..100)
+            .map(|i| {
+                let side = if i % 2 == 0 { Side::Buy } else { Side::Sell };
+                Arc::new(Order::new(
+                    "benchmark_user".to_string(),
+                    "BTC-USD".to_string(),
+                    side,
+                    OrderType::Limit,
+                    Some(Price::from_integer(50000).unwrap()),
+                    Quantity::from_integer(1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ))
+            })
+            .collect()
+    };
+
+    group.bench_function("loop", |b| {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(true)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        b.iter_batched(
+            make_orders,
+            |orders| {
+                for order in orders {
+                    black_box(engine.submit_order(order));
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.bench_function("batch", |b| {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(true)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        b.iter_batched(
+            make_orders,
+            |orders| black_box(engine.submit_orders(orders)),
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
+// ============================================================================
+// Trade Buffer Benchmarks
+// Allocating match_order vs. match_order_into a buffer reused across calls
+// ============================================================================
+
+fn benchmark_match_order_into(c: &mut Criterion) {
+    let mut group = c.benchmark_group("match_order_buffer");
+    let algorithm = PriceTimePriority::new(false);
+
+    // 20 one-lot asks; the buy sweeps the best 10
+    let setup = || {
+        let asks = OrderBookSide::new(Side::Sell);
+        for i in 0..20 {
+            asks.add_order(Arc::new(Order::new(
+                format!("user{}", i),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(50000 + i).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+        let buy = Arc::new(Order::new(
+            "benchmark_user".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50009).unwrap()),
+            Quantity::from_integer(10).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        (asks, buy)
+    };
+
+    group.bench_function("match_order", |b| {
+        b.iter_batched(
+            setup,
+            // Returning the book keeps its teardown out of the measurement
+            |(asks, buy)| {
+                let trades = algorithm.match_order(buy, &asks);
+                (asks, black_box(trades))
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    let mut trades = Vec::new();
+    group.bench_function("match_order_into", |b| {
+        b.iter_batched(
+            setup,
+            |(asks, buy)| {
+                trades.clear();
+                algorithm.match_order_into(buy, &asks, &mut trades);
+                black_box(trades.len());
+                asks
+            },
+            BatchSize::SmallInput,
+        );
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_simd_price_matcher,
     benchmark_price_time_matching,
     benchmark_price_time_simd,
     benchmark_simd_no_match,
+    benchmark_simd_threshold,
+    benchmark_price_gather,
     benchmark_pro_rata_matching,
     benchmark_order_book_snapshot,
     benchmark_order_submission_no_match,
+    benchmark_batch_submission,
+    benchmark_match_order_into,
 );
 criterion_main!(benches);
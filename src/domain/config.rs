@@ -3,8 +3,11 @@
 // Comprehensive configuration for order book type and matching behavior
 // ============================================================================
 
+use super::Trade;
 use crate::numeric::{Price, Quantity};
+use rust_decimal::Decimal;
 use std::collections::HashSet;
+use std::time::Duration;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -90,6 +93,180 @@ pub enum MatchingAlgorithmType {
         /// Minimum size for pro-rata participation (only for large orders)
         minimum_quantity: Quantity,
     },
+
+    /// Weighted Pro-Rata
+    /// Allocation proportional to `remaining ^ exponent`
+    /// Use case: Dampening the advantage of very large orders
+    WeightedProRata {
+        /// Power applied to each order's size (1.0 = plain pro-rata)
+        exponent: Decimal,
+        /// Minimum order size to participate in the allocation
+        minimum_quantity: Quantity,
+    },
+
+    /// Midpoint Match
+    /// Contra orders cross at an externally supplied reference mid, not at
+    /// their posted prices (limits only bound eligibility)
+    /// Use case: Dark pools pegged to a lit market
+    MidpointMatch,
+}
+
+// ============================================================================
+// Pro-Rata Remainder Policy
+// ============================================================================
+
+/// How pro-rata algorithms hand out the units left over after truncating
+/// each order's proportional share
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RemainderPolicy {
+    /// Entire leftover goes to the first order in queue
+    #[default]
+    FirstOrder,
+    /// Entire leftover goes to the largest order (earliest on ties)
+    LargestOrder,
+    /// One unit each to the orders with the largest truncated fraction
+    LargestFraction,
+    /// One unit each, rotating the starting order between allocations
+    RoundRobin,
+}
+
+// ============================================================================
+// Trade Price Rule
+// ============================================================================
+
+/// Price printed on a trade from continuous matching
+///
+/// The rule adjusts the price the matching algorithm printed, which is the
+/// maker's price for every algorithm except `MidpointMatch`. Auction trades
+/// always print at the clearing price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TradePriceRule {
+    /// The resting order's price
+    #[default]
+    MakerPrice,
+    /// Halfway between the maker's price and the taker's limit, truncated to
+    /// the price precision. Takers without a limit trade at the maker's price
+    Midpoint,
+    /// The price most favourable to the taker. A maker only trades when its
+    /// price is at or better than the taker's limit, so this is always the
+    /// maker's price; it names the intent for venues that advertise price
+    /// improvement rather than printing differently from `MakerPrice`
+    TakerBenefit,
+}
+
+impl TradePriceRule {
+    /// Price a trade between a maker at `maker_price` and a taker limited to
+    /// `taker_limit` prints at
+    pub fn trade_price(self, maker_price: Price, taker_limit: Option<Price>) -> Price {
+        match (self, taker_limit) {
+            (Self::Midpoint, Some(limit)) => {
+                let sum = maker_price.raw_value() as i128 + limit.raw_value() as i128;
+                Price::from_raw((sum / 2) as i64)
+            },
+            _ => maker_price,
+        }
+    }
+}
+
+// ============================================================================
+// Price Band
+// ============================================================================
+
+/// Collar around a reference price outside of which limit orders are rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PriceBand {
+    /// Reference price the band is centred on
+    pub reference: Price,
+    /// Maximum relative deviation from the reference (0.05 = 5%)
+    pub max_deviation_pct: Decimal,
+}
+
+impl PriceBand {
+    pub fn new(reference: Price, max_deviation_pct: Decimal) -> Self {
+        Self {
+            reference,
+            max_deviation_pct,
+        }
+    }
+
+    /// Whether `price` lies within the band (bounds inclusive)
+    pub fn contains(&self, price: Price) -> bool {
+        let reference = self.reference.to_decimal();
+        if reference.is_zero() {
+            return price == self.reference;
+        }
+
+        let deviation = (price.to_decimal() - reference).abs() / reference.abs();
+        deviation <= self.max_deviation_pct
+    }
+}
+
+/// Halts trading when the price would move too far within a short window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CircuitBreaker {
+    /// Maximum relative move from any trade inside the window (0.10 = 10%)
+    pub move_pct: Decimal,
+    /// How far back trade prices are remembered
+    pub window: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(move_pct: Decimal, window: Duration) -> Self {
+        Self { move_pct, window }
+    }
+
+    /// Whether trading at `price` moves more than `move_pct` away from `reference`
+    pub fn is_breached(&self, reference: Price, price: Price) -> bool {
+        !PriceBand::new(reference, self.move_pct).contains(price)
+    }
+}
+
+/// Publishes a book checksum on the event stream every few book changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ChecksumSchedule {
+    /// Levels per side covered by each checksum
+    pub depth: usize,
+    /// Number of book changes between checksums
+    pub interval: u64,
+}
+
+impl ChecksumSchedule {
+    pub fn new(depth: usize, interval: u64) -> Self {
+        Self { depth, interval }
+    }
+}
+
+/// Maker/taker fee rates in basis points of trade notional
+///
+/// Negative rates are allowed and express rebates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FeeSchedule {
+    /// Fee charged to the passive (resting) order
+    pub maker_bps: Decimal,
+    /// Fee charged to the aggressive (incoming) order
+    pub taker_bps: Decimal,
+}
+
+impl FeeSchedule {
+    pub fn new(maker_bps: Decimal, taker_bps: Decimal) -> Self {
+        Self {
+            maker_bps,
+            taker_bps,
+        }
+    }
+
+    /// Fill in `maker_fee` and `taker_fee` from the trade's notional
+    pub fn apply(&self, trade: &mut Trade) {
+        let notional = trade.price.to_decimal() * trade.quantity.to_decimal();
+        trade.maker_fee = notional * self.maker_bps / Decimal::from(10_000);
+        trade.taker_fee = notional * self.taker_bps / Decimal::from(10_000);
+    }
 }
 
 // ============================================================================
@@ -110,6 +287,7 @@ pub struct OrderBookConfig {
     pub matching_algorithm: MatchingAlgorithmType,
 
     /// Optional: Maximum order book depth to maintain (for memory optimization)
+    /// Orders that would open a level deeper than this on a full side are rejected.
     /// None means unlimited depth
     pub max_depth: Option<usize>,
 
@@ -120,6 +298,55 @@ pub struct OrderBookConfig {
     /// Optional: Lot size (minimum quantity increment)
     /// None means no lot size enforcement
     pub lot_size: Option<Quantity>,
+
+    /// Optional: Price band around a reference price
+    /// None means no price collar
+    pub price_band: Option<PriceBand>,
+
+    /// Distribution of pro-rata rounding leftovers (ignored by price/time)
+    pub remainder_policy: RemainderPolicy,
+
+    /// Optional: Smallest quantity a pro-rata share may trade
+    /// None means shares are only truncated to the quantity precision
+    pub min_trade_increment: Option<Quantity>,
+
+    /// Maker/taker fees stamped on every trade (zero by default)
+    pub fee_schedule: FeeSchedule,
+
+    /// Optional: Halt matching on rapid price moves
+    /// None means trading is never halted
+    pub circuit_breaker: Option<CircuitBreaker>,
+
+    /// Optional: Maximum orders per second per user (token bucket)
+    /// None means unlimited
+    pub max_orders_per_sec: Option<u32>,
+
+    /// Optional: Maximum resting orders at a single price
+    /// None means unlimited
+    pub max_orders_per_level: Option<usize>,
+
+    /// Accept zero and negative limit prices (calendar spreads,
+    /// negative-rate products). Quantities must stay positive
+    pub allow_non_positive_prices: bool,
+
+    /// Lower prices are better for buyers (instruments quoted as a yield):
+    /// the best bid is the lowest and the best ask the highest
+    pub price_inverted: bool,
+
+    /// Optional: Periodic `BookChecksum` events for feed consumers
+    /// None means no checksums are published
+    pub checksum_schedule: Option<ChecksumSchedule>,
+
+    /// Reject incoming orders that would trade against the same user or
+    /// account group
+    pub self_trade_prevention: bool,
+
+    /// Price printed on trades from continuous matching
+    pub trade_price_rule: TradePriceRule,
+
+    /// Track each user's position and realized P&L from fills. Off by
+    /// default; reduce-only orders are rejected while it is off
+    pub track_positions: bool,
 }
 
 impl OrderBookConfig {
@@ -136,6 +363,19 @@ impl OrderBookConfig {
             max_depth: None,
             tick_size: None,
             lot_size: None,
+            price_band: None,
+            remainder_policy: RemainderPolicy::default(),
+            min_trade_increment: None,
+            fee_schedule: FeeSchedule::default(),
+            circuit_breaker: None,
+            max_orders_per_sec: None,
+            max_orders_per_level: None,
+            allow_non_positive_prices: false,
+            price_inverted: false,
+            checksum_schedule: None,
+            self_trade_prevention: false,
+            trade_price_rule: TradePriceRule::default(),
+            track_positions: false,
         }
     }
 
@@ -157,6 +397,84 @@ impl OrderBookConfig {
         self
     }
 
+    /// Builder method: Set price band
+    pub fn with_price_band(mut self, band: PriceBand) -> Self {
+        self.price_band = Some(band);
+        self
+    }
+
+    /// Builder method: Set pro-rata remainder policy
+    pub fn with_remainder_policy(mut self, policy: RemainderPolicy) -> Self {
+        self.remainder_policy = policy;
+        self
+    }
+
+    /// Builder method: Set pro-rata minimum trade increment
+    pub fn with_min_trade_increment(mut self, increment: Quantity) -> Self {
+        self.min_trade_increment = Some(increment);
+        self
+    }
+
+    /// Builder method: Set maker/taker fee schedule
+    pub fn with_fee_schedule(mut self, fees: FeeSchedule) -> Self {
+        self.fee_schedule = fees;
+        self
+    }
+
+    /// Builder method: Set circuit breaker
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Builder method: Set per-user order rate limit
+    pub fn with_rate_limit(mut self, max_orders_per_sec: u32) -> Self {
+        self.max_orders_per_sec = Some(max_orders_per_sec);
+        self
+    }
+
+    /// Builder method: Set maximum resting orders per price level
+    pub fn with_max_orders_per_level(mut self, max_orders: usize) -> Self {
+        self.max_orders_per_level = Some(max_orders);
+        self
+    }
+
+    /// Builder method: Accept zero and negative prices
+    pub fn with_non_positive_prices(mut self) -> Self {
+        self.allow_non_positive_prices = true;
+        self
+    }
+
+    /// Builder method: Rank prices inverted (lowest bid and highest ask are best)
+    pub fn with_inverted_prices(mut self) -> Self {
+        self.price_inverted = true;
+        self
+    }
+
+    /// Builder method: Publish a book checksum every `interval` book changes
+    pub fn with_checksum_schedule(mut self, schedule: ChecksumSchedule) -> Self {
+        self.checksum_schedule = Some(schedule);
+        self
+    }
+
+    /// Builder method: Reject orders that would trade with their own user or group
+    pub fn with_self_trade_prevention(mut self) -> Self {
+        self.self_trade_prevention = true;
+        self
+    }
+
+    /// Builder method: Set the price trades print at
+    pub fn with_trade_price_rule(mut self, rule: TradePriceRule) -> Self {
+        self.trade_price_rule = rule;
+        self
+    }
+
+    /// Builder method: Track positions and P&L per user (needed for reduce-only)
+    pub fn with_position_tracking(mut self) -> Self {
+        self.track_positions = true;
+        self
+    }
+
     /// Validate the configuration
     pub fn validate(&self) -> Result<(), String> {
         // Validate instrument name
@@ -178,6 +496,47 @@ impl OrderBookConfig {
             }
         }
 
+        // Validate minimum trade increment
+        if let Some(increment) = self.min_trade_increment {
+            if !increment.is_positive() {
+                return Err("Minimum trade increment must be positive".to_string());
+            }
+        }
+
+        // Validate price band
+        if let Some(band) = self.price_band {
+            if !band.reference.is_positive() {
+                return Err("Price band reference must be positive".to_string());
+            }
+            if band.max_deviation_pct.is_sign_negative() {
+                return Err("Price band deviation cannot be negative".to_string());
+            }
+        }
+
+        // Validate circuit breaker
+        if let Some(breaker) = self.circuit_breaker {
+            if breaker.move_pct.is_sign_negative() {
+                return Err("Circuit breaker move cannot be negative".to_string());
+            }
+        }
+
+        // Validate rate limit
+        if self.max_orders_per_sec == Some(0) {
+            return Err("Rate limit must be positive".to_string());
+        }
+
+        // Validate level capacity
+        if self.max_orders_per_level == Some(0) {
+            return Err("Orders per level must be positive".to_string());
+        }
+
+        // Validate checksum schedule
+        if let Some(schedule) = self.checksum_schedule {
+            if schedule.depth == 0 || schedule.interval == 0 {
+                return Err("Checksum depth and interval must be positive".to_string());
+            }
+        }
+
         // Validate matching algorithm parameters
         match &self.matching_algorithm {
             MatchingAlgorithmType::ProRata {
@@ -198,7 +557,6 @@ impl OrderBookConfig {
                 ..
             } => {
                 // LMM allocation should be between 0 and 1 (0% to 100%)
-                // Since we're using Quantity (9 decimals), 1.0 = 1_000_000_000 raw
                 let one = Quantity::ONE;
                 if lmm_allocation_pct.is_negative() || *lmm_allocation_pct > one {
                     return Err("LMM allocation percentage must be between 0 and 1".to_string());
@@ -218,6 +576,17 @@ impl OrderBookConfig {
                     return Err("Minimum quantity cannot be negative".to_string());
                 }
             },
+            MatchingAlgorithmType::WeightedProRata {
+                exponent,
+                minimum_quantity,
+            } => {
+                if exponent.is_sign_negative() {
+                    return Err("Pro-rata exponent cannot be negative".to_string());
+                }
+                if minimum_quantity.is_negative() {
+                    return Err("Minimum quantity cannot be negative".to_string());
+                }
+            },
             _ => {},
         }
 
@@ -240,7 +609,7 @@ impl OrderBookConfig {
             OrderBookType::Transparent,
             MatchingAlgorithmType::PriceTime { use_simd: true },
         )
-        .with_tick_size(Price::from_parts(0, 10_000_000).unwrap()) // $0.01
+        .with_tick_size("0.01".parse().unwrap()) // $0.01
     }
 
     /// CME-style futures configuration
@@ -360,4 +729,47 @@ mod tests {
         let dark = OrderBookConfig::dark_pool("BLOCK-TRADE".to_string());
         assert!(matches!(dark.order_book_type, OrderBookType::DarkPool));
     }
+
+    #[test]
+    fn test_fee_schedule() {
+        // 2.5 @ 40000 = 100000 notional; 1bp maker rebate, 5bp taker fee
+        let fees = FeeSchedule::new(Decimal::from(-1), Decimal::from(5));
+        let mut trade = Trade::new(
+            "BTC-USD".to_string(),
+            crate::domain::OrderId::new(),
+            crate::domain::OrderId::new(),
+            Price::from_integer(40000).unwrap(),
+            "2.5".parse::<Quantity>().unwrap(),
+            crate::domain::Side::Buy,
+        );
+        fees.apply(&mut trade);
+
+        assert_eq!(trade.maker_fee, Decimal::from(-10));
+        assert_eq!(trade.taker_fee, Decimal::from(50));
+
+        FeeSchedule::default().apply(&mut trade);
+        assert!(trade.maker_fee.is_zero() && trade.taker_fee.is_zero());
+    }
+
+    #[test]
+    fn test_trade_price_rule() {
+        let maker = Price::from_integer(100).unwrap();
+        let limit = Some(Price::from_integer(103).unwrap());
+
+        assert_eq!(TradePriceRule::MakerPrice.trade_price(maker, limit), maker);
+        assert_eq!(
+            TradePriceRule::TakerBenefit.trade_price(maker, limit),
+            maker
+        );
+        assert_eq!(
+            TradePriceRule::Midpoint.trade_price(maker, limit),
+            "101.5".parse::<Price>().unwrap()
+        );
+        // Odd raw sums truncate; unpriced takers trade at the maker's price
+        assert_eq!(
+            TradePriceRule::Midpoint.trade_price(Price::from_raw(3), Some(Price::from_raw(6))),
+            Price::from_raw(4)
+        );
+        assert_eq!(TradePriceRule::Midpoint.trade_price(maker, None), maker);
+    }
 }
@@ -8,10 +8,18 @@ pub mod order;
 pub mod order_book;
 pub mod trade;
 
-pub use config::{MatchingAlgorithmType, OrderBookConfig, OrderBookType};
-pub use order::{Order, OrderId, OrderType, Side, TimeInForce};
-pub use order_book::{OrderBookLevel, OrderBookSide, OrderBookSnapshot};
-pub use trade::Trade;
+pub use config::{
+    ChecksumSchedule, CircuitBreaker, FeeSchedule, MatchingAlgorithmType, OrderBookConfig,
+    OrderBookType, PriceBand, RemainderPolicy, TradePriceRule,
+};
+pub use order::{
+    Order, OrderId, OrderOrigin, OrderStatus, OrderType, PegReference, Side, TimeInForce,
+};
+pub use order_book::{
+    FillEstimate, L3Level, L3Snapshot, LevelDiff, OrderBookEntry, OrderBookLevel, OrderBookSide,
+    OrderBookSnapshot, OrderQueue, SnapshotDiff,
+};
+pub use trade::{FillReport, Trade};
 
 // Re-export state machine
 pub use order::state::{OrderState, OrderStateTransition};
@@ -4,7 +4,7 @@
 
 use crate::numeric::{Price, Quantity};
 use chrono::{DateTime, Utc};
-use std::sync::atomic::{AtomicI64, AtomicU8, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU8, Ordering};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -15,7 +15,7 @@ use serde::{Deserialize, Serialize};
 // Value Objects
 // ============================================================================
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OrderId(Uuid);
 
@@ -46,6 +46,16 @@ pub enum Side {
     Sell,
 }
 
+impl Side {
+    /// The side an order on this side trades against
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OrderType {
@@ -56,6 +66,40 @@ pub enum OrderType {
     Market,
     /// A stop order that, once triggered, becomes a limit order
     StopLimit { trigger_price: Price },
+    /// A limit order priced at `reference + offset`, repriced by the engine
+    /// whenever the reference moves; `Order::price` reads the current price.
+    Pegged {
+        reference: PegReference,
+        offset: Price,
+    },
+    /// A market order that only trades up to `protection_ticks` ticks
+    /// through the best opposite price seen on arrival (ticks of the engine's
+    /// tick size); whatever is left at the boundary is cancelled. The engine
+    /// sets the order's price to the boundary on arrival.
+    MarketWithProtection { protection_ticks: u32 },
+}
+
+/// Book price a pegged order tracks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PegReference {
+    BestBid,
+    BestAsk,
+    Mid,
+}
+
+/// Where an order's liquidity comes from
+///
+/// Pro-rata algorithms fill `Natural` orders at a level before any
+/// `Implied` ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum OrderOrigin {
+    /// Outright order entered directly on this instrument
+    #[default]
+    Natural,
+    /// Liquidity derived from spread or other-leg orders
+    Implied,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -162,6 +206,24 @@ pub mod state {
     }
 }
 
+// ============================================================================
+// Order Status
+// ============================================================================
+
+/// Point-in-time view of an order's progress
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OrderStatus {
+    pub order_id: OrderId,
+    pub side: Side,
+    pub price: Option<Price>,
+    pub original_quantity: Quantity,
+    pub filled_quantity: Quantity,
+    pub remaining_quantity: Quantity,
+    pub state: state::OrderState,
+    pub sequence_number: i64,
+}
+
 // ============================================================================
 // Lock-Free Order Entity
 // ============================================================================
@@ -174,7 +236,6 @@ pub struct Order {
     pub instrument: Arc<String>,
     pub side: Side,
     pub order_type: OrderType,
-    pub price: Option<Price>,
     pub quantity: Quantity,
     pub time_in_force: TimeInForce,
     pub timestamp: DateTime<Utc>,
@@ -185,11 +246,43 @@ pub struct Order {
     /// For iceberg orders: visible quantity (None = fully visible)
     pub display_quantity: Option<Quantity>,
 
+    // Execution controls
+    /// If true, the order is rejected instead of taking liquidity (maker-only)
+    pub post_only: bool,
+    /// If true, the order may only shrink the owner's net position
+    pub reduce_only: bool,
+    /// Smallest acceptable fill on arrival; the order is rejected if less is
+    /// available (None = any fill)
+    pub min_fill_quantity: Option<Quantity>,
+    /// Cap on the average price of the arrival sweep; matching stops before
+    /// the next fill would push the average past it and the rest is
+    /// cancelled (None = no cap)
+    pub max_avg_price: Option<Price>,
+    /// Firm or account hierarchy the user trades under; self-trade
+    /// prevention treats orders in one group as one owner (None = user only)
+    pub account_group: Option<Arc<String>>,
+    /// Client-assigned order id (FIX ClOrdID); at most one live order per
+    /// user may carry a given value (None = not tracked)
+    pub client_order_id: Option<Arc<String>>,
+    /// Natural or implied liquidity (allocation priority under pro-rata)
+    pub origin: OrderOrigin,
+
     // Atomic fields for lock-free updates (stored as raw i64 from FixedDecimal)
+    /// Limit price; the engine sets it in place for pegged and protected
+    /// orders, and it is only meaningful while `priced` is set
+    price: AtomicI64,
+    priced: AtomicBool,
     filled_quantity: AtomicI64,
     remaining_quantity: AtomicI64,
     state: AtomicU8,
     sequence_number: AtomicI64,
+    /// Quantity taken off the order before it traded (e.g. reduce-only capping)
+    reduced_quantity: AtomicI64,
+
+    // Iceberg bookkeeping: quantity left in the current display slice,
+    // and whether the slice was replenished since the engine last checked
+    display_remaining: AtomicI64,
+    display_refreshed: AtomicBool,
 }
 
 impl Order {
@@ -208,16 +301,27 @@ impl Order {
             instrument: Arc::new(instrument),
             side,
             order_type,
-            price,
             quantity,
             time_in_force,
             timestamp: Utc::now(),
             is_hidden: false,
             display_quantity: None,
+            post_only: false,
+            reduce_only: false,
+            min_fill_quantity: None,
+            max_avg_price: None,
+            account_group: None,
+            client_order_id: None,
+            origin: OrderOrigin::Natural,
+            price: AtomicI64::new(price.map_or(0, |p| p.raw_value())),
+            priced: AtomicBool::new(price.is_some()),
             filled_quantity: AtomicI64::new(0),
             remaining_quantity: AtomicI64::new(quantity.raw_value()),
             state: AtomicU8::new(state::OrderState::Pending as u8),
             sequence_number: AtomicI64::new(0),
+            reduced_quantity: AtomicI64::new(0),
+            display_remaining: AtomicI64::new(0),
+            display_refreshed: AtomicBool::new(false),
         }
     }
 
@@ -266,17 +370,82 @@ impl Order {
             time_in_force,
         );
         order.display_quantity = Some(display_quantity);
+        order.display_remaining = AtomicI64::new(display_quantity.min(quantity).raw_value());
         order
     }
 
+    /// Builder method: Mark the order as post-only (rejected if it would cross)
+    pub fn with_post_only(mut self) -> Self {
+        self.post_only = true;
+        self
+    }
+
+    /// Builder method: Mark the order as reduce-only (may not grow the net position)
+    pub fn with_reduce_only(mut self) -> Self {
+        self.reduce_only = true;
+        self
+    }
+
+    /// Builder method: Require at least `quantity` to fill on arrival
+    pub fn with_min_fill(mut self, quantity: Quantity) -> Self {
+        self.min_fill_quantity = Some(quantity);
+        self
+    }
+
+    /// Builder method: Cap the average price of the arrival sweep
+    pub fn with_max_avg_price(mut self, price: Price) -> Self {
+        self.max_avg_price = Some(price);
+        self
+    }
+
+    /// Builder method: Place the order under an account group for self-trade prevention
+    pub fn with_account_group(mut self, group: String) -> Self {
+        self.account_group = Some(Arc::new(group));
+        self
+    }
+
+    /// Builder method: Tag the order with a client order id (FIX ClOrdID)
+    pub fn with_client_order_id(mut self, client_order_id: String) -> Self {
+        self.client_order_id = Some(Arc::new(client_order_id));
+        self
+    }
+
+    /// Builder method: Set whether the order is natural or implied liquidity
+    pub fn with_origin(mut self, origin: OrderOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Whether a trade between `self` and `other` would be a self-trade
+    ///
+    /// True for the same user, or for two orders in the same account group.
+    pub fn shares_owner_with(&self, other: &Order) -> bool {
+        self.user_id == other.user_id
+            || matches!(
+                (&self.account_group, &other.account_group),
+                (Some(a), Some(b)) if a == b
+            )
+    }
+
     /// Get the visible quantity for this order (respects iceberg display quantity)
     pub fn get_visible_quantity(&self) -> Quantity {
         if self.is_hidden {
             Quantity::ZERO
-        } else if let Some(display) = self.display_quantity {
-            display.min(self.get_remaining_quantity())
         } else {
-            self.get_remaining_quantity()
+            self.get_executable_quantity()
+        }
+    }
+
+    /// Get the quantity that can currently trade against this order while it rests
+    ///
+    /// For iceberg orders this is what is left of the current display slice;
+    /// for all other orders it is the full remaining quantity.
+    pub fn get_executable_quantity(&self) -> Quantity {
+        let remaining = self.get_remaining_quantity();
+        if self.is_iceberg() {
+            Quantity::from_raw(self.display_remaining.load(Ordering::Acquire)).min(remaining)
+        } else {
+            remaining
         }
     }
 
@@ -284,6 +453,13 @@ impl Order {
     // Atomic Getters
     // ========================================================================
 
+    /// Limit price, if the order has one
+    pub fn price(&self) -> Option<Price> {
+        self.priced
+            .load(Ordering::Acquire)
+            .then(|| Price::from_raw(self.price.load(Ordering::Acquire)))
+    }
+
     pub fn get_filled_quantity(&self) -> Quantity {
         Quantity::from_raw(self.filled_quantity.load(Ordering::Acquire))
     }
@@ -300,10 +476,40 @@ impl Order {
         self.sequence_number.load(Ordering::Acquire)
     }
 
+    /// Snapshot the order's progress
+    ///
+    /// Filled quantity is derived from a single load of the remaining quantity,
+    /// so `filled + remaining == original` holds even while the order is filling.
+    /// For orders reduced before trading, `original_quantity` is the reduced size.
+    pub fn status(&self) -> OrderStatus {
+        let remaining = self.get_remaining_quantity();
+        let original =
+            self.quantity - Quantity::from_raw(self.reduced_quantity.load(Ordering::Acquire));
+        OrderStatus {
+            order_id: self.id,
+            side: self.side,
+            price: self.price(),
+            original_quantity: original,
+            filled_quantity: original - remaining,
+            remaining_quantity: remaining,
+            state: self.get_state(),
+            sequence_number: self.get_sequence_number(),
+        }
+    }
+
     // ========================================================================
     // Atomic Operations
     // ========================================================================
 
+    /// Set the limit price (pegged repricing, protection boundaries)
+    ///
+    /// Only call while the order is out of the book: resting orders are
+    /// filed under their price.
+    pub(crate) fn set_price(&self, price: Price) {
+        self.price.store(price.raw_value(), Ordering::Release);
+        self.priced.store(true, Ordering::Release);
+    }
+
     /// Atomically fill a quantity of this order
     /// Returns true if successful, false if insufficient quantity
     pub fn try_fill(&self, quantity: Quantity) -> bool {
@@ -348,9 +554,62 @@ impl Order {
         }
     }
 
+    /// Copy of the order with only `quantity` left to fill
+    ///
+    /// Lets the engine hand an algorithm part of an order; fills on the copy
+    /// are applied back to the original with `try_fill`.
+    pub(crate) fn slice(&self, quantity: Quantity) -> Order {
+        let slice = self.clone();
+        slice
+            .remaining_quantity
+            .store(quantity.raw_value(), Ordering::Release);
+        slice
+    }
+
+    /// Shrink an order that has not traded yet to `quantity`
+    ///
+    /// Returns false if the order has already filled, or if `quantity` is not
+    /// below the current remaining quantity.
+    pub fn reduce_quantity(&self, quantity: Quantity) -> bool {
+        if self.filled_quantity.load(Ordering::Acquire) != 0 {
+            return false;
+        }
+
+        let current = self.remaining_quantity.load(Ordering::Acquire);
+        let target = quantity.raw_value();
+        if target >= current || target < 0 {
+            return false;
+        }
+
+        if self
+            .remaining_quantity
+            .compare_exchange(current, target, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return false;
+        }
+        self.reduced_quantity
+            .fetch_add(current - target, Ordering::AcqRel);
+        if let Some(display) = self.display_quantity {
+            self.display_remaining
+                .store(display.min(quantity).raw_value(), Ordering::Release);
+        }
+        true
+    }
+
     /// Atomically cancel this order
     /// Returns true if successfully cancelled
     pub fn try_cancel(&self) -> bool {
+        self.try_close(state::OrderState::Cancelled)
+    }
+
+    /// Try to expire the order (GTD deadline reached)
+    pub fn try_expire(&self) -> bool {
+        self.try_close(state::OrderState::Expired)
+    }
+
+    /// Move a live order into a terminal state, failing if it is already closed
+    fn try_close(&self, target: state::OrderState) -> bool {
         let current_state = self.state.load(Ordering::Acquire);
         let state = state::OrderState::from_u8(current_state);
 
@@ -361,13 +620,37 @@ impl Order {
         self.state
             .compare_exchange(
                 current_state,
-                state::OrderState::Cancelled as u8,
+                target as u8,
                 Ordering::AcqRel,
                 Ordering::Acquire,
             )
             .is_ok()
     }
 
+    /// Consume part of the iceberg display slice after a fill
+    /// Returns true if the slice is exhausted
+    pub fn consume_display(&self, quantity: Quantity) -> bool {
+        let previous = self
+            .display_remaining
+            .fetch_sub(quantity.raw_value(), Ordering::AcqRel);
+        previous - quantity.raw_value() <= 0
+    }
+
+    /// Replenish the iceberg display slice from the hidden reserve
+    pub fn refresh_display(&self) {
+        if let Some(display) = self.display_quantity {
+            let slice = display.min(self.get_remaining_quantity());
+            self.display_remaining
+                .store(slice.raw_value(), Ordering::Release);
+            self.display_refreshed.store(true, Ordering::Release);
+        }
+    }
+
+    /// Returns true (once) if the display slice was replenished since the last call
+    pub fn take_display_refreshed(&self) -> bool {
+        self.display_refreshed.swap(false, Ordering::AcqRel)
+    }
+
     /// Set the sequence number (called by matching engine)
     pub fn set_sequence_number(&self, seq: i64) {
         self.sequence_number.store(seq, Ordering::Release);
@@ -382,6 +665,14 @@ impl Order {
     // Helper Methods
     // ========================================================================
 
+    /// Whether a GoodTillDate deadline has passed at `now`
+    pub fn is_expired_at(&self, now: DateTime<Utc>) -> bool {
+        match self.time_in_force {
+            TimeInForce::GoodTillDate(deadline) => deadline <= now,
+            _ => false,
+        }
+    }
+
     pub fn is_market_order(&self) -> bool {
         matches!(self.order_type, OrderType::Market)
     }
@@ -389,6 +680,18 @@ impl Order {
     pub fn is_limit_order(&self) -> bool {
         matches!(self.order_type, OrderType::Limit)
     }
+
+    pub fn is_pegged(&self) -> bool {
+        matches!(self.order_type, OrderType::Pegged { .. })
+    }
+
+    pub fn is_protected_market(&self) -> bool {
+        matches!(self.order_type, OrderType::MarketWithProtection { .. })
+    }
+
+    pub fn is_iceberg(&self) -> bool {
+        self.display_quantity.is_some()
+    }
 }
 
 // Clone implementation for Order
@@ -400,16 +703,27 @@ impl Clone for Order {
             instrument: Arc::clone(&self.instrument),
             side: self.side,
             order_type: self.order_type,
-            price: self.price,
             quantity: self.quantity,
             time_in_force: self.time_in_force,
             timestamp: self.timestamp,
             is_hidden: self.is_hidden,
             display_quantity: self.display_quantity,
+            post_only: self.post_only,
+            reduce_only: self.reduce_only,
+            min_fill_quantity: self.min_fill_quantity,
+            max_avg_price: self.max_avg_price,
+            account_group: self.account_group.clone(),
+            client_order_id: self.client_order_id.clone(),
+            origin: self.origin,
+            price: AtomicI64::new(self.price.load(Ordering::Acquire)),
+            priced: AtomicBool::new(self.priced.load(Ordering::Acquire)),
             filled_quantity: AtomicI64::new(self.filled_quantity.load(Ordering::Acquire)),
             remaining_quantity: AtomicI64::new(self.remaining_quantity.load(Ordering::Acquire)),
             state: AtomicU8::new(self.state.load(Ordering::Acquire)),
             sequence_number: AtomicI64::new(self.sequence_number.load(Ordering::Acquire)),
+            reduced_quantity: AtomicI64::new(self.reduced_quantity.load(Ordering::Acquire)),
+            display_remaining: AtomicI64::new(self.display_remaining.load(Ordering::Acquire)),
+            display_refreshed: AtomicBool::new(self.display_refreshed.load(Ordering::Acquire)),
         }
     }
 }
@@ -418,6 +732,13 @@ impl Clone for Order {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_side_opposite() {
+        assert_eq!(Side::Buy.opposite(), Side::Sell);
+        assert_eq!(Side::Sell.opposite(), Side::Buy);
+        assert_eq!(Side::Buy.opposite().opposite(), Side::Buy);
+    }
+
     #[test]
     fn test_order_creation() {
         let order = Order::new(
@@ -497,4 +818,74 @@ mod tests {
         assert!(order.try_cancel());
         assert_eq!(order.get_state(), state::OrderState::Cancelled);
     }
+
+    #[test]
+    fn test_reduce_quantity() {
+        let order = Order::new(
+            "user123".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(5).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )
+        .with_reduce_only();
+
+        assert!(!order.reduce_quantity(Quantity::from_integer(5).unwrap()));
+        assert!(order.reduce_quantity(Quantity::from_integer(3).unwrap()));
+        assert_eq!(
+            order.get_remaining_quantity(),
+            Quantity::from_integer(3).unwrap()
+        );
+
+        let status = order.status();
+        assert_eq!(status.original_quantity, Quantity::from_integer(3).unwrap());
+        assert_eq!(status.filled_quantity, Quantity::ZERO);
+
+        // Once trading has started the size is fixed
+        assert!(order.try_fill(Quantity::from_integer(1).unwrap()));
+        assert!(!order.reduce_quantity(Quantity::from_integer(1).unwrap()));
+        assert_eq!(
+            order.status().filled_quantity,
+            Quantity::from_integer(1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_iceberg_display_slice() {
+        let order = Order::new_iceberg(
+            "user123".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(5).unwrap(),
+            Quantity::from_integer(2).unwrap(),
+            TimeInForce::GoodTillCancel,
+        );
+
+        assert_eq!(
+            order.get_visible_quantity(),
+            Quantity::from_integer(2).unwrap()
+        );
+
+        assert!(order.try_fill(Quantity::from_integer(2).unwrap()));
+        assert!(order.consume_display(Quantity::from_integer(2).unwrap()));
+        assert_eq!(order.get_visible_quantity(), Quantity::ZERO);
+
+        order.refresh_display();
+        assert!(order.take_display_refreshed());
+        assert!(!order.take_display_refreshed());
+        assert_eq!(
+            order.get_visible_quantity(),
+            Quantity::from_integer(2).unwrap()
+        );
+
+        // Final slice is capped by what is left
+        assert!(order.try_fill(Quantity::from_integer(2).unwrap()));
+        order.consume_display(Quantity::from_integer(2).unwrap());
+        order.refresh_display();
+        assert_eq!(order.get_visible_quantity(), Quantity::ONE);
+    }
 }
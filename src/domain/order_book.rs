@@ -3,16 +3,98 @@
 // ============================================================================
 
 use crate::numeric::{Price, Quantity};
-use crossbeam::queue::SegQueue;
+use crossbeam_skiplist::map::Entry;
 use crossbeam_skiplist::SkipMap;
-use std::sync::atomic::{AtomicI64, Ordering};
+use rust_decimal::Decimal;
+use std::sync::atomic::{AtomicI64, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
 
-use super::{Order, Side};
+use super::{Order, OrderId, Side};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+// ============================================================================
+// Order Queue
+// ============================================================================
+
+/// Lock-free FIFO queue of resting orders with removal by id
+///
+/// Orders sit in a skip list keyed by arrival slot, with a second skip list
+/// from order id to slot. `push` and `pop` behave like a plain FIFO queue,
+/// while `get` and `remove` reach a single order in O(log n) and leave every
+/// other order's position untouched.
+#[derive(Debug, Default)]
+pub struct OrderQueue {
+    /// Orders by arrival slot; the front is the oldest
+    slots: SkipMap<u64, Arc<Order>>,
+    /// Slot of each queued order
+    index: SkipMap<OrderId, u64>,
+    /// Next arrival slot
+    next_slot: AtomicU64,
+}
+
+impl OrderQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an order at the back of the queue
+    pub fn push(&self, order: Arc<Order>) {
+        let slot = self.next_slot.fetch_add(1, Ordering::AcqRel);
+        self.index.insert(order.id, slot);
+        self.slots.insert(slot, order);
+    }
+
+    /// Take the order at the front of the queue
+    pub fn pop(&self) -> Option<Arc<Order>> {
+        let entry = self.slots.pop_front()?;
+        let order = Arc::clone(entry.value());
+        // Only forget the id if it still points at this slot
+        if let Some(indexed) = self.index.get(&order.id) {
+            if indexed.value() == entry.key() {
+                indexed.remove();
+            }
+        }
+        Some(order)
+    }
+
+    /// The order at the front of the queue, left in place
+    pub fn front(&self) -> Option<Arc<Order>> {
+        self.slots.front().map(|entry| Arc::clone(entry.value()))
+    }
+
+    /// Take a specific order out of the queue, wherever it sits
+    pub fn remove(&self, id: OrderId) -> Option<Arc<Order>> {
+        let slot = *self.index.remove(&id)?.value();
+        self.slots
+            .remove(&slot)
+            .map(|entry| Arc::clone(entry.value()))
+    }
+
+    /// Look up a queued order without moving it
+    pub fn get(&self, id: OrderId) -> Option<Arc<Order>> {
+        let slot = *self.index.get(&id)?.value();
+        self.slots.get(&slot).map(|entry| Arc::clone(entry.value()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Queued orders, front first
+    pub fn to_vec(&self) -> Vec<Arc<Order>> {
+        self.slots
+            .iter()
+            .map(|entry| Arc::clone(entry.value()))
+            .collect()
+    }
+}
+
 // ============================================================================
 // Order Book Level
 // ============================================================================
@@ -22,39 +104,118 @@ use serde::{Deserialize, Serialize};
 pub struct OrderBookLevel {
     pub price: Price,
     /// Lock-free FIFO queue of orders
-    pub orders: SegQueue<Arc<Order>>,
+    pub orders: OrderQueue,
     /// Atomic total quantity at this price level (stored as raw i64)
     total_quantity: AtomicI64,
+    /// Running total of the owning side, moved by every change to `total_quantity`
+    side_total: Arc<AtomicI64>,
+    /// Number of resting orders at this price level
+    order_count: AtomicUsize,
 }
 
 impl OrderBookLevel {
     pub fn new(price: Price) -> Self {
+        Self::in_side(price, Arc::new(AtomicI64::new(0)))
+    }
+
+    /// A level whose quantity changes are mirrored into `side_total`
+    pub(crate) fn in_side(price: Price, side_total: Arc<AtomicI64>) -> Self {
         Self {
             price,
-            orders: SegQueue::new(),
+            orders: OrderQueue::new(),
             total_quantity: AtomicI64::new(0),
+            side_total,
+            order_count: AtomicUsize::new(0),
         }
     }
 
     pub fn add_order(&self, order: Arc<Order>) {
-        let quantity_raw = order.get_remaining_quantity().raw_value();
-        self.total_quantity
-            .fetch_add(quantity_raw, Ordering::AcqRel);
+        self.adjust_total(order.get_remaining_quantity().raw_value());
+        self.order_count.fetch_add(1, Ordering::AcqRel);
         self.orders.push(order);
     }
 
+    /// Number of resting orders at this level
+    ///
+    /// Unlike inspecting `orders`, this is stable while a matcher is
+    /// draining and re-filling the queue.
+    pub fn order_count(&self) -> usize {
+        self.order_count.load(Ordering::Acquire)
+    }
+
     pub fn get_total_quantity(&self) -> Quantity {
         Quantity::from_raw(self.total_quantity.load(Ordering::Acquire))
     }
 
     pub fn subtract_quantity(&self, quantity: Quantity) {
-        self.total_quantity
-            .fetch_sub(quantity.raw_value(), Ordering::AcqRel);
+        self.adjust_total(-quantity.raw_value());
+    }
+
+    /// Move the level total and the side total by `delta` (raw)
+    fn adjust_total(&self, delta: i64) {
+        self.total_quantity.fetch_add(delta, Ordering::AcqRel);
+        self.side_total.fetch_add(delta, Ordering::AcqRel);
+    }
+
+    /// Account for `quantity` executed against `maker`
+    ///
+    /// Call after the fill has been applied to the order; a maker left with
+    /// nothing remaining no longer counts towards `order_count`.
+    pub fn record_fill(&self, maker: &Order, quantity: Quantity) {
+        self.subtract_quantity(quantity);
+        if maker.get_remaining_quantity() == Quantity::ZERO {
+            self.release_order();
+        }
+    }
+
+    fn release_order(&self) {
+        let _ = self
+            .order_count
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1));
     }
 
     pub fn is_empty(&self) -> bool {
         self.orders.is_empty()
     }
+
+    /// Collect the resting orders in FIFO order
+    ///
+    /// The queue is read in place, so queue position is preserved.
+    pub fn snapshot_orders(&self) -> Vec<Arc<Order>> {
+        self.orders.to_vec()
+    }
+
+    /// Remove a specific order from the level, keeping the others in FIFO order
+    ///
+    /// O(log n) in the number of orders at the level. The order's remaining
+    /// quantity is subtracted from the level total.
+    pub fn remove_order(&self, id: OrderId) -> Option<Arc<Order>> {
+        let removed = self.orders.remove(id)?;
+        self.subtract_quantity(removed.get_remaining_quantity());
+        self.release_order();
+        Some(removed)
+    }
+
+    /// Sum of the queued orders' remaining quantities, to check the cached
+    /// total against
+    ///
+    /// O(n) and leaves the cached total alone. Only expected to agree with
+    /// [`get_total_quantity`](Self::get_total_quantity) while no matcher or
+    /// cancel is working the level: a fill lands on the order before the level.
+    pub(crate) fn recompute_total(&self) -> Quantity {
+        self.orders.slots.iter().fold(Quantity::ZERO, |acc, entry| {
+            acc + entry.value().get_remaining_quantity()
+        })
+    }
+
+    /// Get the displayed quantity at this level (excludes hidden and iceberg reserve)
+    pub fn get_visible_quantity(&self) -> Quantity {
+        self.snapshot_orders()
+            .iter()
+            .fold(Quantity::ZERO, |acc, order| {
+                acc + order.get_visible_quantity()
+            })
+    }
 }
 
 // ============================================================================
@@ -69,6 +230,11 @@ pub struct OrderBookSide {
     /// Value: Arc to price level
     pub levels: Arc<SkipMap<i64, Arc<OrderBookLevel>>>,
     pub side: Side,
+    /// Lower prices are better for buyers (e.g. quoted as a yield): the best
+    /// bid is the lowest and the best ask the highest
+    pub inverted: bool,
+    /// Sum of the level totals (raw), kept current by the levels themselves
+    total_quantity: Arc<AtomicI64>,
 }
 
 impl OrderBookSide {
@@ -76,53 +242,143 @@ impl OrderBookSide {
         Self {
             levels: Arc::new(SkipMap::new()),
             side,
+            inverted: false,
+            total_quantity: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Flip which end of the price range is best (see `inverted`)
+    pub fn with_inverted_prices(mut self, inverted: bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// Whether the best level is the highest price
+    fn best_is_highest(&self) -> bool {
+        (self.side == Side::Buy) != self.inverted
+    }
+
+    /// Price levels from best to worst
+    pub(crate) fn levels_best_first(
+        &self,
+    ) -> Box<dyn Iterator<Item = Entry<'_, i64, Arc<OrderBookLevel>>> + '_> {
+        if self.best_is_highest() {
+            Box::new(self.levels.iter().rev())
+        } else {
+            Box::new(self.levels.iter())
+        }
+    }
+
+    /// Whether `price` is at least as good as `other` for this side
+    pub fn is_at_or_better(&self, price: Price, other: Price) -> bool {
+        if self.best_is_highest() {
+            price >= other
+        } else {
+            price <= other
         }
     }
 
     /// Add an order to the book side
     pub fn add_order(&self, order: Arc<Order>) {
-        let price = order.price.expect("Only limit orders can be added to book");
+        let price = order
+            .price()
+            .expect("Only limit orders can be added to book");
         let price_key = price.raw_value();
 
         // Get or insert price level
-        let level = self
-            .levels
-            .get_or_insert(price_key, Arc::new(OrderBookLevel::new(price)));
+        let level = self.levels.get_or_insert_with(price_key, || {
+            Arc::new(OrderBookLevel::in_side(
+                price,
+                Arc::clone(&self.total_quantity),
+            ))
+        });
 
         level.value().add_order(order);
     }
 
     /// Get the best (top-of-book) price
     pub fn best_price(&self) -> Option<Price> {
-        match self.side {
-            Side::Buy => {
-                // Highest bid (last in sorted order)
-                self.levels
-                    .iter()
-                    .next_back()
-                    .map(|entry| entry.value().price)
-            },
-            Side::Sell => {
-                // Lowest ask (first in sorted order)
-                self.levels.iter().next().map(|entry| entry.value().price)
-            },
-        }
+        self.levels_best_first()
+            .next()
+            .map(|entry| entry.value().price)
+    }
+
+    /// Get the worst (deepest) price
+    pub fn worst_price(&self) -> Option<Price> {
+        let worst = if self.best_is_highest() {
+            self.levels.front()
+        } else {
+            self.levels.back()
+        };
+        worst.map(|entry| entry.value().price)
+    }
+
+    /// Whether a level strictly better than `price` is resting
+    ///
+    /// Matching loops use this to detect a better level inserted while they
+    /// were working a worse one.
+    pub fn has_better_level_than(&self, price: Price) -> bool {
+        self.best_price()
+            .is_some_and(|best| best != price && self.is_at_or_better(best, price))
+    }
+
+    /// Get the level resting at `price`, if any
+    pub fn level_at_price(&self, price: Price) -> Option<Arc<OrderBookLevel>> {
+        self.levels
+            .get(&price.raw_value())
+            .map(|entry| Arc::clone(entry.value()))
+    }
+
+    /// Number of price levels on this side
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Total resting quantity on this side, hidden and iceberg reserve
+    /// included
+    ///
+    /// O(1): maintained as levels change rather than summed on demand.
+    pub fn total_quantity(&self) -> Quantity {
+        Quantity::from_raw(self.total_quantity.load(Ordering::Acquire))
+    }
+
+    /// Sum of the level totals, to check [`total_quantity`](Self::total_quantity)
+    /// against
+    ///
+    /// O(levels). Only expected to agree while no order is being added,
+    /// matched or cancelled on this side.
+    pub(crate) fn levels_total(&self) -> Quantity {
+        self.levels.iter().fold(Quantity::ZERO, |total, entry| {
+            total + entry.value().get_total_quantity()
+        })
+    }
+
+    /// Drop every level and reset the side total
+    pub fn clear(&self) {
+        self.levels.clear();
+        self.total_quantity.store(0, Ordering::Release);
     }
 
     /// Get the best price level
     pub fn best_level(&self) -> Option<Arc<OrderBookLevel>> {
-        match self.side {
-            Side::Buy => self
-                .levels
-                .iter()
-                .next_back()
-                .map(|entry| Arc::clone(entry.value())),
-            Side::Sell => self
-                .levels
-                .iter()
-                .next()
-                .map(|entry| Arc::clone(entry.value())),
+        self.levels_best_first()
+            .next()
+            .map(|entry| Arc::clone(entry.value()))
+    }
+
+    /// Remove a resting order from its price level
+    ///
+    /// The level total is reduced and the level is dropped if it becomes empty.
+    pub fn remove_order(&self, order: &Order) -> Option<Arc<Order>> {
+        let price = order.price()?;
+        let entry = self.levels.get(&price.raw_value())?;
+        let level = Arc::clone(entry.value());
+
+        let removed = level.remove_order(order.id);
+        if level.is_empty() {
+            self.levels.remove(&price.raw_value());
         }
+        removed
     }
 
     /// Remove empty price levels
@@ -140,12 +396,24 @@ impl OrderBookSide {
         }
     }
 
+    /// Write the raw prices of the best levels into `out`, best first
+    ///
+    /// Returns how many entries were written. Does not allocate, so it can
+    /// feed SIMD pre-checks on the hot path.
+    pub fn best_prices_raw(&self, out: &mut [i64]) -> usize {
+        let iter = self.levels_best_first();
+
+        let mut written = 0;
+        for (slot, entry) in out.iter_mut().zip(iter) {
+            *slot = *entry.key();
+            written += 1;
+        }
+        written
+    }
+
     /// Get depth at N levels
     pub fn get_depth(&self, num_levels: usize) -> Vec<(Price, Quantity)> {
-        let iter: Box<dyn Iterator<Item = _>> = match self.side {
-            Side::Buy => Box::new(self.levels.iter().rev()),
-            Side::Sell => Box::new(self.levels.iter()),
-        };
+        let iter = self.levels_best_first();
 
         iter.take(num_levels)
             .map(|entry| {
@@ -154,6 +422,186 @@ impl OrderBookSide {
             })
             .collect()
     }
+
+    /// Get depth at N levels using displayed quantity only
+    ///
+    /// Levels whose orders are entirely hidden are skipped, so they neither
+    /// appear in the result nor count towards `num_levels`.
+    pub fn get_visible_depth(&self, num_levels: usize) -> Vec<(Price, Quantity)> {
+        let iter = self.levels_best_first();
+
+        iter.map(|entry| {
+            let level = entry.value();
+            (level.price, level.get_visible_quantity())
+        })
+        .filter(|(_, quantity)| quantity.is_positive())
+        .take(num_levels)
+        .collect()
+    }
+
+    /// Total quantity resting at prices up to and including `target`
+    ///
+    /// "Up to" is from the taker's point of view: bids at or above `target`,
+    /// asks at or below it (the other way round on an inverted book).
+    pub fn cumulative_quantity_to_price(&self, target: Price) -> Quantity {
+        let key = target.raw_value();
+        let iter: Box<dyn Iterator<Item = _>> = if self.best_is_highest() {
+            Box::new(self.levels.range(key..))
+        } else {
+            Box::new(self.levels.range(..=key))
+        };
+
+        iter.fold(Quantity::ZERO, |total, entry| {
+            total.saturating_add(entry.value().get_total_quantity())
+        })
+    }
+
+    /// Total quantity over the best `num_levels` levels
+    pub fn quantity_at_depth(&self, num_levels: usize) -> Quantity {
+        let iter = self.levels_best_first();
+
+        iter.take(num_levels).fold(Quantity::ZERO, |total, entry| {
+            total.saturating_add(entry.value().get_total_quantity())
+        })
+    }
+
+    /// Estimate sweeping `quantity` through this side without touching the book
+    ///
+    /// Levels are walked best price first. With `visible_only`, only displayed
+    /// quantity is counted.
+    pub fn estimate_fill(&self, quantity: Quantity, visible_only: bool) -> FillEstimate {
+        self.sweep(quantity, visible_only, None, None)
+    }
+
+    /// Like [`estimate_fill`](Self::estimate_fill), but stop before the
+    /// average fill price gets worse than `max_avg_price`
+    ///
+    /// Worse is from the taker's side: above the cap when sweeping asks,
+    /// below it when sweeping bids (the other way round on an inverted book).
+    /// The last level reached may be taken only in part.
+    pub fn estimate_capped_fill(
+        &self,
+        quantity: Quantity,
+        visible_only: bool,
+        max_avg_price: Price,
+    ) -> FillEstimate {
+        self.sweep(quantity, visible_only, None, Some(max_avg_price))
+    }
+
+    /// Walk levels best first for up to `quantity`, stopping at the first
+    /// level beyond `limit` and where the average would pass `max_avg_price`
+    pub(crate) fn sweep(
+        &self,
+        quantity: Quantity,
+        visible_only: bool,
+        limit: Option<Price>,
+        max_avg_price: Option<Price>,
+    ) -> FillEstimate {
+        let iter = self.levels_best_first();
+
+        let mut estimate = FillEstimate::default();
+        // Sum of price * quantity in raw units (scale squared)
+        let mut notional: i128 = 0;
+        // How far the fills so far are inside the cap, in the same units
+        let mut slack: i128 = 0;
+        // Prices get worse for the taker moving away from the best level
+        let worse: i128 = if self.best_is_highest() { -1 } else { 1 };
+
+        for entry in iter {
+            let remaining = quantity - estimate.filled_quantity;
+            if !remaining.is_positive() {
+                break;
+            }
+
+            let level = entry.value();
+            if limit.is_some_and(|limit| !self.is_at_or_better(level.price, limit)) {
+                break;
+            }
+            let available = if visible_only {
+                level.get_visible_quantity()
+            } else {
+                level.get_total_quantity()
+            };
+            if !available.is_positive() {
+                continue;
+            }
+
+            let mut take = remaining.min(available);
+            if let Some(cap) = max_avg_price {
+                // Slack each unit at this level adds (negative beyond the cap)
+                let gain = worse * (cap.raw_value() as i128 - level.price.raw_value() as i128);
+                if gain < 0 {
+                    let affordable = slack / -gain;
+                    if affordable < take.raw_value() as i128 {
+                        take = Quantity::from_raw(affordable as i64);
+                        estimate.cap_reached = true;
+                    }
+                }
+                slack += gain * take.raw_value() as i128;
+            }
+            if !take.is_positive() {
+                break;
+            }
+
+            notional += level.price.raw_value() as i128 * take.raw_value() as i128;
+            estimate.filled_quantity = estimate.filled_quantity + take;
+            estimate.worst_price = Some(level.price);
+            estimate.levels_consumed += 1;
+            if estimate.cap_reached {
+                break;
+            }
+        }
+
+        if estimate.filled_quantity.is_positive() {
+            // VWAP = notional / filled, rounded half away from zero
+            let filled = estimate.filled_quantity.raw_value() as i128;
+            let half = if notional >= 0 {
+                filled / 2
+            } else {
+                -(filled / 2)
+            };
+            estimate.average_price = Some(Price::from_raw(((notional + half) / filled) as i64));
+        }
+        estimate.fully_filled = estimate.filled_quantity >= quantity;
+        estimate
+    }
+
+    /// Get individual orders (L3) for N levels, in queue order within each level
+    ///
+    /// With `visible_only`, hidden orders are omitted and icebergs report their
+    /// displayed quantity; levels left without any entries are skipped.
+    pub fn get_l3_depth(&self, num_levels: usize, visible_only: bool) -> Vec<L3Level> {
+        let iter = self.levels_best_first();
+
+        iter.map(|entry| {
+            let level = entry.value();
+            let orders = level
+                .snapshot_orders()
+                .iter()
+                .filter_map(|order| {
+                    let remaining = if visible_only {
+                        order.get_visible_quantity()
+                    } else {
+                        order.get_remaining_quantity()
+                    };
+                    remaining.is_positive().then(|| OrderBookEntry {
+                        order_id: order.id,
+                        user_id: (*order.user_id).clone(),
+                        remaining,
+                        sequence_number: order.get_sequence_number(),
+                    })
+                })
+                .collect();
+
+            L3Level {
+                price: level.price,
+                orders,
+            }
+        })
+        .filter(|level| !level.orders.is_empty())
+        .take(num_levels)
+        .collect()
+    }
 }
 
 // ============================================================================
@@ -161,7 +609,7 @@ impl OrderBookSide {
 // ============================================================================
 
 /// Immutable snapshot of the order book state
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct OrderBookSnapshot {
     pub instrument: String,
@@ -169,10 +617,20 @@ pub struct OrderBookSnapshot {
     pub bids: Vec<(Price, Quantity)>,
     /// Ask levels (price, quantity)
     pub asks: Vec<(Price, Quantity)>,
+    /// Running bid quantity from the top of book, one entry per level in `bids`
+    pub bid_cumulative: Vec<Quantity>,
+    /// Running ask quantity from the top of book, one entry per level in `asks`
+    pub ask_cumulative: Vec<Quantity>,
     /// Current spread (ask - bid)
     pub spread: Option<Price>,
     /// Mid price
     pub mid_price: Option<Price>,
+    /// Price of the most recent trade
+    pub last_trade_price: Option<Price>,
+    /// Quantity of the most recent trade
+    pub last_trade_quantity: Option<Quantity>,
+    /// Engine trade id of the most recent trade
+    pub last_trade_id: Option<u64>,
 }
 
 impl OrderBookSnapshot {
@@ -181,8 +639,13 @@ impl OrderBookSnapshot {
             instrument,
             bids: Vec::new(),
             asks: Vec::new(),
+            bid_cumulative: Vec::new(),
+            ask_cumulative: Vec::new(),
             spread: None,
             mid_price: None,
+            last_trade_price: None,
+            last_trade_quantity: None,
+            last_trade_id: None,
         }
     }
 
@@ -209,10 +672,15 @@ impl OrderBookSnapshot {
 
         Self {
             instrument,
+            bid_cumulative: cumulative(&bids),
+            ask_cumulative: cumulative(&asks),
             bids,
             asks,
             spread,
             mid_price,
+            last_trade_price: None,
+            last_trade_quantity: None,
+            last_trade_id: None,
         }
     }
 
@@ -227,14 +695,311 @@ impl OrderBookSnapshot {
     pub fn total_bid_quantity(&self) -> Quantity {
         self.bids
             .iter()
-            .fold(Quantity::ZERO, |acc, (_, qty)| acc + *qty)
+            .fold(Quantity::ZERO, |acc, (_, qty)| acc.saturating_add(*qty))
     }
 
     pub fn total_ask_quantity(&self) -> Quantity {
         self.asks
             .iter()
-            .fold(Quantity::ZERO, |acc, (_, qty)| acc + *qty)
+            .fold(Quantity::ZERO, |acc, (_, qty)| acc.saturating_add(*qty))
     }
+
+    /// Order-flow imbalance over the top `levels` levels of each side
+    ///
+    /// `(bid_qty - ask_qty) / (bid_qty + ask_qty)`, ranging from -1 (all
+    /// asks) to 1 (all bids). `None` if either side is empty.
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        if self.bids.is_empty() || self.asks.is_empty() || levels == 0 {
+            return None;
+        }
+
+        let depth = |side: &[(Price, Quantity)]| -> i128 {
+            side.iter()
+                .take(levels)
+                .map(|(_, qty)| qty.raw_value() as i128)
+                .sum()
+        };
+        let bid_qty = depth(&self.bids);
+        let ask_qty = depth(&self.asks);
+
+        let total = Decimal::try_from_i128_with_scale(bid_qty + ask_qty, 0).ok()?;
+        if total.is_zero() {
+            return None;
+        }
+        let difference = Decimal::try_from_i128_with_scale(bid_qty - ask_qty, 0).ok()?;
+        difference.checked_div(total)
+    }
+
+    /// Size-weighted mid price of the top of book
+    ///
+    /// `(best_bid * ask_qty + best_ask * bid_qty) / (bid_qty + ask_qty)`,
+    /// which leans toward the side with less resting size. Rounded half away
+    /// from zero. `None` if either side is empty.
+    pub fn microprice(&self) -> Option<Price> {
+        let (bid, bid_qty) = self.bids.first()?;
+        let (ask, ask_qty) = self.asks.first()?;
+
+        let bid_qty = bid_qty.raw_value() as i128;
+        let ask_qty = ask_qty.raw_value() as i128;
+        let total = bid_qty + ask_qty;
+        if total <= 0 {
+            return None;
+        }
+
+        let weighted = bid.raw_value() as i128 * ask_qty + ask.raw_value() as i128 * bid_qty;
+        let half = if weighted >= 0 { total / 2 } else { -total / 2 };
+        i64::try_from((weighted + half) / total)
+            .ok()
+            .map(Price::from_raw)
+    }
+
+    /// CRC32 (IEEE) of the book depth, for feed integrity checks
+    ///
+    /// Covers every level in the snapshot: bids best first, then asks best
+    /// first, each as its raw price then raw quantity in little-endian `i64`.
+    /// A consumer rebuilding its local book with `with_depth` at the same
+    /// depth gets the same value as the engine.
+    pub fn checksum(&self) -> u32 {
+        let mut crc = !0u32;
+        for (price, quantity) in self.bids.iter().chain(&self.asks) {
+            for byte in price
+                .raw_value()
+                .to_le_bytes()
+                .into_iter()
+                .chain(quantity.raw_value().to_le_bytes())
+            {
+                crc ^= byte as u32;
+                for _ in 0..8 {
+                    crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+                }
+            }
+        }
+        !crc
+    }
+
+    /// Level changes needed to turn this snapshot into `other`
+    ///
+    /// Levels are matched by price. Added and changed levels carry the
+    /// quantity in `other`, removed levels the quantity they had here.
+    pub fn diff(&self, other: &OrderBookSnapshot) -> SnapshotDiff {
+        SnapshotDiff {
+            bids: LevelDiff::between(&self.bids, &other.bids),
+            asks: LevelDiff::between(&self.asks, &other.asks),
+        }
+    }
+}
+
+/// Running totals of `levels` from the first (best) level down
+pub(crate) fn cumulative(levels: &[(Price, Quantity)]) -> Vec<Quantity> {
+    levels
+        .iter()
+        .scan(Quantity::ZERO, |total, (_, quantity)| {
+            *total = total.saturating_add(*quantity);
+            Some(*total)
+        })
+        .collect()
+}
+
+#[cfg(feature = "serde")]
+impl OrderBookSnapshot {
+    /// Serialize the full snapshot as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("snapshot serialization cannot fail")
+    }
+
+    /// Compact little-endian encoding of the book depth
+    ///
+    /// Layout: `u32` instrument length and UTF-8 bytes, then for bids and
+    /// asks in turn a `u32` level count followed by raw `i64` price/quantity
+    /// pairs. The same book always encodes to the same bytes. Spread, mid and
+    /// cumulative totals are derived on decode; last-trade fields are not
+    /// carried.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let levels = self.bids.len() + self.asks.len();
+        let mut bytes = Vec::with_capacity(12 + self.instrument.len() + levels * 16);
+
+        bytes.extend_from_slice(&(self.instrument.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(self.instrument.as_bytes());
+        for side in [&self.bids, &self.asks] {
+            bytes.extend_from_slice(&(side.len() as u32).to_le_bytes());
+            for (price, quantity) in side {
+                bytes.extend_from_slice(&price.raw_value().to_le_bytes());
+                bytes.extend_from_slice(&quantity.raw_value().to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Decode a snapshot written by `to_bytes`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = ByteReader { bytes };
+
+        let instrument_len = reader.u32()? as usize;
+        let instrument = String::from_utf8(reader.take(instrument_len)?.to_vec())
+            .map_err(|_| "Instrument is not valid UTF-8".to_string())?;
+
+        let mut read_side = || -> Result<Vec<(Price, Quantity)>, String> {
+            let count = reader.u32()? as usize;
+            (0..count)
+                .map(|_| {
+                    Ok((
+                        Price::from_raw(reader.i64()?),
+                        Quantity::from_raw(reader.i64()?),
+                    ))
+                })
+                .collect()
+        };
+        let bids = read_side()?;
+        let asks = read_side()?;
+
+        if !reader.bytes.is_empty() {
+            return Err(format!(
+                "{} trailing bytes after snapshot",
+                reader.bytes.len()
+            ));
+        }
+        Ok(Self::with_depth(instrument, bids, asks))
+    }
+}
+
+#[cfg(feature = "serde")]
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+}
+
+#[cfg(feature = "serde")]
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() < len {
+            return Err("Snapshot bytes truncated".to_string());
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        Ok(i64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+// ============================================================================
+// Snapshot Diff
+// ============================================================================
+
+/// Level changes on one side between two snapshots
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LevelDiff {
+    /// Levels only in the newer snapshot
+    pub added: Vec<(Price, Quantity)>,
+    /// Levels only in the older snapshot
+    pub removed: Vec<(Price, Quantity)>,
+    /// Levels in both whose quantity moved (newer quantity)
+    pub changed: Vec<(Price, Quantity)>,
+}
+
+impl LevelDiff {
+    fn between(old: &[(Price, Quantity)], new: &[(Price, Quantity)]) -> Self {
+        let find = |levels: &[(Price, Quantity)], price: Price| {
+            levels
+                .iter()
+                .find(|(level, _)| *level == price)
+                .map(|(_, quantity)| *quantity)
+        };
+
+        let mut diff = Self::default();
+        for &(price, quantity) in new {
+            match find(old, price) {
+                None => diff.added.push((price, quantity)),
+                Some(previous) if previous != quantity => diff.changed.push((price, quantity)),
+                Some(_) => {},
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|(price, _)| find(new, *price).is_none())
+            .copied()
+            .collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Result of `OrderBookSnapshot::diff`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SnapshotDiff {
+    pub bids: LevelDiff,
+    pub asks: LevelDiff,
+}
+
+impl SnapshotDiff {
+    /// Whether the two snapshots had identical depth
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+}
+
+// ============================================================================
+// Fill Estimation
+// ============================================================================
+
+/// Result of a read-only sweep of one side of the book
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FillEstimate {
+    /// Quantity that the book could absorb
+    pub filled_quantity: Quantity,
+    /// Volume-weighted average execution price (None if nothing fills)
+    pub average_price: Option<Price>,
+    /// Price of the deepest level reached
+    pub worst_price: Option<Price>,
+    /// Number of price levels touched
+    pub levels_consumed: usize,
+    /// Whether the full requested quantity would fill
+    pub fully_filled: bool,
+    /// Whether the sweep stopped early to keep the average within a price cap
+    pub cap_reached: bool,
+}
+
+// ============================================================================
+// L3 (Order-by-Order) Snapshot
+// ============================================================================
+
+/// A single resting order as seen in an L3 snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OrderBookEntry {
+    pub order_id: OrderId,
+    pub user_id: String,
+    /// Remaining (or, for non-transparent books, displayed) quantity
+    pub remaining: Quantity,
+    pub sequence_number: i64,
+}
+
+/// A price level with its orders in queue (FIFO) order
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct L3Level {
+    pub price: Price,
+    pub orders: Vec<OrderBookEntry>,
+}
+
+/// Order-by-order snapshot of the book, best levels first
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct L3Snapshot {
+    pub instrument: String,
+    pub bids: Vec<L3Level>,
+    pub asks: Vec<L3Level>,
 }
 
 #[cfg(test)]
@@ -264,6 +1029,259 @@ mod tests {
         assert!(!level.is_empty());
     }
 
+    #[test]
+    fn test_order_book_level_remove_order() {
+        let level = OrderBookLevel::new(Price::from_integer(50000).unwrap());
+
+        let orders: Vec<_> = (1..=3)
+            .map(|i| {
+                Arc::new(Order::new(
+                    format!("user{}", i),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(Price::from_integer(50000).unwrap()),
+                    Quantity::from_integer(i).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ))
+            })
+            .collect();
+        for order in &orders {
+            level.add_order(Arc::clone(order));
+        }
+
+        let removed = level.remove_order(orders[1].id).unwrap();
+        assert_eq!(removed.id, orders[1].id);
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(4).unwrap()
+        );
+
+        let remaining: Vec<_> = level.snapshot_orders().iter().map(|o| o.id).collect();
+        assert_eq!(remaining, vec![orders[0].id, orders[2].id]);
+        assert!(level.remove_order(orders[1].id).is_none());
+    }
+
+    #[test]
+    fn test_order_queue_removal_keeps_fifo() {
+        let level = OrderBookLevel::new(Price::from_integer(100).unwrap());
+        let orders: Vec<_> = (1..=5)
+            .map(|i| {
+                Arc::new(Order::new(
+                    "user".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(Price::from_integer(100).unwrap()),
+                    Quantity::from_integer(i).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ))
+            })
+            .collect();
+        for order in &orders {
+            level.add_order(Arc::clone(order));
+        }
+
+        // Pull two orders out of the middle
+        level.remove_order(orders[2].id).unwrap();
+        level.remove_order(orders[1].id).unwrap();
+        assert_eq!(level.order_count(), 3);
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(10).unwrap()
+        );
+        assert!(level.orders.get(orders[2].id).is_none());
+        assert_eq!(level.orders.get(orders[3].id).unwrap().id, orders[3].id);
+
+        // Matching still pops oldest first; a re-pushed order goes to the back
+        let front = level.orders.pop().unwrap();
+        assert_eq!(front.id, orders[0].id);
+        level.orders.push(front);
+        let popped: Vec<_> = std::iter::from_fn(|| level.orders.pop())
+            .map(|o| o.id)
+            .collect();
+        assert_eq!(popped, vec![orders[3].id, orders[4].id, orders[0].id]);
+        assert!(level.is_empty());
+        assert!(level.orders.remove(orders[0].id).is_none());
+    }
+
+    #[test]
+    fn test_level_total_survives_cancel_mid_fill() {
+        let price = Price::from_integer(100).unwrap();
+        let level = OrderBookLevel::new(price);
+        let orders: Vec<_> = (1..=3)
+            .map(|i| {
+                Arc::new(Order::new(
+                    format!("user{}", i),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(price),
+                    Quantity::from_integer(5).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ))
+            })
+            .collect();
+        for order in &orders {
+            level.add_order(Arc::clone(order));
+        }
+        assert_eq!(level.get_total_quantity(), level.recompute_total());
+
+        // Another order is cancelled between a matcher filling the front
+        // order and recording that fill on the level
+        let two = Quantity::from_integer(2).unwrap();
+        assert!(orders[0].try_fill(two));
+        level.remove_order(orders[1].id).unwrap();
+        level.record_fill(&orders[0], two);
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(8).unwrap()
+        );
+        assert_eq!(level.get_total_quantity(), level.recompute_total());
+
+        level.remove_order(orders[0].id).unwrap();
+        assert_eq!(level.recompute_total(), Quantity::from_integer(5).unwrap());
+        assert_eq!(level.get_total_quantity(), level.recompute_total());
+        assert_eq!(level.order_count(), 1);
+    }
+
+    #[test]
+    fn test_side_total_tracks_levels() {
+        let qty = |q| Quantity::from_integer(q).unwrap();
+        let side = OrderBookSide::new(Side::Buy);
+        let orders: Vec<_> = [(100, 5), (100, 3), (99, 4), (98, 2)]
+            .into_iter()
+            .map(|(price, quantity)| {
+                Arc::new(Order::new(
+                    "user".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    qty(quantity),
+                    TimeInForce::GoodTillCancel,
+                ))
+            })
+            .collect();
+        for order in &orders {
+            side.add_order(Arc::clone(order));
+        }
+        assert_eq!(side.total_quantity(), qty(14));
+
+        // Partial fill of the first order at the best level
+        assert!(orders[0].try_fill(qty(2)));
+        side.best_level().unwrap().record_fill(&orders[0], qty(2));
+
+        let remaining = orders.iter().fold(Quantity::ZERO, |total, order| {
+            total + order.get_remaining_quantity()
+        });
+        assert_eq!(side.total_quantity(), remaining);
+        assert_eq!(side.total_quantity(), side.quantity_at_depth(usize::MAX));
+        assert_eq!(side.total_quantity(), side.levels_total());
+
+        // Cancelling the only order at a level takes its quantity with it
+        side.remove_order(&orders[3]).unwrap();
+        assert_eq!(side.total_quantity(), qty(10));
+        assert_eq!(side.total_quantity(), side.levels_total());
+
+        side.clear();
+        assert_eq!(side.total_quantity(), Quantity::ZERO);
+    }
+
+    #[test]
+    fn test_estimate_capped_fill_on_bids() {
+        let px = |p: &str| p.parse::<Price>().unwrap();
+        let fill = |side: &OrderBookSide, cap: &str| {
+            side.estimate_capped_fill(Quantity::from_integer(10).unwrap(), false, px(cap))
+        };
+        let bids = OrderBookSide::new(Side::Buy);
+        let inverted = OrderBookSide::new(Side::Buy).with_inverted_prices(true);
+        for (price, quantity) in [("100", 2), ("99", 2), ("96", 2)] {
+            for side in [&bids, &inverted] {
+                side.add_order(Arc::new(Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(px(price)),
+                    Quantity::from_integer(quantity).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )));
+            }
+        }
+
+        // Selling into bids the average may not drop below the cap:
+        // 2 @ 100 + 2 @ 99 + 1.6 @ 96 = 551.6 over 5.6 => exactly 98.5
+        let estimate = fill(&bids, "98.5");
+        assert_eq!(estimate.filled_quantity, "5.6".parse().unwrap());
+        assert!(estimate.cap_reached);
+        assert_eq!(estimate.worst_price, Some(px("96")));
+        assert_eq!(estimate.average_price, Some(px("98.5")));
+
+        // A loose cap changes nothing
+        let estimate = fill(&bids, "98");
+        assert!(!estimate.cap_reached);
+        assert_eq!(
+            estimate,
+            bids.estimate_fill(Quantity::from_integer(10).unwrap(), false)
+        );
+
+        // Inverted: 96 is the best bid and higher prices are worse, so the
+        // cap is a ceiling: 2 @ 96 + 1 @ 99 => 97
+        let estimate = fill(&inverted, "97");
+        assert_eq!(estimate.filled_quantity, Quantity::from_integer(3).unwrap());
+        assert!(estimate.cap_reached);
+        assert_eq!(estimate.average_price, Some(px("97")));
+    }
+
+    #[test]
+    fn test_level_at_price_and_order_count() {
+        let side = OrderBookSide::new(Side::Sell);
+        let price = Price::from_integer(50000).unwrap();
+        let orders: Vec<_> = (1..=3)
+            .map(|i| {
+                Arc::new(Order::new(
+                    format!("user{}", i),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(price),
+                    Quantity::from_integer(i).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ))
+            })
+            .collect();
+        for order in &orders {
+            side.add_order(Arc::clone(order));
+        }
+
+        assert!(side
+            .level_at_price(Price::from_integer(50001).unwrap())
+            .is_none());
+        let level = side.level_at_price(price).unwrap();
+        assert_eq!(level.order_count(), 3);
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(6).unwrap()
+        );
+
+        // A partial fill keeps the maker counted, a full fill releases it
+        let one = Quantity::from_integer(1).unwrap();
+        assert!(orders[1].try_fill(one));
+        level.record_fill(&orders[1], one);
+        assert_eq!(level.order_count(), 3);
+        assert!(orders[0].try_fill(one));
+        level.record_fill(&orders[0], one);
+        assert_eq!(level.order_count(), 2);
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(4).unwrap()
+        );
+
+        level.remove_order(orders[2].id);
+        assert_eq!(level.order_count(), 1);
+    }
+
     #[test]
     fn test_order_book_side_best_price() {
         let side = OrderBookSide::new(Side::Buy);
@@ -295,6 +1313,83 @@ mod tests {
         assert_eq!(side.best_price(), Some(Price::from_integer(50100).unwrap()));
     }
 
+    #[test]
+    fn test_cumulative_quantity() {
+        let bids = OrderBookSide::new(Side::Buy);
+        let asks = OrderBookSide::new(Side::Sell);
+        // Levels 100..=104 holding 1..=5 on both sides
+        for (i, price) in (100..=104).enumerate() {
+            for (side, book) in [(Side::Buy, &bids), (Side::Sell, &asks)] {
+                book.add_order(Arc::new(Order::new(
+                    "user1".to_string(),
+                    "BTC-USD".to_string(),
+                    side,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(i as i64 + 1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )));
+            }
+        }
+        let qty = |n| Quantity::from_integer(n).unwrap();
+        let price = |p| Price::from_integer(p).unwrap();
+
+        assert_eq!(bids.cumulative_quantity_to_price(price(103)), qty(9));
+        assert_eq!(asks.cumulative_quantity_to_price(price(103)), qty(10));
+        assert_eq!(bids.cumulative_quantity_to_price(price(105)), qty(0));
+        assert_eq!(asks.cumulative_quantity_to_price(price(99)), qty(0));
+        assert_eq!(asks.cumulative_quantity_to_price(price(200)), qty(15));
+
+        assert_eq!(bids.quantity_at_depth(2), qty(9));
+        assert_eq!(asks.quantity_at_depth(2), qty(3));
+        assert_eq!(asks.quantity_at_depth(0), qty(0));
+        assert_eq!(bids.quantity_at_depth(10), qty(15));
+    }
+
+    #[test]
+    fn test_inverted_side_ordering() {
+        let bids = OrderBookSide::new(Side::Buy).with_inverted_prices(true);
+        let asks = OrderBookSide::new(Side::Sell).with_inverted_prices(true);
+        // Levels 100..=102 holding 1..=3 on both sides
+        for (i, price) in (100..=102).enumerate() {
+            for (side, book) in [(Side::Buy, &bids), (Side::Sell, &asks)] {
+                book.add_order(Arc::new(Order::new(
+                    "user1".to_string(),
+                    "BTC-USD".to_string(),
+                    side,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(i as i64 + 1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )));
+            }
+        }
+        let qty = |n| Quantity::from_integer(n).unwrap();
+        let price = |p| Price::from_integer(p).unwrap();
+
+        // Lowest bid and highest ask are best
+        assert_eq!(bids.best_price(), Some(price(100)));
+        assert_eq!(bids.worst_price(), Some(price(102)));
+        assert_eq!(asks.best_price(), Some(price(102)));
+        assert_eq!(asks.best_level().unwrap().price, price(102));
+        assert_eq!(
+            bids.get_depth(2),
+            vec![(price(100), qty(1)), (price(101), qty(2))]
+        );
+        assert_eq!(
+            asks.get_depth(2),
+            vec![(price(102), qty(3)), (price(101), qty(2))]
+        );
+        assert!(bids.is_at_or_better(price(100), price(101)));
+        assert!(asks.is_at_or_better(price(102), price(101)));
+        assert!(asks.has_better_level_than(price(101)));
+        assert!(!asks.has_better_level_than(price(102)));
+
+        // Bids at or below the target, asks at or above it
+        assert_eq!(bids.cumulative_quantity_to_price(price(101)), qty(3));
+        assert_eq!(asks.cumulative_quantity_to_price(price(101)), qty(5));
+    }
+
     #[test]
     fn test_order_book_snapshot() {
         let snapshot = OrderBookSnapshot::with_depth(
@@ -323,4 +1418,161 @@ mod tests {
             Some(Price::from_integer(50050).unwrap())
         );
     }
+
+    #[test]
+    fn test_snapshot_totals_saturate() {
+        let price = Price::from_integer(50000).unwrap();
+        let snapshot = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            vec![(price, Quantity::MAX), (price, Quantity::MAX)],
+            vec![(price, Quantity::from_integer(2).unwrap())],
+        );
+
+        assert_eq!(snapshot.total_bid_quantity(), Quantity::MAX);
+        assert_eq!(
+            snapshot.total_ask_quantity(),
+            Quantity::from_integer(2).unwrap()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_snapshot_export_round_trip() {
+        let empty = OrderBookSnapshot::new("BTC-USD".to_string());
+        let deep = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            (0..100)
+                .map(|i| {
+                    (
+                        Price::from_integer(50000 - i).unwrap() + "0.25".parse::<Price>().unwrap(),
+                        Quantity::from_integer(i + 1).unwrap() + Quantity::from_raw(1),
+                    )
+                })
+                .collect(),
+            (0..100)
+                .map(|i| {
+                    (
+                        Price::from_integer(50001 + i).unwrap(),
+                        Quantity::from_integer(2 * i + 1).unwrap(),
+                    )
+                })
+                .collect(),
+        );
+
+        for snapshot in [empty, deep] {
+            let json: OrderBookSnapshot = serde_json::from_str(&snapshot.to_json()).unwrap();
+            assert_eq!(json, snapshot);
+
+            let bytes = snapshot.to_bytes();
+            assert_eq!(
+                bytes.len(),
+                12 + 7 + 16 * (snapshot.bids.len() + snapshot.asks.len())
+            );
+            assert_eq!(bytes, snapshot.clone().to_bytes());
+            assert_eq!(OrderBookSnapshot::from_bytes(&bytes).unwrap(), snapshot);
+        }
+
+        let bytes = OrderBookSnapshot::new("BTC-USD".to_string()).to_bytes();
+        assert!(OrderBookSnapshot::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_snapshot_checksum_is_crc32() {
+        assert_eq!(OrderBookSnapshot::new("BTC-USD".to_string()).checksum(), 0);
+
+        // Reference value from zlib.crc32 over the same little-endian bytes;
+        // raw values (100, 2, 101 and 0.5 at nine decimals) keep the bytes
+        // the same at any precision
+        let snapshot = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            vec![(
+                Price::from_raw(100_000_000_000),
+                Quantity::from_raw(2_000_000_000),
+            )],
+            vec![(
+                Price::from_raw(101_000_000_000),
+                Quantity::from_raw(500_000_000),
+            )],
+        );
+        assert_eq!(snapshot.checksum(), 0xBBAC_EAE3);
+    }
+
+    #[test]
+    fn test_snapshot_diff() {
+        let px = |p: i64| Price::from_integer(p).unwrap();
+        let qty = |q: i64| Quantity::from_integer(q).unwrap();
+
+        let before = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            vec![(px(100), qty(3)), (px(99), qty(1))],
+            vec![(px(102), qty(2))],
+        );
+        assert!(before.diff(&before.clone()).is_empty());
+
+        // 99 bid leaves, 100 bid shrinks, 98 bid and 103 ask arrive
+        let after = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            vec![(px(100), qty(2)), (px(98), qty(4))],
+            vec![(px(102), qty(2)), (px(103), qty(5))],
+        );
+        let diff = before.diff(&after);
+        assert_eq!(diff.bids.added, vec![(px(98), qty(4))]);
+        assert_eq!(diff.bids.removed, vec![(px(99), qty(1))]);
+        assert_eq!(diff.bids.changed, vec![(px(100), qty(2))]);
+        assert_eq!(diff.asks.added, vec![(px(103), qty(5))]);
+        assert!(diff.asks.removed.is_empty());
+        assert!(diff.asks.changed.is_empty());
+        assert!(!diff.is_empty());
+
+        // Diffing the other way swaps added and removed
+        let reverse = after.diff(&before);
+        assert_eq!(reverse.bids.added, diff.bids.removed);
+        assert_eq!(reverse.bids.removed, diff.bids.added);
+        assert_eq!(reverse.bids.changed, vec![(px(100), qty(3))]);
+    }
+
+    #[test]
+    fn test_imbalance_and_microprice() {
+        let px = |p: i64| Price::from_integer(p).unwrap();
+        let qty = |q: i64| Quantity::from_integer(q).unwrap();
+
+        // Heavy bid side: 3 + 1 against 1 + 5
+        let snapshot = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            vec![(px(100), qty(3)), (px(99), qty(1))],
+            vec![(px(102), qty(1)), (px(103), qty(5))],
+        );
+
+        // Top of book only: (3 - 1) / 4
+        assert_eq!(snapshot.imbalance(1), Some(Decimal::new(5, 1)));
+        // Both levels: (4 - 6) / 10
+        assert_eq!(snapshot.imbalance(2), Some(Decimal::new(-2, 1)));
+        assert_eq!(snapshot.imbalance(10), snapshot.imbalance(2));
+
+        // (100 * 1 + 102 * 3) / 4 = 101.5, pulled toward the thin ask
+        let micro = snapshot.microprice().unwrap();
+        assert_eq!(micro, "101.5".parse::<Price>().unwrap());
+        assert!(micro > snapshot.mid_price.unwrap());
+        assert!(micro > px(100) && micro < px(102));
+
+        // Heavy ask side flips the sign and pulls toward the bid
+        let snapshot = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            vec![(px(100), qty(1))],
+            vec![(px(102), qty(3))],
+        );
+        assert!(snapshot.imbalance(1).unwrap().is_sign_negative());
+        let micro = snapshot.microprice().unwrap();
+        assert!(micro < snapshot.mid_price.unwrap());
+        assert!(micro > px(100) && micro < px(102));
+
+        // One-sided book
+        let snapshot = OrderBookSnapshot::with_depth(
+            "BTC-USD".to_string(),
+            vec![(px(100), qty(1))],
+            Vec::new(),
+        );
+        assert_eq!(snapshot.imbalance(1), None);
+        assert_eq!(snapshot.microprice(), None);
+    }
 }
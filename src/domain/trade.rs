@@ -2,11 +2,12 @@
 // Trade Domain Model
 // ============================================================================
 
-use crate::numeric::{NumericResult, Price, Quantity};
+use crate::numeric::{Notional, NumericError, NumericResult, Price, Quantity, PRICE_DECIMALS};
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use uuid::Uuid;
 
-use super::OrderId;
+use super::{OrderId, Side};
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -18,6 +19,10 @@ pub struct Trade {
     /// Unique trade identifier
     pub id: Uuid,
 
+    /// Monotonic per-engine trade sequence (assigned by the matching engine;
+    /// zero until the trade has been published)
+    pub trade_id: u64,
+
     /// Trading instrument
     pub instrument: String,
 
@@ -33,6 +38,15 @@ pub struct Trade {
     /// Executed quantity
     pub quantity: Quantity,
 
+    /// Side of the aggressive (taker) order
+    pub aggressor_side: Side,
+
+    /// Fee owed by the maker in quote currency (stamped by the matching engine)
+    pub maker_fee: Decimal,
+
+    /// Fee owed by the taker in quote currency (stamped by the matching engine)
+    pub taker_fee: Decimal,
+
     /// Trade timestamp
     pub timestamp: DateTime<Utc>,
 }
@@ -44,14 +58,19 @@ impl Trade {
         taker_order_id: OrderId,
         price: Price,
         quantity: Quantity,
+        aggressor_side: Side,
     ) -> Self {
         Self {
             id: Uuid::new_v4(),
+            trade_id: 0,
             instrument,
             maker_order_id,
             taker_order_id,
             price,
             quantity,
+            aggressor_side,
+            maker_fee: Decimal::ZERO,
+            taker_fee: Decimal::ZERO,
             timestamp: Utc::now(),
         }
     }
@@ -59,8 +78,56 @@ impl Trade {
     /// Calculate the notional value of the trade (price * quantity)
     ///
     /// Returns a Result because multiplication can overflow.
-    pub fn notional_value(&self) -> NumericResult<Price> {
-        self.price.checked_mul(self.quantity)
+    pub fn notional_value(&self) -> NumericResult<Notional> {
+        self.price.notional(self.quantity)
+    }
+}
+
+/// All of one order's fills from a single submission, consolidated
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FillReport {
+    pub order_id: OrderId,
+    /// Sum of the trade quantities
+    pub total_filled: Quantity,
+    /// Notional-weighted average price, rounded to the nearest `Price` step
+    /// (None if nothing filled)
+    pub average_price: Option<Price>,
+    /// Sum of the trade notionals (saturating)
+    pub notional: Notional,
+    /// Engine trade ids of the constituent trades, in execution order
+    pub trade_ids: Vec<u64>,
+}
+
+impl FillReport {
+    /// Summarize the trades in which `order_id` took part (as either side)
+    pub fn from_trades<'a>(order_id: OrderId, trades: impl IntoIterator<Item = &'a Trade>) -> Self {
+        let mut report = Self {
+            order_id,
+            total_filled: Quantity::ZERO,
+            average_price: None,
+            notional: Notional::ZERO,
+            trade_ids: Vec::new(),
+        };
+        for trade in trades {
+            if trade.taker_order_id != order_id && trade.maker_order_id != order_id {
+                continue;
+            }
+            report.total_filled = report.total_filled.saturating_add(trade.quantity);
+            let notional = match trade.notional_value() {
+                Ok(notional) => notional,
+                Err(NumericError::Underflow) => Notional::MIN,
+                Err(_) => Notional::MAX,
+            };
+            report.notional = report.notional.saturating_add(notional);
+            report.trade_ids.push(trade.trade_id);
+        }
+        if report.total_filled.is_positive() {
+            let average = report.notional.to_decimal() / report.total_filled.to_decimal();
+            report.average_price =
+                Price::from_decimal(average.round_dp(PRICE_DECIMALS as u32)).ok();
+        }
+        report
     }
 }
 
@@ -76,14 +143,16 @@ mod tests {
             OrderId::new(),
             Price::from_integer(50000).unwrap(),
             Quantity::from_integer(1).unwrap(),
+            Side::Buy,
         );
 
         assert_eq!(trade.instrument, "BTC-USD");
+        assert_eq!(trade.aggressor_side, Side::Buy);
         assert_eq!(trade.price, Price::from_integer(50000).unwrap());
         assert_eq!(trade.quantity, Quantity::from_integer(1).unwrap());
         assert_eq!(
             trade.notional_value().unwrap(),
-            Price::from_integer(50000).unwrap()
+            Notional::from_integer(50000).unwrap()
         );
     }
 
@@ -93,14 +162,15 @@ mod tests {
             "BTC-USD".to_string(),
             OrderId::new(),
             OrderId::new(),
-            Price::from_parts(100, 500_000_000).unwrap(), // 100.5
+            "100.5".parse::<Price>().unwrap(), // 100.5
             Quantity::from_integer(2).unwrap(),
+            Side::Sell,
         );
 
         // 100.5 * 2 = 201.0
         assert_eq!(
             trade.notional_value().unwrap(),
-            Price::from_integer(201).unwrap()
+            Notional::from_integer(201).unwrap()
         );
     }
 }
@@ -0,0 +1,431 @@
+// ============================================================================
+// Pro-Rata Allocation
+// Shared proportional split used by the pro-rata family of algorithms
+// ============================================================================
+
+use crate::domain::{
+    Order, OrderBookLevel, OrderBookSide, OrderId, OrderOrigin, RemainderPolicy, Trade,
+};
+use crate::interfaces::MatchingAlgorithm;
+use crate::numeric::Quantity;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// How the pro-rata algorithms round the shares they hand out
+#[derive(Debug, Default)]
+pub struct ShareRounding {
+    /// Distribution of the leftover from truncated shares
+    pub remainder_policy: RemainderPolicy,
+    /// Shares are rounded down to multiples of this; `None` allows any size
+    pub min_trade_increment: Option<Quantity>,
+    /// Rotation state for `RemainderPolicy::RoundRobin`
+    remainder_cursor: AtomicUsize,
+}
+
+impl ShareRounding {
+    /// Split `total` across `sizes` with these rounding settings
+    pub(crate) fn split(&self, sizes: &[Quantity], total: Quantity) -> Vec<Quantity> {
+        pro_rata_split(
+            sizes,
+            total,
+            self.remainder_policy,
+            self.min_trade_increment,
+            &self.remainder_cursor,
+        )
+    }
+}
+
+/// Rounding builders shared by the pro-rata family of algorithms
+pub trait ProRataRounding: Sized {
+    /// The algorithm's share rounding settings
+    fn rounding_mut(&mut self) -> &mut ShareRounding;
+
+    /// Set how pro-rata rounding leftovers are distributed
+    fn with_remainder_policy(mut self, policy: RemainderPolicy) -> Self {
+        self.rounding_mut().remainder_policy = policy;
+        self
+    }
+
+    /// Round every pro-rata share down to a multiple of `increment`
+    ///
+    /// The part of an incoming order too small to form an increment is left
+    /// unfilled at the level rather than traded as dust.
+    fn with_min_trade_increment(mut self, increment: Quantity) -> Self {
+        self.rounding_mut().min_trade_increment = Some(increment);
+        self
+    }
+}
+
+/// Match `incoming_order` level by level, filling each maker with the share
+/// `allocate` assigns it at that level
+///
+/// Makers are filled in place and keep their queue position. A level is
+/// revisited until it is exhausted or a pass over it fills nothing.
+pub(crate) fn match_by_allocation<A, F>(
+    algorithm: &A,
+    incoming_order: &Arc<Order>,
+    opposite_side: &OrderBookSide,
+    mut allocate: F,
+) -> Vec<Trade>
+where
+    A: MatchingAlgorithm + ?Sized,
+    F: FnMut(&OrderBookLevel, Quantity) -> Vec<(OrderId, Quantity)>,
+{
+    let mut trades = Vec::new();
+
+    while incoming_order.get_remaining_quantity() > Quantity::ZERO {
+        let best_level = match opposite_side.best_level() {
+            Some(level) => level,
+            None => break,
+        };
+
+        if !algorithm.crosses_level(incoming_order, opposite_side, best_level.price) {
+            break;
+        }
+
+        let remaining_to_fill = incoming_order.get_remaining_quantity();
+        let allocations = allocate(&best_level, remaining_to_fill);
+        if allocations.is_empty() {
+            break;
+        }
+
+        // Execute allocations
+        let mut preempted = false;
+        for (order_id, allocated_qty) in allocations {
+            if allocated_qty <= Quantity::ZERO {
+                continue;
+            }
+
+            // Never trade through: a better level inserted while this one was
+            // being allocated must be taken first
+            if opposite_side.has_better_level_than(best_level.price) {
+                preempted = true;
+                break;
+            }
+
+            if let Some(maker_order) = best_level.orders.get(order_id) {
+                // A share can exceed what the maker holds; the excess is
+                // re-split on the next pass over the level
+                let trade_quantity = allocated_qty.min(maker_order.get_remaining_quantity());
+
+                if trade_quantity > Quantity::ZERO
+                    && maker_order.try_fill(trade_quantity)
+                    && incoming_order.try_fill(trade_quantity)
+                {
+                    let trade = Trade::new(
+                        (*incoming_order.instrument).clone(),
+                        maker_order.id,
+                        incoming_order.id,
+                        maker_order.price().unwrap(),
+                        trade_quantity,
+                        incoming_order.side,
+                    );
+
+                    best_level.record_fill(&maker_order, trade_quantity);
+                    trades.push(trade);
+
+                    if maker_order.get_remaining_quantity() == Quantity::ZERO {
+                        best_level.orders.remove(maker_order.id);
+                    }
+                }
+            }
+
+            if incoming_order.get_remaining_quantity() == Quantity::ZERO {
+                break;
+            }
+        }
+
+        // Clean up empty levels
+        if best_level.is_empty() {
+            opposite_side.remove_empty_levels();
+        }
+
+        // A better level appeared: re-fetch it instead of stopping
+        if preempted {
+            continue;
+        }
+
+        // Prevent infinite loop
+        if incoming_order.get_remaining_quantity() == remaining_to_fill {
+            break;
+        }
+    }
+
+    trades
+}
+
+/// Split `total` across `sizes` in proportion to each size
+///
+/// Shares are truncated to a multiple of `increment` (the smallest quantity
+/// step when `None`); the increments left over by truncation are handed out
+/// according to `policy`. Any part of `total` below one increment is not
+/// allocated and stays with the caller. `cursor` carries the rotation for
+/// [`RemainderPolicy::RoundRobin`] between calls.
+pub(crate) fn pro_rata_split(
+    sizes: &[Quantity],
+    total: Quantity,
+    policy: RemainderPolicy,
+    increment: Option<Quantity>,
+    cursor: &AtomicUsize,
+) -> Vec<Quantity> {
+    let unit = increment.map_or(1, |step| step.raw_value().max(1));
+    let snap = |qty: Quantity| Quantity::from_raw(qty.raw_value() / unit * unit);
+
+    let total = snap(total);
+    // Summed in i128: many large orders can overflow a `Quantity`
+    let eligible: i128 = sizes.iter().map(|size| size.raw_value() as i128).sum();
+    if eligible <= 0 || !total.is_positive() {
+        return vec![Quantity::ZERO; sizes.len()];
+    }
+
+    let mut shares = Vec::with_capacity(sizes.len());
+    let mut fractions = Vec::with_capacity(sizes.len());
+    for size in sizes {
+        let exact = size.raw_value() as i128 * total.raw_value() as i128;
+        // A share never exceeds `total`, so it fits back into an i64
+        let share = (exact / eligible) as i64 / unit * unit;
+        shares.push(share);
+        // Truncated part of the exact share, scaled by `eligible`
+        fractions.push(exact - share as i128 * eligible);
+    }
+
+    let allocated: i64 = shares.iter().sum();
+    // Both sides are multiples of `unit`, so this is a whole number of increments
+    let remainder = (total.raw_value() - allocated) / unit;
+
+    if remainder > 0 {
+        match policy {
+            RemainderPolicy::FirstOrder => shares[0] += remainder * unit,
+            RemainderPolicy::LargestOrder => {
+                // Earliest order wins ties
+                let largest = (0..sizes.len())
+                    .rev()
+                    .max_by_key(|&i| sizes[i])
+                    .unwrap_or(0);
+                shares[largest] += remainder * unit;
+            },
+            RemainderPolicy::LargestFraction => {
+                // The leftover is less than one increment per order, so each of
+                // the orders with the biggest truncated fractions gets one
+                let mut ranked: Vec<usize> = (0..sizes.len()).collect();
+                ranked.sort_by(|&a, &b| fractions[b].cmp(&fractions[a]).then(a.cmp(&b)));
+                for &i in ranked.iter().cycle().take(remainder as usize) {
+                    shares[i] += unit;
+                }
+            },
+            RemainderPolicy::RoundRobin => {
+                let start = cursor.fetch_add(remainder as usize, Ordering::Relaxed);
+                for step in 0..remainder as usize {
+                    shares[(start + step) % sizes.len()] += unit;
+                }
+            },
+        }
+    }
+
+    shares.into_iter().map(Quantity::from_raw).collect()
+}
+
+/// Run `allocate` over natural orders first, then implied ones
+///
+/// Natural orders are offered no more than they can absorb; whatever they
+/// leave unfilled is allocated across the implied orders. Relative order
+/// within each group is preserved. With no implied orders this is a plain
+/// call to `allocate`.
+pub(crate) fn natural_first<F>(
+    orders: &[Arc<Order>],
+    quantity_to_fill: Quantity,
+    mut allocate: F,
+) -> Vec<(OrderId, Quantity)>
+where
+    F: FnMut(&[Arc<Order>], Quantity) -> Vec<(OrderId, Quantity)>,
+{
+    let (natural, implied): (Vec<_>, Vec<_>) = orders
+        .iter()
+        .cloned()
+        .partition(|order| order.origin == OrderOrigin::Natural);
+    if implied.is_empty() {
+        return allocate(&natural, quantity_to_fill);
+    }
+
+    let capacity = natural.iter().fold(Quantity::ZERO, |acc, order| {
+        acc.saturating_add(order.get_remaining_quantity())
+    });
+    let mut allocations = allocate(&natural, quantity_to_fill.min(capacity));
+
+    // Shares can exceed an order's size; count only what can actually fill
+    let served = allocations.iter().fold(Quantity::ZERO, |acc, (id, qty)| {
+        let remaining = natural
+            .iter()
+            .find(|order| order.id == *id)
+            .map_or(Quantity::ZERO, |order| order.get_remaining_quantity());
+        acc.saturating_add((*qty).min(remaining))
+    });
+    let leftover = quantity_to_fill.saturating_sub(served);
+    if leftover.is_positive() {
+        allocations.extend(allocate(&implied, leftover));
+    }
+    allocations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(values: &[i64]) -> Vec<Quantity> {
+        values.iter().map(|&v| Quantity::from_raw(v)).collect()
+    }
+
+    // Sizes 3, 3, 5 splitting 10 units: exact shares 2.72, 2.72, 4.54
+    // -> truncated 2, 2, 4 with 2 units left over
+    const SIZES: [i64; 3] = [3, 3, 5];
+
+    #[test]
+    fn test_first_order_policy() {
+        let cursor = AtomicUsize::new(0);
+        let shares = pro_rata_split(
+            &raw(&SIZES),
+            Quantity::from_raw(10),
+            RemainderPolicy::FirstOrder,
+            None,
+            &cursor,
+        );
+        assert_eq!(shares, raw(&[4, 2, 4]));
+    }
+
+    #[test]
+    fn test_largest_order_policy() {
+        let cursor = AtomicUsize::new(0);
+        let shares = pro_rata_split(
+            &raw(&SIZES),
+            Quantity::from_raw(10),
+            RemainderPolicy::LargestOrder,
+            None,
+            &cursor,
+        );
+        assert_eq!(shares, raw(&[2, 2, 6]));
+    }
+
+    #[test]
+    fn test_largest_fraction_policy() {
+        let cursor = AtomicUsize::new(0);
+        // Sizes 1, 2, 7 splitting 7: exact 0.7, 1.4, 4.9 -> 0, 1, 4 with 2 left
+        // Fractions .7 and .9 win the leftover units
+        let shares = pro_rata_split(
+            &raw(&[1, 2, 7]),
+            Quantity::from_raw(7),
+            RemainderPolicy::LargestFraction,
+            None,
+            &cursor,
+        );
+        assert_eq!(shares, raw(&[1, 1, 5]));
+    }
+
+    #[test]
+    fn test_round_robin_policy_rotates() {
+        let cursor = AtomicUsize::new(0);
+        let first = pro_rata_split(
+            &raw(&SIZES),
+            Quantity::from_raw(10),
+            RemainderPolicy::RoundRobin,
+            None,
+            &cursor,
+        );
+        let second = pro_rata_split(
+            &raw(&SIZES),
+            Quantity::from_raw(10),
+            RemainderPolicy::RoundRobin,
+            None,
+            &cursor,
+        );
+        assert_eq!(first, raw(&[3, 3, 4]));
+        assert_eq!(second, raw(&[3, 2, 5]));
+    }
+
+    #[test]
+    fn test_shares_snap_to_increment() {
+        let cursor = AtomicUsize::new(0);
+        // Sizes 30, 30, 50 splitting 100 in steps of 10: exact 27.2, 27.2, 45.4
+        // -> 20, 20, 40 with two increments left over
+        let shares = pro_rata_split(
+            &raw(&[30, 30, 50]),
+            Quantity::from_raw(100),
+            RemainderPolicy::LargestFraction,
+            Some(Quantity::from_raw(10)),
+            &cursor,
+        );
+        assert_eq!(shares, raw(&[30, 30, 40]));
+    }
+
+    #[test]
+    fn test_sub_increment_leftover_is_not_allocated() {
+        let cursor = AtomicUsize::new(0);
+        // 107 in steps of 10: the last 7 cannot form a trade and is carried
+        let shares = pro_rata_split(
+            &raw(&[50, 50]),
+            Quantity::from_raw(107),
+            RemainderPolicy::FirstOrder,
+            Some(Quantity::from_raw(10)),
+            &cursor,
+        );
+        assert_eq!(shares, raw(&[50, 50]));
+
+        let shares = pro_rata_split(
+            &raw(&[50, 50]),
+            Quantity::from_raw(7),
+            RemainderPolicy::FirstOrder,
+            Some(Quantity::from_raw(10)),
+            &cursor,
+        );
+        assert_eq!(shares, raw(&[0, 0]));
+    }
+
+    /// The i128 split used before the allocation went through `mul_div_trunc`
+    fn reference_shares(sizes: &[i64], total: i64) -> Vec<i64> {
+        let eligible: i128 = sizes.iter().map(|&s| s as i128).sum();
+        sizes
+            .iter()
+            .map(|&s| (s as i128 * total as i128 / eligible) as i64)
+            .collect()
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_truncated_shares_match_reference(
+            sizes in proptest::collection::vec(1i64..1_000_000_000_000, 1..20),
+            total in 0i64..1_000_000_000_000,
+        ) {
+            // Round-robin with a fresh cursor hands leftovers to the first orders,
+            // so subtracting one unit from each of those recovers the truncated split
+            let cursor = AtomicUsize::new(0);
+            let shares = pro_rata_split(
+                &raw(&sizes),
+                Quantity::from_raw(total),
+                RemainderPolicy::RoundRobin,
+                None,
+                &cursor,
+            );
+            let mut expected = reference_shares(&sizes, total);
+            let leftover = total - expected.iter().sum::<i64>();
+            for share in expected.iter_mut().take(leftover as usize) {
+                *share += 1;
+            }
+            proptest::prop_assert_eq!(shares, raw(&expected));
+        }
+    }
+
+    #[test]
+    fn test_policies_preserve_total() {
+        let sizes = raw(&[7, 11, 13, 17]);
+        for policy in [
+            RemainderPolicy::FirstOrder,
+            RemainderPolicy::LargestOrder,
+            RemainderPolicy::LargestFraction,
+            RemainderPolicy::RoundRobin,
+        ] {
+            let cursor = AtomicUsize::new(0);
+            let shares = pro_rata_split(&sizes, Quantity::from_raw(29), policy, None, &cursor);
+            let total: i64 = shares.iter().map(|q| q.raw_value()).sum();
+            assert_eq!(total, 29, "{:?}", policy);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+// ============================================================================
+// Call Auction
+// Single-price uncrossing for opening and closing auctions
+// ============================================================================
+
+use crate::domain::{OrderBookSide, Trade};
+use crate::numeric::{Price, Quantity};
+
+/// Outcome of uncrossing the book in an auction
+#[derive(Debug, Clone, Default)]
+pub struct AuctionResult {
+    /// Price every auction trade executed at (None if the book did not cross)
+    pub clearing_price: Option<Price>,
+    /// Total quantity executed
+    pub matched_quantity: Quantity,
+    /// Trades in execution order
+    pub trades: Vec<Trade>,
+}
+
+/// Price that maximizes executable quantity, with that quantity
+///
+/// Every price resting on either side is a candidate. At price `p` buyers
+/// bid at or above `p` and sellers offer at or below it; the executable
+/// quantity is the smaller of the two. Ties go to the smaller imbalance
+/// between them, then to the lower price.
+pub(crate) fn clearing_price(
+    bids: &OrderBookSide,
+    asks: &OrderBookSide,
+) -> Option<(Price, Quantity)> {
+    let candidates = bids
+        .levels
+        .iter()
+        .chain(asks.levels.iter())
+        .map(|entry| entry.value().price);
+
+    let mut best: Option<(Price, Quantity, Quantity)> = None;
+    for price in candidates {
+        let demand = bids.cumulative_quantity_to_price(price);
+        let supply = asks.cumulative_quantity_to_price(price);
+        let executable = demand.min(supply);
+        if !executable.is_positive() {
+            continue;
+        }
+        let imbalance = demand.max(supply).saturating_sub(executable);
+
+        let better = match best {
+            None => true,
+            Some((best_price, best_executable, best_imbalance)) => {
+                (executable, best_imbalance, best_price) > (best_executable, imbalance, price)
+            },
+        };
+        if better {
+            best = Some((price, executable, imbalance));
+        }
+    }
+
+    best.map(|(price, executable, _)| (price, executable))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Order, OrderType, Side, TimeInForce};
+    use std::sync::Arc;
+
+    fn book(side: Side, levels: &[(i64, i64)]) -> OrderBookSide {
+        let book = OrderBookSide::new(side);
+        for &(price, quantity) in levels {
+            book.add_order(Arc::new(Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(quantity).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+        book
+    }
+
+    #[test]
+    fn test_clearing_price() {
+        let price = |p| Price::from_integer(p).unwrap();
+        let qty = |q| Quantity::from_integer(q).unwrap();
+
+        // Executable: 2 @ 99, 6 @ 100, 7 @ 101, 3 @ 102
+        let bids = book(Side::Buy, &[(102, 3), (101, 4), (100, 5)]);
+        let asks = book(Side::Sell, &[(99, 2), (100, 4), (101, 6), (102, 2)]);
+        assert_eq!(clearing_price(&bids, &asks), Some((price(101), qty(7))));
+
+        // 5 executable at both 100 and 101; 101 leaves the smaller imbalance
+        let bids = book(Side::Buy, &[(101, 5), (100, 3)]);
+        let asks = book(Side::Sell, &[(100, 5), (101, 1)]);
+        assert_eq!(clearing_price(&bids, &asks), Some((price(101), qty(5))));
+
+        // Equal volume and imbalance: the lower price wins
+        let bids = book(Side::Buy, &[(101, 5)]);
+        let asks = book(Side::Sell, &[(100, 5)]);
+        assert_eq!(clearing_price(&bids, &asks), Some((price(100), qty(5))));
+
+        // An uncrossed book has nothing to clear
+        let bids = book(Side::Buy, &[(99, 5)]);
+        assert_eq!(clearing_price(&bids, &asks), None);
+    }
+}
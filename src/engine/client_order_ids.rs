@@ -0,0 +1,80 @@
+// ============================================================================
+// Client Order Ids
+// Rejects a new order whose ClOrdID is already held by a live order
+// ============================================================================
+
+use crate::domain::{Order, OrderId};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// (user id, client order id)
+type Key = (Arc<String>, Arc<String>);
+
+/// Client order ids of live orders, keyed by (user, client order id)
+///
+/// An id is held from acceptance until the order reaches a terminal state.
+/// The engine releases ids as orders leave; an entry whose order is already
+/// terminal counts as free in any case, so a missed release never blocks
+/// reuse.
+pub(crate) struct ClientOrderIds {
+    live: Mutex<HashMap<Key, Arc<Order>>>,
+}
+
+impl ClientOrderIds {
+    pub(crate) fn new() -> Self {
+        Self {
+            live: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Hold `order`'s client order id, returning false if another live order
+    /// of the same user already holds it
+    ///
+    /// Orders without a client order id always succeed.
+    pub(crate) fn claim(&self, order: &Arc<Order>) -> bool {
+        let Some(client_order_id) = &order.client_order_id else {
+            return true;
+        };
+        let key = (Arc::clone(&order.user_id), Arc::clone(client_order_id));
+        let mut live = self.live.lock();
+        if let Some(holder) = live.get(&key) {
+            if holder.id != order.id && !holder.get_state().is_terminal() {
+                return false;
+            }
+        }
+        live.insert(key, Arc::clone(order));
+        true
+    }
+
+    /// Free `order`'s client order id if `order` is the one holding it
+    pub(crate) fn release(&self, order: &Order) {
+        let Some(client_order_id) = &order.client_order_id else {
+            return;
+        };
+        let key = (Arc::clone(&order.user_id), Arc::clone(client_order_id));
+        let mut live = self.live.lock();
+        if live.get(&key).is_some_and(|holder| holder.id == order.id) {
+            live.remove(&key);
+        }
+    }
+
+    /// Id of the live order `user_id` holds `client_order_id` with
+    pub(crate) fn live_order(&self, user_id: &str, client_order_id: &str) -> Option<OrderId> {
+        let key = (
+            Arc::new(user_id.to_string()),
+            Arc::new(client_order_id.to_string()),
+        );
+        self.live
+            .lock()
+            .get(&key)
+            .filter(|holder| !holder.get_state().is_terminal())
+            .map(|holder| holder.id)
+    }
+
+    /// Number of ids currently held
+    #[cfg(test)]
+    pub(crate) fn len(&self) -> usize {
+        self.live.lock().len()
+    }
+}
@@ -3,11 +3,14 @@
 // Creates matching engines with proper configuration
 // ============================================================================
 
-use crate::domain::config::{MatchingAlgorithmType, OrderBookConfig, OrderBookType};
+use crate::domain::config::{
+    ChecksumSchedule, MatchingAlgorithmType, OrderBookConfig, OrderBookType, RemainderPolicy,
+};
 use crate::engine::{
-    LmmPriority, MatchingEngine, PriceTimePriority, ProRata, ProRataTobFifo, ThresholdProRata,
+    Clock, LmmPriority, MatchingEngine, MidpointMatch, PriceTimePriority, ProRata, ProRataRounding,
+    ProRataTobFifo, ThresholdProRata, WeightedProRata,
 };
-use crate::interfaces::{EventHandler, MatchingAlgorithm};
+use crate::interfaces::{CompositeEventHandler, EventHandler, MatchingAlgorithm, NoOpEventHandler};
 use std::sync::Arc;
 
 // ============================================================================
@@ -40,23 +43,26 @@ pub fn create_from_config(
     config.validate()?;
 
     // Create the matching algorithm based on configuration
-    let algorithm = create_matching_algorithm(&config.matching_algorithm)?;
+    let algorithm = create_matching_algorithm(&config)?;
 
     // Create the matching engine
-    let engine = MatchingEngine::new(config.instrument.clone(), algorithm, event_handler);
-
-    // Note: Dark pool visibility is handled at the snapshot/query level
-    // The order book type is stored in the config but enforcement happens
-    // when clients request order book data
+    // Dark pool / hybrid visibility is enforced by the engine at snapshot time
+    let engine = MatchingEngine::with_config(&config, algorithm, event_handler);
 
     Ok(engine)
 }
 
 /// Creates the appropriate matching algorithm from configuration
+///
+/// The pro-rata family also picks up the remainder policy and the minimum
+/// trade increment.
 fn create_matching_algorithm(
-    algo_type: &MatchingAlgorithmType,
+    config: &OrderBookConfig,
 ) -> Result<Box<dyn MatchingAlgorithm>, String> {
-    match algo_type {
+    let policy = config.remainder_policy;
+    let increment = config.min_trade_increment;
+
+    match &config.matching_algorithm {
         MatchingAlgorithmType::PriceTime { use_simd } => {
             Ok(Box::new(PriceTimePriority::new(*use_simd)))
         },
@@ -64,29 +70,55 @@ fn create_matching_algorithm(
         MatchingAlgorithmType::ProRata {
             minimum_quantity,
             top_of_book_fifo,
-        } => Ok(Box::new(ProRata::new(*minimum_quantity, *top_of_book_fifo))),
+        } => {
+            let mut algo =
+                ProRata::new(*minimum_quantity, *top_of_book_fifo).with_remainder_policy(policy);
+            algo.rounding.min_trade_increment = increment;
+            Ok(Box::new(algo))
+        },
 
         MatchingAlgorithmType::ProRataTobFifo { minimum_quantity } => {
-            Ok(Box::new(ProRataTobFifo::new(*minimum_quantity)))
+            let mut algo = ProRataTobFifo::new(*minimum_quantity).with_remainder_policy(policy);
+            algo.rounding.min_trade_increment = increment;
+            Ok(Box::new(algo))
         },
 
         MatchingAlgorithmType::LmmPriority {
             lmm_accounts,
             lmm_allocation_pct,
             minimum_quantity,
-        } => Ok(Box::new(LmmPriority::new(
-            lmm_accounts.iter().cloned().collect(),
-            *lmm_allocation_pct,
-            *minimum_quantity,
-        ))),
+        } => {
+            let mut algo = LmmPriority::new(
+                lmm_accounts.iter().cloned().collect(),
+                *lmm_allocation_pct,
+                *minimum_quantity,
+            )
+            .with_remainder_policy(policy);
+            algo.rounding.min_trade_increment = increment;
+            Ok(Box::new(algo))
+        },
 
         MatchingAlgorithmType::ThresholdProRata {
             threshold,
             minimum_quantity,
-        } => Ok(Box::new(ThresholdProRata::new(
-            *threshold,
-            *minimum_quantity,
-        ))),
+        } => {
+            let mut algo =
+                ThresholdProRata::new(*threshold, *minimum_quantity).with_remainder_policy(policy);
+            algo.rounding.min_trade_increment = increment;
+            Ok(Box::new(algo))
+        },
+
+        MatchingAlgorithmType::WeightedProRata {
+            exponent,
+            minimum_quantity,
+        } => {
+            let mut algo =
+                WeightedProRata::new(*exponent, *minimum_quantity).with_remainder_policy(policy);
+            algo.rounding.min_trade_increment = increment;
+            Ok(Box::new(algo))
+        },
+
+        MatchingAlgorithmType::MidpointMatch => Ok(Box::new(MidpointMatch::new())),
     }
 }
 
@@ -106,26 +138,36 @@ fn create_matching_algorithm(
 /// let engine = MatchingEngineBuilder::new("BTC-USD")
 ///     .transparent_order_book()
 ///     .price_time_matching(true)
-///     .with_tick_size(Price::from_parts(0, 10_000_000).unwrap())
+///     .with_tick_size("0.01".parse::<Price>().unwrap())
 ///     .build(Arc::new(NoOpEventHandler))
 ///     .unwrap();
 /// ```
 pub struct MatchingEngineBuilder {
     config: OrderBookConfig,
+    /// Handlers added with `with_handler`, in call order
+    handlers: Vec<Arc<dyn EventHandler>>,
+    /// Time source (None = system clock)
+    clock: Option<Arc<dyn Clock>>,
 }
 
 impl MatchingEngineBuilder {
-    /// Create a new builder for the specified instrument
-    pub fn new(instrument: impl Into<String>) -> Self {
+    fn from_config(config: OrderBookConfig) -> Self {
         Self {
-            config: OrderBookConfig::new(
-                instrument.into(),
-                OrderBookType::Transparent,
-                MatchingAlgorithmType::PriceTime { use_simd: true },
-            ),
+            config,
+            handlers: Vec::new(),
+            clock: None,
         }
     }
 
+    /// Create a new builder for the specified instrument
+    pub fn new(instrument: impl Into<String>) -> Self {
+        Self::from_config(OrderBookConfig::new(
+            instrument.into(),
+            OrderBookType::Transparent,
+            MatchingAlgorithmType::PriceTime { use_simd: true },
+        ))
+    }
+
     // ========================================================================
     // Order Book Type Configuration
     // ========================================================================
@@ -208,6 +250,25 @@ impl MatchingEngineBuilder {
         self
     }
 
+    /// Configure weighted pro-rata matching (`remaining ^ exponent` weights)
+    pub fn weighted_pro_rata_matching(
+        mut self,
+        exponent: rust_decimal::Decimal,
+        minimum_quantity: crate::numeric::Quantity,
+    ) -> Self {
+        self.config.matching_algorithm = MatchingAlgorithmType::WeightedProRata {
+            exponent,
+            minimum_quantity,
+        };
+        self
+    }
+
+    /// Configure midpoint matching (set the mid with `MatchingEngine::set_reference_mid`)
+    pub fn midpoint_matching(mut self) -> Self {
+        self.config.matching_algorithm = MatchingAlgorithmType::MidpointMatch;
+        self
+    }
+
     // ========================================================================
     // Additional Configuration
     // ========================================================================
@@ -230,15 +291,91 @@ impl MatchingEngineBuilder {
         self
     }
 
+    /// Set how pro-rata algorithms distribute rounding leftovers
+    pub fn with_remainder_policy(mut self, policy: RemainderPolicy) -> Self {
+        self.config.remainder_policy = policy;
+        self
+    }
+
+    /// Set the smallest quantity a pro-rata share may trade
+    pub fn with_min_trade_increment(mut self, increment: crate::numeric::Quantity) -> Self {
+        self.config.min_trade_increment = Some(increment);
+        self
+    }
+
+    /// Set price band (fat-finger collar)
+    pub fn with_price_band(mut self, band: crate::domain::PriceBand) -> Self {
+        self.config.price_band = Some(band);
+        self
+    }
+
+    /// Set maker/taker fee schedule
+    pub fn with_fee_schedule(mut self, fees: crate::domain::FeeSchedule) -> Self {
+        self.config.fee_schedule = fees;
+        self
+    }
+
+    /// Set circuit breaker (halts matching on rapid price moves)
+    pub fn with_circuit_breaker(mut self, breaker: crate::domain::CircuitBreaker) -> Self {
+        self.config.circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Set per-user order rate limit
+    pub fn with_rate_limit(mut self, max_orders_per_sec: u32) -> Self {
+        self.config.max_orders_per_sec = Some(max_orders_per_sec);
+        self
+    }
+
+    /// Set maximum resting orders per price level
+    pub fn with_max_orders_per_level(mut self, max_orders: usize) -> Self {
+        self.config.max_orders_per_level = Some(max_orders);
+        self
+    }
+
+    /// Accept zero and negative prices (spreads, negative-rate products)
+    pub fn with_non_positive_prices(mut self) -> Self {
+        self.config.allow_non_positive_prices = true;
+        self
+    }
+
+    /// Rank prices inverted, for instruments quoted as a yield
+    pub fn with_inverted_prices(mut self) -> Self {
+        self.config.price_inverted = true;
+        self
+    }
+
+    /// Reject orders that would trade with their own user or account group
+    pub fn with_self_trade_prevention(mut self) -> Self {
+        self.config.self_trade_prevention = true;
+        self
+    }
+
+    /// Set the price trades from continuous matching print at
+    pub fn with_trade_price_rule(mut self, rule: crate::domain::TradePriceRule) -> Self {
+        self.config.trade_price_rule = rule;
+        self
+    }
+
+    /// Track positions and P&L per user from fills (needed for reduce-only orders)
+    pub fn with_position_tracking(mut self) -> Self {
+        self.config.track_positions = true;
+        self
+    }
+
+    /// Publish a checksum of the top `depth` levels every `interval` book changes
+    pub fn with_checksum_schedule(mut self, depth: usize, interval: u64) -> Self {
+        self.config.checksum_schedule = Some(ChecksumSchedule::new(depth, interval));
+        self
+    }
+
     // ========================================================================
     // Preset Configurations
     // ========================================================================
 
     /// Apply NASDAQ-style configuration
     pub fn nasdaq_style(instrument: impl Into<String>) -> Self {
-        Self {
-            config: OrderBookConfig::nasdaq_style(instrument.into()),
-        }
+        Self::from_config(OrderBookConfig::nasdaq_style(instrument.into()))
     }
 
     /// Apply CME-style configuration
@@ -246,9 +383,10 @@ impl MatchingEngineBuilder {
         instrument: impl Into<String>,
         minimum_quantity: crate::numeric::Quantity,
     ) -> Self {
-        Self {
-            config: OrderBookConfig::cme_style(instrument.into(), minimum_quantity),
-        }
+        Self::from_config(OrderBookConfig::cme_style(
+            instrument.into(),
+            minimum_quantity,
+        ))
     }
 
     /// Apply Eurex-style configuration
@@ -256,25 +394,57 @@ impl MatchingEngineBuilder {
         instrument: impl Into<String>,
         minimum_quantity: crate::numeric::Quantity,
     ) -> Self {
-        Self {
-            config: OrderBookConfig::eurex_style(instrument.into(), minimum_quantity),
-        }
+        Self::from_config(OrderBookConfig::eurex_style(
+            instrument.into(),
+            minimum_quantity,
+        ))
     }
 
     /// Apply dark pool configuration
     pub fn dark_pool_preset(instrument: impl Into<String>) -> Self {
-        Self {
-            config: OrderBookConfig::dark_pool(instrument.into()),
-        }
+        Self::from_config(OrderBookConfig::dark_pool(instrument.into()))
     }
 
     // ========================================================================
     // Build
     // ========================================================================
 
+    /// Add an event handler; call repeatedly to compose several
+    ///
+    /// Handlers receive every event in the order they were added (see
+    /// [`CompositeEventHandler`] for what happens when one panics).
+    pub fn with_handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    /// Use `clock` for event timestamps and GTD expiry
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
     /// Build the matching engine
+    ///
+    /// `event_handler` runs after any handlers added with `with_handler`.
     pub fn build(self, event_handler: Arc<dyn EventHandler>) -> Result<MatchingEngine, String> {
-        create_from_config(self.config, event_handler)
+        self.with_handler(event_handler).build_with_handlers()
+    }
+
+    /// Build the matching engine with the handlers added via `with_handler`
+    ///
+    /// Without any handlers, events are discarded.
+    pub fn build_with_handlers(mut self) -> Result<MatchingEngine, String> {
+        let event_handler: Arc<dyn EventHandler> = match self.handlers.len() {
+            0 => Arc::new(NoOpEventHandler),
+            1 => self.handlers.remove(0),
+            _ => Arc::new(CompositeEventHandler::new(self.handlers)),
+        };
+        let engine = create_from_config(self.config, event_handler)?;
+        Ok(match self.clock {
+            Some(clock) => engine.with_clock(clock),
+            None => engine,
+        })
     }
 
     /// Get the configuration without building (for inspection)
@@ -310,6 +480,7 @@ mod tests {
         let config = OrderBookConfig::dark_pool("DARK-POOL".to_string());
         let engine = create_from_config(config, Arc::new(NoOpEventHandler)).unwrap();
         assert_eq!(engine.get_instrument(), "DARK-POOL");
+        assert_eq!(engine.get_order_book_type(), OrderBookType::DarkPool);
     }
 
     #[test]
@@ -317,7 +488,7 @@ mod tests {
         let engine = MatchingEngineBuilder::new("BTC-USD")
             .transparent_order_book()
             .price_time_matching(true)
-            .with_tick_size(Price::from_parts(0, 10_000_000).unwrap())
+            .with_tick_size("0.01".parse::<Price>().unwrap())
             .build(Arc::new(NoOpEventHandler))
             .unwrap();
 
@@ -334,6 +505,20 @@ mod tests {
         assert_eq!(engine.get_instrument(), "ETH-USD");
     }
 
+    #[test]
+    fn test_builder_weighted_pro_rata() {
+        let engine = MatchingEngineBuilder::new("ETH-USD")
+            .weighted_pro_rata_matching(rust_decimal::Decimal::new(5, 1), Quantity::ZERO)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        assert_eq!(engine.get_instrument(), "ETH-USD");
+
+        let negative = MatchingEngineBuilder::new("ETH-USD")
+            .weighted_pro_rata_matching(rust_decimal::Decimal::NEGATIVE_ONE, Quantity::ZERO)
+            .build(Arc::new(NoOpEventHandler));
+        assert!(negative.is_err());
+    }
+
     #[test]
     fn test_builder_dark_pool() {
         let engine = MatchingEngineBuilder::new("BLOCK-TRADE")
@@ -354,7 +539,7 @@ mod tests {
         let engine = MatchingEngineBuilder::new("BTC-USD")
             .lmm_priority_matching(
                 lmm_accounts,
-                Quantity::from_parts(0, 400_000_000).unwrap(), // 0.4 = 40%
+                "0.4".parse::<Quantity>().unwrap(), // 0.4 = 40%
                 Quantity::from_integer(10).unwrap(),
             )
             .build(Arc::new(NoOpEventHandler))
@@ -375,4 +560,45 @@ mod tests {
             .unwrap();
         assert_eq!(dark.get_instrument(), "DARK");
     }
+
+    #[test]
+    fn test_with_handler_fans_out_to_each() {
+        use crate::domain::{Order, OrderType, Side, TimeInForce};
+        use crate::interfaces::OrderEvent;
+        use parking_lot::Mutex;
+
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<OrderEvent>>);
+
+        impl EventHandler for Recorder {
+            fn on_event(&self, event: OrderEvent) {
+                self.0.lock().push(event);
+            }
+        }
+
+        let first = Arc::new(Recorder::default());
+        let second = Arc::new(Recorder::default());
+        let engine = MatchingEngineBuilder::nasdaq_style("AAPL")
+            .with_handler(first.clone())
+            .with_handler(second.clone())
+            .build_with_handlers()
+            .unwrap();
+
+        for side in [Side::Sell, Side::Buy] {
+            engine.submit_order(Arc::new(Order::new(
+                "user".to_string(),
+                "AAPL".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(100).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+
+        let first = format!("{:?}", first.0.lock());
+        let second = format!("{:?}", second.0.lock());
+        assert!(first.contains("OrderMatched"));
+        assert_eq!(first, second);
+    }
 }
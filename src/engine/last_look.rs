@@ -0,0 +1,166 @@
+// ============================================================================
+// Last Look
+// RFQ-style hold window letting makers confirm or reject each match
+// ============================================================================
+
+use crate::domain::{Order, OrderBookSide, OrderId, Trade};
+use crate::interfaces::MatchingAlgorithm;
+use crate::numeric::{Price, Quantity};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Last-look wrapper around another matching algorithm
+///
+/// The wrapped algorithm decides who would trade with an incoming order, but
+/// nothing executes on arrival. The engine holds each prospective match,
+/// emits `OrderEvent::LastLookPending` and waits for the maker to call
+/// `confirm_last_look` or `reject_last_look`. A match that is still pending
+/// after `hold` on the engine clock is rejected, either by `expire_last_looks`
+/// or by the expiry reaper.
+///
+/// A taker with held matches never rests: once its last match is resolved,
+/// whatever it did not trade is cancelled. Orders that would not match at
+/// all go through the normal flow.
+pub struct LastLook {
+    /// Algorithm that picks the prospective matches
+    inner: Box<dyn MatchingAlgorithm>,
+    /// How long makers have to respond
+    pub hold: Duration,
+    name: String,
+}
+
+impl LastLook {
+    pub fn new(inner: Box<dyn MatchingAlgorithm>, hold: Duration) -> Self {
+        let name = format!("LastLook({})", inner.name());
+        Self { inner, hold, name }
+    }
+}
+
+impl MatchingAlgorithm for LastLook {
+    fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
+        self.inner.match_order(incoming_order, opposite_side)
+    }
+
+    fn match_order_into(
+        &self,
+        incoming_order: Arc<Order>,
+        opposite_side: &OrderBookSide,
+        out: &mut Vec<Trade>,
+    ) {
+        self.inner
+            .match_order_into(incoming_order, opposite_side, out);
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn set_reference_mid(&self, mid: Price) {
+        self.inner.set_reference_mid(mid);
+    }
+
+    fn supports_market_orders(&self) -> bool {
+        self.inner.supports_market_orders()
+    }
+
+    fn last_look_hold(&self) -> Option<Duration> {
+        Some(self.hold)
+    }
+
+    fn prices_cross(&self, incoming: &Order, book_price: Price) -> bool {
+        self.inner.prices_cross(incoming, book_price)
+    }
+
+    fn crosses_level(&self, incoming: &Order, book: &OrderBookSide, level_price: Price) -> bool {
+        self.inner.crosses_level(incoming, book, level_price)
+    }
+}
+
+/// A prospective match waiting for the maker's decision
+pub(crate) struct PendingMatch {
+    pub maker_id: OrderId,
+    pub taker: Arc<Order>,
+    pub price: Price,
+    pub quantity: Quantity,
+    /// Engine clock time the hold runs out
+    pub deadline: DateTime<Utc>,
+}
+
+/// Trades `algorithm` would produce for `taker`, leaving the book untouched
+///
+/// Matching runs against copies of the taker and of every resting order at a
+/// crossing level, so fills only land on the copies.
+pub(crate) fn prospective_trades(
+    algorithm: &dyn MatchingAlgorithm,
+    taker: &Order,
+    opposite_side: &OrderBookSide,
+) -> Vec<Trade> {
+    let scratch =
+        OrderBookSide::new(opposite_side.side).with_inverted_prices(opposite_side.inverted);
+    for entry in opposite_side.levels.iter() {
+        let level = entry.value();
+        if !algorithm.crosses_level(taker, opposite_side, level.price) {
+            continue;
+        }
+        for order in level.snapshot_orders() {
+            scratch.add_order(Arc::new((*order).clone()));
+        }
+    }
+
+    algorithm.match_order(Arc::new(taker.clone()), &scratch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OrderType, Side, TimeInForce};
+    use crate::engine::PriceTimePriority;
+
+    #[test]
+    fn test_prospective_trades_leave_book_untouched() {
+        let algo = LastLook::new(
+            Box::new(PriceTimePriority::new(false)),
+            Duration::from_secs(1),
+        );
+        assert_eq!(algo.name(), "LastLook(PriceTime)");
+
+        let asks = OrderBookSide::new(Side::Sell);
+        let maker = Arc::new(Order::new(
+            "maker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(100).unwrap()),
+            Quantity::from_integer(5).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        asks.add_order(Arc::clone(&maker));
+        let taker = Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(100).unwrap()),
+            Quantity::from_integer(3).unwrap(),
+            TimeInForce::GoodTillCancel,
+        );
+
+        let trades = prospective_trades(&algo, &taker, &asks);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].maker_order_id, maker.id);
+        assert_eq!(trades[0].quantity, Quantity::from_integer(3).unwrap());
+        assert_eq!(
+            maker.get_remaining_quantity(),
+            Quantity::from_integer(5).unwrap()
+        );
+        assert_eq!(
+            taker.get_remaining_quantity(),
+            Quantity::from_integer(3).unwrap()
+        );
+        assert_eq!(
+            asks.best_level().unwrap().get_total_quantity(),
+            Quantity::from_integer(5).unwrap()
+        );
+    }
+}
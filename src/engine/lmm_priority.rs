@@ -3,6 +3,7 @@
 // Used by many derivatives exchanges to incentivize market makers
 // ============================================================================
 
+use super::allocation::{match_by_allocation, natural_first, ProRataRounding, ShareRounding};
 use crate::domain::{Order, OrderBookLevel, OrderBookSide, OrderId, Trade};
 use crate::interfaces::MatchingAlgorithm;
 use crate::numeric::Quantity;
@@ -47,10 +48,16 @@ pub struct LmmPriority {
     pub lmm_accounts: HashSet<String>,
 
     /// Percentage of each trade allocated to LMMs (e.g., 0.4 for 40%)
+    ///
+    /// Clamped to [0, 1]. At 0 the level is split plain pro-rata; at 1 the
+    /// LMMs absorb all they can before anyone else is allocated.
     pub lmm_allocation_pct: Quantity,
 
     /// Minimum order size to participate in pro-rata allocation
     pub minimum_quantity: Quantity,
+
+    /// Rounding of the pro-rata shares
+    pub rounding: ShareRounding,
 }
 
 impl LmmPriority {
@@ -63,6 +70,7 @@ impl LmmPriority {
             lmm_accounts: lmm_accounts.into_iter().collect(),
             lmm_allocation_pct,
             minimum_quantity,
+            rounding: ShareRounding::default(),
         }
     }
 
@@ -77,21 +85,26 @@ impl LmmPriority {
         level: &OrderBookLevel,
         quantity_to_fill: Quantity,
     ) -> Vec<(OrderId, Quantity)> {
-        let mut allocations = Vec::new();
-
-        // Collect all orders from the level
-        let mut all_orders = Vec::new();
-        while let Some(order) = level.orders.pop() {
-            all_orders.push(order);
-        }
-
+        // Orders in queue order
+        let all_orders = level.snapshot_orders();
         if all_orders.is_empty() {
-            return allocations;
+            return Vec::new();
         }
 
-        // Put all orders back
-        for order in all_orders.iter().rev() {
-            level.orders.push(Arc::clone(order));
+        natural_first(&all_orders, quantity_to_fill, |orders, quantity| {
+            self.allocate(orders, quantity)
+        })
+    }
+
+    /// LMM share first, then pro-rata across every eligible order
+    fn allocate(
+        &self,
+        orders: &[Arc<Order>],
+        quantity_to_fill: Quantity,
+    ) -> Vec<(OrderId, Quantity)> {
+        let mut allocations = Vec::new();
+        if orders.is_empty() {
+            return allocations;
         }
 
         // Separate LMM and non-LMM orders, filter by minimum quantity
@@ -100,7 +113,7 @@ impl LmmPriority {
         let mut lmm_total_quantity = Quantity::ZERO;
         let mut total_eligible_quantity = Quantity::ZERO;
 
-        for order in all_orders.iter() {
+        for order in orders.iter() {
             let remaining = order.get_remaining_quantity();
 
             if remaining >= self.minimum_quantity {
@@ -120,60 +133,44 @@ impl LmmPriority {
 
         // Step 1: LMM allocation
         // lmm_allocation_qty = quantity_to_fill * lmm_allocation_pct
-        // Since lmm_allocation_pct is stored as a Quantity (e.g., 0.4 = 400_000_000 raw),
-        // we need to multiply and then divide by 10^9
+        // Since lmm_allocation_pct is stored as a Quantity (e.g., 0.4 = 0.4 * SCALE raw),
+        // we need to multiply and then divide by the scale. Capped at the LMMs' own
+        // size so an unfillable share never crowds out the pro-rata step.
+        let lmm_allocation_pct = self.lmm_allocation_pct.clamp(Quantity::ZERO, Quantity::ONE);
         let lmm_allocation_qty = Quantity::from_raw(
-            (quantity_to_fill.raw_value() as i128 * self.lmm_allocation_pct.raw_value() as i128
-                / 1_000_000_000) as i64,
-        );
+            (quantity_to_fill.raw_value() as i128 * lmm_allocation_pct.raw_value() as i128
+                / Quantity::SCALE as i128) as i64,
+        )
+        .min(lmm_total_quantity);
         let mut lmm_allocated = Quantity::ZERO;
 
+        // No LMM orders (or a zero percentage) skips straight to pro-rata
         if lmm_total_quantity > Quantity::ZERO && lmm_allocation_qty > Quantity::ZERO {
-            let lmm_total_raw = lmm_total_quantity.raw_value();
-            let lmm_alloc_raw = lmm_allocation_qty.raw_value();
-
-            for (order_id, order_quantity) in lmm_orders.iter() {
-                let order_raw = order_quantity.raw_value();
-                let allocation_raw =
-                    ((order_raw as i128 * lmm_alloc_raw as i128) / lmm_total_raw as i128) as i64;
-                let allocation = Quantity::from_raw(allocation_raw);
+            let sizes: Vec<Quantity> = lmm_orders.iter().map(|(_, qty)| *qty).collect();
+            let shares = self.rounding.split(&sizes, lmm_allocation_qty);
 
+            for ((order_id, _), allocation) in lmm_orders.iter().zip(shares) {
                 allocations.push((*order_id, allocation));
                 lmm_allocated = lmm_allocated + allocation;
             }
-
-            // Handle LMM remainder - give to first LMM
-            let lmm_remainder = lmm_allocation_qty - lmm_allocated;
-            if lmm_remainder > Quantity::ZERO && !allocations.is_empty() {
-                allocations[0].1 = allocations[0].1 + lmm_remainder;
-                lmm_allocated = lmm_allocated + lmm_remainder;
-            }
         }
 
-        // Step 2: Pro-rata allocation for remaining quantity among ALL eligible orders
+        // Step 2: Pro-rata allocation for remaining quantity among ALL eligible orders,
+        // except LMM orders the first step already filled in full
         let remaining_qty = quantity_to_fill - lmm_allocated;
-
-        if remaining_qty > Quantity::ZERO && total_eligible_quantity > Quantity::ZERO {
-            let mut prorata_allocated = Quantity::ZERO;
-            let mut prorata_allocs = Vec::new();
-            let total_eligible_raw = total_eligible_quantity.raw_value();
-            let remaining_raw = remaining_qty.raw_value();
-
-            for (order_id, order_quantity, _is_lmm) in all_eligible_orders.iter() {
-                let order_raw = order_quantity.raw_value();
-                let allocation_raw = ((order_raw as i128 * remaining_raw as i128)
-                    / total_eligible_raw as i128) as i64;
-                let allocation = Quantity::from_raw(allocation_raw);
-
-                prorata_allocs.push((*order_id, allocation));
-                prorata_allocated = prorata_allocated + allocation;
-            }
-
-            // Handle pro-rata remainder
-            let prorata_remainder = remaining_qty - prorata_allocated;
-            if prorata_remainder > Quantity::ZERO && !prorata_allocs.is_empty() {
-                prorata_allocs[0].1 = prorata_allocs[0].1 + prorata_remainder;
-            }
+        all_eligible_orders.retain(|(order_id, remaining, _)| {
+            !allocations
+                .iter()
+                .any(|(id, allocated)| id == order_id && allocated >= remaining)
+        });
+
+        if remaining_qty > Quantity::ZERO && !all_eligible_orders.is_empty() {
+            let sizes: Vec<Quantity> = all_eligible_orders.iter().map(|(_, qty, _)| *qty).collect();
+            let shares = self.rounding.split(&sizes, remaining_qty);
+            let prorata_allocs = all_eligible_orders
+                .iter()
+                .map(|(order_id, _, _)| *order_id)
+                .zip(shares);
 
             // Merge allocations (sum up for orders that appear in both lists)
             for (order_id, prorata_qty) in prorata_allocs {
@@ -189,95 +186,17 @@ impl LmmPriority {
     }
 }
 
+impl ProRataRounding for LmmPriority {
+    fn rounding_mut(&mut self) -> &mut ShareRounding {
+        &mut self.rounding
+    }
+}
+
 impl MatchingAlgorithm for LmmPriority {
     fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
-        let mut trades = Vec::new();
-
-        while incoming_order.get_remaining_quantity() > Quantity::ZERO {
-            let best_level = match opposite_side.best_level() {
-                Some(level) => level,
-                None => break,
-            };
-
-            if !self.prices_cross(&incoming_order, best_level.price) {
-                break;
-            }
-
-            let remaining_to_fill = incoming_order.get_remaining_quantity();
-
-            // Calculate allocation (LMM priority + pro-rata)
-            let allocations = self.calculate_allocation(&best_level, remaining_to_fill);
-
-            if allocations.is_empty() {
-                break;
-            }
-
-            // Execute allocations
-            for (order_id, allocated_qty) in allocations {
-                if allocated_qty <= Quantity::ZERO {
-                    continue;
-                }
-
-                // Find the order in the level
-                let mut found_order: Option<Arc<Order>> = None;
-                let mut temp_orders = Vec::new();
-
-                while let Some(order) = best_level.orders.pop() {
-                    if order.id == order_id {
-                        found_order = Some(order);
-                        break;
-                    } else {
-                        temp_orders.push(order);
-                    }
-                }
-
-                // Put back orders we didn't match
-                for order in temp_orders.into_iter().rev() {
-                    best_level.orders.push(order);
-                }
-
-                if let Some(maker_order) = found_order {
-                    let trade_quantity = allocated_qty.min(maker_order.get_remaining_quantity());
-
-                    if trade_quantity > Quantity::ZERO
-                        && maker_order.try_fill(trade_quantity)
-                        && incoming_order.try_fill(trade_quantity)
-                    {
-                        let trade = Trade::new(
-                            (*incoming_order.instrument).clone(),
-                            maker_order.id,
-                            incoming_order.id,
-                            maker_order.price.unwrap(),
-                            trade_quantity,
-                        );
-
-                        best_level.subtract_quantity(trade_quantity);
-                        trades.push(trade);
-
-                        // Put maker order back if not fully filled
-                        if maker_order.get_remaining_quantity() > Quantity::ZERO {
-                            best_level.orders.push(maker_order);
-                        }
-                    }
-                }
-
-                if incoming_order.get_remaining_quantity() == Quantity::ZERO {
-                    break;
-                }
-            }
-
-            // Clean up empty levels
-            if best_level.is_empty() {
-                opposite_side.remove_empty_levels();
-            }
-
-            // Prevent infinite loop
-            if incoming_order.get_remaining_quantity() == remaining_to_fill {
-                break;
-            }
-        }
-
-        trades
+        match_by_allocation(self, &incoming_order, opposite_side, |level, quantity| {
+            self.calculate_allocation(level, quantity)
+        })
     }
 
     fn name(&self) -> &str {
@@ -289,14 +208,52 @@ impl MatchingAlgorithm for LmmPriority {
 mod tests {
     use super::*;
     use crate::domain::{OrderType, Side, TimeInForce};
+    use crate::engine::ProRata;
     use crate::numeric::Price;
 
+    fn sell(user: &str, size: i64) -> Arc<Order> {
+        Arc::new(Order::new(
+            user.to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(size).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ))
+    }
+
+    /// Fill of each resting sell, in book order, after `algo` matches a buy
+    /// of `quantity` against a level of (user, size) orders
+    fn fills(algo: &dyn MatchingAlgorithm, book: &[(&str, i64)], quantity: i64) -> Vec<Quantity> {
+        let side = OrderBookSide::new(Side::Sell);
+        let resting: Vec<Arc<Order>> = book.iter().map(|&(user, size)| sell(user, size)).collect();
+        for order in &resting {
+            side.add_order(Arc::clone(order));
+        }
+
+        let buy = Arc::new(Order::new(
+            "buyer".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(quantity).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        algo.match_order(buy, &side);
+        resting
+            .iter()
+            .map(|order| order.get_filled_quantity())
+            .collect()
+    }
+
     #[test]
     fn test_lmm_priority_allocation() {
         // Setup: 40% LMM allocation, 10 BTC minimum
         let algo = LmmPriority::new(
             vec!["mm1".to_string(), "mm2".to_string()],
-            Quantity::from_parts(0, 400_000_000).unwrap(), // 0.4 = 40%
+            "0.4".parse::<Quantity>().unwrap(), // 0.4 = 40%
             Quantity::from_integer(10).unwrap(),
         );
 
@@ -396,7 +353,7 @@ mod tests {
         // Only LMM orders in book
         let algo = LmmPriority::new(
             vec!["mm1".to_string()],
-            Quantity::from_parts(0, 500_000_000).unwrap(), // 0.5 = 50%
+            "0.5".parse::<Quantity>().unwrap(), // 0.5 = 50%
             Quantity::ZERO,
         );
 
@@ -437,7 +394,7 @@ mod tests {
         // No LMM orders - should behave like regular pro-rata
         let algo = LmmPriority::new(
             vec!["mm1".to_string()],
-            Quantity::from_parts(0, 400_000_000).unwrap(),
+            "0.4".parse::<Quantity>().unwrap(),
             Quantity::ZERO,
         );
 
@@ -489,7 +446,7 @@ mod tests {
     fn test_lmm_empty_book() {
         let algo = LmmPriority::new(
             vec!["mm1".to_string()],
-            Quantity::from_parts(0, 400_000_000).unwrap(),
+            "0.4".parse::<Quantity>().unwrap(),
             Quantity::ZERO,
         );
 
@@ -508,4 +465,52 @@ mod tests {
         let trades = algo.match_order(buy, &side);
         assert!(trades.is_empty(), "No trades with empty book");
     }
+
+    #[test]
+    fn test_zero_pct_and_no_lmms_equal_pro_rata() {
+        let pro_rata = ProRata::new(Quantity::ZERO, false);
+        let lmms = vec!["mm1".to_string(), "mm2".to_string()];
+
+        // Odd sizes leave rounding remainders to hand out
+        let mixed = [("mm1", 30), ("user1", 50), ("mm2", 20), ("user2", 7)];
+        let zero_pct = LmmPriority::new(lmms.clone(), Quantity::ZERO, Quantity::ZERO);
+        assert_eq!(fills(&zero_pct, &mixed, 41), fills(&pro_rata, &mixed, 41));
+
+        // A positive percentage with no LMM resting degrades to pro-rata
+        let no_lmms = [("user1", 30), ("user2", 50), ("user3", 7)];
+        let forty_pct = LmmPriority::new(lmms, "0.4".parse::<Quantity>().unwrap(), Quantity::ZERO);
+        assert_eq!(
+            fills(&forty_pct, &no_lmms, 41),
+            fills(&pro_rata, &no_lmms, 41)
+        );
+    }
+
+    #[test]
+    fn test_full_pct_fills_lmms_first() {
+        let book = [("mm1", 30), ("user1", 50), ("user2", 20)];
+        let qty = |n| Quantity::from_integer(n).unwrap();
+
+        // Percentages above one are treated as one
+        for pct in [Quantity::ONE, qty(2)] {
+            let algo = LmmPriority::new(vec!["mm1".to_string()], pct, Quantity::ZERO);
+
+            // The LMM absorbs the whole order when it can
+            assert_eq!(fills(&algo, &book, 20), vec![qty(20), qty(0), qty(0)]);
+
+            // Only the leftover beyond the LMM's size is split, among the others
+            let filled = fills(&algo, &book, 40);
+            assert_eq!(filled[0], qty(30));
+            assert_eq!(filled[1] + filled[2], qty(10));
+            assert!(filled[1] > filled[2]);
+
+            // ...and within a single allocation pass, not by retrying the level
+            let orders: Vec<_> = book.iter().map(|&(user, size)| sell(user, size)).collect();
+            let allocations = algo.allocate(&orders, qty(40));
+            assert_eq!(allocations[0], (orders[0].id, qty(30)));
+            let allocated = allocations
+                .iter()
+                .fold(Quantity::ZERO, |total, (_, share)| total + *share);
+            assert_eq!(allocated, qty(40));
+        }
+    }
 }
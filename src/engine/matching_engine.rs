@@ -4,37 +4,181 @@
 // ============================================================================
 
 use crate::domain::order::state::OrderState;
-use crate::domain::{Order, OrderBookSide, OrderBookSnapshot, OrderId, Side};
-use crate::interfaces::{EventHandler, MatchingAlgorithm, OrderEvent};
+use crate::domain::order_book::cumulative;
+use crate::domain::{
+    ChecksumSchedule, CircuitBreaker, FeeSchedule, FillEstimate, FillReport, L3Snapshot, Order,
+    OrderBookConfig, OrderBookLevel, OrderBookSide, OrderBookSnapshot, OrderBookType, OrderId,
+    OrderStatus, OrderType, PegReference, PriceBand, Side, Trade, TradePriceRule,
+};
+use crate::engine::auction::{self, AuctionResult};
+use crate::engine::client_order_ids::ClientOrderIds;
+use crate::engine::last_look::{self, PendingMatch};
+use crate::engine::rate_limit::RateLimiter;
+use crate::engine::reaper::ReaperHandle;
+use crate::engine::simulation::ArrivalQueue;
+use crate::engine::{
+    Clock, EngineStats, MockClock, Position, PositionBook, PriceTimePriority, SystemClock,
+};
+use crate::interfaces::{
+    EventHandler, MatchingAlgorithm, NoOpEventHandler, OrderEvent, RejectReason, SequencedEvent,
+};
+#[cfg(feature = "metrics")]
+use crate::metrics::{EngineMetrics, PrometheusWriter};
 use crate::numeric::{Price, Quantity};
-use chrono::Utc;
-use parking_lot::RwLock;
-use std::collections::HashMap;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::platform::pin_current_thread_to_core;
+use chrono::{DateTime, Utc};
+use crossbeam::channel::{self, Receiver, Sender};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Lock-free matching engine with pluggable matching algorithm
 pub struct MatchingEngine {
     /// Trading instrument (e.g., "BTC-USD")
     instrument: Arc<String>,
 
+    /// Order book transparency (controls what snapshots reveal)
+    order_book_type: OrderBookType,
+
+    /// Optional collar around a reference price (reference moves with the market)
+    price_band: RwLock<Option<PriceBand>>,
+
+    /// Maximum number of price levels per side (None = unlimited)
+    max_depth: Option<usize>,
+
+    /// Maximum resting orders at one price (None = unlimited)
+    max_orders_per_level: Option<usize>,
+
+    /// Accept zero and negative prices
+    allow_non_positive_prices: bool,
+
+    /// Prices must be multiples of this (None = no tick enforcement)
+    tick_size: Option<Price>,
+
+    /// Quantities must be multiples of this (None = no lot enforcement)
+    lot_size: Option<Quantity>,
+
+    /// Reject orders that would trade with their own user or account group
+    self_trade_prevention: bool,
+
+    /// Maker/taker fees stamped on each trade
+    fee_schedule: FeeSchedule,
+
+    /// Price trades from continuous matching print at
+    trade_price_rule: TradePriceRule,
+
+    /// Optional halt on rapid price moves
+    circuit_breaker: Option<CircuitBreaker>,
+
+    /// Set when the circuit breaker trips, cleared by `resume`
+    halted: AtomicBool,
+
+    /// While set, orders rest without matching until `run_auction`
+    in_auction: AtomicBool,
+
+    /// Set by `begin_shutdown`: new orders are rejected
+    draining: AtomicBool,
+
+    /// Trade prices inside the circuit breaker window with their clock
+    /// times, oldest first
+    recent_trades: Mutex<VecDeque<(DateTime<Utc>, Price)>>,
+
+    /// Per-user order rate limit (None = unlimited)
+    rate_limiter: Option<RateLimiter>,
+
+    /// Bumped after every book change; cached snapshots are tagged with it
+    book_version: AtomicU64,
+
+    /// Periodic book checksum (None = never published)
+    checksum_schedule: Option<ChecksumSchedule>,
+
+    /// Last snapshot served as (book version, depth, snapshot)
+    snapshot_cache: RwLock<Option<(u64, usize, OrderBookSnapshot)>>,
+
+    /// Running trade aggregates
+    statistics: Mutex<EngineStats>,
+
+    /// Most recent trade as (trade id, price, quantity)
+    last_trade: Mutex<Option<(u64, Price, Quantity)>>,
+
+    /// One-cancels-other links, stored in both directions
+    oco_links: RwLock<HashMap<OrderId, OrderId>>,
+
+    /// Resting pegged orders, repriced when their reference moves
+    pegged_orders: RwLock<HashSet<OrderId>>,
+
+    /// Matches held for the maker's last look, by match id
+    pending_last_looks: Mutex<HashMap<u64, PendingMatch>>,
+
+    /// Last issued last-look match id
+    last_look_counter: AtomicU64,
+
     /// Bid side of the order book
     bids: OrderBookSide,
 
     /// Ask side of the order book
     asks: OrderBookSide,
 
-    /// Pluggable matching algorithm
-    algorithm: Box<dyn MatchingAlgorithm>,
+    /// Pluggable matching algorithm (write-locked only to swap it)
+    algorithm: RwLock<Box<dyn MatchingAlgorithm>>,
+
+    /// Reusable trade buffer for `process_order`'s matching step
+    scratch_trades: Mutex<Vec<Trade>>,
 
     /// Order index for fast lookups (for cancellations)
     order_index: Arc<RwLock<HashMap<OrderId, Arc<Order>>>>,
 
+    /// Client order ids held by live orders
+    client_order_ids: ClientOrderIds,
+
     /// Event handler for processing events
     event_handler: Arc<dyn EventHandler>,
 
+    /// Last sequence number handed to an emitted event
+    event_sequence: AtomicU64,
+
+    /// Held while a batch's book deltas are read and numbered (see `seal`)
+    feed: Mutex<()>,
+
+    /// Debug builds: book mutations in progress and ever started, so
+    /// `debug_check_levels` knows when no other thread can be mid-update
+    writers: AtomicUsize,
+    writes_started: AtomicU64,
+
+    /// Core the constructing thread was pinned to (see `with_affinity`)
+    pinned_core: Option<usize>,
+
     /// Sequence counter for order sequencing
     sequence_counter: AtomicU64,
+
+    /// Trade id counter (ids start at 1)
+    trade_counter: AtomicU64,
+
+    /// Subscribers to incremental book updates
+    delta_subscribers: RwLock<Vec<Sender<OrderEvent>>>,
+
+    /// Best bid and best ask (price, quantity) as last published
+    last_bbo: Mutex<[Option<(Price, Quantity)>; 2]>,
+
+    /// Position and realized P&L per user, updated on every fill (None =
+    /// not tracked)
+    positions: Option<PositionBook>,
+
+    /// Time source for event and trade timestamps
+    clock: Arc<dyn Clock>,
+
+    /// Clock driven by `advance_to` (None unless `with_simulated_time`)
+    simulated_clock: Option<Arc<MockClock>>,
+
+    /// Orders submitted with `submit_order_at`, waiting for their arrival time
+    arrivals: ArrivalQueue,
+
+    /// Hot-path counters for `render_prometheus`
+    #[cfg(feature = "metrics")]
+    metrics: EngineMetrics,
 }
 
 impl MatchingEngine {
@@ -46,34 +190,308 @@ impl MatchingEngine {
     ) -> Self {
         Self {
             instrument: Arc::new(instrument),
+            order_book_type: OrderBookType::Transparent,
+            price_band: RwLock::new(None),
+            max_depth: None,
+            max_orders_per_level: None,
+            allow_non_positive_prices: false,
+            tick_size: None,
+            lot_size: None,
+            self_trade_prevention: false,
+            fee_schedule: FeeSchedule::default(),
+            trade_price_rule: TradePriceRule::default(),
+            circuit_breaker: None,
+            halted: AtomicBool::new(false),
+            in_auction: AtomicBool::new(false),
+            draining: AtomicBool::new(false),
+            recent_trades: Mutex::new(VecDeque::new()),
+            rate_limiter: None,
+            book_version: AtomicU64::new(0),
+            checksum_schedule: None,
+            snapshot_cache: RwLock::new(None),
+            statistics: Mutex::new(EngineStats::default()),
+            last_trade: Mutex::new(None),
+            oco_links: RwLock::new(HashMap::new()),
+            pegged_orders: RwLock::new(HashSet::new()),
+            pending_last_looks: Mutex::new(HashMap::new()),
+            last_look_counter: AtomicU64::new(0),
             bids: OrderBookSide::new(Side::Buy),
             asks: OrderBookSide::new(Side::Sell),
-            algorithm,
+            algorithm: RwLock::new(algorithm),
+            scratch_trades: Mutex::new(Vec::new()),
             order_index: Arc::new(RwLock::new(HashMap::new())),
+            client_order_ids: ClientOrderIds::new(),
             event_handler,
+            event_sequence: AtomicU64::new(0),
+            feed: Mutex::new(()),
+            writers: AtomicUsize::new(0),
+            writes_started: AtomicU64::new(0),
+            pinned_core: None,
             sequence_counter: AtomicU64::new(0),
+            trade_counter: AtomicU64::new(0),
+            delta_subscribers: RwLock::new(Vec::new()),
+            last_bbo: Mutex::new([None, None]),
+            positions: None,
+            clock: Arc::new(SystemClock),
+            simulated_clock: None,
+            arrivals: ArrivalQueue::new(),
+            #[cfg(feature = "metrics")]
+            metrics: EngineMetrics::default(),
         }
     }
 
+    /// Use `clock` for event timestamps, trade timestamps and GTD expiry
+    ///
+    /// Defaults to [`SystemClock`]; pass a [`MockClock`](crate::engine::MockClock)
+    /// for reproducible event streams.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Run on simulated time starting at `start`, for backtests
+    ///
+    /// Orders are submitted with [`submit_order_at`](Self::submit_order_at)
+    /// and processed by [`advance_to`](Self::advance_to), which also moves
+    /// the clock. Replaces any clock set with `with_clock`.
+    pub fn with_simulated_time(mut self, start: DateTime<Utc>) -> Self {
+        let clock = Arc::new(MockClock::new(start));
+        self.clock = Arc::clone(&clock) as Arc<dyn Clock>;
+        self.simulated_clock = Some(clock);
+        self
+    }
+
+    /// Create a matching engine and pin the calling thread to `core_id`
+    ///
+    /// Keeps the thread that will drive the engine on one core so its caches
+    /// stay warm; submit orders from this same thread. Pinning needs the
+    /// `numa` feature: without it (or if the core does not exist) this is
+    /// plain `new` and `pinned_core` returns `None`. See
+    /// [`recommended_core`](crate::platform::recommended_core) for picking a core.
+    pub fn with_affinity(
+        instrument: String,
+        algorithm: Box<dyn MatchingAlgorithm>,
+        event_handler: Arc<dyn EventHandler>,
+        core_id: usize,
+    ) -> Self {
+        let mut engine = Self::new(instrument, algorithm, event_handler);
+        engine.pinned_core = pin_current_thread_to_core(core_id).then_some(core_id);
+        engine
+    }
+
+    /// Create a matching engine that honors the book-level settings of `config`
+    ///
+    /// The matching algorithm is passed separately; use
+    /// [`create_from_config`](crate::engine::create_from_config) to build it from
+    /// the configuration as well.
+    pub fn with_config(
+        config: &OrderBookConfig,
+        algorithm: Box<dyn MatchingAlgorithm>,
+        event_handler: Arc<dyn EventHandler>,
+    ) -> Self {
+        let mut engine = Self::new(config.instrument.clone(), algorithm, event_handler);
+        engine.order_book_type = config.order_book_type;
+        engine.price_band = RwLock::new(config.price_band);
+        engine.max_depth = config.max_depth;
+        engine.max_orders_per_level = config.max_orders_per_level;
+        engine.allow_non_positive_prices = config.allow_non_positive_prices;
+        engine.bids = OrderBookSide::new(Side::Buy).with_inverted_prices(config.price_inverted);
+        engine.asks = OrderBookSide::new(Side::Sell).with_inverted_prices(config.price_inverted);
+        engine.tick_size = config.tick_size;
+        engine.lot_size = config.lot_size;
+        engine.checksum_schedule = config.checksum_schedule;
+        engine.self_trade_prevention = config.self_trade_prevention;
+        engine.fee_schedule = config.fee_schedule;
+        engine.trade_price_rule = config.trade_price_rule;
+        engine.circuit_breaker = config.circuit_breaker;
+        engine.rate_limiter = config.max_orders_per_sec.map(RateLimiter::new);
+        engine.positions = config.track_positions.then(PositionBook::new);
+        engine
+    }
+
     /// Submit an order to the matching engine
     pub fn submit_order(&self, order: Arc<Order>) -> Vec<OrderEvent> {
+        let sequenced = self.process_order(order);
+        let events = Self::unsequenced(&sequenced);
+        self.deliver(sequenced);
+        events
+    }
+
+    /// Buffer `order` until simulated time reaches `arrival`
+    ///
+    /// Needs [`with_simulated_time`](Self::with_simulated_time). Nothing is
+    /// processed until [`advance_to`](Self::advance_to) passes `arrival`;
+    /// arrivals before the current simulated time are refused.
+    pub fn submit_order_at(&self, order: Arc<Order>, arrival: DateTime<Utc>) -> Result<(), String> {
+        let clock = self
+            .simulated_clock
+            .as_ref()
+            .ok_or("simulated time is not enabled")?;
+        if arrival < clock.now() {
+            return Err(format!(
+                "arrival {} is before simulated time {}",
+                arrival,
+                clock.now()
+            ));
+        }
+        self.arrivals.push(order, arrival);
+        Ok(())
+    }
+
+    /// Move simulated time to `until`, processing every buffered order that
+    /// arrives at or before it
+    ///
+    /// Orders run in arrival order, ties in the order they were submitted,
+    /// with the clock set to each order's arrival so event timestamps are
+    /// reproducible. Returns the events of all processed orders. Time never
+    /// moves backwards, and GTD expiry is still left to `expire_stale_orders`.
+    pub fn advance_to(&self, until: DateTime<Utc>) -> Vec<OrderEvent> {
+        let Some(clock) = &self.simulated_clock else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        while let Some((arrival, order)) = self.arrivals.pop_due(until) {
+            clock.set(arrival);
+            events.extend(self.submit_order(order));
+        }
+        if until > clock.now() {
+            clock.set(until);
+        }
+        events
+    }
+
+    /// Orders buffered by `submit_order_at` that have not arrived yet
+    pub fn pending_arrivals(&self) -> usize {
+        self.arrivals.len()
+    }
+
+    /// Submit an order and summarize its fills from this submission
+    ///
+    /// The events are the same as from [`submit_order`](Self::submit_order);
+    /// the report consolidates every trade the order took part in, which is
+    /// handy when one aggressive order produces many pro-rata fills.
+    pub fn submit_order_with_report(&self, order: Arc<Order>) -> (Vec<OrderEvent>, FillReport) {
+        let order_id = order.id;
+        let events = self.submit_order(order);
+        let trades = events.iter().filter_map(|event| match event {
+            OrderEvent::OrderMatched { trade, .. } => Some(trade),
+            _ => None,
+        });
+        let report = FillReport::from_trades(order_id, trades);
+        (events, report)
+    }
+
+    /// Submit a batch of orders, flushing events to the handler once
+    ///
+    /// Orders are processed one after another exactly as with
+    /// [`submit_order`](Self::submit_order); only the event delivery is batched.
+    /// Book deltas still reach subscribers as each order is processed.
+    pub fn submit_orders(&self, orders: Vec<Arc<Order>>) -> Vec<OrderEvent> {
+        let mut sequenced = Vec::new();
+        for order in orders {
+            sequenced.extend(self.process_order(order));
+        }
+
+        let events = Self::unsequenced(&sequenced);
+        self.deliver(sequenced);
+        events
+    }
+
+    /// Submit two orders linked as one-cancels-other
+    ///
+    /// The primary is processed first, then the secondary. As soon as either
+    /// leg trades (fully or partially) the other is cancelled and an
+    /// `OcoTriggered` event is emitted; if the primary trades on arrival the
+    /// secondary is cancelled without being submitted. A leg that is rejected
+    /// or leaves without trading unlinks the pair.
+    pub fn submit_oco(&self, primary: Arc<Order>, secondary: Arc<Order>) -> Vec<OrderEvent> {
+        {
+            let mut links = self.oco_links.write();
+            links.insert(primary.id, secondary.id);
+            links.insert(secondary.id, primary.id);
+        }
+
+        let mut sequenced = self.process_order(Arc::clone(&primary));
+        if primary.get_filled_quantity().is_positive() {
+            secondary.set_state(OrderState::Cancelled);
+            sequenced.extend(self.sequence(vec![OrderEvent::OrderCancelled {
+                order_id: secondary.id,
+                timestamp: self.now(),
+            }]));
+        } else {
+            if !self.order_index.read().contains_key(&primary.id) {
+                self.unlink_oco(primary.id);
+            }
+            sequenced.extend(self.process_order(Arc::clone(&secondary)));
+        }
+        if !self.order_index.read().contains_key(&secondary.id) {
+            self.unlink_oco(secondary.id);
+        }
+
+        let events = Self::unsequenced(&sequenced);
+        self.deliver(sequenced);
+        events
+    }
+
+    /// Run an order through validation and matching without notifying the
+    /// handler, returning its events already sequenced
+    fn process_order(&self, order: Arc<Order>) -> Vec<SequencedEvent> {
+        #[cfg(feature = "metrics")]
+        let _timer = {
+            self.metrics.orders_submitted.inc();
+            self.metrics.match_latency.start_timer()
+        };
+
+        let _writer = self.book_writer();
+        let mut touched = Vec::new();
+        let events = self.match_incoming(order, &mut touched);
+        self.seal(events, &touched)
+    }
+
+    /// Validation and matching for `process_order`, collecting the levels
+    /// whose quantity changed into `touched`
+    fn match_incoming(
+        &self,
+        order: Arc<Order>,
+        touched: &mut Vec<(Side, Price)>,
+    ) -> Vec<OrderEvent> {
         let mut events = Vec::new();
 
         // Event: Order received
         events.push(OrderEvent::OrderReceived {
             order_id: order.id,
-            timestamp: Utc::now(),
+            timestamp: self.now(),
         });
 
-        // Validate order
-        if let Err(reason) = self.validate_order(&order) {
+        // Pegged and protected market orders take their price from the book on arrival
+        self.price_from_book(&order);
+
+        // Validate order; reduce-only orders are capped to the reducible size
+        let reduce_only_cap = self
+            .check_accepting()
+            .and_then(|_| self.check_rate_limit(&order))
+            .and_then(|_| self.validate_order(&order))
+            .and_then(|_| self.check_self_trade(&order))
+            .and_then(|_| self.reduce_only_cap(&order))
+            .and_then(|cap| self.check_min_fill(&order, cap).map(|_| cap))
+            .and_then(|cap| match self.check_circuit_breaker(&order) {
+                Some(halted) => {
+                    events.push(halted);
+                    Err(RejectReason::Halted)
+                },
+                None => Ok(cap),
+            })
+            .and_then(|cap| self.claim_client_order_id(&order).map(|_| cap));
+        if let Err(code) = reduce_only_cap {
+            #[cfg(feature = "metrics")]
+            self.metrics.orders_rejected.inc();
             order.set_state(OrderState::Rejected);
             events.push(OrderEvent::OrderRejected {
                 order_id: order.id,
-                reason,
-                timestamp: Utc::now(),
+                code,
+                reason: code.to_string(),
+                timestamp: self.now(),
             });
-            self.event_handler.on_events(events.clone());
             return events;
         }
 
@@ -85,27 +503,61 @@ impl MatchingEngine {
         order.set_state(OrderState::Accepted);
         events.push(OrderEvent::OrderAccepted {
             order_id: order.id,
-            timestamp: Utc::now(),
+            timestamp: self.now(),
         });
 
+        if let Ok(Some(cap)) = reduce_only_cap {
+            if order.reduce_quantity(cap) {
+                events.push(OrderEvent::OrderAmended {
+                    order_id: order.id,
+                    new_quantity: cap,
+                    timestamp: self.now(),
+                });
+            }
+        }
+
         // Match order
-        let opposite_side = match order.side {
-            Side::Buy => &self.asks,
-            Side::Sell => &self.bids,
+        let opposite_side = self.opposite_side_for(order.side);
+
+        // An average price cap limits the sweep to what keeps it under the cap
+        let sweep_limit = self.average_price_limit(&order);
+        let taker = match sweep_limit {
+            Some(limit) => Arc::new(order.slice(limit)),
+            None => Arc::clone(&order),
         };
 
-        let trades = self
-            .algorithm
-            .match_order(Arc::clone(&order), opposite_side);
+        // Trades go into the scratch buffer, or a fresh one if another
+        // submission holds it
+        let mut scratch = self.scratch_trades.try_lock();
+        let mut fresh = Vec::new();
+        let trades = scratch.as_deref_mut().unwrap_or(&mut fresh);
+        trades.clear();
 
-        // Generate trade events
-        for trade in trades {
-            events.push(OrderEvent::OrderMatched {
-                trade,
-                timestamp: Utc::now(),
-            });
+        // Auction orders accumulate until the book is uncrossed in one go
+        if !self.in_auction() {
+            let algorithm = self.algorithm.read();
+            match algorithm.last_look_hold() {
+                // Makers get to look at each match first; the taker waits off-book
+                Some(hold) => {
+                    let prospective =
+                        last_look::prospective_trades(&**algorithm, &taker, opposite_side);
+                    if !prospective.is_empty() {
+                        drop(algorithm);
+                        self.hold_for_last_look(&order, prospective, hold, &mut events);
+                        return events;
+                    }
+                },
+                None => algorithm.match_order_into(Arc::clone(&taker), opposite_side, trades),
+            }
+        }
+        if !Arc::ptr_eq(&taker, &order) && taker.get_filled_quantity().is_positive() {
+            order.try_fill(taker.get_filled_quantity());
         }
 
+        self.apply_trade_price_rule(&order, trades);
+        self.record_trades(&order, trades.drain(..), &mut events, touched);
+        drop(scratch);
+
         // Check final state
         let remaining = order.get_remaining_quantity();
         let filled = order.get_filled_quantity();
@@ -115,7 +567,7 @@ impl MatchingEngine {
             events.push(OrderEvent::OrderFilled {
                 order_id: order.id,
                 total_filled: filled,
-                timestamp: Utc::now(),
+                timestamp: self.now(),
             });
         } else if filled > Quantity::ZERO {
             // Partially filled
@@ -123,264 +575,6084 @@ impl MatchingEngine {
                 order_id: order.id,
                 filled_quantity: filled,
                 remaining_quantity: remaining,
-                timestamp: Utc::now(),
+                timestamp: self.now(),
             });
 
             // Add remainder to book based on time-in-force
             match order.time_in_force {
-                crate::domain::TimeInForce::GoodTillCancel => {
+                crate::domain::TimeInForce::GoodTillCancel
+                | crate::domain::TimeInForce::GoodTillDate(_)
+                    if !order.is_market_order()
+                        && !order.is_protected_market()
+                        && sweep_limit.is_none() =>
+                {
                     self.add_to_book(Arc::clone(&order));
+                    touched.push((order.side, order.price().unwrap()));
                     events.push(OrderEvent::OrderAddedToBook {
                         order_id: order.id,
-                        price: order.price.unwrap(),
+                        side: order.side,
+                        price: order.price().unwrap(),
                         quantity: remaining,
-                        timestamp: Utc::now(),
+                        timestamp: self.now(),
                     });
                 },
                 crate::domain::TimeInForce::ImmediateOrCancel => {
                     order.set_state(OrderState::Cancelled);
                     events.push(OrderEvent::OrderCancelled {
                         order_id: order.id,
-                        timestamp: Utc::now(),
+                        timestamp: self.now(),
                     });
                 },
                 crate::domain::TimeInForce::FillOrKill => {
-                    // FOK should have been rejected if can't fill completely
+                    // Only reached if the algorithm filled less than the book
+                    // scan promised (e.g. pro-rata minimums)
                     order.set_state(OrderState::Cancelled);
                     events.push(OrderEvent::OrderCancelled {
                         order_id: order.id,
-                        timestamp: Utc::now(),
+                        timestamp: self.now(),
+                    });
+                },
+                _ => {
+                    // Market orders have no price to rest at, and a sweep
+                    // stopped by its average price cap is over
+                    order.set_state(OrderState::Cancelled);
+                    events.push(OrderEvent::OrderCancelled {
+                        order_id: order.id,
+                        timestamp: self.now(),
                     });
                 },
-                _ => {},
             }
+        } else if order.is_market_order() || order.is_protected_market() || sweep_limit.is_some() {
+            // Nothing to trade against (or nothing within the average price
+            // cap): a market order never rests
+            order.set_state(OrderState::Cancelled);
+            events.push(OrderEvent::OrderCancelled {
+                order_id: order.id,
+                timestamp: self.now(),
+            });
         } else {
             // Not matched at all, add to book
             self.add_to_book(Arc::clone(&order));
+            touched.push((order.side, order.price().unwrap()));
             events.push(OrderEvent::OrderAddedToBook {
                 order_id: order.id,
-                price: order.price.unwrap(),
+                side: order.side,
+                price: order.price().unwrap(),
                 quantity: remaining,
-                timestamp: Utc::now(),
+                timestamp: self.now(),
             });
         }
 
-        // Emit events
-        self.event_handler.on_events(events.clone());
+        if order.get_state().is_terminal() {
+            self.client_order_ids.release(&order);
+        }
 
+        self.settle_book(&mut events, touched);
         events
     }
 
-    /// Cancel an order
-    pub fn cancel_order(&self, order_id: OrderId) -> Option<OrderEvent> {
-        if let Some(order) = self.order_index.write().remove(&order_id) {
-            if order.try_cancel() {
-                let event = OrderEvent::OrderCancelled {
+    /// Rebuild a price-time engine's resting book from recorded events
+    ///
+    /// See [`apply_events`](Self::apply_events) for what is restored.
+    pub fn replay(instrument: String, events: &[OrderEvent]) -> MatchingEngine {
+        let engine = MatchingEngine::new(
+            instrument,
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        engine.apply_events(events);
+        engine
+    }
+
+    /// Apply recorded events to this engine's book without matching
+    ///
+    /// Book-changing events (`OrderAddedToBook`, `OrderMatched`,
+    /// `OrderCancelled`, `OrderExpired`, `OrderAmended`, `OrderRepriced`) are
+    /// applied in order;
+    /// informational events are ignored. The rebuilt book keeps each order's
+    /// id, side, price, remaining quantity and queue position. User ids and
+    /// order flags are not part of the event stream, so restored orders are
+    /// plain limit orders with an empty user id. No events are emitted.
+    pub fn apply_events(&self, events: &[OrderEvent]) {
+        let _writer = self.book_writer();
+        let mut amended = Vec::new();
+        for event in events {
+            match event {
+                OrderEvent::OrderAddedToBook {
+                    order_id,
+                    side,
+                    price,
+                    quantity,
+                    ..
+                } => {
+                    let mut order = Order::new(
+                        String::new(),
+                        (*self.instrument).clone(),
+                        *side,
+                        crate::domain::OrderType::Limit,
+                        Some(*price),
+                        *quantity,
+                        crate::domain::TimeInForce::GoodTillCancel,
+                    );
+                    order.id = *order_id;
+                    let seq = self.sequence_counter.fetch_add(1, Ordering::AcqRel);
+                    order.set_sequence_number(seq as i64);
+                    order.set_state(OrderState::Accepted);
+                    self.add_to_book(Arc::new(order));
+                },
+                OrderEvent::OrderRepriced {
                     order_id,
-                    timestamp: Utc::now(),
+                    new_price,
+                    ..
+                } => {
+                    let order = self.order_index.read().get(order_id).cloned();
+                    if let Some(order) = order {
+                        self.remove_from_book(&order);
+                        order.set_price(*new_price);
+                        let seq = self.sequence_counter.fetch_add(1, Ordering::AcqRel);
+                        order.set_sequence_number(seq as i64);
+                        self.add_to_book(order);
+                    }
+                },
+                OrderEvent::OrderMatched { trade, .. } => {
+                    self.trade_counter
+                        .fetch_max(trade.trade_id, Ordering::AcqRel);
+                    *self.last_trade.lock() = Some((trade.trade_id, trade.price, trade.quantity));
+                    // The taker is only on the book when a crossed book was re-matched
+                    for order_id in [trade.maker_order_id, trade.taker_order_id] {
+                        self.replay_fill(order_id, trade.quantity);
+                    }
+                },
+                OrderEvent::OrderCancelled { order_id, .. }
+                | OrderEvent::OrderExpired { order_id, .. } => {
+                    let order = self.order_index.write().remove(order_id);
+                    if let Some(order) = order {
+                        self.remove_from_book(&order);
+                        order.try_cancel();
+                    }
+                },
+                OrderEvent::OrderAmended {
+                    order_id,
+                    new_quantity,
+                    ..
+                } => {
+                    let order = self.order_index.read().get(order_id).cloned();
+                    if let Some(order) = order {
+                        let before = order.get_remaining_quantity();
+                        if order.reduce_quantity(*new_quantity) {
+                            if let Some(level) = self.level_of(&order) {
+                                level.subtract_quantity(before - *new_quantity);
+                            }
+                            amended.extend(order.price().map(|price| (order.side, price)));
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        self.debug_check_levels(&amended);
+        self.book_version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Place known non-crossing orders straight onto the book (startup warm-up)
+    ///
+    /// Orders keep their remaining quantity and are queued in the given order
+    /// behind anything already resting; no validation, matching or trades take
+    /// place. Only `OrderAddedToBook` events are emitted, so delta subscribers
+    /// should take a snapshot after loading. The whole batch is rejected,
+    /// leaving the book untouched, if an order has no limit price or nothing
+    /// left to fill, is already on the book, or the loaded book would be
+    /// crossed.
+    pub fn load_resting_orders(&self, orders: Vec<Arc<Order>>) -> Result<Vec<OrderEvent>, String> {
+        let _writer = self.book_writer();
+        let mut best_bid = self.bids.best_price();
+        let mut best_ask = self.asks.best_price();
+        let mut ids = HashSet::with_capacity(orders.len());
+        {
+            let index = self.order_index.read();
+            for order in &orders {
+                let price = match order.price() {
+                    Some(price) if !order.is_market_order() => price,
+                    _ => return Err(format!("Order {} has no limit price", order.id.as_uuid())),
                 };
-                self.event_handler.on_event(event.clone());
-                Some(event)
-            } else {
-                None
+                if !order.get_remaining_quantity().is_positive() {
+                    return Err(format!(
+                        "Order {} has no remaining quantity",
+                        order.id.as_uuid()
+                    ));
+                }
+                if index.contains_key(&order.id) || !ids.insert(order.id) {
+                    return Err(format!(
+                        "Order {} is already on the book",
+                        order.id.as_uuid()
+                    ));
+                }
+                let (book, best) = match order.side {
+                    Side::Buy => (&self.bids, &mut best_bid),
+                    Side::Sell => (&self.asks, &mut best_ask),
+                };
+                if best.is_none_or(|best| book.is_at_or_better(price, best)) {
+                    *best = Some(price);
+                }
             }
-        } else {
-            None
         }
+        if let (Some(bid), Some(ask)) = (best_bid, best_ask) {
+            if self.bids.is_at_or_better(bid, ask) {
+                return Err(format!(
+                    "Loaded book would be crossed (bid {bid}, ask {ask})"
+                ));
+            }
+        }
+
+        let mut events = Vec::with_capacity(orders.len());
+        for order in orders {
+            let seq = self.sequence_counter.fetch_add(1, Ordering::AcqRel);
+            order.set_sequence_number(seq as i64);
+            if order.get_state() == OrderState::Pending {
+                order.set_state(OrderState::Accepted);
+            }
+            events.push(OrderEvent::OrderAddedToBook {
+                order_id: order.id,
+                side: order.side,
+                price: order.price().unwrap(),
+                quantity: order.get_remaining_quantity(),
+                timestamp: self.now(),
+            });
+            self.add_to_book(order);
+        }
+
+        self.book_version.fetch_add(1, Ordering::AcqRel);
+        self.publish(events.clone());
+        Ok(events)
     }
 
-    /// Get order book snapshot
-    pub fn get_snapshot(&self, depth: usize) -> OrderBookSnapshot {
-        let bids = self.bids.get_depth(depth);
-        let asks = self.asks.get_depth(depth);
+    /// Look up a resting order's current progress
+    ///
+    /// Returns `None` for unknown ids and for orders that are no longer
+    /// resting (filled, cancelled or expired).
+    pub fn get_order(&self, order_id: OrderId) -> Option<OrderStatus> {
+        self.order_index
+            .read()
+            .get(&order_id)
+            .map(|order| order.status())
+    }
 
-        OrderBookSnapshot::with_depth((*self.instrument).clone(), bids, asks)
+    /// Statuses of every resting order, oldest first
+    ///
+    /// The index is copied under a brief read lock and statuses are read as
+    /// the iterator advances, so matching is never blocked for the walk and
+    /// the book's queues are not touched.
+    pub fn iter_orders(&self) -> impl Iterator<Item = OrderStatus> {
+        let mut orders: Vec<Arc<Order>> = self.order_index.read().values().cloned().collect();
+        orders.sort_by_key(|order| order.get_sequence_number());
+        orders.into_iter().map(|order| order.status())
     }
 
-    /// Get spread
-    pub fn get_spread(&self) -> Option<Price> {
-        match (self.bids.best_price(), self.asks.best_price()) {
-            (Some(bid), Some(ask)) => ask.checked_sub(bid).ok(),
-            _ => None,
-        }
+    /// The `n` largest resting orders by remaining quantity, largest first,
+    /// optionally restricted to one side
+    ///
+    /// Orders of equal size are listed oldest first.
+    pub fn top_n_orders_by_size(&self, n: usize, side: Option<Side>) -> Vec<OrderStatus> {
+        let mut orders: Vec<OrderStatus> = self
+            .order_index
+            .read()
+            .values()
+            .filter(|order| side.is_none_or(|side| order.side == side))
+            .map(|order| order.status())
+            .collect();
+        orders.sort_by_key(|status| {
+            (
+                std::cmp::Reverse(status.remaining_quantity),
+                status.sequence_number,
+            )
+        });
+        orders.truncate(n);
+        orders
     }
 
-    /// Get mid price
-    pub fn get_mid_price(&self) -> Option<Price> {
-        match (self.bids.best_price(), self.asks.best_price()) {
-            (Some(bid), Some(ask)) => bid
-                .checked_add(ask)
-                .ok()
-                .map(|sum| Price::from_raw(sum.raw_value() / 2)),
-            _ => None,
-        }
+    /// Cancel an order
+    pub fn cancel_order(&self, order_id: OrderId) -> Option<OrderEvent> {
+        let _writer = self.book_writer();
+        let order = self.order_index.write().remove(&order_id)?;
+        let event = self.cancel_resting(&order)?;
+        self.emit(vec![event.clone()], &Self::resting_levels(&[order]));
+        Some(event)
     }
 
-    /// Get the instrument name
-    pub fn get_instrument(&self) -> &str {
-        &self.instrument
+    /// Engine id of the live order `user_id` submitted under `client_order_id`
+    ///
+    /// Lets order entry gateways cancel by the client's own id (FIX
+    /// OrigClOrdID) without keeping a map of their own.
+    pub fn resolve_client_order_id(&self, user_id: &str, client_order_id: &str) -> Option<OrderId> {
+        self.client_order_ids.live_order(user_id, client_order_id)
     }
 
-    // ========================================================================
-    // Private methods
-    // ========================================================================
+    /// Cancel every resting order belonging to `user_id`
+    ///
+    /// Orders are removed from the index and their levels in one pass and one
+    /// `OrderCancelled` event is returned per order, oldest first. Orders
+    /// submitted concurrently after the index scan are left untouched.
+    pub fn cancel_all_for_user(&self, user_id: &str) -> Vec<OrderEvent> {
+        let _writer = self.book_writer();
+        let mut orders: Vec<Arc<Order>> = {
+            let mut index = self.order_index.write();
+            let ids: Vec<OrderId> = index
+                .values()
+                .filter(|order| order.user_id.as_str() == user_id)
+                .map(|order| order.id)
+                .collect();
+            ids.iter().filter_map(|id| index.remove(id)).collect()
+        };
+        orders.sort_by_key(|order| order.get_sequence_number());
 
-    fn add_to_book(&self, order: Arc<Order>) {
-        match order.side {
-            Side::Buy => self.bids.add_order(Arc::clone(&order)),
-            Side::Sell => self.asks.add_order(Arc::clone(&order)),
+        let events: Vec<OrderEvent> = orders
+            .iter()
+            .filter_map(|order| self.cancel_resting(order))
+            .collect();
+
+        if !events.is_empty() {
+            self.emit(events.clone(), &Self::resting_levels(&orders));
         }
+        events
+    }
 
-        // Index for cancellation
-        self.order_index.write().insert(order.id, order);
+    /// Cancel every resting order on `side` priced within `[min_price, max_price]`
+    ///
+    /// Works like `cancel_all_for_user`: one pass over the index, one
+    /// `OrderCancelled` event per order, oldest first. An empty range
+    /// (`min_price > max_price`) cancels nothing.
+    pub fn cancel_range(&self, side: Side, min_price: Price, max_price: Price) -> Vec<OrderEvent> {
+        let _writer = self.book_writer();
+        let in_range = |order: &Order| {
+            order.side == side
+                && order
+                    .price()
+                    .is_some_and(|price| min_price <= price && price <= max_price)
+        };
+        let mut orders: Vec<Arc<Order>> = {
+            let mut index = self.order_index.write();
+            let ids: Vec<OrderId> = index
+                .values()
+                .filter(|order| in_range(order))
+                .map(|order| order.id)
+                .collect();
+            ids.iter().filter_map(|id| index.remove(id)).collect()
+        };
+        orders.sort_by_key(|order| order.get_sequence_number());
+
+        let events: Vec<OrderEvent> = orders
+            .iter()
+            .filter_map(|order| self.cancel_resting(order))
+            .collect();
+
+        if !events.is_empty() {
+            self.emit(events.clone(), &Self::resting_levels(&orders));
+        }
+        events
     }
 
-    fn validate_order(&self, order: &Order) -> Result<(), String> {
-        // Basic validation
-        if !order.quantity.is_positive() {
-            return Err("Quantity must be positive".to_string());
+    /// Remove every resting order from the book
+    ///
+    /// For end-of-day resets and test fixtures. Each order is cancelled and
+    /// reported with one `OrderCancelled` event, oldest first; both sides end
+    /// up with no levels.
+    pub fn clear_book(&self) -> Vec<OrderEvent> {
+        let _writer = self.book_writer();
+        let mut orders: Vec<Arc<Order>> = std::mem::take(&mut *self.order_index.write())
+            .into_values()
+            .collect();
+        orders.sort_by_key(|order| order.get_sequence_number());
+
+        let events: Vec<OrderEvent> = orders
+            .iter()
+            .filter_map(|order| self.cancel_resting(order))
+            .collect();
+
+        // Drop levels left behind by orders the index no longer tracked
+        self.bids.clear();
+        self.asks.clear();
+        self.pegged_orders.write().clear();
+
+        if !orders.is_empty() {
+            self.emit(events.clone(), &Self::resting_levels(&orders));
         }
+        events
+    }
+
+    /// Expire resting GoodTillDate orders whose deadline is at or before `now`
+    ///
+    /// Callers drive expiry by invoking this periodically, either from their
+    /// own scheduler or via `start_expiry_reaper`. Expired orders are
+    /// removed from the book and one `OrderExpired` event is returned per order,
+    /// oldest first.
+    pub fn expire_stale_orders(&self, now: DateTime<Utc>) -> Vec<OrderEvent> {
+        let _writer = self.book_writer();
+        let mut orders: Vec<Arc<Order>> = {
+            let mut index = self.order_index.write();
+            let ids: Vec<OrderId> = index
+                .values()
+                .filter(|order| order.is_expired_at(now))
+                .map(|order| order.id)
+                .collect();
+            ids.iter().filter_map(|id| index.remove(id)).collect()
+        };
+        orders.sort_by_key(|order| order.get_sequence_number());
+
+        let events: Vec<OrderEvent> = orders
+            .iter()
+            .filter_map(|order| {
+                self.remove_from_book(order);
+                self.client_order_ids.release(order);
+                order.try_expire().then(|| OrderEvent::OrderExpired {
+                    order_id: order.id,
+                    timestamp: self.now(),
+                })
+            })
+            .collect();
 
-        if order.is_limit_order() && order.price.is_none() {
-            return Err("Limit orders must have a price".to_string());
+        if !events.is_empty() {
+            self.emit(events.clone(), &Self::resting_levels(&orders));
         }
+        events
+    }
 
-        if order.is_limit_order() {
-            if let Some(price) = order.price {
-                if !price.is_positive() {
-                    return Err("Price must be positive".to_string());
+    /// Expire GoodTillDate orders and last-look holds from a background thread
+    /// every `tick`
+    ///
+    /// An opt-in alternative to calling `expire_stale_orders` by hand. The
+    /// resulting events go to the engine's event handler. Fails if the thread
+    /// cannot be spawned.
+    pub fn start_expiry_reaper(self: &Arc<Self>, tick: Duration) -> io::Result<ReaperHandle> {
+        ReaperHandle::spawn(Arc::downgrade(self), tick)
+    }
+
+    /// Execute a match held for the maker's last look
+    ///
+    /// Trades the held quantity at the held price, capped at what maker and
+    /// taker still have. A match whose hold has run out, or whose maker has
+    /// left the book, is rejected instead. Unknown or already resolved match
+    /// ids produce no events.
+    pub fn confirm_last_look(&self, match_id: u64) -> Vec<OrderEvent> {
+        let pending = match self.pending_last_looks.lock().remove(&match_id) {
+            Some(pending) => pending,
+            None => return Vec::new(),
+        };
+
+        let _writer = self.book_writer();
+        let mut events = Vec::new();
+        let mut touched = Vec::new();
+        let taker = Arc::clone(&pending.taker);
+        let maker = self.order_index.read().get(&pending.maker_id).cloned();
+        match maker {
+            Some(maker) if self.now() < pending.deadline => {
+                let quantity = pending
+                    .quantity
+                    .min(maker.get_remaining_quantity())
+                    .min(taker.get_remaining_quantity());
+                if quantity.is_positive() {
+                    self.fill_in_place(&maker, quantity);
+                    taker.try_fill(quantity);
+                    let mut trades = vec![Trade::new(
+                        (*self.instrument).clone(),
+                        maker.id,
+                        taker.id,
+                        pending.price,
+                        quantity,
+                        taker.side,
+                    )];
+                    self.apply_trade_price_rule(&taker, &mut trades);
+                    self.record_trades(&taker, trades, &mut events, &mut touched);
+                } else {
+                    events.push(self.last_look_rejected(match_id, &pending));
                 }
-            }
+            },
+            _ => events.push(self.last_look_rejected(match_id, &pending)),
         }
+        self.finish_last_look(&taker, &mut events);
 
-        // TODO: Add more validations:
-        // - User balance check
-        // - Instrument validation
-        // - Price/quantity precision check
-        // - Self-trade prevention
-
-        Ok(())
+        self.emit(events.clone(), &touched);
+        events
     }
-}
 
-unsafe impl Send for MatchingEngine {}
-unsafe impl Sync for MatchingEngine {}
+    /// Reject a match held for the maker's last look
+    ///
+    /// The taker is told through `LastLookRejected`. Unknown or already
+    /// resolved match ids produce no events.
+    pub fn reject_last_look(&self, match_id: u64) -> Vec<OrderEvent> {
+        let pending = match self.pending_last_looks.lock().remove(&match_id) {
+            Some(pending) => pending,
+            None => return Vec::new(),
+        };
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::domain::{OrderType, TimeInForce};
-    use crate::engine::PriceTimePriority;
-    use crate::interfaces::NoOpEventHandler;
+        let mut events = vec![self.last_look_rejected(match_id, &pending)];
+        self.finish_last_look(&pending.taker, &mut events);
 
-    #[test]
-    fn test_matching_engine_basic() {
-        let engine = MatchingEngine::new(
-            "BTC-USD".to_string(),
-            Box::new(PriceTimePriority::new(false)),
-            Arc::new(NoOpEventHandler),
-        );
+        self.publish(events.clone());
+        events
+    }
 
-        // Add sell order
-        let sell = Arc::new(Order::new(
-            "user1".to_string(),
-            "BTC-USD".to_string(),
-            Side::Sell,
-            OrderType::Limit,
-            Some(Price::from_integer(50000).unwrap()),
-            Quantity::from_integer(1).unwrap(),
-            TimeInForce::GoodTillCancel,
-        ));
+    /// Reject every held match whose last-look window closed at or before `now`
+    ///
+    /// Like `expire_stale_orders`, this is driven by the caller or by
+    /// `start_expiry_reaper`, and `now` is a time on the engine's clock.
+    /// Matches are rejected in the order they were held.
+    pub fn expire_last_looks(&self, now: DateTime<Utc>) -> Vec<OrderEvent> {
+        let mut expired: Vec<(u64, PendingMatch)> = {
+            let mut pending = self.pending_last_looks.lock();
+            let ids: Vec<u64> = pending
+                .iter()
+                .filter(|(_, held)| held.deadline <= now)
+                .map(|(match_id, _)| *match_id)
+                .collect();
+            ids.into_iter()
+                .filter_map(|match_id| pending.remove(&match_id).map(|held| (match_id, held)))
+                .collect()
+        };
+        expired.sort_by_key(|(match_id, _)| *match_id);
 
-        engine.submit_order(sell);
+        let mut events = Vec::new();
+        for (match_id, held) in &expired {
+            events.push(self.last_look_rejected(*match_id, held));
+            self.finish_last_look(&held.taker, &mut events);
+        }
+
+        self.publish(events.clone());
+        events
+    }
+
+    /// Subscribe to incremental book updates
+    ///
+    /// The returned channel receives every `OrderEvent::BookDelta` emitted
+    /// after subscription. Dropping the receiver unsubscribes.
+    pub fn subscribe_deltas(&self) -> Receiver<OrderEvent> {
+        let (sender, receiver) = channel::unbounded();
+        self.delta_subscribers.write().push(sender);
+        receiver
+    }
+
+    /// Get order book snapshot
+    ///
+    /// The content depends on the order book type:
+    /// - `Transparent`: full resting quantity per level
+    /// - `Hybrid`: displayed quantity only (hidden orders and iceberg reserve excluded)
+    /// - `DarkPool`: no levels; only spread and mid price are published
+    ///
+    /// The result is cached until the book next changes, so repeated polling at
+    /// the same depth does not re-walk the levels.
+    pub fn get_snapshot(&self, depth: usize) -> OrderBookSnapshot {
+        // Read the version before building: a change racing with the build
+        // bumps it again, so the cached copy can never be stale yet current
+        let version = self.book_version.load(Ordering::Acquire);
+        if let Some((cached_version, cached_depth, snapshot)) = &*self.snapshot_cache.read() {
+            if *cached_version == version && *cached_depth == depth {
+                return snapshot.clone();
+            }
+        }
+
+        let snapshot = self.build_snapshot(depth);
+        *self.snapshot_cache.write() = Some((version, depth, snapshot.clone()));
+        snapshot
+    }
+
+    /// Snapshot together with the sequence number of the last event it reflects
+    ///
+    /// Taken atomically with respect to the event stream: book deltas
+    /// numbered at or below the sequence are already in the snapshot, and
+    /// applying those numbered above it, in sequence order, brings it up to
+    /// date. To bootstrap, start buffering sequenced events from the handler,
+    /// take this snapshot, then apply the buffered deltas past the sequence.
+    pub fn snapshot_with_sequence(&self, depth: usize) -> (OrderBookSnapshot, u64) {
+        let _feed = self.feed.lock();
+        let snapshot = self.get_snapshot(depth);
+        (snapshot, self.event_sequence.load(Ordering::Acquire))
+    }
+
+    /// CRC32 over the top `depth` levels of both sides
+    ///
+    /// Computed from the published snapshot at that depth (so it follows the
+    /// book type's visibility rules); a consumer can recompute it from its
+    /// own book with `OrderBookSnapshot::checksum`.
+    pub fn book_checksum(&self, depth: usize) -> u32 {
+        self.get_snapshot(depth).checksum()
+    }
+
+    /// Get a snapshot with levels grouped into coarser price buckets
+    ///
+    /// Each level's price is floored to a multiple of `bucket` and quantities
+    /// falling in the same bucket are summed; `depth` counts buckets per side.
+    /// Visibility follows [`get_snapshot`](Self::get_snapshot), and spread, mid
+    /// and last trade are those of the ungrouped book. A bucket equal to the
+    /// tick size (or a non-positive one) leaves the levels ungrouped.
+    pub fn get_aggregated_snapshot(&self, depth: usize, bucket: Price) -> OrderBookSnapshot {
+        if !bucket.is_positive() {
+            return self.get_snapshot(depth);
+        }
+
+        let mut snapshot = self.get_snapshot(usize::MAX);
+        snapshot.bids = Self::bucket_levels(&snapshot.bids, bucket, depth);
+        snapshot.asks = Self::bucket_levels(&snapshot.asks, bucket, depth);
+        snapshot.bid_cumulative = cumulative(&snapshot.bids);
+        snapshot.ask_cumulative = cumulative(&snapshot.asks);
+        snapshot
+    }
+
+    /// Sum best-first `levels` into `bucket`-wide groups, keeping the first `depth`
+    fn bucket_levels(
+        levels: &[(Price, Quantity)],
+        bucket: Price,
+        depth: usize,
+    ) -> Vec<(Price, Quantity)> {
+        let width = bucket.raw_value();
+        let mut grouped: Vec<(Price, Quantity)> = Vec::new();
+        for &(price, quantity) in levels {
+            let floor = Price::from_raw(price.raw_value().div_euclid(width) * width);
+            if let Some((last, total)) = grouped.last_mut() {
+                if *last == floor {
+                    *total = total.saturating_add(quantity);
+                    continue;
+                }
+            }
+            if grouped.len() == depth {
+                break;
+            }
+            grouped.push((floor, quantity));
+        }
+        grouped
+    }
+
+    fn build_snapshot(&self, depth: usize) -> OrderBookSnapshot {
+        let mut snapshot = match self.order_book_type {
+            OrderBookType::Transparent => {
+                let bids = self.bids.get_depth(depth);
+                let asks = self.asks.get_depth(depth);
+
+                OrderBookSnapshot::with_depth((*self.instrument).clone(), bids, asks)
+            },
+            OrderBookType::Hybrid => {
+                let bids = self.bids.get_visible_depth(depth);
+                let asks = self.asks.get_visible_depth(depth);
+
+                OrderBookSnapshot::with_depth((*self.instrument).clone(), bids, asks)
+            },
+            OrderBookType::DarkPool => {
+                let mut snapshot = OrderBookSnapshot::new((*self.instrument).clone());
+                snapshot.spread = self.get_spread();
+                snapshot.mid_price = self.get_mid_price();
+                snapshot
+            },
+        };
+
+        // Trades are public on every book type
+        if let Some((trade_id, price, quantity)) = *self.last_trade.lock() {
+            snapshot.last_trade_id = Some(trade_id);
+            snapshot.last_trade_price = Some(price);
+            snapshot.last_trade_quantity = Some(quantity);
+        }
+        snapshot
+    }
+
+    /// Get order-by-order (L3) snapshot
+    ///
+    /// Each level lists its orders in queue order. Visibility follows the same
+    /// rules as [`get_snapshot`](Self::get_snapshot): hybrid books omit hidden
+    /// orders and show iceberg display quantity, dark pools publish nothing.
+    pub fn get_l3_snapshot(&self, depth: usize) -> L3Snapshot {
+        let (bids, asks) = match self.order_book_type {
+            OrderBookType::Transparent => (
+                self.bids.get_l3_depth(depth, false),
+                self.asks.get_l3_depth(depth, false),
+            ),
+            OrderBookType::Hybrid => (
+                self.bids.get_l3_depth(depth, true),
+                self.asks.get_l3_depth(depth, true),
+            ),
+            OrderBookType::DarkPool => (Vec::new(), Vec::new()),
+        };
+
+        L3Snapshot {
+            instrument: (*self.instrument).clone(),
+            bids,
+            asks,
+        }
+    }
+
+    /// Estimate the outcome of a market order of `quantity` on `side`
+    ///
+    /// Read-only: walks the opposite side in price order without filling
+    /// anything. Liquidity is counted with snapshot visibility, so hybrid books
+    /// only count displayed quantity and dark pools report no fill.
+    pub fn estimate_fill(&self, side: Side, quantity: Quantity) -> FillEstimate {
+        self.estimate(side, quantity, None)
+    }
+
+    /// Estimate a market order of `quantity` on `side` whose average fill
+    /// price may not get worse than `max_avg_price`
+    ///
+    /// The sweep a real order with [`Order::max_avg_price`] would make; same
+    /// visibility rules as [`estimate_fill`](Self::estimate_fill).
+    pub fn estimate_capped_fill(
+        &self,
+        side: Side,
+        quantity: Quantity,
+        max_avg_price: Price,
+    ) -> FillEstimate {
+        self.estimate(side, quantity, Some(max_avg_price))
+    }
+
+    fn estimate(&self, side: Side, quantity: Quantity, cap: Option<Price>) -> FillEstimate {
+        let opposite_side = self.opposite_side_for(side);
+
+        match self.order_book_type {
+            OrderBookType::Transparent => opposite_side.sweep(quantity, false, None, cap),
+            OrderBookType::Hybrid => opposite_side.sweep(quantity, true, None, cap),
+            OrderBookType::DarkPool => FillEstimate {
+                fully_filled: !quantity.is_positive(),
+                ..FillEstimate::default()
+            },
+        }
+    }
+
+    /// Set or clear the price band
+    pub fn set_price_band(&self, band: Option<PriceBand>) {
+        *self.price_band.write() = band;
+    }
+
+    /// Move the price band reference as the market moves
+    ///
+    /// Has no effect if no price band is configured.
+    pub fn set_reference_price(&self, reference: Price) {
+        if let Some(band) = self.price_band.write().as_mut() {
+            band.reference = reference;
+        }
+    }
+
+    /// Update the reference mid used by midpoint-style algorithms
+    ///
+    /// A no-op for algorithms that trade at posted prices.
+    pub fn set_reference_mid(&self, mid: Price) {
+        self.algorithm.read().set_reference_mid(mid);
+    }
+
+    /// Get the current price band
+    pub fn get_price_band(&self) -> Option<PriceBand> {
+        *self.price_band.read()
+    }
+
+    /// Get spread
+    pub fn get_spread(&self) -> Option<Price> {
+        match (self.bids.best_price(), self.asks.best_price()) {
+            (Some(bid), Some(ask)) => ask.checked_sub(bid).ok(),
+            _ => None,
+        }
+    }
+
+    /// Get mid price
+    pub fn get_mid_price(&self) -> Option<Price> {
+        match (self.bids.best_price(), self.asks.best_price()) {
+            (Some(bid), Some(ask)) => bid
+                .checked_add(ask)
+                .ok()
+                .map(|sum| Price::from_raw(sum.raw_value() / 2)),
+            _ => None,
+        }
+    }
+
+    /// Whether an order for `quantity` on `side` at `price` would fill in full
+    /// against the current book (`None` prices like a market order)
+    ///
+    /// This is the scan fill-or-kill orders are checked with on arrival, so a
+    /// `true` here means an FOK submitted against the same book is not
+    /// rejected with `FillOrKillUnavailable`.
+    pub fn can_fully_fill(&self, side: Side, price: Option<Price>, quantity: Quantity) -> bool {
+        self.reachable_quantity(side, price) >= quantity
+    }
+
+    /// Position of `user_id` built from fills on this engine: net quantity
+    /// (long positive), average entry price and realized P&L
+    ///
+    /// Always flat unless position tracking is enabled in the config.
+    pub fn get_position(&self, user_id: &str) -> Position {
+        self.positions
+            .as_ref()
+            .map_or_else(Position::default, |positions| positions.get(user_id))
+    }
+
+    /// Running trade statistics since start or the last reset
+    pub fn get_statistics(&self) -> EngineStats {
+        self.statistics.lock().clone()
+    }
+
+    /// Clear the running trade statistics
+    pub fn reset_statistics(&self) {
+        *self.statistics.lock() = EngineStats::default();
+    }
+
+    /// Engine metrics in the Prometheus text exposition format
+    ///
+    /// Counters and the latency histogram are cumulative since start; book
+    /// gauges are read from the book at call time.
+    #[cfg(feature = "metrics")]
+    pub fn render_prometheus(&self) -> String {
+        let metrics = &self.metrics;
+        let mut out = PrometheusWriter::new(&[("instrument", self.instrument.as_str())]);
+        out.counter(
+            "matching_engine_orders_submitted_total",
+            "Orders submitted to the engine",
+            metrics.orders_submitted.get(),
+        );
+        out.counter(
+            "matching_engine_orders_rejected_total",
+            "Orders rejected on arrival",
+            metrics.orders_rejected.get(),
+        );
+        out.counter(
+            "matching_engine_trades_total",
+            "Trades executed",
+            metrics.trades.get(),
+        );
+        out.histogram(
+            "matching_engine_match_latency_seconds",
+            "Time to process a submitted order",
+            &metrics.match_latency,
+        );
+        let bid: &[(&str, &str)] = &[("side", "bid")];
+        let ask: &[(&str, &str)] = &[("side", "ask")];
+        out.gauge(
+            "matching_engine_book_depth",
+            "Resting quantity per side",
+            &[
+                (bid, self.bids.total_quantity()),
+                (ask, self.asks.total_quantity()),
+            ],
+        );
+        out.gauge(
+            "matching_engine_book_levels",
+            "Price levels per side",
+            &[
+                (bid, self.bids.level_count()),
+                (ask, self.asks.level_count()),
+            ],
+        );
+        out.finish()
+    }
+
+    /// Stop accepting orders ahead of a shutdown
+    ///
+    /// Every order submitted afterwards is rejected with
+    /// `RejectReason::Draining`; orders already being matched complete
+    /// normally and resting orders stay on the book (cancels, amends and
+    /// last-look decisions still work) until `finish_shutdown`.
+    pub fn begin_shutdown(&self) {
+        self.draining.store(true, Ordering::Release);
+    }
+
+    /// Cancel everything left on the book and return the `OrderCancelled` events
+    ///
+    /// Begins the shutdown first if `begin_shutdown` was not called, so no
+    /// new order can rest behind the sweep.
+    pub fn finish_shutdown(&self) -> Vec<OrderEvent> {
+        self.begin_shutdown();
+        self.clear_book()
+    }
+
+    /// Whether `begin_shutdown` has been called
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::Acquire)
+    }
+
+    /// Whether the circuit breaker has halted trading
+    pub fn is_halted(&self) -> bool {
+        self.halted.load(Ordering::Acquire)
+    }
+
+    /// Lift a circuit breaker halt
+    ///
+    /// The remembered trade prices are discarded so trading restarts from the
+    /// first trade after the halt. Returns `None` if trading was not halted.
+    pub fn resume(&self) -> Option<OrderEvent> {
+        if !self.halted.swap(false, Ordering::AcqRel) {
+            return None;
+        }
+        self.recent_trades.lock().clear();
+
+        let event = OrderEvent::TradingResumed {
+            timestamp: self.now(),
+        };
+        self.publish(vec![event.clone()]);
+        Some(event)
+    }
+
+    /// Replace the matching algorithm, e.g. at a session boundary
+    ///
+    /// Waits for any match in progress to finish; the new algorithm applies
+    /// from the next match. Resting orders keep their place in the book.
+    pub fn set_algorithm(&self, algorithm: Box<dyn MatchingAlgorithm>) -> OrderEvent {
+        let event = {
+            let mut current = self.algorithm.write();
+            let from = current.name().to_string();
+            *current = algorithm;
+            OrderEvent::AlgorithmChanged {
+                from,
+                to: current.name().to_string(),
+                timestamp: self.now(),
+            }
+        };
+        self.publish(vec![event.clone()]);
+        event
+    }
+
+    /// Stop matching on arrival: orders rest, even when they cross, until
+    /// `run_auction` uncrosses the book
+    pub fn enter_auction(&self) {
+        self.in_auction.store(true, Ordering::Release);
+    }
+
+    /// Return to continuous matching
+    ///
+    /// Call `run_auction` first; anything still crossed is matched by the
+    /// continuous algorithm. Returns the events of that clean-up.
+    pub fn exit_auction(&self) -> Vec<OrderEvent> {
+        if !self.in_auction.swap(false, Ordering::AcqRel) {
+            return Vec::new();
+        }
+
+        let _writer = self.book_writer();
+        let mut events = Vec::new();
+        let mut touched = Vec::new();
+        self.settle_book(&mut events, &mut touched);
+        let sequenced = self.seal(events, &touched);
+        let events = Self::unsequenced(&sequenced);
+        self.deliver(sequenced);
+        events
+    }
+
+    /// Whether the engine is collecting orders for an auction
+    pub fn in_auction(&self) -> bool {
+        self.in_auction.load(Ordering::Acquire)
+    }
+
+    /// Uncross the book at the single price that maximizes executed quantity
+    ///
+    /// Orders trade best price first and in time priority within a level, all
+    /// at the clearing price. Of each pair, the earlier order is the maker.
+    /// Every order that traded gets an `OrderFilled` or `OrderPartiallyFilled`
+    /// event. With self-trade prevention on, a pair with the same owner does
+    /// not trade: the newer of the two is cancelled and matching goes on.
+    /// Does nothing unless the engine is in auction mode.
+    pub fn run_auction(&self) -> AuctionResult {
+        if !self.in_auction() {
+            return AuctionResult::default();
+        }
+
+        let _writer = self.book_writer();
+        let (clearing_price, volume) = match auction::clearing_price(&self.bids, &self.asks) {
+            Some(clearing) => clearing,
+            None => return AuctionResult::default(),
+        };
+
+        let front = |book: &OrderBookSide| {
+            book.best_level()
+                .and_then(|level| level.snapshot_orders().into_iter().next())
+        };
+        let mut events = Vec::new();
+        let mut touched = Vec::new();
+        let mut participants: Vec<Arc<Order>> = Vec::new();
+        let mut cancelled = Vec::new();
+        let mut left = volume;
+        while left.is_positive() {
+            // Enough quantity crosses the clearing price on both sides to fill
+            // `volume`, unless self-trade cancellations took some of it away
+            let (bid, ask) = match (front(&self.bids), front(&self.asks)) {
+                (Some(bid), Some(ask)) => (bid, ask),
+                _ => break,
+            };
+            let crosses = |book: &OrderBookSide, order: &Order| {
+                order
+                    .price()
+                    .is_none_or(|price| book.is_at_or_better(price, clearing_price))
+            };
+            if !crosses(&self.bids, &bid) || !crosses(&self.asks, &ask) {
+                break;
+            }
+
+            if self.self_trade_prevention && bid.shares_owner_with(&ask) {
+                let newer = if bid.get_sequence_number() < ask.get_sequence_number() {
+                    ask
+                } else {
+                    bid
+                };
+                self.order_index.write().remove(&newer.id);
+                cancelled.extend(self.cancel_resting(&newer));
+                if let Some(price) = newer.price() {
+                    touched.push((newer.side, price));
+                }
+                continue;
+            }
+
+            let quantity = left
+                .min(bid.get_remaining_quantity())
+                .min(ask.get_remaining_quantity());
+            for order in [&bid, &ask] {
+                self.fill_in_place(order, quantity);
+                if let Some(price) = order.price() {
+                    touched.push((order.side, price));
+                }
+                if !participants.iter().any(|p| p.id == order.id) {
+                    participants.push(Arc::clone(order));
+                }
+            }
+
+            let (maker, taker) = if bid.get_sequence_number() < ask.get_sequence_number() {
+                (&bid, &ask)
+            } else {
+                (&ask, &bid)
+            };
+            let trade = Trade::new(
+                (*self.instrument).clone(),
+                maker.id,
+                taker.id,
+                clearing_price,
+                quantity,
+                taker.side,
+            );
+            self.record_trades(taker, vec![trade], &mut events, &mut touched);
+            left = left.saturating_sub(quantity);
+        }
+
+        for order in &participants {
+            let filled = order.get_filled_quantity();
+            let remaining = order.get_remaining_quantity();
+            if remaining == Quantity::ZERO {
+                self.order_index.write().remove(&order.id);
+                self.client_order_ids.release(order);
+                events.push(OrderEvent::OrderFilled {
+                    order_id: order.id,
+                    total_filled: filled,
+                    timestamp: self.now(),
+                });
+            } else {
+                events.push(OrderEvent::OrderPartiallyFilled {
+                    order_id: order.id,
+                    filled_quantity: filled,
+                    remaining_quantity: remaining,
+                    timestamp: self.now(),
+                });
+            }
+        }
+        events.extend(cancelled);
+
+        let trades: Vec<Trade> = events
+            .iter()
+            .filter_map(|event| match event {
+                OrderEvent::OrderMatched { trade, .. } => Some(trade.clone()),
+                _ => None,
+            })
+            .collect();
+        let matched_quantity = volume.saturating_sub(left);
+        self.emit(events, &touched);
+
+        AuctionResult {
+            clearing_price: Some(clearing_price),
+            matched_quantity,
+            trades,
+        }
+    }
+
+    /// Whether the best bid is at or above the best ask
+    /// (at or below it on an inverted book)
+    pub fn is_crossed(&self) -> bool {
+        match (self.bids.best_price(), self.asks.best_price()) {
+            (Some(bid), Some(ask)) => self.bids.is_at_or_better(bid, ask),
+            _ => false,
+        }
+    }
+
+    /// Whether the best bid equals the best ask
+    pub fn is_locked(&self) -> bool {
+        match (self.bids.best_price(), self.asks.best_price()) {
+            (Some(bid), Some(ask)) => bid == ask,
+            _ => false,
+        }
+    }
+
+    /// Current time according to the engine's clock
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Core the constructing thread was pinned to, if pinning succeeded
+    pub fn pinned_core(&self) -> Option<usize> {
+        self.pinned_core
+    }
+
+    /// Get the instrument name
+    pub fn get_instrument(&self) -> &str {
+        &self.instrument
+    }
+
+    /// Get the order book type
+    pub fn get_order_book_type(&self) -> OrderBookType {
+        self.order_book_type
+    }
+
+    // ========================================================================
+    // Private methods
+    // ========================================================================
+
+    /// Book side where orders on `side` rest
+    fn side_for(&self, side: Side) -> &OrderBookSide {
+        match side {
+            Side::Buy => &self.bids,
+            Side::Sell => &self.asks,
+        }
+    }
+
+    /// Book side that orders on `side` trade against
+    fn opposite_side_for(&self, side: Side) -> &OrderBookSide {
+        self.side_for(side.opposite())
+    }
+
+    fn add_to_book(&self, order: Arc<Order>) {
+        self.side_for(order.side).add_order(Arc::clone(&order));
+
+        if order.is_pegged() {
+            self.pegged_orders.write().insert(order.id);
+        }
+
+        // Index for cancellation
+        self.order_index.write().insert(order.id, order);
+    }
+
+    /// Reprice trades against `taker` under the engine's trade price rule
+    ///
+    /// Runs before `record_trades` so fees, positions and statistics see the
+    /// printed price.
+    fn apply_trade_price_rule(&self, taker: &Order, trades: &mut [Trade]) {
+        if self.trade_price_rule == TradePriceRule::MakerPrice {
+            return;
+        }
+        for trade in trades {
+            trade.price = self
+                .trade_price_rule
+                .trade_price(trade.price, taker.price());
+        }
+    }
+
+    /// Stamp trade ids and fees, emit `OrderMatched` events, update positions and
+    /// settle the makers
+    fn record_trades(
+        &self,
+        taker: &Order,
+        trades: impl IntoIterator<Item = Trade>,
+        events: &mut Vec<OrderEvent>,
+        touched: &mut Vec<(Side, Price)>,
+    ) {
+        let mut makers: Vec<Arc<Order>> = Vec::new();
+        let mut filled_ids = Vec::new();
+        for mut trade in trades {
+            trade.trade_id = self.trade_counter.fetch_add(1, Ordering::AcqRel) + 1;
+            trade.timestamp = self.now();
+            self.fee_schedule.apply(&mut trade);
+            self.statistics.lock().record(&trade);
+            #[cfg(feature = "metrics")]
+            self.metrics.trades.inc();
+            *self.last_trade.lock() = Some((trade.trade_id, trade.price, trade.quantity));
+            if self.circuit_breaker.is_some() {
+                self.recent_trades
+                    .lock()
+                    .push_back((trade.timestamp, trade.price));
+            }
+
+            let known = makers.iter().position(|m| m.id == trade.maker_order_id);
+            let maker = match known {
+                Some(i) => Some(Arc::clone(&makers[i])),
+                None => {
+                    let maker = self.order_index.read().get(&trade.maker_order_id).cloned();
+                    makers.extend(maker.clone());
+                    maker
+                },
+            };
+            if let Some(positions) = &self.positions {
+                positions.apply_fill(&taker.user_id, taker.side, trade.price, trade.quantity);
+                if let Some(maker) = &maker {
+                    positions.apply_fill(&maker.user_id, maker.side, trade.price, trade.quantity);
+                }
+            }
+            filled_ids.push(taker.id);
+            filled_ids.push(trade.maker_order_id);
+
+            events.push(OrderEvent::OrderMatched {
+                trade,
+                timestamp: self.now(),
+            });
+        }
+
+        for maker in makers {
+            // Fully filled makers have left the book
+            if maker.get_remaining_quantity() == Quantity::ZERO {
+                self.order_index.write().remove(&maker.id);
+                self.client_order_ids.release(&maker);
+            }
+
+            if let Some(price) = maker.price() {
+                touched.push((maker.side, price));
+            }
+
+            // Iceberg makers that reloaded their display slice during matching
+            if maker.take_display_refreshed() {
+                events.push(OrderEvent::IcebergRefreshed {
+                    order_id: maker.id,
+                    visible_quantity: maker.get_visible_quantity(),
+                    timestamp: self.now(),
+                });
+            }
+        }
+
+        if !self.oco_links.read().is_empty() {
+            for filled_id in filled_ids {
+                self.trigger_oco(filled_id, events, touched);
+            }
+        }
+    }
+
+    /// Cancel the other leg of `filled_id`'s OCO pair, if it has one
+    fn trigger_oco(
+        &self,
+        filled_id: OrderId,
+        events: &mut Vec<OrderEvent>,
+        touched: &mut Vec<(Side, Price)>,
+    ) {
+        let cancelled_id = match self.unlink_oco(filled_id) {
+            Some(id) => id,
+            None => return,
+        };
+
+        events.push(OrderEvent::OcoTriggered {
+            filled_id,
+            cancelled_id,
+            timestamp: self.now(),
+        });
+        let resting = self.order_index.write().remove(&cancelled_id);
+        if let Some(order) = resting {
+            events.extend(self.cancel_resting(&order));
+            touched.extend(Self::resting_levels(&[order]));
+        }
+    }
+
+    /// Drop the OCO link of `order_id`, returning the other leg
+    fn unlink_oco(&self, order_id: OrderId) -> Option<OrderId> {
+        let mut links = self.oco_links.write();
+        let other = links.remove(&order_id)?;
+        links.remove(&other);
+        Some(other)
+    }
+
+    /// Fill a resting order in place during replay, removing it once filled
+    fn replay_fill(&self, order_id: OrderId, quantity: Quantity) {
+        let order = match self.order_index.read().get(&order_id).cloned() {
+            Some(order) => order,
+            None => return,
+        };
+        if !order.try_fill(quantity) {
+            return;
+        }
+
+        if let Some(level) = self.level_of(&order) {
+            level.subtract_quantity(quantity);
+        }
+        if order.get_remaining_quantity() == Quantity::ZERO {
+            self.order_index.write().remove(&order_id);
+            self.remove_from_book(&order);
+        }
+    }
+
+    /// The level a resting order sits on
+    fn level_of(&self, order: &Order) -> Option<Arc<OrderBookLevel>> {
+        let book = self.side_for(order.side);
+        book.levels
+            .get(&order.price()?.raw_value())
+            .map(|entry| Arc::clone(entry.value()))
+    }
+
+    /// Quantity a reduce-only order may trade without growing the position
+    ///
+    /// Returns `Ok(None)` for ordinary orders and orders that already fit,
+    /// `Ok(Some(cap))` when the order must be shrunk to `cap`, and an error
+    /// when there is no opposing position to reduce (always the case when
+    /// positions are not tracked).
+    fn reduce_only_cap(&self, order: &Order) -> Result<Option<Quantity>, RejectReason> {
+        if !order.reduce_only {
+            return Ok(None);
+        }
+
+        let position = self.positions.as_ref().map_or(Quantity::ZERO, |positions| {
+            positions.net_position(&order.user_id)
+        });
+        let reducible = match order.side {
+            Side::Buy => Quantity::ZERO.saturating_sub(position),
+            Side::Sell => position,
+        };
+
+        if !reducible.is_positive() {
+            Err(RejectReason::ReduceOnlyIncreasesPosition)
+        } else if order.get_remaining_quantity() > reducible {
+            Ok(Some(reducible))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reject `order` if it crosses a resting order with the same owner
+    ///
+    /// Ownership follows `Order::shares_owner_with` (same user, or same
+    /// account group). The incoming order is rejected as a whole, even if
+    /// better-priced liquidity would have filled it first. Not applied during
+    /// an auction, where orders rest without matching; `run_auction` checks
+    /// owners as it uncrosses.
+    fn check_self_trade(&self, order: &Order) -> Result<(), RejectReason> {
+        if !self.self_trade_prevention || self.in_auction() {
+            return Ok(());
+        }
+        let opposite_side = self.opposite_side_for(order.side);
+        let crosses = |level: Price| match (order.price(), order.is_market_order()) {
+            (Some(price), false) => opposite_side.is_at_or_better(level, price),
+            _ => true,
+        };
+
+        let self_trade = opposite_side
+            .levels
+            .iter()
+            .filter(|entry| crosses(entry.value().price))
+            .any(|entry| {
+                entry
+                    .value()
+                    .snapshot_orders()
+                    .iter()
+                    .any(|resting| resting.shares_owner_with(order))
+            });
+        if self_trade {
+            return Err(RejectReason::SelfTrade);
+        }
+        Ok(())
+    }
+
+    /// Reject `order` if less than its minimum fill would execute on arrival
+    ///
+    /// Fill-or-kill orders must fill their whole size, so they are rejected
+    /// here instead of partly trading and cancelling the rest. Counts the
+    /// opposite-side quantity at prices the order crosses, limited to the
+    /// order's size (after any reduce-only `cap`) and to what its average
+    /// price cap allows. Neither check applies during an auction.
+    fn check_min_fill(&self, order: &Order, cap: Option<Quantity>) -> Result<(), RejectReason> {
+        if self.in_auction() {
+            return Ok(());
+        }
+        let size = cap.unwrap_or_else(|| order.get_remaining_quantity());
+        let price = order.price().filter(|_| !order.is_market_order());
+        let sweep_limit = self.average_price_limit(order);
+
+        if order.time_in_force == crate::domain::TimeInForce::FillOrKill
+            && (!self.can_fully_fill(order.side, price, size)
+                || sweep_limit.is_some_and(|limit| limit < size))
+        {
+            return Err(RejectReason::FillOrKillUnavailable);
+        }
+
+        if let Some(min_fill) = order.min_fill_quantity {
+            let reachable = self.reachable_quantity(order.side, price).min(size);
+            if sweep_limit.map_or(reachable, |limit| reachable.min(limit)) < min_fill {
+                return Err(RejectReason::MinFillUnavailable);
+            }
+        }
+        Ok(())
+    }
+
+    /// How much of `order` can trade before its average fill price would pass
+    /// `max_avg_price`, if the cap stops the sweep short of its remaining size
+    fn average_price_limit(&self, order: &Order) -> Option<Quantity> {
+        let cap = order.max_avg_price?;
+        let price = order.price().filter(|_| !order.is_market_order());
+        let sweep = self.opposite_side_for(order.side).sweep(
+            order.get_remaining_quantity(),
+            false,
+            price,
+            Some(cap),
+        );
+        sweep.cap_reached.then_some(sweep.filled_quantity)
+    }
+
+    /// Resting quantity an order on `side` at `price` could trade against
+    /// (every level of the opposite side when `price` is `None`)
+    fn reachable_quantity(&self, side: Side, price: Option<Price>) -> Quantity {
+        let opposite_side = self.opposite_side_for(side);
+        match price {
+            Some(price) => opposite_side.cumulative_quantity_to_price(price),
+            None => opposite_side.total_quantity(),
+        }
+    }
+
+    /// Re-match resting orders until the book is no longer crossed
+    ///
+    /// The later-sequenced of the two top-of-book orders is treated as the
+    /// aggressor: it is pulled off its level, matched against the other side
+    /// and any remainder is rested again. Stops as soon as a pass produces no
+    /// trades so a misbehaving algorithm cannot loop forever.
+    fn resolve_crossed(&self, events: &mut Vec<OrderEvent>, touched: &mut Vec<(Side, Price)>) {
+        let (best_bid, best_ask) = match (self.bids.best_price(), self.asks.best_price()) {
+            (Some(bid), Some(ask)) if self.bids.is_at_or_better(bid, ask) => (bid, ask),
+            _ => return,
+        };
+
+        let mut trade_count = 0;
+        while self.is_crossed() {
+            let front = |book: &OrderBookSide| {
+                book.best_level()
+                    .and_then(|level| level.snapshot_orders().into_iter().next())
+            };
+            let aggressor = match (front(&self.bids), front(&self.asks)) {
+                (Some(bid), Some(ask)) => {
+                    if bid.get_sequence_number() > ask.get_sequence_number() {
+                        bid
+                    } else {
+                        ask
+                    }
+                },
+                _ => break,
+            };
+
+            if self.remove_from_book(&aggressor).is_none() {
+                break;
+            }
+            let opposite_side = self.opposite_side_for(aggressor.side);
+            let mut trades = self
+                .algorithm
+                .read()
+                .match_order(Arc::clone(&aggressor), opposite_side);
+            let matched = trades.len();
+            trade_count += matched;
+            self.apply_trade_price_rule(&aggressor, &mut trades);
+            self.record_trades(&aggressor, trades, events, touched);
+
+            let remaining = aggressor.get_remaining_quantity();
+            if remaining == Quantity::ZERO {
+                self.order_index.write().remove(&aggressor.id);
+                self.client_order_ids.release(&aggressor);
+                events.push(OrderEvent::OrderFilled {
+                    order_id: aggressor.id,
+                    total_filled: aggressor.get_filled_quantity(),
+                    timestamp: self.now(),
+                });
+            } else {
+                self.side_for(aggressor.side)
+                    .add_order(Arc::clone(&aggressor));
+            }
+            if let Some(price) = aggressor.price() {
+                touched.push((aggressor.side, price));
+            }
+
+            if matched == 0 {
+                break;
+            }
+        }
+
+        if trade_count > 0 {
+            events.push(OrderEvent::BookCrossedResolved {
+                best_bid,
+                best_ask,
+                trade_count,
+                timestamp: self.now(),
+            });
+        }
+    }
+
+    /// Append book deltas for the touched levels and hand the batch to the event handler
+    fn emit(&self, mut events: Vec<OrderEvent>, touched: &[(Side, Price)]) {
+        let mut touched = touched.to_vec();
+        self.settle_book(&mut events, &mut touched);
+        self.deliver(self.seal(events, &touched));
+    }
+
+    /// Hand events to the event handler, stamped with consecutive sequence numbers
+    fn publish(&self, events: Vec<OrderEvent>) {
+        self.deliver(self.sequence(events));
+    }
+
+    /// Append book deltas for the touched levels and number the batch
+    ///
+    /// Deltas are read and numbered under the feed lock, so sequence order
+    /// follows the order levels were read in and `snapshot_with_sequence`
+    /// falls wholly before or after each batch.
+    fn seal(&self, mut events: Vec<OrderEvent>, touched: &[(Side, Price)]) -> Vec<SequencedEvent> {
+        let _feed = self.feed.lock();
+        self.append_deltas(&mut events, touched);
+        self.sequence(events)
+    }
+
+    /// Stamp events with consecutive sequence numbers
+    fn sequence(&self, events: Vec<OrderEvent>) -> Vec<SequencedEvent> {
+        if events.is_empty() {
+            return Vec::new();
+        }
+        let first = self
+            .event_sequence
+            .fetch_add(events.len() as u64, Ordering::AcqRel)
+            + 1;
+        events
+            .into_iter()
+            .zip(first..)
+            .map(|(event, seq)| SequencedEvent { seq, event })
+            .collect()
+    }
+
+    /// Hand sequenced events to the event handler
+    fn deliver(&self, events: Vec<SequencedEvent>) {
+        if !events.is_empty() {
+            self.event_handler.on_sequenced_events(events);
+        }
+    }
+
+    /// The events of a sequenced batch, as returned to callers
+    fn unsequenced(events: &[SequencedEvent]) -> Vec<OrderEvent> {
+        events.iter().map(|e| e.event.clone()).collect()
+    }
+
+    /// Follow-up work after the book changed: reprice pegged orders, then
+    /// uncross the book if an algorithm stopped early or a peg crossed it
+    ///
+    /// A crossed book is expected during an auction and left alone.
+    fn settle_book(&self, events: &mut Vec<OrderEvent>, touched: &mut Vec<(Side, Price)>) {
+        self.reprice_pegged(events, touched);
+        if !self.in_auction() {
+            self.resolve_crossed(events, touched);
+        }
+    }
+
+    /// Price a pegged or protected market order off the current book
+    ///
+    /// Other orders are left unchanged, as are pegged orders whose
+    /// reference is missing (validation rejects those for lack of a price)
+    /// and protected market orders facing an empty book (they cancel like
+    /// market orders).
+    fn price_from_book(&self, order: &Order) {
+        let price = match order.order_type {
+            OrderType::Pegged { reference, offset } => self.peg_price(reference, offset),
+            OrderType::MarketWithProtection { protection_ticks } => {
+                self.protection_price(order.side, protection_ticks)
+            },
+            _ => return,
+        };
+        if let Some(price) = price {
+            order.set_price(price);
+        }
+    }
+
+    /// Best opposite price moved `ticks` ticks against an incoming order on
+    /// `side`, if there is a tick size and an opposite side to measure from
+    ///
+    /// A limit past the end of the price range leaves the order unbounded.
+    fn protection_price(&self, side: Side, ticks: u32) -> Option<Price> {
+        let tick = self.tick_size?;
+        let ticks = i64::from(ticks);
+        match side {
+            Side::Buy => Some(
+                self.asks
+                    .best_price()?
+                    .add_ticks(ticks, tick)
+                    .unwrap_or(Price::MAX),
+            ),
+            Side::Sell => Some(
+                self.bids
+                    .best_price()?
+                    .add_ticks(-ticks, tick)
+                    .unwrap_or(Price::MIN),
+            ),
+        }
+    }
+
+    /// `reference + offset`, if the reference exists and the result is a
+    /// valid price for this book
+    fn peg_price(&self, reference: PegReference, offset: Price) -> Option<Price> {
+        let base = match reference {
+            PegReference::BestBid => self.best_unpegged(&self.bids)?,
+            PegReference::BestAsk => self.best_unpegged(&self.asks)?,
+            PegReference::Mid => {
+                let bid = self.best_unpegged(&self.bids)?;
+                let ask = self.best_unpegged(&self.asks)?;
+                Price::from_raw(((bid.raw_value() as i128 + ask.raw_value() as i128) / 2) as i64)
+            },
+        };
+        base.checked_add(offset)
+            .ok()
+            .filter(|price| self.allow_non_positive_prices || price.is_positive())
+    }
+
+    /// Best price on `side` backed by at least one non-pegged order
+    ///
+    /// Pegged orders are skipped so they never chase their own price.
+    fn best_unpegged(&self, side: &OrderBookSide) -> Option<Price> {
+        side.levels_best_first()
+            .map(|entry| Arc::clone(entry.value()))
+            .find(|level| level.snapshot_orders().iter().any(|o| !o.is_pegged()))
+            .map(|level| level.price)
+    }
+
+    /// Move resting pegged orders whose reference price changed
+    ///
+    /// A repriced order is re-queued at its new level with a fresh sequence
+    /// number, so it loses time priority.
+    fn reprice_pegged(&self, events: &mut Vec<OrderEvent>, touched: &mut Vec<(Side, Price)>) {
+        let ids: Vec<OrderId> = self.pegged_orders.read().iter().copied().collect();
+        for id in ids {
+            let order = match self.order_index.read().get(&id).cloned() {
+                Some(order) => order,
+                None => {
+                    self.pegged_orders.write().remove(&id);
+                    continue;
+                },
+            };
+            let new_price = match order.order_type {
+                OrderType::Pegged { reference, offset } => self.peg_price(reference, offset),
+                _ => None,
+            };
+            let (old_price, new_price) = match (order.price(), new_price) {
+                (Some(old), Some(new)) if old != new => (old, new),
+                _ => continue,
+            };
+
+            self.remove_from_book(&order);
+            order.set_price(new_price);
+            let seq = self.sequence_counter.fetch_add(1, Ordering::AcqRel);
+            order.set_sequence_number(seq as i64);
+            self.add_to_book(Arc::clone(&order));
+
+            touched.push((order.side, old_price));
+            touched.push((order.side, new_price));
+            events.push(OrderEvent::OrderRepriced {
+                order_id: id,
+                old_price,
+                new_price,
+                timestamp: self.now(),
+            });
+        }
+    }
+
+    /// Append book deltas for the touched levels and publish them to subscribers
+    ///
+    /// Also invalidates the cached snapshot. In debug builds the touched
+    /// levels' cached totals are checked against their orders first.
+    fn append_deltas(&self, events: &mut Vec<OrderEvent>, touched: &[(Side, Price)]) {
+        if touched.is_empty() {
+            return;
+        }
+        self.debug_check_levels(touched);
+        let version = self.book_version.fetch_add(1, Ordering::AcqRel) + 1;
+
+        let deltas = self.book_deltas(touched);
+        if !deltas.is_empty() {
+            self.publish_deltas(&deltas);
+            events.extend(deltas);
+        }
+        self.append_bbo_changes(events);
+
+        // Checksum after the deltas so consumers verify the updated book
+        if let Some(schedule) = self.checksum_schedule {
+            if version.is_multiple_of(schedule.interval) {
+                events.push(OrderEvent::BookChecksum {
+                    crc: self.book_checksum(schedule.depth),
+                    depth: schedule.depth,
+                    timestamp: self.now(),
+                });
+            }
+        }
+    }
+
+    /// Debug builds: mark a book mutation in progress until the guard drops
+    fn book_writer(&self) -> BookWriter<'_> {
+        if cfg!(debug_assertions) {
+            self.writers.fetch_add(1, Ordering::AcqRel);
+            self.writes_started.fetch_add(1, Ordering::AcqRel);
+        }
+        BookWriter(self)
+    }
+
+    /// Debug builds: check that each touched level's cached total matches its
+    /// orders, and each side's total its levels
+    ///
+    /// Drift panics when this thread was the only writer for the whole check
+    /// (cancels, amends and matching without concurrent callers). If another
+    /// mutation was in flight it may be between updating an order and its
+    /// level, so drift is only logged.
+    fn debug_check_levels(&self, touched: &[(Side, Price)]) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let started = self.writes_started.load(Ordering::Acquire);
+        let mut drift = Vec::new();
+        for &(side, price) in touched {
+            if let Some(level) = self.side_for(side).level_at_price(price) {
+                let (cached, recomputed) = (level.get_total_quantity(), level.recompute_total());
+                if cached != recomputed {
+                    drift.push(format!(
+                        "cached total at {price} is {cached} but its orders hold {recomputed}"
+                    ));
+                }
+            }
+        }
+        for side in [&self.bids, &self.asks] {
+            let (cached, recomputed) = (side.total_quantity(), side.levels_total());
+            if cached != recomputed {
+                drift.push(format!(
+                    "{:?} side total is {cached} but its levels hold {recomputed}",
+                    side.side
+                ));
+            }
+        }
+        if drift.is_empty() {
+            return;
+        }
+
+        let sole_writer = self.writers.load(Ordering::Acquire) == 1
+            && self.writes_started.load(Ordering::Acquire) == started;
+        for message in drift {
+            if sole_writer {
+                panic!("{message}");
+            }
+            tracing::warn!("{}", message);
+        }
+    }
+
+    /// Current quantity of each touched level, one delta per distinct level
+    ///
+    /// Follows snapshot visibility: hybrid books report displayed quantity and
+    /// dark pools publish no deltas.
+    fn book_deltas(&self, touched: &[(Side, Price)]) -> Vec<OrderEvent> {
+        if self.order_book_type == OrderBookType::DarkPool {
+            return Vec::new();
+        }
+
+        let mut seen = Vec::with_capacity(touched.len());
+        for level in touched {
+            if !seen.contains(level) {
+                seen.push(*level);
+            }
+        }
+
+        seen.into_iter()
+            .map(|(side, price)| {
+                let book = self.side_for(side);
+                let new_quantity = book
+                    .levels
+                    .get(&price.raw_value())
+                    .map(|entry| match self.order_book_type {
+                        OrderBookType::Hybrid => entry.value().get_visible_quantity(),
+                        _ => entry.value().get_total_quantity(),
+                    })
+                    .unwrap_or(Quantity::ZERO);
+
+                OrderEvent::BookDelta {
+                    side,
+                    price,
+                    new_quantity,
+                    timestamp: self.now(),
+                }
+            })
+            .collect()
+    }
+
+    /// Emit `BestBidChanged`/`BestAskChanged` for each side whose top of book
+    /// moved since it was last published
+    ///
+    /// Follows snapshot visibility like `book_deltas`.
+    fn append_bbo_changes(&self, events: &mut Vec<OrderEvent>) {
+        if self.order_book_type == OrderBookType::DarkPool {
+            return;
+        }
+
+        let top = |book: &OrderBookSide| {
+            let depth = match self.order_book_type {
+                OrderBookType::Hybrid => book.get_visible_depth(1),
+                _ => book.get_depth(1),
+            };
+            depth.first().copied()
+        };
+        let bid = top(&self.bids);
+        let ask = top(&self.asks);
+
+        let mut last = self.last_bbo.lock();
+        if last[0] != bid {
+            events.push(OrderEvent::BestBidChanged {
+                price: bid.map(|(price, _)| price),
+                quantity: bid.map_or(Quantity::ZERO, |(_, quantity)| quantity),
+                timestamp: self.now(),
+            });
+        }
+        if last[1] != ask {
+            events.push(OrderEvent::BestAskChanged {
+                price: ask.map(|(price, _)| price),
+                quantity: ask.map_or(Quantity::ZERO, |(_, quantity)| quantity),
+                timestamp: self.now(),
+            });
+        }
+        *last = [bid, ask];
+    }
+
+    /// Send deltas to subscribers, dropping those whose receiver has gone away
+    fn publish_deltas(&self, deltas: &[OrderEvent]) {
+        let mut subscribers = self.delta_subscribers.write();
+        subscribers.retain(|sender| {
+            deltas
+                .iter()
+                .all(|delta| sender.send(delta.clone()).is_ok())
+        });
+    }
+
+    /// Price levels the given resting orders sat on
+    fn resting_levels(orders: &[Arc<Order>]) -> Vec<(Side, Price)> {
+        orders
+            .iter()
+            .filter_map(|order| order.price().map(|price| (order.side, price)))
+            .collect()
+    }
+
+    /// Pull a resting order off its level and mark it cancelled
+    fn cancel_resting(&self, order: &Arc<Order>) -> Option<OrderEvent> {
+        self.remove_from_book(order);
+        self.unlink_oco(order.id);
+        self.client_order_ids.release(order);
+
+        order.try_cancel().then(|| OrderEvent::OrderCancelled {
+            order_id: order.id,
+            timestamp: self.now(),
+        })
+    }
+
+    /// Hold each of `taker`'s prospective trades for the maker's last look
+    fn hold_for_last_look(
+        &self,
+        taker: &Arc<Order>,
+        trades: Vec<Trade>,
+        hold: Duration,
+        events: &mut Vec<OrderEvent>,
+    ) {
+        let deadline = chrono::Duration::from_std(hold)
+            .ok()
+            .and_then(|hold| self.now().checked_add_signed(hold))
+            .unwrap_or(DateTime::<Utc>::MAX_UTC);
+        let mut pending = self.pending_last_looks.lock();
+        for trade in trades {
+            let match_id = self.last_look_counter.fetch_add(1, Ordering::AcqRel) + 1;
+            events.push(OrderEvent::LastLookPending {
+                match_id,
+                maker_id: trade.maker_order_id,
+                taker_id: taker.id,
+                price: trade.price,
+                quantity: trade.quantity,
+                timestamp: self.now(),
+            });
+            pending.insert(
+                match_id,
+                PendingMatch {
+                    maker_id: trade.maker_order_id,
+                    taker: Arc::clone(taker),
+                    price: trade.price,
+                    quantity: trade.quantity,
+                    deadline,
+                },
+            );
+        }
+    }
+
+    /// Close out a last-look taker once none of its matches are pending
+    ///
+    /// Takers never rest under last look: what did not trade is cancelled.
+    fn finish_last_look(&self, taker: &Order, events: &mut Vec<OrderEvent>) {
+        if self
+            .pending_last_looks
+            .lock()
+            .values()
+            .any(|held| held.taker.id == taker.id)
+        {
+            return;
+        }
+
+        let filled = taker.get_filled_quantity();
+        let remaining = taker.get_remaining_quantity();
+        if remaining == Quantity::ZERO && filled > Quantity::ZERO {
+            events.push(OrderEvent::OrderFilled {
+                order_id: taker.id,
+                total_filled: filled,
+                timestamp: self.now(),
+            });
+            return;
+        }
+        if filled > Quantity::ZERO {
+            events.push(OrderEvent::OrderPartiallyFilled {
+                order_id: taker.id,
+                filled_quantity: filled,
+                remaining_quantity: remaining,
+                timestamp: self.now(),
+            });
+        }
+        taker.set_state(OrderState::Cancelled);
+        events.push(OrderEvent::OrderCancelled {
+            order_id: taker.id,
+            timestamp: self.now(),
+        });
+    }
+
+    fn last_look_rejected(&self, match_id: u64, held: &PendingMatch) -> OrderEvent {
+        OrderEvent::LastLookRejected {
+            match_id,
+            maker_id: held.maker_id,
+            taker_id: held.taker.id,
+            timestamp: self.now(),
+        }
+    }
+
+    /// Fill a resting order in place (auction uncrossing, confirmed last looks)
+    ///
+    /// An order that is used up leaves the book; otherwise it keeps its queue
+    /// position and only the level total shrinks.
+    fn fill_in_place(&self, order: &Arc<Order>, quantity: Quantity) {
+        if quantity == order.get_remaining_quantity() {
+            self.remove_from_book(order);
+            order.try_fill(quantity);
+            return;
+        }
+
+        order.try_fill(quantity);
+        if let Some(level) = self.level_of(order) {
+            level.subtract_quantity(quantity);
+        }
+        if order.is_iceberg() && order.consume_display(quantity) {
+            order.refresh_display();
+        }
+    }
+
+    fn remove_from_book(&self, order: &Order) -> Option<Arc<Order>> {
+        self.side_for(order.side).remove_order(order)
+    }
+
+    /// Refuse new orders once shutdown has begun
+    fn check_accepting(&self) -> Result<(), RejectReason> {
+        if self.is_draining() {
+            return Err(RejectReason::Draining);
+        }
+        Ok(())
+    }
+
+    /// Hold `order`'s client order id, refusing one already held by a live order
+    fn claim_client_order_id(&self, order: &Arc<Order>) -> Result<(), RejectReason> {
+        if !self.client_order_ids.claim(order) {
+            return Err(RejectReason::DuplicateClientOrderId);
+        }
+        Ok(())
+    }
+
+    /// Charge `order` against its user's rate limit
+    fn check_rate_limit(&self, order: &Order) -> Result<(), RejectReason> {
+        match &self.rate_limiter {
+            Some(limiter) if !limiter.try_acquire(&order.user_id, self.now()) => {
+                Err(RejectReason::RateLimited)
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Halt trading if matching `order` would trade too far from a recent trade
+    ///
+    /// The worst execution price is estimated by walking the opposite side up to
+    /// the order's limit. Returns the `TradingHalted` event when the breaker trips.
+    fn check_circuit_breaker(&self, order: &Order) -> Option<OrderEvent> {
+        // Auction orders do not trade on arrival
+        if self.in_auction() {
+            return None;
+        }
+        let breaker = self.circuit_breaker?;
+        let opposite_side = self.opposite_side_for(order.side);
+
+        let estimate = opposite_side.estimate_fill(order.get_remaining_quantity(), false);
+        let mut price = estimate.worst_price?;
+        if let Some(limit) = order.price() {
+            if !opposite_side.is_at_or_better(price, limit) {
+                price = limit;
+            }
+            let best = opposite_side.best_price()?;
+            if !opposite_side.is_at_or_better(best, limit) {
+                return None;
+            }
+        }
+
+        let reference_price = {
+            let mut recent = self.recent_trades.lock();
+            let now = self.now();
+            while let Some(&(at, _)) = recent.front() {
+                // A clock set backwards leaves the trade inside the window
+                match (now - at).to_std() {
+                    Ok(age) if age > breaker.window => recent.pop_front(),
+                    _ => break,
+                };
+            }
+            recent
+                .iter()
+                .map(|&(_, reference)| reference)
+                .find(|&reference| breaker.is_breached(reference, price))?
+        };
+
+        self.halted.store(true, Ordering::Release);
+        Some(OrderEvent::TradingHalted {
+            reference_price,
+            price,
+            timestamp: self.now(),
+        })
+    }
+
+    fn validate_order(&self, order: &Order) -> Result<(), RejectReason> {
+        if self.is_halted() {
+            return Err(RejectReason::Halted);
+        }
+
+        // Basic validation
+        if !order.quantity.is_positive() {
+            return Err(RejectReason::NonPositiveQuantity);
+        }
+
+        if let Some(lot) = self.lot_size {
+            if !order.quantity.is_multiple_of(lot) {
+                return Err(RejectReason::LotViolation);
+            }
+        }
+
+        if let Some(display) = order.display_quantity {
+            if !display.is_positive() {
+                return Err(RejectReason::NonPositiveDisplayQuantity);
+            }
+        }
+
+        if let Some(min_fill) = order.min_fill_quantity {
+            if !min_fill.is_positive() {
+                return Err(RejectReason::NonPositiveMinFill);
+            }
+            if min_fill > order.quantity {
+                return Err(RejectReason::MinFillExceedsQuantity);
+            }
+        }
+
+        if order.is_protected_market() && self.tick_size.is_none() {
+            return Err(RejectReason::ProtectionUnavailable);
+        }
+
+        if (order.is_market_order() || order.is_protected_market())
+            && !self.algorithm.read().supports_market_orders()
+        {
+            return Err(RejectReason::MarketOrdersUnsupported);
+        }
+
+        // Nothing executes before the auction runs, so immediate orders could
+        // only be cancelled
+        if self.in_auction()
+            && (order.is_market_order()
+                || order.is_protected_market()
+                || matches!(
+                    order.time_in_force,
+                    crate::domain::TimeInForce::ImmediateOrCancel
+                        | crate::domain::TimeInForce::FillOrKill
+                ))
+        {
+            return Err(RejectReason::NotAcceptedInAuction);
+        }
+
+        if order.is_limit_order() && order.price().is_none() {
+            return Err(RejectReason::MissingLimitPrice);
+        }
+
+        if order.is_pegged() && order.price().is_none() {
+            return Err(RejectReason::PegUnavailable);
+        }
+
+        if (order.is_limit_order() || order.is_pegged()) && !self.allow_non_positive_prices {
+            if let Some(price) = order.price() {
+                if !price.is_positive() {
+                    return Err(RejectReason::NonPositivePrice);
+                }
+            }
+        }
+
+        if let (Some(price), Some(tick)) = (order.price(), self.tick_size) {
+            if (order.is_limit_order() || order.is_pegged()) && !price.is_multiple_of(tick) {
+                return Err(RejectReason::TickViolation);
+            }
+        }
+
+        // Fat-finger protection
+        if let (Some(price), Some(band)) = (order.price(), *self.price_band.read()) {
+            if (order.is_limit_order() || order.is_pegged()) && !band.contains(price) {
+                return Err(RejectReason::OutsidePriceBand);
+            }
+        }
+
+        // Post-only orders must not take liquidity
+        if order.post_only {
+            let opposite = self.opposite_side_for(order.side);
+            if let Some(best) = opposite.best_price() {
+                if self.algorithm.read().crosses_level(order, opposite, best) {
+                    return Err(RejectReason::PostOnlyWouldCross);
+                }
+            }
+        }
+
+        // Depth limit: a full side only takes orders at or inside its worst level
+        if let (Some(max_depth), Some(price)) = (self.max_depth, order.price()) {
+            let own_side = self.side_for(order.side);
+            let new_level = !own_side.levels.contains_key(&price.raw_value());
+            if new_level && own_side.level_count() >= max_depth {
+                let deeper = own_side
+                    .worst_price()
+                    .is_none_or(|worst| !own_side.is_at_or_better(price, worst));
+                if deeper {
+                    return Err(RejectReason::DepthLimit);
+                }
+            }
+        }
+
+        // Level capacity: an order priced at an existing level on its own side
+        // cannot cross, so it would rest there
+        if let (Some(max_orders), Some(price)) = (self.max_orders_per_level, order.price()) {
+            let own_side = self.side_for(order.side);
+            if let Some(level) = own_side.level_at_price(price) {
+                if level.order_count() >= max_orders {
+                    return Err(RejectReason::LevelFull);
+                }
+            }
+        }
+
+        // TODO: Add more validations:
+        // - User balance check
+        // - Instrument validation
+
+        Ok(())
+    }
+}
+
+unsafe impl Send for MatchingEngine {}
+unsafe impl Sync for MatchingEngine {}
+
+/// A book mutation in progress, counted in debug builds (see `book_writer`)
+struct BookWriter<'a>(&'a MatchingEngine);
+
+impl Drop for BookWriter<'_> {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) {
+            self.0.writers.fetch_sub(1, Ordering::AcqRel);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OrderType, TimeInForce};
+    use crate::engine::{LastLook, MatchingEngineBuilder, PriceTimePriority, ProRata};
+    use crate::interfaces::NoOpEventHandler;
+    use crate::numeric::Notional;
+
+    #[test]
+    fn test_matching_engine_basic() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        // Add sell order
+        let sell = Arc::new(Order::new(
+            "user1".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+
+        engine.submit_order(sell);
 
         // Add matching buy order
         let buy = Arc::new(Order::new(
             "user2".to_string(),
             "BTC-USD".to_string(),
-            Side::Buy,
-            OrderType::Limit,
-            Some(Price::from_integer(50000).unwrap()),
-            Quantity::from_integer(1).unwrap(),
-            TimeInForce::GoodTillCancel,
-        ));
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+
+        let events = engine.submit_order(buy);
+
+        // Should have matched
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+
+        // Snapshot should be empty
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(snapshot.bids.len(), 0);
+        assert_eq!(snapshot.asks.len(), 0);
+    }
+
+    #[test]
+    fn test_cancel_order() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        let order = Arc::new(Order::new(
+            "user1".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+
+        let order_id = order.id;
+        engine.submit_order(order);
+
+        // Cancel order
+        let cancel_event = engine.cancel_order(order_id);
+        assert!(cancel_event.is_some());
+        assert!(engine.get_snapshot(10).bids.is_empty());
+    }
+
+    #[test]
+    fn test_order_book_snapshot() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        // Add multiple orders
+        for i in 0i64..5 {
+            let buy = Arc::new(Order::new(
+                format!("user{}", i),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(50000 - i * 100).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ));
+            engine.submit_order(buy);
+
+            let sell = Arc::new(Order::new(
+                format!("user{}", i + 10),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(50100 + i * 100).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ));
+            engine.submit_order(sell);
+        }
+
+        let snapshot = engine.get_snapshot(3);
+        assert_eq!(snapshot.bids.len(), 3);
+        assert_eq!(snapshot.asks.len(), 3);
+        assert!(snapshot.spread.is_some());
+        assert!(snapshot.mid_price.is_some());
+    }
+
+    #[test]
+    fn test_aggregated_snapshot() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        // Prices in cents
+        let price = |cents: i64| Price::from_raw(cents * Price::SCALE / 100);
+        let qty = |q| Quantity::from_integer(q).unwrap();
+        let levels = [
+            (Side::Buy, [100050, 99900, 99500, 99000, 98950, 98100]),
+            (Side::Sell, [100100, 100999, 101000, 101500, 102000, 103000]),
+        ];
+        for (side, prices) in levels {
+            for (cents, size) in prices.into_iter().zip(1..) {
+                engine.submit_order(Arc::new(Order::new(
+                    "user".to_string(),
+                    "BTC-USD".to_string(),
+                    side,
+                    OrderType::Limit,
+                    Some(price(cents)),
+                    qty(size),
+                    TimeInForce::GoodTillCancel,
+                )));
+            }
+        }
+
+        let ten = Price::from_integer(10).unwrap();
+        let grouped = engine.get_aggregated_snapshot(3, ten);
+        // 1000.50 | 999, 995, 990 | 989.50, 981
+        assert_eq!(
+            grouped.bids,
+            vec![
+                (price(100000), qty(1)),
+                (price(99000), qty(9)),
+                (price(98000), qty(11))
+            ]
+        );
+        // 1001, 1009.99 | 1010, 1015 | 1020 (1030 is past the depth)
+        assert_eq!(
+            grouped.asks,
+            vec![
+                (price(100000), qty(3)),
+                (price(101000), qty(7)),
+                (price(102000), qty(5))
+            ]
+        );
+        // Spread and mid come from the ungrouped book
+        assert_eq!(grouped.spread, Some(price(50)));
+        assert_eq!(grouped.mid_price, engine.get_snapshot(1).mid_price);
+
+        // Tick-sized buckets leave the book as it is
+        let ungrouped = engine.get_aggregated_snapshot(4, price(1));
+        assert_eq!(ungrouped.bids, engine.get_snapshot(4).bids);
+        assert_eq!(ungrouped.asks, engine.get_snapshot(4).asks);
+    }
+
+    #[test]
+    fn test_dark_pool_snapshot_hides_levels() {
+        let engine = MatchingEngineBuilder::dark_pool_preset("BLOCK")
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+
+        let buy = Arc::new(Order::new_hidden(
+            "buyer".to_string(),
+            "BLOCK".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(100).unwrap()),
+            Quantity::from_integer(500).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        engine.submit_order(buy);
+
+        let sell = Arc::new(Order::new_hidden(
+            "seller".to_string(),
+            "BLOCK".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(102).unwrap()),
+            Quantity::from_integer(300).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        engine.submit_order(sell);
+
+        let snapshot = engine.get_snapshot(10);
+        assert!(snapshot.bids.is_empty());
+        assert!(snapshot.asks.is_empty());
+        assert_eq!(snapshot.total_bid_quantity(), Quantity::ZERO);
+        assert_eq!(snapshot.total_ask_quantity(), Quantity::ZERO);
+        assert_eq!(snapshot.spread, Some(Price::from_integer(2).unwrap()));
+        assert_eq!(snapshot.mid_price, Some(Price::from_integer(101).unwrap()));
+    }
+
+    #[test]
+    fn test_hybrid_snapshot_shows_visible_quantity_only() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .hybrid_order_book()
+            .price_time_matching(false)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+
+        let price = Price::from_integer(50000).unwrap();
+
+        // Iceberg: 10 total, 2 displayed
+        engine.submit_order(Arc::new(Order::new_iceberg(
+            "user1".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(price),
+            Quantity::from_integer(10).unwrap(),
+            Quantity::from_integer(2).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        // Hidden order at the same level contributes nothing
+        engine.submit_order(Arc::new(Order::new_hidden(
+            "user2".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(price),
+            Quantity::from_integer(7).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        // Plain visible order
+        engine.submit_order(Arc::new(Order::new(
+            "user3".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(price),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        // A level made up only of hidden orders is not published at all
+        engine.submit_order(Arc::new(Order::new_hidden(
+            "user4".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50100).unwrap()),
+            Quantity::from_integer(4).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(
+            snapshot.bids,
+            vec![(price, Quantity::from_integer(3).unwrap())]
+        );
+        assert!(snapshot.asks.is_empty());
+    }
+
+    #[test]
+    fn test_iceberg_refresh_event() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        let iceberg = Arc::new(Order::new_iceberg(
+            "maker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(10).unwrap(),
+            Quantity::from_integer(4).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        let iceberg_id = iceberg.id;
+        engine.submit_order(iceberg);
+
+        let buy = Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(4).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        let events = engine.submit_order(buy);
+
+        assert!(events.iter().any(|e| matches!(
+            e,
+            OrderEvent::IcebergRefreshed { order_id, visible_quantity, .. }
+                if *order_id == iceberg_id
+                    && *visible_quantity == Quantity::from_integer(4).unwrap()
+        )));
+
+        // Book still holds the full reserve, but only the slice is displayed
+        let snapshot = engine.get_snapshot(1);
+        assert_eq!(snapshot.asks[0].1, Quantity::from_integer(6).unwrap());
+    }
+
+    #[test]
+    fn test_post_only_rejected_when_crossing() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        engine.submit_order(Arc::new(Order::new(
+            "seller".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        // At and above the best ask: both would take liquidity
+        for price in [50000, 50100] {
+            let buy = Arc::new(
+                Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )
+                .with_post_only(),
+            );
+            let events = engine.submit_order(buy.clone());
+
+            assert!(events.iter().any(|e| matches!(
+                e,
+                OrderEvent::OrderRejected { reason, .. } if reason == "post-only would cross"
+            )));
+            assert!(!events
+                .iter()
+                .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+            assert_eq!(buy.get_state(), OrderState::Rejected);
+        }
+
+        let snapshot = engine.get_snapshot(10);
+        assert!(snapshot.bids.is_empty());
+        assert_eq!(snapshot.asks[0].1, Quantity::from_integer(1).unwrap());
+    }
+
+    #[test]
+    fn test_post_only_rests_when_not_crossing() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        engine.submit_order(Arc::new(Order::new(
+            "seller".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        let buy = Arc::new(
+            Order::new(
+                "maker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(49900).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )
+            .with_post_only(),
+        );
+        let events = engine.submit_order(buy);
+
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderAddedToBook { .. })));
+        assert_eq!(
+            engine.get_snapshot(1).best_bid(),
+            Some(Price::from_integer(49900).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_l3_snapshot_preserves_queue_order() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        let mut ids = Vec::new();
+        for i in 1i64..=3 {
+            let sell = Arc::new(Order::new(
+                format!("user{}", i),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(50000).unwrap()),
+                Quantity::from_integer(i).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ));
+            ids.push(sell.id);
+            engine.submit_order(sell);
+        }
+        engine.submit_order(Arc::new(Order::new(
+            "user4".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50100).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        let l3 = engine.get_l3_snapshot(10);
+        assert!(l3.bids.is_empty());
+        assert_eq!(l3.asks.len(), 2);
+
+        let top = &l3.asks[0];
+        assert_eq!(top.price, Price::from_integer(50000).unwrap());
+        let queue: Vec<_> = top.orders.iter().map(|e| e.order_id).collect();
+        assert_eq!(queue, ids);
+        assert_eq!(top.orders[2].remaining, Quantity::from_integer(3).unwrap());
+        assert_eq!(top.orders[0].user_id, "user1");
+        assert!(top
+            .orders
+            .windows(2)
+            .all(|w| w[0].sequence_number < w[1].sequence_number));
+
+        // Reading the L3 view must not disturb FIFO matching
+        let buy = Arc::new(Order::new(
+            "buyer".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        let events = engine.submit_order(buy);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            OrderEvent::OrderMatched { trade, .. } if trade.maker_order_id == ids[0]
+        )));
+    }
+
+    #[test]
+    fn test_clear_book() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let orders = [
+            limit_order(Side::Buy, 99, 1),
+            limit_order(Side::Buy, 100, 2),
+            limit_order(Side::Sell, 101, 3),
+        ];
+        for order in &orders {
+            engine.submit_order(Arc::clone(order));
+        }
+
+        let events = engine.clear_book();
+        let cancelled: Vec<_> = events
+            .iter()
+            .map(|e| match e {
+                OrderEvent::OrderCancelled { order_id, .. } => *order_id,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(cancelled, orders.iter().map(|o| o.id).collect::<Vec<_>>());
+        assert!(orders
+            .iter()
+            .all(|o| o.get_state() == OrderState::Cancelled));
+
+        let snapshot = engine.get_snapshot(10);
+        assert!(snapshot.bids.is_empty() && snapshot.asks.is_empty());
+        assert_eq!(snapshot.total_bid_quantity(), Quantity::ZERO);
+        assert!(engine.order_index.read().is_empty());
+        assert!(engine.clear_book().is_empty());
+    }
+
+    #[test]
+    fn test_cancel_range() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let px = |p| Price::from_integer(p).unwrap();
+        let qty = |q| Quantity::from_integer(q).unwrap();
+
+        let mut in_range = Vec::new();
+        for (side, price, quantity) in [
+            (Side::Buy, 100, 1),
+            (Side::Buy, 99, 2),
+            (Side::Buy, 99, 3),
+            (Side::Buy, 97, 4),
+            (Side::Buy, 96, 5),
+            (Side::Sell, 101, 6),
+            (Side::Sell, 102, 7),
+        ] {
+            let order = limit_order(side, price, quantity);
+            if side == Side::Buy && (97..=99).contains(&price) {
+                in_range.push(order.id);
+            }
+            engine.submit_order(order);
+        }
+
+        let events = engine.cancel_range(Side::Buy, px(97), px(99));
+        let cancelled: Vec<_> = events
+            .iter()
+            .map(|e| match e {
+                OrderEvent::OrderCancelled { order_id, .. } => *order_id,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(cancelled, in_range);
+
+        // Bounds are inclusive; levels outside them and the asks are untouched
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(snapshot.bids, vec![(px(100), qty(1)), (px(96), qty(5))]);
+        assert_eq!(snapshot.asks, vec![(px(101), qty(6)), (px(102), qty(7))]);
+
+        // No asks rest in 97..=100, and an inverted range cancels nothing
+        assert!(engine.cancel_range(Side::Sell, px(97), px(100)).is_empty());
+        assert!(engine.cancel_range(Side::Buy, px(101), px(95)).is_empty());
+        assert_eq!(engine.get_snapshot(10), snapshot);
+    }
+
+    #[test]
+    fn test_self_trade_prevention_by_account_group() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_self_trade_prevention()
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let order = |user: &str, group: Option<&str>, side, price| {
+            let order = Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            );
+            Arc::new(match group {
+                Some(group) => order.with_account_group(group.to_string()),
+                None => order,
+            })
+        };
+        let self_trade = |events: &[OrderEvent]| {
+            events.iter().any(|e| {
+                matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "self-trade prevented")
+            })
+        };
+        let traded = |events: &[OrderEvent]| {
+            events
+                .iter()
+                .any(|e| matches!(e, OrderEvent::OrderMatched { .. }))
+        };
+
+        engine.submit_order(order("trader-a", Some("firm-1"), Side::Sell, 100));
+
+        // Different user, same firm: rejected before it can match
+        let events = engine.submit_order(order("trader-b", Some("firm-1"), Side::Buy, 100));
+        assert!(self_trade(&events) && !traded(&events));
+        // Same user still counts without any group
+        let events = engine.submit_order(order("trader-a", None, Side::Buy, 101));
+        assert!(self_trade(&events));
+        // Not crossing the firm's order is fine
+        let events = engine.submit_order(order("trader-b", Some("firm-1"), Side::Buy, 99));
+        assert!(!self_trade(&events));
+
+        // Another firm, or no group at all, trades freely
+        let events = engine.submit_order(order("trader-c", Some("firm-2"), Side::Buy, 100));
+        assert!(traded(&events));
+        engine.submit_order(order("trader-a", Some("firm-1"), Side::Sell, 100));
+        let events = engine.submit_order(order("trader-d", None, Side::Buy, 100));
+        assert!(traded(&events));
+    }
+
+    #[test]
+    fn test_iter_orders() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let qty = |q| Quantity::from_integer(q).unwrap();
+
+        let partially_filled = limit_order(Side::Sell, 101, 5);
+        let filled = limit_order(Side::Sell, 100, 2);
+        let cancelled = limit_order(Side::Buy, 98, 1);
+        let untouched = limit_order(Side::Buy, 99, 3);
+        for order in [&partially_filled, &filled, &cancelled, &untouched] {
+            engine.submit_order(Arc::clone(order));
+        }
+        // Takes all of `filled` and 1 of `partially_filled`
+        engine.submit_order(limit_order(Side::Buy, 101, 3));
+        engine.cancel_order(cancelled.id);
+
+        let resting: Vec<_> = engine
+            .iter_orders()
+            .map(|status| (status.order_id, status.remaining_quantity))
+            .collect();
+        assert_eq!(
+            resting,
+            vec![(partially_filled.id, qty(4)), (untouched.id, qty(3))]
+        );
+
+        // Walking the index leaves the book as it was
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(engine.iter_orders().count(), 2);
+        assert_eq!(engine.get_snapshot(10), snapshot);
+    }
+
+    #[test]
+    fn test_top_n_orders_by_size() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let small_bid = limit_order(Side::Buy, 98, 2);
+        let large_bid = limit_order(Side::Buy, 99, 7);
+        let large_ask = limit_order(Side::Sell, 102, 7);
+        let medium_ask = limit_order(Side::Sell, 101, 4);
+        let partial_ask = limit_order(Side::Sell, 100, 9);
+        for order in [
+            &small_bid,
+            &large_bid,
+            &large_ask,
+            &medium_ask,
+            &partial_ask,
+        ] {
+            engine.submit_order(Arc::clone(order));
+        }
+        // Leaves 5 of `partial_ask`, ranked by what is left rather than its original size
+        engine.submit_order(limit_order(Side::Buy, 100, 4));
+
+        let ids = |statuses: Vec<OrderStatus>| -> Vec<OrderId> {
+            statuses.iter().map(|status| status.order_id).collect()
+        };
+        // Equal sizes: the earlier order comes first
+        assert_eq!(
+            ids(engine.top_n_orders_by_size(3, None)),
+            vec![large_bid.id, large_ask.id, partial_ask.id]
+        );
+        assert_eq!(
+            ids(engine.top_n_orders_by_size(10, Some(Side::Sell))),
+            vec![large_ask.id, partial_ask.id, medium_ask.id]
+        );
+        assert_eq!(
+            ids(engine.top_n_orders_by_size(10, Some(Side::Buy))),
+            vec![large_bid.id, small_bid.id]
+        );
+        assert!(engine.top_n_orders_by_size(0, None).is_empty());
+    }
+
+    #[test]
+    fn test_cancel_all_for_user() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        let mut target_ids = Vec::new();
+        for (user, side, price) in [
+            ("user1", Side::Buy, 49900),
+            ("user2", Side::Buy, 49900),
+            ("user1", Side::Buy, 49800),
+            ("user1", Side::Sell, 50100),
+            ("user2", Side::Sell, 50200),
+        ] {
+            let order = Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ));
+            if user == "user1" {
+                target_ids.push(order.id);
+            }
+            engine.submit_order(order);
+        }
+
+        let events = engine.cancel_all_for_user("user1");
+        let cancelled: Vec<_> = events
+            .iter()
+            .map(|e| match e {
+                OrderEvent::OrderCancelled { order_id, .. } => *order_id,
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect();
+        assert_eq!(cancelled, target_ids);
+
+        // Only user2's orders remain, with level totals reconciled
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(
+            snapshot.bids,
+            vec![(
+                Price::from_integer(49900).unwrap(),
+                Quantity::from_integer(1).unwrap()
+            )]
+        );
+        assert_eq!(
+            snapshot.asks,
+            vec![(
+                Price::from_integer(50200).unwrap(),
+                Quantity::from_integer(1).unwrap()
+            )]
+        );
+
+        assert!(engine.cancel_all_for_user("user1").is_empty());
+    }
+
+    #[test]
+    fn test_expire_stale_orders() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let now = Utc::now();
+
+        let stale = Arc::new(Order::new(
+            "user1".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(49900).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillDate(now - chrono::Duration::minutes(5)),
+        ));
+        let live = Arc::new(Order::new(
+            "user2".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(49800).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillDate(now + chrono::Duration::minutes(5)),
+        ));
+        engine.submit_order(Arc::clone(&stale));
+        engine.submit_order(Arc::clone(&live));
+
+        let events = engine.expire_stale_orders(now);
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            OrderEvent::OrderExpired { order_id, .. } if order_id == stale.id
+        ));
+        assert_eq!(stale.get_state(), OrderState::Expired);
+        assert_eq!(live.get_state(), OrderState::Accepted);
+
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(
+            snapshot.bids,
+            vec![(
+                Price::from_integer(49800).unwrap(),
+                Quantity::from_integer(1).unwrap()
+            )]
+        );
+
+        // Already expired orders are not reported again
+        assert!(engine.expire_stale_orders(now).is_empty());
+    }
+
+    #[test]
+    fn test_mock_clock_expires_gtd_at_exact_instant() {
+        let start = "2024-01-02T09:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let deadline = start + chrono::Duration::seconds(10);
+        let clock = Arc::new(crate::engine::MockClock::new(start));
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        )
+        .with_clock(clock.clone());
+
+        let order = Arc::new(Order::new(
+            "user1".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(49900).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillDate(deadline),
+        ));
+        let events = engine.submit_order(Arc::clone(&order));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            OrderEvent::OrderAddedToBook { timestamp, .. } if *timestamp == start
+        )));
+
+        // One nanosecond early nothing happens
+        clock.advance(chrono::Duration::seconds(10) - chrono::Duration::nanoseconds(1));
+        assert!(engine.expire_stale_orders(engine.now()).is_empty());
+        assert_eq!(order.get_state(), OrderState::Accepted);
+
+        clock.set(deadline);
+        let events = engine.expire_stale_orders(engine.now());
+        assert_eq!(events.len(), 1);
+        assert!(matches!(
+            events[0],
+            OrderEvent::OrderExpired { order_id, timestamp, .. }
+                if order_id == order.id && timestamp == deadline
+        ));
+        assert_eq!(order.get_state(), OrderState::Expired);
+    }
+
+    #[test]
+    fn test_simulated_arrivals_run_in_arrival_order() {
+        let start = "2024-01-02T09:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let at = |ms| start + chrono::Duration::milliseconds(ms);
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        )
+        .with_simulated_time(start);
+
+        // Submitted out of order: the sell arrives last, the two bids tie
+        let sell = limit_order(Side::Sell, 100, 1);
+        let first_bid = limit_order(Side::Buy, 100, 1);
+        let second_bid = limit_order(Side::Buy, 100, 1);
+        engine.submit_order_at(Arc::clone(&sell), at(30)).unwrap();
+        engine
+            .submit_order_at(Arc::clone(&first_bid), at(10))
+            .unwrap();
+        engine
+            .submit_order_at(Arc::clone(&second_bid), at(10))
+            .unwrap();
+        assert_eq!(engine.pending_arrivals(), 3);
+
+        // Nothing before its arrival
+        assert!(engine.advance_to(at(9)).is_empty());
+        let events = engine.advance_to(at(20));
+        assert_eq!(engine.pending_arrivals(), 1);
+        assert_eq!(engine.now(), at(20));
+        let accepted: Vec<_> = events
+            .iter()
+            .filter_map(|e| match e {
+                OrderEvent::OrderAccepted {
+                    order_id,
+                    timestamp,
+                } => Some((*order_id, *timestamp)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            accepted,
+            vec![(first_bid.id, at(10)), (second_bid.id, at(10))]
+        );
+        assert_eq!(engine.get_snapshot(10).bids.len(), 1);
+
+        // The sell trades with the bid submitted first among equal arrivals
+        let events = engine.advance_to(at(50));
+        let trade = events
+            .iter()
+            .find_map(|e| match e {
+                OrderEvent::OrderMatched { trade, timestamp } => Some((trade, *timestamp)),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(trade.0.maker_order_id, first_bid.id);
+        assert_eq!(trade.0.taker_order_id, sell.id);
+        assert_eq!(trade.1, at(30));
+        assert_eq!(engine.pending_arrivals(), 0);
+
+        // Arrivals in the simulated past are refused
+        assert!(engine
+            .submit_order_at(limit_order(Side::Buy, 99, 1), at(40))
+            .is_err());
+    }
+
+    #[test]
+    fn test_snapshot_cumulative_quantities() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        for (side, price, qty) in [
+            (Side::Buy, 100, 2),
+            (Side::Buy, 99, 1),
+            (Side::Buy, 100, 3),
+            (Side::Buy, 97, 4),
+            (Side::Sell, 101, 1),
+            (Side::Sell, 103, 2),
+        ] {
+            engine.submit_order(Arc::new(Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(qty).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+
+        let qty = |q: i64| Quantity::from_integer(q).unwrap();
+        let snapshot = engine.get_snapshot(2);
+        assert_eq!(snapshot.bid_cumulative, vec![qty(5), qty(6)]);
+        assert_eq!(snapshot.ask_cumulative, vec![qty(1), qty(3)]);
+
+        for depth in [1, 2, 3, 10] {
+            let snapshot = engine.get_snapshot(depth);
+            for (levels, cumulative) in [
+                (&snapshot.bids, &snapshot.bid_cumulative),
+                (&snapshot.asks, &snapshot.ask_cumulative),
+            ] {
+                assert_eq!(cumulative.len(), levels.len());
+                assert!(cumulative.windows(2).all(|pair| pair[0] <= pair[1]));
+                let total = levels
+                    .iter()
+                    .fold(Quantity::ZERO, |acc, (_, q)| acc.saturating_add(*q));
+                assert_eq!(cumulative.last().copied(), Some(total));
+            }
+        }
+        assert_eq!(
+            engine.get_snapshot(10).bid_cumulative.last(),
+            Some(&engine.get_snapshot(10).total_bid_quantity())
+        );
+
+        // Grouped levels carry their own running totals: 100 alone, 99 and 97 in 95
+        let grouped = engine.get_aggregated_snapshot(10, Price::from_integer(5).unwrap());
+        assert_eq!(grouped.bids.len(), 2);
+        assert_eq!(grouped.bid_cumulative, vec![qty(5), qty(10)]);
+    }
+
+    #[test]
+    fn test_shutdown_drains_book() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let limit = |side, price: i64| {
+            Arc::new(Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let bid = limit(Side::Buy, 100);
+        engine.submit_order(Arc::clone(&bid));
+        engine.submit_order(limit(Side::Sell, 101));
+
+        engine.begin_shutdown();
+        assert!(engine.is_draining());
+        let late = limit(Side::Buy, 99);
+        let events = engine.submit_order(Arc::clone(&late));
+        assert!(matches!(
+            events.last(),
+            Some(OrderEvent::OrderRejected { code: RejectReason::Draining, reason, .. })
+                if reason == "engine draining"
+        ));
+        assert_eq!(late.get_state(), OrderState::Rejected);
+
+        // Resting orders can still be cancelled while draining
+        assert!(engine.cancel_order(bid.id).is_some());
+
+        let events = engine.finish_shutdown();
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], OrderEvent::OrderCancelled { .. }));
+        let snapshot = engine.get_snapshot(10);
+        assert!(snapshot.bids.is_empty() && snapshot.asks.is_empty());
+        assert_eq!(engine.iter_orders().count(), 0);
+    }
+
+    #[test]
+    fn test_duplicate_client_order_id() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let tagged = |user: &str, side, price: i64, cl_ord_id: &str| {
+            Arc::new(
+                Order::new(
+                    user.to_string(),
+                    "BTC-USD".to_string(),
+                    side,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )
+                .with_client_order_id(cl_ord_id.to_string()),
+            )
+        };
+        let is_duplicate = |events: &[OrderEvent]| {
+            matches!(
+                events.last(),
+                Some(OrderEvent::OrderRejected {
+                    code: RejectReason::DuplicateClientOrderId,
+                    reason,
+                    ..
+                }) if reason == "duplicate client order id"
+            )
+        };
+
+        let first = tagged("alice", Side::Buy, 100, "A");
+        engine.submit_order(Arc::clone(&first));
+
+        // Same user, same id while the first is live: rejected, book untouched
+        let events = engine.submit_order(tagged("alice", Side::Buy, 99, "A"));
+        assert!(is_duplicate(&events));
+        assert_eq!(engine.get_snapshot(10).bids.len(), 1);
+
+        // Ids are scoped per user
+        let other = tagged("bob", Side::Buy, 98, "A");
+        engine.submit_order(Arc::clone(&other));
+        assert_eq!(other.get_state(), OrderState::Accepted);
+        assert_eq!(engine.resolve_client_order_id("alice", "A"), Some(first.id));
+        assert_eq!(engine.resolve_client_order_id("bob", "A"), Some(other.id));
+
+        // Cancelling frees the id for reuse
+        engine.cancel_order(first.id).unwrap();
+        assert_eq!(engine.resolve_client_order_id("alice", "A"), None);
+        let reused = tagged("alice", Side::Buy, 100, "A");
+        engine.submit_order(Arc::clone(&reused));
+        assert_eq!(reused.get_state(), OrderState::Accepted);
+        assert!(is_duplicate(&engine.submit_order(tagged(
+            "alice",
+            Side::Buy,
+            97,
+            "A"
+        ))));
+
+        // So does a full fill, on either side of the trade
+        engine.submit_order(tagged("carol", Side::Sell, 100, "X"));
+        assert_eq!(reused.get_state(), OrderState::Filled);
+        let events = engine.submit_order(tagged("alice", Side::Buy, 96, "A"));
+        assert!(!is_duplicate(&events));
+        let events = engine.submit_order(tagged("carol", Side::Sell, 101, "X"));
+        assert!(!is_duplicate(&events));
+
+        // Only resting orders hold ids
+        engine.cancel_order(other.id).unwrap();
+        assert_eq!(engine.client_order_ids.len(), 2);
+        engine.clear_book();
+        assert_eq!(engine.client_order_ids.len(), 0);
+    }
+
+    #[test]
+    fn test_side_routing() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let limit = |user: &str, side, price: i64| {
+            Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let one = Quantity::from_integer(1).unwrap();
+
+        for (side, price, through) in [(Side::Buy, 100, 99), (Side::Sell, 101, 102)] {
+            // Rests on its own side
+            let resting = limit("maker", side, price);
+            engine.submit_order(Arc::clone(&resting));
+            let snapshot = engine.get_snapshot(5);
+            let (own, other) = match side {
+                Side::Buy => (snapshot.bids, snapshot.asks),
+                Side::Sell => (snapshot.asks, snapshot.bids),
+            };
+            assert_eq!(own, vec![(Price::from_integer(price).unwrap(), one)]);
+            assert!(other.is_empty());
+
+            // Cancelling removes it from that side
+            assert!(engine.cancel_order(resting.id).is_some());
+            assert!(engine.get_snapshot(5).bids.is_empty());
+            assert!(engine.get_snapshot(5).asks.is_empty());
+
+            // An opposite order trades against it
+            let resting = limit("maker", side, price);
+            engine.submit_order(Arc::clone(&resting));
+            let events = engine.submit_order(limit("taker", side.opposite(), through));
+            assert!(events.iter().any(|event| matches!(
+                event,
+                OrderEvent::OrderMatched { trade, .. }
+                    if trade.maker_order_id == resting.id && trade.aggressor_side == side.opposite()
+            )));
+            assert!(engine.get_snapshot(5).bids.is_empty());
+            assert!(engine.get_snapshot(5).asks.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_bbo_change_events() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let limit = |side, price: i64, qty: i64| {
+            Arc::new(Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(qty).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let bbo = |events: &[OrderEvent]| -> Vec<(Side, Option<Price>, Quantity)> {
+            events
+                .iter()
+                .filter_map(|event| match event {
+                    OrderEvent::BestBidChanged {
+                        price, quantity, ..
+                    } => Some((Side::Buy, *price, *quantity)),
+                    OrderEvent::BestAskChanged {
+                        price, quantity, ..
+                    } => Some((Side::Sell, *price, *quantity)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let px = |p: i64| Some(Price::from_integer(p).unwrap());
+        let qty = |q: i64| Quantity::from_integer(q).unwrap();
+
+        let events = engine.submit_order(limit(Side::Buy, 100, 1));
+        assert_eq!(bbo(&events), vec![(Side::Buy, px(100), qty(1))]);
+
+        // Behind the best bid: top of book is unchanged
+        let events = engine.submit_order(limit(Side::Buy, 99, 5));
+        assert!(bbo(&events).is_empty());
+
+        // Joining the best level changes its quantity
+        let events = engine.submit_order(limit(Side::Buy, 100, 2));
+        assert_eq!(bbo(&events), vec![(Side::Buy, px(100), qty(3))]);
+
+        // Improving the bid
+        let events = engine.submit_order(limit(Side::Buy, 101, 1));
+        assert_eq!(bbo(&events), vec![(Side::Buy, px(101), qty(1))]);
+
+        // A lone ask that is then taken out leaves the ask side empty
+        let events = engine.submit_order(limit(Side::Sell, 105, 1));
+        assert_eq!(bbo(&events), vec![(Side::Sell, px(105), qty(1))]);
+        let events = engine.submit_order(limit(Side::Buy, 105, 1));
+        assert_eq!(bbo(&events), vec![(Side::Sell, None, Quantity::ZERO)]);
+    }
+
+    #[test]
+    fn test_load_resting_orders() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let order = |side, price: i64, qty: i64| {
+            Arc::new(Order::new(
+                "loader".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(qty).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+
+        // 500 bids on 1000..951 and 500 asks on 1001..1050, 10 orders per level
+        let orders: Vec<_> = (0..1000)
+            .map(|i| match i % 2 {
+                0 => order(Side::Buy, 1000 - (i / 2) % 50, 1 + i % 3),
+                _ => order(Side::Sell, 1001 + (i / 2) % 50, 1 + i % 3),
+            })
+            .collect();
+        let mut expected_bids = std::collections::BTreeMap::new();
+        let mut expected_asks = std::collections::BTreeMap::new();
+        for order in &orders {
+            let levels = match order.side {
+                Side::Buy => &mut expected_bids,
+                Side::Sell => &mut expected_asks,
+            };
+            let total = levels
+                .entry(order.price().unwrap())
+                .or_insert(Quantity::ZERO);
+            *total = total.saturating_add(order.quantity);
+        }
+        let first_at_best_ask = orders[1].id;
+
+        let events = engine.load_resting_orders(orders).unwrap();
+        assert_eq!(events.len(), 1000);
+        assert!(events
+            .iter()
+            .all(|event| matches!(event, OrderEvent::OrderAddedToBook { .. })));
+        assert_eq!(engine.get_statistics().total_trades, 0);
+        assert!(!engine.is_crossed());
+
+        let snapshot = engine.get_snapshot(100);
+        let bids: Vec<_> = expected_bids.into_iter().rev().collect();
+        let asks: Vec<_> = expected_asks.into_iter().collect();
+        assert_eq!(snapshot.bids, bids);
+        assert_eq!(snapshot.asks, asks);
+
+        // A crossing batch is refused as a whole
+        let before = engine.get_snapshot(100);
+        let crossing = vec![order(Side::Buy, 990, 1), order(Side::Buy, 1001, 1)];
+        assert!(engine.load_resting_orders(crossing).is_err());
+        assert_eq!(engine.get_snapshot(100), before);
+
+        // Loaded orders keep their queue order
+        let events = engine.submit_order(order(Side::Buy, 1001, 1));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            OrderEvent::OrderMatched { trade, .. } if trade.maker_order_id == first_at_best_ask
+        )));
+    }
+
+    #[test]
+    fn test_fill_report_consolidates_sweep() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        for (price, qty) in [(100, 1), (101, 2), (103, 3)] {
+            engine.submit_order(Arc::new(Order::new(
+                "maker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(qty).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+
+        let taker = Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(103).unwrap()),
+            Quantity::from_integer(5).unwrap(),
+            TimeInForce::ImmediateOrCancel,
+        ));
+        let (events, report) = engine.submit_order_with_report(Arc::clone(&taker));
+        let trades: Vec<Trade> = events
+            .into_iter()
+            .filter_map(|event| match event {
+                OrderEvent::OrderMatched { trade, .. } => Some(trade),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(trades.len(), 3);
+
+        // 100 * 1 + 101 * 2 + 103 * 2 = 508 over 5
+        let notional = trades
+            .iter()
+            .map(|trade| trade.notional_value().unwrap())
+            .fold(Notional::ZERO, |total, notional| total + notional);
+        assert_eq!(report.order_id, taker.id);
+        assert_eq!(report.total_filled, Quantity::from_integer(5).unwrap());
+        assert_eq!(report.notional, notional);
+        assert_eq!(report.notional, Notional::from_integer(508).unwrap());
+        assert_eq!(
+            report.average_price,
+            Some("101.6".parse::<Price>().unwrap())
+        );
+        assert_eq!(
+            report.trade_ids,
+            trades
+                .iter()
+                .map(|trade| trade.trade_id)
+                .collect::<Vec<_>>()
+        );
+
+        // An order that does not trade reports nothing
+        let (_, report) = engine.submit_order_with_report(Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(90).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+        assert_eq!(report.total_filled, Quantity::ZERO);
+        assert_eq!(report.average_price, None);
+        assert!(report.trade_ids.is_empty());
+    }
+
+    #[test]
+    fn test_trade_ids_and_aggressor_side() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        let limit = |user: &str, side, price| {
+            Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let trades_of = |events: Vec<OrderEvent>| -> Vec<Trade> {
+            events
+                .into_iter()
+                .filter_map(|e| match e {
+                    OrderEvent::OrderMatched { trade, .. } => Some(trade),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        engine.submit_order(limit("maker1", Side::Sell, 50000));
+        let first = trades_of(engine.submit_order(limit("taker1", Side::Buy, 50000)));
+
+        engine.submit_order(limit("maker2", Side::Buy, 49000));
+        let second = trades_of(engine.submit_order(limit("taker2", Side::Sell, 49000)));
+
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 1);
+        assert_eq!(first[0].aggressor_side, Side::Buy);
+        assert_eq!(second[0].aggressor_side, Side::Sell);
+        assert!(first[0].trade_id > 0);
+        assert!(second[0].trade_id > first[0].trade_id);
+    }
+
+    #[test]
+    fn test_book_deltas() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let deltas = engine.subscribe_deltas();
+        let price = Price::from_integer(50000).unwrap();
+
+        let next_delta = || match deltas.try_recv() {
+            Ok(OrderEvent::BookDelta {
+                side,
+                price,
+                new_quantity,
+                ..
+            }) => (side, price, new_quantity),
+            other => panic!("expected a book delta, got {:?}", other),
+        };
+
+        // Resting add
+        let sell = Arc::new(Order::new(
+            "maker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(price),
+            Quantity::from_integer(3).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        let events = engine.submit_order(Arc::clone(&sell));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::BookDelta { .. })));
+        assert_eq!(
+            next_delta(),
+            (Side::Sell, price, Quantity::from_integer(3).unwrap())
+        );
+
+        // Partial fill of the resting order
+        engine.submit_order(Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(price),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::ImmediateOrCancel,
+        )));
+        assert_eq!(
+            next_delta(),
+            (Side::Sell, price, Quantity::from_integer(2).unwrap())
+        );
+
+        // Cancel removes the level
+        engine.cancel_order(sell.id).unwrap();
+        assert_eq!(next_delta(), (Side::Sell, price, Quantity::ZERO));
+        assert!(deltas.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_estimate_fill() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        for (price, quantity) in [(100, 2), (101, 3), (103, 5)] {
+            engine.submit_order(Arc::new(Order::new(
+                "maker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(quantity).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+        let before = engine.get_snapshot(10);
+
+        // 2 @ 100 + 3 @ 101 + 1 @ 103 = 606 over 6 => 101
+        let estimate = engine.estimate_fill(Side::Buy, Quantity::from_integer(6).unwrap());
+        assert_eq!(estimate.filled_quantity, Quantity::from_integer(6).unwrap());
+        assert_eq!(
+            estimate.average_price,
+            Some(Price::from_integer(101).unwrap())
+        );
+        assert_eq!(
+            estimate.worst_price,
+            Some(Price::from_integer(103).unwrap())
+        );
+        assert_eq!(estimate.levels_consumed, 3);
+        assert!(estimate.fully_filled);
+
+        // 2 @ 100 + 2 @ 101 = 402 over 4 => 100.5
+        let estimate = engine.estimate_fill(Side::Buy, Quantity::from_integer(4).unwrap());
+        assert_eq!(
+            estimate.average_price,
+            Some("100.5".parse::<Price>().unwrap())
+        );
+        assert_eq!(estimate.levels_consumed, 2);
+
+        // Book too thin
+        let estimate = engine.estimate_fill(Side::Buy, Quantity::from_integer(20).unwrap());
+        assert_eq!(
+            estimate.filled_quantity,
+            Quantity::from_integer(10).unwrap()
+        );
+        assert!(!estimate.fully_filled);
+        assert_eq!(
+            estimate.worst_price,
+            Some(Price::from_integer(103).unwrap())
+        );
+
+        // No bids at all
+        let estimate = engine.estimate_fill(Side::Sell, Quantity::from_integer(1).unwrap());
+        assert_eq!(estimate.average_price, None);
+        assert!(!estimate.fully_filled);
+
+        // The book is untouched
+        assert_eq!(engine.get_snapshot(10).asks, before.asks);
+    }
+
+    #[test]
+    fn test_max_avg_price_sweep() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let qty = |q: &str| q.parse::<Quantity>().unwrap();
+        let px = |p: i64| Price::from_integer(p).unwrap();
+        for (price, quantity) in [(100, "1"), (101, "1"), (104, "2")] {
+            engine.submit_order(Arc::new(Order::new(
+                "maker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(px(price)),
+                qty(quantity),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+        let buy = |quantity: &str, price: i64, tif, cap: i64| {
+            Arc::new(
+                Order::new(
+                    "taker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(px(price)),
+                    qty(quantity),
+                    tif,
+                )
+                .with_max_avg_price(px(cap)),
+            )
+        };
+
+        // 100 + 101 + 1.5 @ 104 = 357 over 3.5 => exactly 102
+        let estimate = engine.estimate_capped_fill(Side::Buy, qty("4"), px(102));
+        assert_eq!(estimate.filled_quantity, qty("3.5"));
+        assert_eq!(estimate.average_price, Some(px(102)));
+        assert!(estimate.cap_reached);
+        assert!(!estimate.fully_filled);
+
+        // Fill-or-kill cannot be met within the cap
+        let events = engine.submit_order(buy("4", 105, TimeInForce::FillOrKill, 102));
+        assert!(matches!(
+            events.last(),
+            Some(OrderEvent::OrderRejected {
+                code: RejectReason::FillOrKillUnavailable,
+                ..
+            })
+        ));
+
+        // A real sweep stops at the cap mid-level and cancels the rest,
+        // even though the limit price would allow more and it is GTC
+        let order = buy("4", 105, TimeInForce::GoodTillCancel, 102);
+        let (events, report) = engine.submit_order_with_report(Arc::clone(&order));
+        assert_eq!(report.total_filled, qty("3.5"));
+        assert_eq!(report.average_price, Some(px(102)));
+        assert_eq!(report.trade_ids.len(), 3);
+        assert!(events.iter().any(
+            |e| matches!(e, OrderEvent::OrderCancelled { order_id, .. } if *order_id == order.id)
+        ));
+        assert_eq!(order.get_state(), OrderState::Cancelled);
+        assert_eq!(engine.get_snapshot(10).best_bid(), None);
+        assert_eq!(engine.get_snapshot(10).total_ask_quantity(), qty("0.5"));
+
+        // Already beyond the cap: nothing trades and nothing rests
+        let order = buy("1", 105, TimeInForce::GoodTillCancel, 103);
+        engine.submit_order(Arc::clone(&order));
+        assert_eq!(order.get_filled_quantity(), Quantity::ZERO);
+        assert_eq!(order.get_state(), OrderState::Cancelled);
+        assert_eq!(engine.get_snapshot(10).best_bid(), None);
+
+        // A cap that is never reached leaves the order alone: it fills what
+        // its limit allows and rests the remainder
+        engine.submit_order(Arc::new(Order::new(
+            "maker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(px(100)),
+            qty("1"),
+            TimeInForce::GoodTillCancel,
+        )));
+        let order = buy("3", 101, TimeInForce::GoodTillCancel, 102);
+        engine.submit_order(Arc::clone(&order));
+        assert_eq!(order.get_filled_quantity(), qty("1"));
+        assert_eq!(order.get_state(), OrderState::PartiallyFilled);
+        assert_eq!(engine.get_snapshot(10).best_bid(), Some(px(101)));
+    }
+
+    #[test]
+    fn test_reject_reasons() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_tick_size(Price::ONE)
+            .with_lot_size(Quantity::ONE)
+            .with_price_band(PriceBand::new(
+                Price::from_integer(100).unwrap(),
+                rust_decimal::Decimal::new(10, 2), // 10%
+            ))
+            .with_max_depth(1)
+            .with_self_trade_prevention()
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let order = |user: &str, side, price: Option<Price>, quantity: Quantity| {
+            Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                price,
+                quantity,
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let code = |events: Vec<OrderEvent>| {
+            events.into_iter().find_map(|e| match e {
+                OrderEvent::OrderRejected { code, reason, .. } => {
+                    assert_eq!(reason, code.to_string());
+                    Some(code)
+                },
+                _ => None,
+            })
+        };
+        let price = |p| Some(Price::from_integer(p).unwrap());
+        let one = Quantity::ONE;
+
+        let cases = [
+            (
+                order("bob", Side::Buy, price(100), Quantity::ZERO),
+                RejectReason::NonPositiveQuantity,
+            ),
+            (
+                order("bob", Side::Buy, None, one),
+                RejectReason::MissingLimitPrice,
+            ),
+            (
+                order("bob", Side::Buy, price(0), one),
+                RejectReason::NonPositivePrice,
+            ),
+            (
+                order(
+                    "bob",
+                    Side::Buy,
+                    Some("100.5".parse::<Price>().unwrap()),
+                    one,
+                ),
+                RejectReason::TickViolation,
+            ),
+            (
+                order(
+                    "bob",
+                    Side::Buy,
+                    price(100),
+                    "1.5".parse::<Quantity>().unwrap(),
+                ),
+                RejectReason::LotViolation,
+            ),
+            (
+                order("bob", Side::Buy, price(200), one),
+                RejectReason::OutsidePriceBand,
+            ),
+        ];
+        for (order, expected) in cases {
+            assert_eq!(code(engine.submit_order(order)), Some(expected));
+        }
+
+        // A full side rejects a level deeper than its only one
+        assert_eq!(
+            code(engine.submit_order(order("alice", Side::Buy, price(100), one))),
+            None
+        );
+        assert_eq!(
+            code(engine.submit_order(order("bob", Side::Buy, price(99), one))),
+            Some(RejectReason::DepthLimit)
+        );
+        assert_eq!(
+            code(engine.submit_order(order("alice", Side::Sell, price(100), one))),
+            Some(RejectReason::SelfTrade)
+        );
+
+        engine.halted.store(true, Ordering::Release);
+        assert_eq!(
+            code(engine.submit_order(order("bob", Side::Sell, price(100), one))),
+            Some(RejectReason::Halted)
+        );
+
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_rate_limit(1)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        assert_eq!(
+            code(engine.submit_order(order("bob", Side::Buy, price(100), one))),
+            None
+        );
+        assert_eq!(
+            code(engine.submit_order(order("bob", Side::Buy, price(100), one))),
+            Some(RejectReason::RateLimited)
+        );
+    }
+
+    #[test]
+    fn test_market_with_protection() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_tick_size(Price::ONE)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        for price in 100..=110 {
+            engine.submit_order(limit_order(Side::Sell, price, 1));
+            engine.submit_order(limit_order(Side::Buy, price - 20, 1));
+        }
+        let protected = |side, quantity| {
+            Arc::new(Order::new(
+                "taker".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::MarketWithProtection {
+                    protection_ticks: 3,
+                },
+                None,
+                Quantity::from_integer(quantity).unwrap(),
+                TimeInForce::ImmediateOrCancel,
+            ))
+        };
+
+        // Sweeps 100..=103 and stops: the rest of the 10 lot is cancelled
+        let buy = protected(Side::Buy, 10);
+        let events = engine.submit_order(Arc::clone(&buy));
+        let prices: Vec<Price> = events
+            .iter()
+            .filter_map(|e| match e {
+                OrderEvent::OrderMatched { trade, .. } => Some(trade.price),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            prices,
+            (100..=103)
+                .map(|p| Price::from_integer(p).unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert!(events.iter().any(|e| matches!(
+            e,
+            OrderEvent::OrderCancelled { order_id, .. } if *order_id == buy.id
+        )));
+        assert!(engine.get_order(buy.id).is_none());
+        // The submitted order carries the boundary price, its fills and the cancel
+        assert_eq!(buy.price(), Some(Price::from_integer(103).unwrap()));
+        assert_eq!(
+            buy.get_filled_quantity(),
+            Quantity::from_integer(4).unwrap()
+        );
+        assert_eq!(buy.get_state(), OrderState::Cancelled);
+        assert_eq!(
+            engine.get_snapshot(1).asks[0].0,
+            Price::from_integer(104).unwrap()
+        );
+
+        // Sells measure from the best bid (90) downward
+        let events = engine.submit_order(protected(Side::Sell, 10));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            OrderEvent::OrderPartiallyFilled { filled_quantity, .. }
+                if *filled_quantity == Quantity::from_integer(4).unwrap()
+        )));
+        assert_eq!(
+            engine.get_snapshot(1).bids[0].0,
+            Price::from_integer(86).unwrap()
+        );
+
+        // Nothing to measure from: cancelled like a market order
+        let empty = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_tick_size(Price::ONE)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let events = empty.submit_order(protected(Side::Buy, 1));
+        assert!(matches!(
+            events.last(),
+            Some(OrderEvent::OrderCancelled { .. })
+        ));
+
+        // Without a tick size there is no protection to apply
+        let untick = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        assert!(untick
+            .submit_order(protected(Side::Buy, 1))
+            .iter()
+            .any(|e| matches!(
+                e,
+                OrderEvent::OrderRejected {
+                    code: RejectReason::ProtectionUnavailable,
+                    ..
+                }
+            )));
+    }
+
+    #[test]
+    fn test_price_band() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_price_band(PriceBand::new(
+                Price::from_integer(100).unwrap(),
+                rust_decimal::Decimal::new(5, 2), // 5%
+            ))
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+
+        let bid = |price: Price| {
+            Arc::new(Order::new(
+                "user1".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(price),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let rejected = |events: &[OrderEvent]| {
+            events.iter().any(|e| {
+                matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "outside price band")
+            })
+        };
+
+        // 95.00 is exactly on the lower bound, 94.99 just outside
+        assert!(!rejected(
+            &engine.submit_order(bid(Price::from_integer(95).unwrap()))
+        ));
+        assert!(rejected(
+            &engine.submit_order(bid("94.99".parse::<Price>().unwrap()))
+        ));
+        assert!(!rejected(
+            &engine.submit_order(bid("104.99".parse::<Price>().unwrap()))
+        ));
+        assert!(rejected(
+            &engine.submit_order(bid("105.01".parse::<Price>().unwrap()))
+        ));
+
+        // Moving the reference moves the band
+        engine.set_reference_price(Price::from_integer(90).unwrap());
+        assert!(!rejected(
+            &engine.submit_order(bid(Price::from_integer(86).unwrap()))
+        ));
+        assert!(rejected(
+            &engine.submit_order(bid(Price::from_integer(95).unwrap()))
+        ));
+    }
+
+    #[test]
+    fn test_get_order_status() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+
+        let sell = Arc::new(Order::new(
+            "maker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(5).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        engine.submit_order(Arc::clone(&sell));
+        engine.submit_order(Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(2).unwrap(),
+            TimeInForce::ImmediateOrCancel,
+        )));
+
+        let status = engine.get_order(sell.id).unwrap();
+        assert_eq!(status.order_id, sell.id);
+        assert_eq!(status.side, Side::Sell);
+        assert_eq!(status.price, Some(Price::from_integer(50000).unwrap()));
+        assert_eq!(status.original_quantity, Quantity::from_integer(5).unwrap());
+        assert_eq!(status.filled_quantity, Quantity::from_integer(2).unwrap());
+        assert_eq!(
+            status.remaining_quantity,
+            Quantity::from_integer(3).unwrap()
+        );
+        assert_eq!(status.state, OrderState::PartiallyFilled);
+        assert_eq!(status.sequence_number, 0);
+
+        assert!(engine.get_order(OrderId::new()).is_none());
+
+        // Once fully filled the order no longer rests
+        engine.submit_order(Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(3).unwrap(),
+            TimeInForce::ImmediateOrCancel,
+        )));
+        assert!(engine.get_order(sell.id).is_none());
+    }
+
+    #[test]
+    fn test_submit_orders_matches_sequential() {
+        #[derive(Default)]
+        struct CountingHandler {
+            batches: std::sync::atomic::AtomicUsize,
+        }
+        impl EventHandler for CountingHandler {
+            fn on_event(&self, _event: OrderEvent) {}
+            fn on_events(&self, _events: Vec<OrderEvent>) {
+                self.batches.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let orders = || -> Vec<Arc<Order>> {
+            [
+                (Side::Sell, 50100, 2),
+                (Side::Sell, 50000, 1),
+                (Side::Buy, 49900, 3),
+                (Side::Buy, 50100, 2),
+                (Side::Sell, 49900, 4),
+            ]
+            .iter()
+            .map(|&(side, price, quantity)| {
+                Arc::new(Order::new(
+                    "user".to_string(),
+                    "BTC-USD".to_string(),
+                    side,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(quantity).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ))
+            })
+            .collect()
+        };
+        let summary = |events: &[OrderEvent]| -> Vec<String> {
+            events
+                .iter()
+                .map(|e| match e {
+                    OrderEvent::OrderMatched { trade, .. } => {
+                        format!(
+                            "trade {} {} {}",
+                            trade.trade_id, trade.price, trade.quantity
+                        )
+                    },
+                    OrderEvent::BookDelta {
+                        side,
+                        price,
+                        new_quantity,
+                        ..
+                    } => format!("delta {:?} {} {}", side, price, new_quantity),
+                    other => format!("{:?}", std::mem::discriminant(other)),
+                })
+                .collect()
+        };
+
+        let sequential = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let mut sequential_events = Vec::new();
+        for order in orders() {
+            sequential_events.extend(sequential.submit_order(order));
+        }
+
+        let handler = Arc::new(CountingHandler::default());
+        let batched = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            handler.clone(),
+        );
+        let batch_events = batched.submit_orders(orders());
+
+        assert_eq!(summary(&batch_events), summary(&sequential_events));
+        assert_eq!(handler.batches.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            batched.get_snapshot(10).bids,
+            sequential.get_snapshot(10).bids
+        );
+        assert_eq!(
+            batched.get_snapshot(10).asks,
+            sequential.get_snapshot(10).asks
+        );
+    }
+
+    /// Delegates to price-time matching but skips the first `skips` calls,
+    /// resting crossing orders unmatched
+    struct SkippingAlgorithm {
+        inner: PriceTimePriority,
+        skips: std::sync::atomic::AtomicUsize,
+    }
+
+    impl MatchingAlgorithm for SkippingAlgorithm {
+        fn match_order(
+            &self,
+            incoming_order: Arc<Order>,
+            opposite_side: &OrderBookSide,
+        ) -> Vec<Trade> {
+            let skip = self
+                .skips
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+                .is_ok();
+            if skip {
+                Vec::new()
+            } else {
+                self.inner.match_order(incoming_order, opposite_side)
+            }
+        }
+
+        fn name(&self) -> &str {
+            "Skipping"
+        }
+    }
+
+    fn limit_order(side: Side, price: i64, quantity: i64) -> Arc<Order> {
+        Arc::new(Order::new(
+            "user".to_string(),
+            "BTC-USD".to_string(),
+            side,
+            OrderType::Limit,
+            Some(Price::from_integer(price).unwrap()),
+            Quantity::from_integer(quantity).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ))
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_render_prometheus() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        engine.submit_order(limit_order(Side::Sell, 100, 2));
+        engine.submit_order(limit_order(Side::Sell, 101, 1));
+        engine.submit_order(limit_order(Side::Buy, 99, 1));
+        engine.submit_order(limit_order(Side::Buy, 100, 1));
+        engine.submit_order(limit_order(Side::Buy, 0, 1));
+
+        let text = engine.render_prometheus();
+        let sample = |line: &str| text.lines().any(|l| l == line);
+        assert!(text.contains("# TYPE matching_engine_orders_submitted_total counter"));
+        assert!(sample(
+            "matching_engine_orders_submitted_total{instrument=\"BTC-USD\"} 5"
+        ));
+        assert!(sample(
+            "matching_engine_orders_rejected_total{instrument=\"BTC-USD\"} 1"
+        ));
+        assert!(sample(
+            "matching_engine_trades_total{instrument=\"BTC-USD\"} 1"
+        ));
+        assert!(sample(
+            "matching_engine_match_latency_seconds_bucket{instrument=\"BTC-USD\",le=\"+Inf\"} 5"
+        ));
+        assert!(sample(
+            "matching_engine_match_latency_seconds_count{instrument=\"BTC-USD\"} 5"
+        ));
+        assert!(sample(&format!(
+            "matching_engine_book_depth{{instrument=\"BTC-USD\",side=\"bid\"}} {}",
+            Quantity::ONE
+        )));
+        assert!(sample(&format!(
+            "matching_engine_book_depth{{instrument=\"BTC-USD\",side=\"ask\"}} {}",
+            Quantity::from_integer(2).unwrap()
+        )));
+        assert!(sample(
+            "matching_engine_book_levels{instrument=\"BTC-USD\",side=\"bid\"} 1"
+        ));
+        assert!(sample(
+            "matching_engine_book_levels{instrument=\"BTC-USD\",side=\"ask\"} 2"
+        ));
+    }
+
+    #[test]
+    fn test_crossed_book_self_heals() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(SkippingAlgorithm {
+                inner: PriceTimePriority::new(false),
+                skips: std::sync::atomic::AtomicUsize::new(2),
+            }),
+            Arc::new(NoOpEventHandler),
+        );
+        assert!(!engine.is_crossed());
+
+        // First submit skips matching against an empty book
+        let ask = limit_order(Side::Sell, 100, 2);
+        engine.submit_order(Arc::clone(&ask));
+
+        // Second submit skips matching and rests the bid through the ask;
+        // the guard re-runs matching with the bid as aggressor
+        let bid = limit_order(Side::Buy, 101, 3);
+        let events = engine.submit_order(Arc::clone(&bid));
+
+        let trades: Vec<&Trade> = events
+            .iter()
+            .filter_map(|e| match e {
+                OrderEvent::OrderMatched { trade, .. } => Some(trade),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].maker_order_id, ask.id);
+        assert_eq!(trades[0].taker_order_id, bid.id);
+        assert_eq!(trades[0].price, Price::from_integer(100).unwrap());
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::BookCrossedResolved { trade_count: 1, .. })));
+
+        assert!(!engine.is_crossed());
+        assert!(engine.get_order(ask.id).is_none());
+        assert_eq!(
+            engine.get_order(bid.id).unwrap().remaining_quantity,
+            Quantity::from_integer(1).unwrap()
+        );
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(
+            snapshot.bids,
+            vec![(
+                Price::from_integer(101).unwrap(),
+                Quantity::from_integer(1).unwrap()
+            )]
+        );
+        assert!(snapshot.asks.is_empty());
+    }
+
+    #[test]
+    fn test_locked_book_without_progress() {
+        // An algorithm that never matches leaves the book locked; the guard
+        // gives up instead of looping
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(SkippingAlgorithm {
+                inner: PriceTimePriority::new(false),
+                skips: std::sync::atomic::AtomicUsize::new(usize::MAX),
+            }),
+            Arc::new(NoOpEventHandler),
+        );
+
+        engine.submit_order(limit_order(Side::Sell, 100, 1));
+        assert!(!engine.is_locked());
+        let events = engine.submit_order(limit_order(Side::Buy, 100, 1));
+
+        assert!(engine.is_locked());
+        assert!(engine.is_crossed());
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::BookCrossedResolved { .. })));
+    }
+
+    #[test]
+    fn test_reduce_only_orders() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_position_tracking()
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let order = |user: &str, side: Side, price: i64, quantity: i64| {
+            Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(quantity).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )
+        };
+
+        // No position: a reduce-only order has nothing to reduce
+        let flat = Arc::new(order("alice", Side::Sell, 100, 1).with_reduce_only());
+        let events = engine.submit_order(Arc::clone(&flat));
+        assert!(matches!(
+            events.last(),
+            Some(OrderEvent::OrderRejected { .. })
+        ));
+        assert_eq!(flat.get_state(), OrderState::Rejected);
+
+        // Alice buys 3 from Bob: alice long 3, bob short 3
+        engine.submit_order(Arc::new(order("bob", Side::Sell, 100, 3)));
+        engine.submit_order(Arc::new(order("alice", Side::Buy, 100, 3)));
+        assert_eq!(
+            engine.get_position("alice").net,
+            Quantity::from_integer(3).unwrap()
+        );
+        assert_eq!(
+            engine.get_position("bob").net,
+            Quantity::from_integer(-3).unwrap()
+        );
+
+        // Buying more would grow alice's long
+        let grow = Arc::new(order("alice", Side::Buy, 90, 1).with_reduce_only());
+        let events = engine.submit_order(grow);
+        assert!(matches!(
+            events.last(),
+            Some(OrderEvent::OrderRejected { .. })
+        ));
+
+        // Selling 5 is capped to the 3 alice holds and rests
+        let reduce = Arc::new(order("alice", Side::Sell, 110, 5).with_reduce_only());
+        let events = engine.submit_order(Arc::clone(&reduce));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            OrderEvent::OrderAmended { new_quantity, .. }
+                if *new_quantity == Quantity::from_integer(3).unwrap()
+        )));
+        assert_eq!(
+            reduce.get_remaining_quantity(),
+            Quantity::from_integer(3).unwrap()
+        );
+        assert_eq!(
+            engine.get_snapshot(10).asks,
+            vec![(
+                Price::from_integer(110).unwrap(),
+                Quantity::from_integer(3).unwrap()
+            )]
+        );
+
+        // A reduce-only order within the position is left as is
+        let within = Arc::new(order("bob", Side::Buy, 100, 2).with_reduce_only());
+        let events = engine.submit_order(within);
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderAmended { .. })));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderAddedToBook { .. })));
+    }
+
+    #[test]
+    fn test_position_and_pnl_tracking() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_position_tracking()
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let order = |user: &str, side: Side, price: i64, quantity: i64| {
+            Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(quantity).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+
+        // Alice buys 4 at 100 from Bob
+        engine.submit_order(order("bob", Side::Sell, 100, 4));
+        engine.submit_order(order("alice", Side::Buy, 100, 4));
+        let position = engine.get_position("alice");
+        assert_eq!(position.net, Quantity::from_integer(4).unwrap());
+        assert_eq!(
+            position.avg_entry_price,
+            Some(Price::from_integer(100).unwrap())
+        );
+        assert_eq!(position.realized_pnl, rust_decimal::Decimal::ZERO);
+
+        // ...and sells all 4 to Carol at 103: flat, 3 * 4 realized
+        engine.submit_order(order("carol", Side::Buy, 103, 4));
+        engine.submit_order(order("alice", Side::Sell, 103, 4));
+        let position = engine.get_position("alice");
+        assert_eq!(position.net, Quantity::ZERO);
+        assert_eq!(position.avg_entry_price, None);
+        assert_eq!(position.realized_pnl, rust_decimal::Decimal::from(12));
+
+        // Bob is still short 4 from 100 with nothing realized
+        let bob = engine.get_position("bob");
+        assert_eq!(bob.net, Quantity::from_integer(-4).unwrap());
+        assert_eq!(bob.realized_pnl, rust_decimal::Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_positions_untracked_by_default() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let order = |user: &str, side: Side| {
+            Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(100).unwrap()),
+                Quantity::from_integer(2).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )
+        };
+
+        engine.submit_order(Arc::new(order("bob", Side::Sell)));
+        engine.submit_order(Arc::new(order("alice", Side::Buy)));
+        assert_eq!(engine.get_position("alice"), Position::default());
+
+        // With nothing tracked there is no position to reduce
+        let reduce = Arc::new(order("alice", Side::Sell).with_reduce_only());
+        engine.submit_order(Arc::clone(&reduce));
+        assert_eq!(reduce.get_state(), OrderState::Rejected);
+    }
+
+    #[test]
+    fn test_replay_rebuilds_book() {
+        #[derive(Default)]
+        struct RecordingHandler {
+            events: parking_lot::Mutex<Vec<OrderEvent>>,
+        }
+        impl EventHandler for RecordingHandler {
+            fn on_event(&self, event: OrderEvent) {
+                self.events.lock().push(event);
+            }
+            fn on_events(&self, events: Vec<OrderEvent>) {
+                self.events.lock().extend(events);
+            }
+        }
+
+        let handler = Arc::new(RecordingHandler::default());
+        let live = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            handler.clone(),
+        );
+        let order = |side: Side, price: i64, quantity: i64, tif: TimeInForce| {
+            Arc::new(Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(quantity).unwrap(),
+                tif,
+            ))
+        };
+
+        let gtc = TimeInForce::GoodTillCancel;
+        live.submit_order(order(Side::Sell, 101, 2, gtc));
+        live.submit_order(order(Side::Sell, 101, 3, gtc));
+        let cancelled = order(Side::Sell, 103, 1, gtc);
+        live.submit_order(Arc::clone(&cancelled));
+        live.submit_order(order(Side::Sell, 102, 4, gtc));
+        live.submit_order(order(Side::Buy, 99, 5, gtc));
+        // Partially fills the second ask at 101 and rests nothing
+        live.submit_order(order(Side::Buy, 101, 3, TimeInForce::ImmediateOrCancel));
+        // Sweeps the rest of 101 and part of 102, then rests at 102
+        live.submit_order(order(Side::Buy, 102, 9, gtc));
+        live.cancel_order(cancelled.id);
+
+        let recorded = handler.events.lock().clone();
+        let replayed = MatchingEngine::replay("BTC-USD".to_string(), &recorded);
+
+        let expected = live.get_snapshot(10);
+        let actual = replayed.get_snapshot(10);
+        assert_eq!(actual.bids, expected.bids);
+        assert_eq!(actual.asks, expected.asks);
+        assert!(!expected.bids.is_empty());
+
+        // Queue order and per-order quantities survive as well
+        let ids = |snapshot: L3Snapshot| -> Vec<(OrderId, Quantity)> {
+            snapshot
+                .bids
+                .iter()
+                .chain(snapshot.asks.iter())
+                .flat_map(|level| level.orders.iter().map(|o| (o.order_id, o.remaining)))
+                .collect()
+        };
+        assert_eq!(
+            ids(replayed.get_l3_snapshot(10)),
+            ids(live.get_l3_snapshot(10))
+        );
+
+        // New trades continue the recorded id sequence
+        let events = replayed.submit_order(order(Side::Sell, 99, 1, gtc));
+        let next_id = events.iter().find_map(|e| match e {
+            OrderEvent::OrderMatched { trade, .. } => Some(trade.trade_id),
+            _ => None,
+        });
+        let recorded_trades = recorded
+            .iter()
+            .filter(|e| matches!(e, OrderEvent::OrderMatched { .. }))
+            .count() as u64;
+        assert_eq!(next_id, Some(recorded_trades + 1));
+    }
+
+    #[test]
+    fn test_inverted_self_trade_check() {
+        let config = OrderBookConfig::nasdaq_style("BTC-USD".to_string())
+            .with_inverted_prices()
+            .with_self_trade_prevention();
+        let engine = MatchingEngine::with_config(
+            &config,
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let order = |side: Side, price: i64| {
+            Arc::new(Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let rejected = |events: &[OrderEvent]| {
+            events.iter().any(|event| {
+                matches!(
+                    event,
+                    OrderEvent::OrderRejected {
+                        code: RejectReason::SelfTrade,
+                        ..
+                    }
+                )
+            })
+        };
+
+        engine.submit_order(order(Side::Sell, 100));
+        // On an inverted book a bid at 101 does not reach the ask at 100
+        assert!(!rejected(&engine.submit_order(order(Side::Buy, 101))));
+        assert!(rejected(&engine.submit_order(order(Side::Buy, 99))));
+    }
+
+    #[test]
+    fn test_inverted_price_matching() {
+        let config = OrderBookConfig::nasdaq_style("BTC-USD".to_string()).with_inverted_prices();
+        for use_simd in [false, true] {
+            let engine = MatchingEngine::with_config(
+                &config,
+                Box::new(PriceTimePriority::new(use_simd)),
+                Arc::new(NoOpEventHandler),
+            );
+            let order = |side: Side, price: &str| {
+                Arc::new(Order::new(
+                    "user".to_string(),
+                    "BTC-USD".to_string(),
+                    side,
+                    OrderType::Limit,
+                    Some(price.parse().unwrap()),
+                    Quantity::from_integer(1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ))
+            };
+            let price = |p: &str| p.parse::<Price>().unwrap();
+
+            // Asks at 3 and 4: the higher one is best. A bid at 5 is below
+            // neither, so it rests without trading and the book is not crossed
+            engine.submit_order(order(Side::Sell, "3"));
+            engine.submit_order(order(Side::Sell, "4"));
+            engine.submit_order(order(Side::Buy, "5"));
+            let snapshot = engine.get_snapshot(5);
+            assert_eq!(snapshot.best_ask(), Some(price("4")));
+            assert_eq!(snapshot.best_bid(), Some(price("5")));
+            assert!(!engine.is_crossed());
+
+            // A bid at 3.5 reaches the ask at 4 but not the one at 3
+            let events = engine.submit_order(order(Side::Buy, "3.5"));
+            let trades: Vec<Price> = events
+                .iter()
+                .filter_map(|e| match e {
+                    OrderEvent::OrderMatched { trade, .. } => Some(trade.price),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(trades, vec![price("4")], "use_simd = {use_simd}");
+            assert_eq!(engine.get_snapshot(5).best_ask(), Some(price("3")));
+
+            // Post-only is judged the inverted way round too
+            let post_only = Arc::new(
+                Order::new(
+                    "user".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(price("6")),
+                    Quantity::from_integer(1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )
+                .with_post_only(),
+            );
+            let events = engine.submit_order(post_only);
+            assert!(matches!(
+                events.last(),
+                Some(OrderEvent::OrderRejected {
+                    code: RejectReason::PostOnlyWouldCross,
+                    ..
+                })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let config = OrderBookConfig::nasdaq_style("BTC-USD".to_string()).with_max_depth(3);
+        let engine = MatchingEngine::with_config(
+            &config,
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let bid = |price: i64| {
+            Arc::new(Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let rejected = |events: &[OrderEvent]| {
+            events.iter().any(|e| {
+                matches!(e, OrderEvent::OrderRejected { reason, .. }
+                    if reason == "book depth limit reached")
+            })
+        };
+
+        for price in [100, 99, 98] {
+            assert!(!rejected(&engine.submit_order(bid(price))));
+        }
+
+        // A new level below the worst bid is out of range
+        let deeper = bid(97);
+        assert!(rejected(&engine.submit_order(Arc::clone(&deeper))));
+        assert_eq!(deeper.get_state(), OrderState::Rejected);
+
+        // Joining an existing level or improving the book is still allowed
+        assert!(!rejected(&engine.submit_order(bid(98))));
+        assert!(!rejected(&engine.submit_order(bid(101))));
+
+        let bids = engine.get_snapshot(10).bids;
+        assert_eq!(bids.len(), 4);
+        assert_eq!(bids[0].0, Price::from_integer(101).unwrap());
+        assert_eq!(
+            bids[3],
+            (
+                Price::from_integer(98).unwrap(),
+                Quantity::from_integer(2).unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn test_fees_attach_to_sides() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_fee_schedule(FeeSchedule::new(
+                rust_decimal::Decimal::from(2),
+                rust_decimal::Decimal::from(10),
+            ))
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+
+        let maker = limit_order(Side::Sell, 100, 5);
+        engine.submit_order(Arc::clone(&maker));
+        let taker = limit_order(Side::Buy, 100, 5);
+        let events = engine.submit_order(Arc::clone(&taker));
+
+        let trade = events
+            .iter()
+            .find_map(|e| match e {
+                OrderEvent::OrderMatched { trade, .. } => Some(trade),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(trade.maker_order_id, maker.id);
+        assert_eq!(trade.taker_order_id, taker.id);
+        // 500 notional: 2bp maker, 10bp taker
+        assert_eq!(trade.maker_fee, rust_decimal::Decimal::new(1, 1));
+        assert_eq!(trade.taker_fee, rust_decimal::Decimal::new(5, 1));
+    }
+
+    #[test]
+    fn test_trade_price_rules() {
+        let print = |rule, maker_side, maker_price, taker_price| {
+            let engine = MatchingEngineBuilder::new("BTC-USD")
+                .price_time_matching(false)
+                .with_trade_price_rule(rule)
+                .build(Arc::new(NoOpEventHandler))
+                .unwrap();
+            engine.submit_order(limit_order(maker_side, maker_price, 1));
+            let taker = limit_order(maker_side.opposite(), taker_price, 1);
+            engine
+                .submit_order(taker)
+                .into_iter()
+                .find_map(|e| match e {
+                    OrderEvent::OrderMatched { trade, .. } => Some(trade.price),
+                    _ => None,
+                })
+                .unwrap()
+        };
+        let price = |s: &str| s.parse::<Price>().unwrap();
+
+        assert_eq!(
+            print(TradePriceRule::MakerPrice, Side::Sell, 100, 103),
+            price("100")
+        );
+        assert_eq!(
+            print(TradePriceRule::TakerBenefit, Side::Sell, 100, 103),
+            price("100")
+        );
+        assert_eq!(
+            print(TradePriceRule::Midpoint, Side::Sell, 100, 103),
+            price("101.5")
+        );
+
+        // A selling taker below a resting bid
+        assert_eq!(
+            print(TradePriceRule::MakerPrice, Side::Buy, 100, 97),
+            price("100")
+        );
+        assert_eq!(
+            print(TradePriceRule::TakerBenefit, Side::Buy, 100, 97),
+            price("100")
+        );
+        assert_eq!(
+            print(TradePriceRule::Midpoint, Side::Buy, 100, 97),
+            price("98.5")
+        );
+    }
+
+    #[test]
+    fn test_circuit_breaker_halts_and_resumes() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_circuit_breaker(CircuitBreaker::new(
+                rust_decimal::Decimal::new(10, 2), // 10%
+                std::time::Duration::from_secs(60),
+            ))
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let rejected = |events: &[OrderEvent]| {
+            events.iter().any(|e| {
+                matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "market halted")
+            })
+        };
+
+        engine.submit_order(limit_order(Side::Sell, 100, 1));
+        engine.submit_order(limit_order(Side::Sell, 101, 1));
+        engine.submit_order(limit_order(Side::Sell, 150, 10));
+
+        // First trade sets the reference
+        let events = engine.submit_order(limit_order(Side::Buy, 100, 1));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+
+        // Sweeping up to 150 is a 50% move: halted before anything trades
+        let events = engine.submit_order(limit_order(Side::Buy, 200, 5));
+        assert!(rejected(&events));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            OrderEvent::TradingHalted { reference_price, price, .. }
+                if *reference_price == Price::from_integer(100).unwrap()
+                    && *price == Price::from_integer(150).unwrap()
+        )));
+        assert!(engine.is_halted());
+        assert_eq!(engine.get_snapshot(10).asks.len(), 2);
+
+        // Even a small in-band order is rejected while halted
+        assert!(rejected(&engine.submit_order(limit_order(
+            Side::Buy,
+            101,
+            1
+        ))));
+
+        assert!(matches!(
+            engine.resume(),
+            Some(OrderEvent::TradingResumed { .. })
+        ));
+        assert!(engine.resume().is_none());
+        let events = engine.submit_order(limit_order(Side::Buy, 101, 1));
+        assert!(!rejected(&events));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderFilled { .. })));
+    }
+
+    #[test]
+    fn test_circuit_breaker_window_follows_engine_clock() {
+        let start = Utc::now();
+        let clock = Arc::new(crate::engine::MockClock::new(start));
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_circuit_breaker(CircuitBreaker::new(
+                rust_decimal::Decimal::new(10, 2), // 10%
+                std::time::Duration::from_secs(60),
+            ))
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap()
+            .with_clock(clock.clone());
+
+        engine.submit_order(limit_order(Side::Sell, 100, 1));
+        engine.submit_order(limit_order(Side::Sell, 150, 2));
+        engine.submit_order(limit_order(Side::Buy, 100, 1));
+
+        // However long the test takes in wall time, the trade at 100 is
+        // still 60 clock seconds old: a 50% move halts
+        clock.advance(chrono::Duration::seconds(60));
+        let probe = limit_order(Side::Buy, 150, 1);
+        engine.submit_order(Arc::clone(&probe));
+        assert!(engine.is_halted());
+        assert_eq!(probe.get_state(), OrderState::Rejected);
+        engine.resume();
+
+        // One clock second later the reference has left the window
+        clock.advance(chrono::Duration::seconds(1));
+        let buy = limit_order(Side::Buy, 150, 1);
+        engine.submit_order(Arc::clone(&buy));
+        assert!(!engine.is_halted());
+        assert_eq!(buy.get_state(), OrderState::Filled);
+    }
+
+    #[test]
+    fn test_rate_limit_per_user() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_rate_limit(3)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let order = |user: &str, price: i64| {
+            Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let throttled = |events: &[OrderEvent]| {
+            events.iter().any(|e| {
+                matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "rate limit exceeded")
+            })
+        };
+
+        for price in 100..103 {
+            assert!(!throttled(&engine.submit_order(order("alice", price))));
+        }
+        assert!(throttled(&engine.submit_order(order("alice", 103))));
+
+        // Another user has a bucket of their own
+        assert!(!throttled(&engine.submit_order(order("bob", 99))));
+        assert_eq!(engine.get_snapshot(10).bids.len(), 4);
+    }
+
+    #[test]
+    fn test_rate_limit_refills_on_engine_clock() {
+        let clock = Arc::new(crate::engine::MockClock::new(Utc::now()));
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_rate_limit(2)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap()
+            .with_clock(clock.clone());
+        let accepted = |order: Arc<Order>| {
+            engine.submit_order(Arc::clone(&order));
+            order.get_state() == OrderState::Accepted
+        };
+
+        assert!(accepted(limit_order(Side::Buy, 100, 1)));
+        assert!(accepted(limit_order(Side::Buy, 100, 1)));
+        // Wall time passing does not refill the bucket; clock time does
+        std::thread::sleep(std::time::Duration::from_millis(600));
+        assert!(!accepted(limit_order(Side::Buy, 100, 1)));
+        clock.advance(chrono::Duration::milliseconds(500));
+        assert!(accepted(limit_order(Side::Buy, 100, 1)));
+        assert!(!accepted(limit_order(Side::Buy, 100, 1)));
+    }
+
+    #[test]
+    fn test_level_capacity() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_max_orders_per_level(2)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let level_full = |events: &[OrderEvent]| {
+            events.iter().any(
+                |e| matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "level full"),
+            )
+        };
+
+        for _ in 0..2 {
+            assert!(!level_full(&engine.submit_order(limit_order(
+                Side::Buy,
+                100,
+                1
+            ))));
+        }
+        assert!(level_full(&engine.submit_order(limit_order(
+            Side::Buy,
+            100,
+            1
+        ))));
+
+        // Other levels and the other side stay open
+        assert!(!level_full(&engine.submit_order(limit_order(
+            Side::Buy,
+            99,
+            1
+        ))));
+        assert!(!level_full(&engine.submit_order(limit_order(
+            Side::Sell,
+            101,
+            1
+        ))));
+
+        // A fill frees a slot
+        engine.submit_order(limit_order(Side::Sell, 100, 1));
+        assert!(!level_full(&engine.submit_order(limit_order(
+            Side::Buy,
+            100,
+            1
+        ))));
+    }
+
+    #[test]
+    fn test_non_positive_prices() {
+        let price = -"0.05".parse::<Price>().unwrap();
+        let order = |side| {
+            Arc::new(Order::new(
+                "user".to_string(),
+                "SPREAD-Z5H6".to_string(),
+                side,
+                OrderType::Limit,
+                Some(price),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+
+        let strict = MatchingEngineBuilder::new("SPREAD-Z5H6")
+            .price_time_matching(false)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        assert!(strict.submit_order(order(Side::Sell)).iter().any(|e| {
+            matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "Price must be positive")
+        }));
+
+        let engine = MatchingEngineBuilder::new("SPREAD-Z5H6")
+            .price_time_matching(false)
+            .with_non_positive_prices()
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        engine.submit_order(order(Side::Sell));
+        assert_eq!(engine.get_snapshot(1).best_ask(), Some(price));
+
+        let events = engine.submit_order(order(Side::Buy));
+        let trade = events
+            .iter()
+            .find_map(|e| match e {
+                OrderEvent::OrderMatched { trade, .. } => Some(trade),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(trade.price, price);
+        assert!(engine.get_snapshot(1).asks.is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_last_trade() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        engine.submit_order(limit_order(Side::Sell, 100, 5));
+
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(snapshot.last_trade_price, None);
+        assert_eq!(snapshot.last_trade_quantity, None);
+        assert_eq!(snapshot.last_trade_id, None);
+
+        engine.submit_order(limit_order(Side::Buy, 100, 2));
+        engine.submit_order(limit_order(Side::Buy, 101, 1));
+
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(
+            snapshot.last_trade_price,
+            Some(Price::from_integer(100).unwrap())
+        );
+        assert_eq!(
+            snapshot.last_trade_quantity,
+            Some(Quantity::from_integer(1).unwrap())
+        );
+        assert_eq!(snapshot.last_trade_id, Some(2));
+    }
+
+    #[test]
+    fn test_set_algorithm() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let first = limit_order(Side::Sell, 100, 4);
+        let second = limit_order(Side::Sell, 100, 4);
+        engine.submit_order(Arc::clone(&first));
+        engine.submit_order(Arc::clone(&second));
+
+        // Price/time: the oldest maker fills first
+        engine.submit_order(limit_order(Side::Buy, 100, 2));
+        assert_eq!(
+            first.get_filled_quantity(),
+            Quantity::from_integer(2).unwrap()
+        );
+        assert_eq!(second.get_filled_quantity(), Quantity::ZERO);
+
+        let event = engine.set_algorithm(Box::new(ProRata::new(Quantity::ZERO, false)));
+        assert!(matches!(
+            event,
+            OrderEvent::AlgorithmChanged { ref from, ref to, .. }
+                if from == "PriceTime" && to == "ProRata"
+        ));
+
+        // Pro-rata: 3 split over remaining sizes 2 and 4
+        engine.submit_order(limit_order(Side::Buy, 100, 3));
+        assert_eq!(
+            first.get_filled_quantity(),
+            Quantity::from_integer(3).unwrap()
+        );
+        assert_eq!(
+            second.get_filled_quantity(),
+            Quantity::from_integer(2).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_call_auction() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        engine.enter_auction();
+
+        // Executable: 2 @ 99, 6 @ 100, 7 @ 101, 3 @ 102
+        let orders = [
+            (Side::Buy, 102, 3),
+            (Side::Buy, 101, 4),
+            (Side::Buy, 100, 5),
+            (Side::Sell, 99, 2),
+            (Side::Sell, 100, 4),
+            (Side::Sell, 101, 6),
+            (Side::Sell, 102, 2),
+        ];
+        for (side, price, quantity) in orders {
+            let events = engine.submit_order(limit_order(side, price, quantity));
+            assert!(!events
+                .iter()
+                .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+        }
+        assert!(engine.is_crossed());
+        let ioc = Arc::new(Order::new(
+            "user".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(100).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::ImmediateOrCancel,
+        ));
+        assert!(engine.submit_order(ioc).iter().any(|e| {
+            matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "not accepted during auction")
+        }));
+
+        let result = engine.run_auction();
+        let clearing_price = Price::from_integer(101).unwrap();
+        assert_eq!(result.clearing_price, Some(clearing_price));
+        assert_eq!(result.matched_quantity, Quantity::from_integer(7).unwrap());
+        assert!(result.trades.iter().all(|t| t.price == clearing_price));
+        assert_eq!(
+            result
+                .trades
+                .iter()
+                .fold(Quantity::ZERO, |total, t| total + t.quantity),
+            Quantity::from_integer(7).unwrap()
+        );
+
+        let snapshot = engine.get_snapshot(10);
+        assert_eq!(
+            snapshot.bids,
+            vec![(
+                Price::from_integer(100).unwrap(),
+                Quantity::from_integer(5).unwrap()
+            )]
+        );
+        assert_eq!(
+            snapshot.asks,
+            vec![
+                (clearing_price, Quantity::from_integer(5).unwrap()),
+                (
+                    Price::from_integer(102).unwrap(),
+                    Quantity::from_integer(2).unwrap()
+                ),
+            ]
+        );
+
+        // Nothing left to uncross; continuous matching resumes
+        assert!(engine.run_auction().clearing_price.is_none());
+        assert!(engine.exit_auction().is_empty());
+        let events = engine.submit_order(limit_order(Side::Buy, 101, 1));
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+    }
+
+    #[test]
+    fn test_auction_cancels_newer_self_trade() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_self_trade_prevention()
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let order = |user: &str, side: Side, price: i64, quantity: i64| {
+            Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(quantity).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+        let buy = order("alice", Side::Buy, 101, 3);
+        let own_sell = order("alice", Side::Sell, 100, 2);
+
+        // Nothing was collected: no auction to run
+        assert!(engine.run_auction().clearing_price.is_none());
+
+        engine.enter_auction();
+        engine.submit_order(Arc::clone(&buy));
+        engine.submit_order(Arc::clone(&own_sell));
+        engine.submit_order(order("bob", Side::Sell, 101, 2));
+
+        // Alice's sell meets her own buy first and is cancelled; bob fills 2
+        let result = engine.run_auction();
+        assert_eq!(
+            result.clearing_price,
+            Some(Price::from_integer(101).unwrap())
+        );
+        assert_eq!(result.matched_quantity, Quantity::from_integer(2).unwrap());
+        assert_eq!(result.trades.len(), 1);
+        assert_eq!(own_sell.get_state(), OrderState::Cancelled);
+        assert_eq!(
+            buy.get_remaining_quantity(),
+            Quantity::from_integer(1).unwrap()
+        );
+        assert!(engine.get_snapshot(10).asks.is_empty());
+        assert!(!engine.is_crossed());
+    }
+
+    #[test]
+    fn test_event_sequence_is_gapless() {
+        #[derive(Default)]
+        struct SequenceRecorder(Mutex<Vec<u64>>);
+
+        impl EventHandler for SequenceRecorder {
+            fn on_event(&self, _event: OrderEvent) {}
+
+            fn on_sequenced_events(&self, events: Vec<SequencedEvent>) {
+                self.0.lock().extend(events.iter().map(|e| e.seq));
+            }
+        }
+
+        let recorder = Arc::new(SequenceRecorder::default());
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::clone(&recorder) as Arc<dyn EventHandler>,
+        );
+        let resting = limit_order(Side::Buy, 99, 1);
+        engine.submit_order(Arc::clone(&resting));
+        engine.submit_order(limit_order(Side::Sell, 101, 2));
+        engine.submit_order(limit_order(Side::Buy, 101, 1));
+        engine.cancel_order(resting.id);
 
-        let events = engine.submit_order(buy);
+        // Handlers also receive book deltas, so count what was delivered
+        let seqs = recorder.0.lock().clone();
+        assert!(seqs.len() > 10);
+        assert_eq!(seqs, (1..=seqs.len() as u64).collect::<Vec<_>>());
+    }
 
-        // Should have matched
-        assert!(events
+    #[test]
+    fn test_cancel_while_matching_keeps_level_totals() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        // Takers only ever reach the front orders; the back ones are cancelled
+        // from another thread while those fills are in progress
+        for _ in 0..100 {
+            engine.submit_order(limit_order(Side::Sell, 100, 10));
+        }
+        let back: Vec<_> = (0..300)
+            .map(|_| {
+                let order = limit_order(Side::Sell, 100, 1);
+                engine.submit_order(Arc::clone(&order));
+                order
+            })
+            .collect();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..900 {
+                    engine.submit_order(limit_order(Side::Buy, 100, 1));
+                }
+            });
+            scope.spawn(|| {
+                for order in &back {
+                    assert!(engine.cancel_order(order.id).is_some());
+                }
+            });
+        });
+
+        let level = engine
+            .asks
+            .level_at_price(Price::from_integer(100).unwrap())
+            .unwrap();
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(100).unwrap()
+        );
+        assert_eq!(level.get_total_quantity(), level.recompute_total());
+        assert_eq!(engine.asks.total_quantity(), engine.asks.levels_total());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "cached total")]
+    fn test_level_drift_panics_on_cancel() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let first = limit_order(Side::Sell, 100, 1);
+        engine.submit_order(Arc::clone(&first));
+        engine.submit_order(limit_order(Side::Sell, 100, 1));
+        engine
+            .asks
+            .level_at_price(Price::from_integer(100).unwrap())
+            .unwrap()
+            .subtract_quantity(Quantity::from_raw(1));
+
+        engine.cancel_order(first.id);
+    }
+
+    #[test]
+    fn test_snapshot_with_sequence_plus_deltas_rebuilds_book() {
+        #[derive(Default)]
+        struct EventRecorder(Mutex<Vec<SequencedEvent>>);
+
+        impl EventHandler for EventRecorder {
+            fn on_event(&self, _event: OrderEvent) {}
+
+            fn on_sequenced_events(&self, events: Vec<SequencedEvent>) {
+                self.0.lock().extend(events);
+            }
+        }
+
+        let recorder = Arc::new(EventRecorder::default());
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::clone(&recorder) as Arc<dyn EventHandler>,
+        );
+
+        // Bids below 100 and asks above, so every thread only adds liquidity
+        let (snapshot, seq) = std::thread::scope(|scope| {
+            for thread in 0..4u64 {
+                let engine = &engine;
+                scope.spawn(move || {
+                    let mut state = thread * 7919 + 1;
+                    for _ in 0..500 {
+                        state = state
+                            .wrapping_mul(6364136223846793005)
+                            .wrapping_add(1442695040888963407);
+                        let offset = 1 + (state >> 40) as i64 % 10;
+                        let (side, price) = if state >> 63 == 0 {
+                            (Side::Buy, 100 - offset)
+                        } else {
+                            (Side::Sell, 100 + offset)
+                        };
+                        let qty = 1 + (state >> 20) as i64 % 5;
+                        engine.submit_order(limit_order(side, price, qty));
+                    }
+                });
+            }
+            while recorder.0.lock().len() < 500 {
+                std::thread::yield_now();
+            }
+            engine.snapshot_with_sequence(usize::MAX)
+        });
+        assert!(seq > 0);
+
+        let mut bids: std::collections::BTreeMap<Price, Quantity> =
+            snapshot.bids.into_iter().collect();
+        let mut asks: std::collections::BTreeMap<Price, Quantity> =
+            snapshot.asks.into_iter().collect();
+        let mut events = recorder.0.lock().clone();
+        events.sort_by_key(|e| e.seq);
+        for event in events.iter().filter(|e| e.seq > seq) {
+            if let OrderEvent::BookDelta {
+                side,
+                price,
+                new_quantity,
+                ..
+            } = event.event
+            {
+                let levels = match side {
+                    Side::Buy => &mut bids,
+                    Side::Sell => &mut asks,
+                };
+                if new_quantity.is_zero() {
+                    levels.remove(&price);
+                } else {
+                    levels.insert(price, new_quantity);
+                }
+            }
+        }
+
+        let expected = engine.get_snapshot(usize::MAX);
+        let bids: Vec<_> = bids.into_iter().rev().collect();
+        let asks: Vec<_> = asks.into_iter().collect();
+        assert_eq!(bids, expected.bids);
+        assert_eq!(asks, expected.asks);
+    }
+
+    #[test]
+    fn test_can_fully_fill_matches_fok_outcome() {
+        let book = || {
+            let engine = MatchingEngine::new(
+                "BTC-USD".to_string(),
+                Box::new(PriceTimePriority::new(false)),
+                Arc::new(NoOpEventHandler),
+            );
+            engine.submit_order(limit_order(Side::Sell, 100, 3));
+            engine.submit_order(limit_order(Side::Sell, 101, 2));
+            engine.submit_order(limit_order(Side::Buy, 98, 4));
+            engine
+        };
+        let cases = [
+            (Side::Buy, Some(101), 5),
+            (Side::Buy, Some(101), 6),
+            (Side::Buy, Some(99), 1),
+            (Side::Buy, None, 5),
+            (Side::Buy, None, 6),
+            (Side::Sell, Some(98), 4),
+            (Side::Sell, Some(97), 5),
+        ];
+
+        for (side, price, quantity) in cases {
+            let engine = book();
+            let price = price.map(|p| Price::from_integer(p).unwrap());
+            let quantity = Quantity::from_integer(quantity).unwrap();
+            let expected = engine.can_fully_fill(side, price, quantity);
+
+            let fok = Arc::new(Order::new(
+                "taker".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                if price.is_some() {
+                    OrderType::Limit
+                } else {
+                    OrderType::Market
+                },
+                price,
+                quantity,
+                TimeInForce::FillOrKill,
+            ));
+            let events = engine.submit_order(Arc::clone(&fok));
+
+            let case = format!("{side:?} {quantity} @ {price:?}");
+            if expected {
+                assert_eq!(fok.get_state(), OrderState::Filled, "{case}");
+            } else {
+                // Rejected up front: nothing traded and the book is untouched
+                assert!(
+                    matches!(
+                        events.last(),
+                        Some(OrderEvent::OrderRejected {
+                            code: RejectReason::FillOrKillUnavailable,
+                            ..
+                        })
+                    ),
+                    "{case}"
+                );
+                assert!(!events
+                    .iter()
+                    .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+                assert_eq!(engine.get_snapshot(10), book().get_snapshot(10), "{case}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_min_fill() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        engine.submit_order(limit_order(Side::Sell, 100, 3));
+        engine.submit_order(limit_order(Side::Sell, 101, 2));
+        engine.submit_order(limit_order(Side::Sell, 102, 4));
+        let buy = |price, min_fill| {
+            Arc::new(
+                Order::new(
+                    "user".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(10).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )
+                .with_min_fill(Quantity::from_integer(min_fill).unwrap()),
+            )
+        };
+        let filled = |events: &[OrderEvent]| {
+            events.iter().find_map(|e| match e {
+                OrderEvent::OrderPartiallyFilled {
+                    filled_quantity, ..
+                } => Some(*filled_quantity),
+                OrderEvent::OrderRejected { reason, .. } => {
+                    assert_eq!(reason, "minimum fill not available");
+                    Some(Quantity::ZERO)
+                },
+                _ => None,
+            })
+        };
+
+        // 5 available up to 101: a minimum of 6 is rejected without trading
+        assert_eq!(
+            filled(&engine.submit_order(buy(101, 6))),
+            Some(Quantity::ZERO)
+        );
+        assert_eq!(
+            engine.get_snapshot(1).asks[0].1,
+            Quantity::from_integer(3).unwrap()
+        );
+
+        // Exactly met
+        assert_eq!(
+            filled(&engine.submit_order(buy(101, 5))),
+            Some(Quantity::from_integer(5).unwrap())
+        );
+
+        // Comfortably met; the bid left at 101 is not counted as supply
+        assert_eq!(
+            filled(&engine.submit_order(buy(102, 1))),
+            Some(Quantity::from_integer(4).unwrap())
+        );
+    }
+
+    fn last_look_engine(hold: Duration) -> MatchingEngine {
+        MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(LastLook::new(Box::new(PriceTimePriority::new(false)), hold)),
+            Arc::new(NoOpEventHandler),
+        )
+    }
+
+    /// Match ids of the `LastLookPending` events in `events`
+    fn pending_matches(events: &[OrderEvent]) -> Vec<u64> {
+        events
             .iter()
-            .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+            .filter_map(|event| match event {
+                OrderEvent::LastLookPending { match_id, .. } => Some(*match_id),
+                _ => None,
+            })
+            .collect()
+    }
 
-        // Snapshot should be empty
-        let snapshot = engine.get_snapshot(10);
-        assert_eq!(snapshot.bids.len(), 0);
-        assert_eq!(snapshot.asks.len(), 0);
+    #[test]
+    fn test_last_look_confirm() {
+        let engine = last_look_engine(Duration::from_secs(60));
+        let maker = limit_order(Side::Sell, 100, 5);
+        engine.submit_order(Arc::clone(&maker));
+
+        // Nothing trades on arrival and the taker stays off the book
+        let taker = limit_order(Side::Buy, 100, 8);
+        let events = engine.submit_order(Arc::clone(&taker));
+        let held = pending_matches(&events);
+        assert_eq!(held.len(), 1);
+        assert!(engine.get_order(taker.id).is_none());
+        assert_eq!(
+            maker.get_remaining_quantity(),
+            Quantity::from_integer(5).unwrap()
+        );
+        assert_eq!(engine.get_snapshot(1).bids.len(), 0);
+
+        let events = engine.confirm_last_look(held[0]);
+        let trade = events
+            .iter()
+            .find_map(|event| match event {
+                OrderEvent::OrderMatched { trade, .. } => Some(trade.clone()),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(trade.maker_order_id, maker.id);
+        assert_eq!(trade.taker_order_id, taker.id);
+        assert_eq!(trade.quantity, Quantity::from_integer(5).unwrap());
+
+        // The unmatched remainder is cancelled, not rested
+        assert!(events.iter().any(|event| matches!(
+            event,
+            OrderEvent::OrderCancelled { order_id, .. } if *order_id == taker.id
+        )));
+        assert!(engine.get_order(maker.id).is_none());
+        assert_eq!(engine.get_snapshot(1).asks.len(), 0);
+        assert_eq!(engine.get_snapshot(1).bids.len(), 0);
+
+        // Resolved matches cannot be confirmed twice
+        assert!(engine.confirm_last_look(held[0]).is_empty());
     }
 
     #[test]
-    fn test_cancel_order() {
+    fn test_last_look_reject() {
+        let engine = last_look_engine(Duration::from_secs(60));
+        let maker = limit_order(Side::Sell, 100, 5);
+        engine.submit_order(Arc::clone(&maker));
+        let taker = limit_order(Side::Buy, 100, 5);
+        let held = pending_matches(&engine.submit_order(Arc::clone(&taker)));
+
+        let events = engine.reject_last_look(held[0]);
+        assert!(matches!(
+            events[0],
+            OrderEvent::LastLookRejected { maker_id, taker_id, .. }
+                if maker_id == maker.id && taker_id == taker.id
+        ));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, OrderEvent::OrderMatched { .. })));
+        assert_eq!(taker.get_state(), OrderState::Cancelled);
+
+        // The maker keeps its full size and its place in the book
+        assert_eq!(
+            maker.get_remaining_quantity(),
+            Quantity::from_integer(5).unwrap()
+        );
+        assert_eq!(
+            engine.get_snapshot(1).asks[0].1,
+            Quantity::from_integer(5).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_last_look_timeout() {
+        let clock = Arc::new(crate::engine::MockClock::new(Utc::now()));
+        let engine = last_look_engine(Duration::from_millis(20)).with_clock(clock.clone());
+        engine.submit_order(limit_order(Side::Sell, 100, 5));
+        engine.submit_order(limit_order(Side::Sell, 101, 5));
+        let taker = limit_order(Side::Buy, 101, 10);
+        let held = pending_matches(&engine.submit_order(Arc::clone(&taker)));
+        assert_eq!(held.len(), 2);
+
+        // Still inside the window, however long the test has taken
+        clock.advance(chrono::Duration::milliseconds(19));
+        assert!(engine.expire_last_looks(engine.now()).is_empty());
+
+        clock.advance(chrono::Duration::milliseconds(1));
+
+        // Confirming too late rejects the match
+        let events = engine.confirm_last_look(held[0]);
+        assert!(matches!(events[0], OrderEvent::LastLookRejected { .. }));
+        assert_eq!(events.len(), 1);
+
+        // The last expiry closes out the taker
+        let events = engine.expire_last_looks(engine.now());
+        assert!(matches!(
+            events[0],
+            OrderEvent::LastLookRejected { match_id, .. } if match_id == held[1]
+        ));
+        assert!(matches!(
+            events[1],
+            OrderEvent::OrderCancelled { order_id, .. } if order_id == taker.id
+        ));
+        assert_eq!(taker.get_filled_quantity(), Quantity::ZERO);
+        assert_eq!(engine.get_snapshot(2).asks.len(), 2);
+    }
+
+    #[cfg(all(feature = "numa", target_os = "linux"))]
+    #[test]
+    fn test_with_affinity_pins_constructing_thread() {
+        use crate::platform::{recommended_core, NumaTopology};
+
+        let core = recommended_core(&NumaTopology::detect()).unwrap();
+        // Pin a scratch thread rather than the test harness thread
+        let pinned = std::thread::spawn(move || {
+            MatchingEngine::with_affinity(
+                "BTC-USD".to_string(),
+                Box::new(PriceTimePriority::new(false)),
+                Arc::new(NoOpEventHandler),
+                core,
+            )
+            .pinned_core()
+        })
+        .join()
+        .unwrap();
+        assert_eq!(pinned, Some(core));
+    }
+
+    #[test]
+    fn test_snapshot_cache() {
         let engine = MatchingEngine::new(
             "BTC-USD".to_string(),
             Box::new(PriceTimePriority::new(false)),
             Arc::new(NoOpEventHandler),
         );
+        engine.submit_order(limit_order(Side::Buy, 100, 1));
 
-        let order = Arc::new(Order::new(
-            "user1".to_string(),
+        let first = engine.get_snapshot(5);
+        let version = engine.book_version.load(Ordering::Acquire);
+        assert_eq!(engine.get_snapshot(5), first);
+        assert!(matches!(
+            &*engine.snapshot_cache.read(),
+            Some((v, 5, _)) if *v == version
+        ));
+
+        // A different depth is rebuilt rather than served from the cache
+        assert_eq!(engine.get_snapshot(1), first);
+        assert!(matches!(&*engine.snapshot_cache.read(), Some((_, 1, _))));
+
+        engine.submit_order(limit_order(Side::Sell, 101, 2));
+        assert!(engine.book_version.load(Ordering::Acquire) > version);
+        let second = engine.get_snapshot(5);
+        assert_ne!(second, first);
+        assert_eq!(
+            second.asks,
+            vec![(
+                Price::from_integer(101).unwrap(),
+                Quantity::from_integer(2).unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_statistics() {
+        let engine = MatchingEngine::new(
             "BTC-USD".to_string(),
-            Side::Buy,
-            OrderType::Limit,
-            Some(Price::from_integer(50000).unwrap()),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        assert_eq!(engine.get_statistics(), EngineStats::default());
+
+        engine.submit_order(limit_order(Side::Sell, 101, 1));
+        engine.submit_order(limit_order(Side::Sell, 103, 2));
+        engine.submit_order(limit_order(Side::Buy, 98, 4));
+        let mut trades = Vec::new();
+        for order in [
+            limit_order(Side::Buy, 103, 3),
+            limit_order(Side::Sell, 98, 2),
+        ] {
+            trades.extend(
+                engine
+                    .submit_order(order)
+                    .into_iter()
+                    .filter_map(|e| match e {
+                        OrderEvent::OrderMatched { trade, .. } => Some(trade),
+                        _ => None,
+                    }),
+            );
+        }
+        assert_eq!(trades.len(), 3);
+
+        let stats = engine.get_statistics();
+        assert_eq!(stats.total_trades, 3);
+        assert_eq!(
+            stats.total_volume,
+            trades
+                .iter()
+                .fold(Quantity::ZERO, |acc, t| acc + t.quantity)
+        );
+        assert_eq!(
+            stats.total_notional,
+            trades
+                .iter()
+                .map(|t| t.price.to_decimal() * t.quantity.to_decimal())
+                .sum::<rust_decimal::Decimal>()
+        );
+        assert_eq!(
+            stats.last_trade_price,
+            Some(Price::from_integer(98).unwrap())
+        );
+        assert_eq!(stats.high, Some(Price::from_integer(103).unwrap()));
+        assert_eq!(stats.low, Some(Price::from_integer(98).unwrap()));
+
+        engine.reset_statistics();
+        assert_eq!(engine.get_statistics(), EngineStats::default());
+    }
+
+    #[test]
+    fn test_oco_cancels_other_leg() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        let triggered = |events: &[OrderEvent], filled: OrderId, cancelled: OrderId| {
+            events.iter().any(|e| {
+                matches!(e, OrderEvent::OcoTriggered { filled_id, cancelled_id, .. }
+                    if *filled_id == filled && *cancelled_id == cancelled)
+            }) && events.iter().any(
+                |e| matches!(e, OrderEvent::OrderCancelled { order_id, .. } if *order_id == cancelled),
+            )
+        };
+
+        // Primary fills: the secondary leaves the book
+        let primary = limit_order(Side::Buy, 95, 2);
+        let secondary = limit_order(Side::Sell, 105, 2);
+        engine.submit_oco(Arc::clone(&primary), Arc::clone(&secondary));
+        assert_eq!(engine.get_snapshot(10).asks.len(), 1);
+
+        let events = engine.submit_order(limit_order(Side::Sell, 95, 1));
+        assert!(triggered(&events, primary.id, secondary.id));
+        assert!(engine.get_order(secondary.id).is_none());
+        assert!(engine.get_snapshot(10).asks.is_empty());
+        // Only the first fill triggers; the partially filled primary keeps resting
+        assert!(engine.get_order(primary.id).is_some());
+        engine.cancel_order(primary.id);
+
+        // Secondary fills: the primary leaves the book
+        let primary = limit_order(Side::Buy, 95, 2);
+        let secondary = limit_order(Side::Sell, 105, 2);
+        engine.submit_oco(Arc::clone(&primary), Arc::clone(&secondary));
+
+        let events = engine.submit_order(limit_order(Side::Buy, 105, 2));
+        assert!(triggered(&events, secondary.id, primary.id));
+        assert!(engine.get_order(primary.id).is_none());
+        assert!(engine.get_snapshot(10).bids.is_empty());
+
+        // A primary that trades on arrival cancels the secondary before it rests
+        engine.submit_order(limit_order(Side::Sell, 100, 1));
+        let primary = limit_order(Side::Buy, 100, 1);
+        let secondary = limit_order(Side::Sell, 110, 1);
+        let events = engine.submit_oco(Arc::clone(&primary), Arc::clone(&secondary));
+        assert!(triggered(&events, primary.id, secondary.id));
+        assert!(engine.get_snapshot(10).asks.is_empty());
+        assert!(engine.oco_links.read().is_empty());
+    }
+
+    #[test]
+    fn test_oco_with_pegged_primary() {
+        let engine = MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        );
+        engine.submit_order(limit_order(Side::Buy, 100, 1));
+
+        // Pegged to the best bid, the primary sells into it on arrival
+        let primary = Arc::new(Order::new(
+            "trader".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Pegged {
+                reference: PegReference::BestBid,
+                offset: Price::ZERO,
+            },
+            None,
             Quantity::from_integer(1).unwrap(),
             TimeInForce::GoodTillCancel,
         ));
+        let secondary = limit_order(Side::Sell, 110, 1);
+        let events = engine.submit_oco(Arc::clone(&primary), Arc::clone(&secondary));
 
-        let order_id = order.id;
-        engine.submit_order(order);
-
-        // Cancel order
-        let cancel_event = engine.cancel_order(order_id);
-        assert!(cancel_event.is_some());
+        let trades = events
+            .iter()
+            .filter(|e| matches!(e, OrderEvent::OrderMatched { .. }))
+            .count();
+        assert_eq!(trades, 1);
+        assert_eq!(primary.price(), Some(Price::from_integer(100).unwrap()));
+        assert_eq!(primary.get_state(), OrderState::Filled);
+        assert_eq!(primary.get_filled_quantity(), Quantity::ONE);
+        assert_eq!(secondary.get_state(), OrderState::Cancelled);
+        assert!(engine.get_snapshot(10).asks.is_empty());
     }
 
     #[test]
-    fn test_order_book_snapshot() {
+    fn test_pegged_order_tracks_best_bid() {
         let engine = MatchingEngine::new(
             "BTC-USD".to_string(),
             Box::new(PriceTimePriority::new(false)),
             Arc::new(NoOpEventHandler),
         );
-
-        // Add multiple orders
-        for i in 0i64..5 {
-            let buy = Arc::new(Order::new(
-                format!("user{}", i),
+        let tick = "0.01".parse::<Price>().unwrap();
+        let pegged_bid = || {
+            Arc::new(Order::new(
+                "mm".to_string(),
                 "BTC-USD".to_string(),
                 Side::Buy,
-                OrderType::Limit,
-                Some(Price::from_integer(50000 - i * 100).unwrap()),
+                OrderType::Pegged {
+                    reference: PegReference::BestBid,
+                    offset: -tick,
+                },
+                None,
                 Quantity::from_integer(1).unwrap(),
                 TimeInForce::GoodTillCancel,
-            ));
-            engine.submit_order(buy);
+            ))
+        };
+        let repriced = |events: &[OrderEvent]| {
+            events.iter().find_map(|e| match e {
+                OrderEvent::OrderRepriced {
+                    old_price,
+                    new_price,
+                    ..
+                } => Some((*old_price, *new_price)),
+                _ => None,
+            })
+        };
+        let price =
+            |whole: i64, cents: u64| format!("{whole}.{cents:02}").parse::<Price>().unwrap();
 
-            let sell = Arc::new(Order::new(
-                format!("user{}", i + 10),
+        // Nothing to peg to yet
+        let events = engine.submit_order(pegged_bid());
+        assert!(events.iter().any(|e| {
+            matches!(e, OrderEvent::OrderRejected { reason, .. } if reason == "peg reference unavailable")
+        }));
+
+        engine.submit_order(limit_order(Side::Sell, 105, 1));
+        engine.submit_order(limit_order(Side::Buy, 100, 1));
+        let peg = pegged_bid();
+        engine.submit_order(Arc::clone(&peg));
+        assert_eq!(engine.get_order(peg.id).unwrap().price, Some(price(99, 99)));
+
+        // Best bid moves up: the peg follows
+        let better = limit_order(Side::Buy, 101, 1);
+        let events = engine.submit_order(Arc::clone(&better));
+        assert_eq!(repriced(&events), Some((price(99, 99), price(100, 99))));
+        assert_eq!(
+            engine.get_order(peg.id).unwrap().price,
+            Some(price(100, 99))
+        );
+        // The submitted order itself is repriced, not a copy of it
+        assert_eq!(peg.price(), Some(price(100, 99)));
+        let bids: Vec<Price> = engine
+            .get_snapshot(10)
+            .bids
+            .iter()
+            .map(|(p, _)| *p)
+            .collect();
+        assert_eq!(bids, vec![price(101, 0), price(100, 99), price(100, 0)]);
+
+        // And back down when that bid leaves
+        engine.cancel_order(better.id);
+        assert_eq!(engine.get_order(peg.id).unwrap().price, Some(price(99, 99)));
+        let bids: Vec<Price> = engine
+            .get_snapshot(10)
+            .bids
+            .iter()
+            .map(|(p, _)| *p)
+            .collect();
+        assert_eq!(bids, vec![price(100, 0), price(99, 99)]);
+
+        // Unrelated changes leave it alone
+        let events = engine.submit_order(limit_order(Side::Sell, 106, 1));
+        assert_eq!(repriced(&events), None);
+    }
+
+    #[test]
+    fn test_market_order_remainder_is_cancelled() {
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .pro_rata_matching(Quantity::ZERO, false)
+            .build(Arc::new(NoOpEventHandler))
+            .unwrap();
+        let market_sell = |qty: i64| {
+            Arc::new(Order::new(
+                "taker".to_string(),
                 "BTC-USD".to_string(),
                 Side::Sell,
-                OrderType::Limit,
-                Some(Price::from_integer(50100 + i * 100).unwrap()),
-                Quantity::from_integer(1).unwrap(),
+                OrderType::Market,
+                None,
+                Quantity::from_integer(qty).unwrap(),
                 TimeInForce::GoodTillCancel,
-            ));
-            engine.submit_order(sell);
+            ))
+        };
+        let cancelled = |events: &[OrderEvent]| {
+            events
+                .iter()
+                .any(|e| matches!(e, OrderEvent::OrderCancelled { .. }))
+        };
+
+        // Empty book: nothing trades and nothing rests
+        assert!(cancelled(&engine.submit_order(market_sell(1))));
+
+        engine.submit_order(limit_order(Side::Buy, 100, 1));
+        engine.submit_order(limit_order(Side::Buy, 99, 2));
+        let order = market_sell(5);
+        let events = engine.submit_order(Arc::clone(&order));
+        assert_eq!(
+            order.get_filled_quantity(),
+            Quantity::from_integer(3).unwrap()
+        );
+        assert!(cancelled(&events));
+        let snapshot = engine.get_snapshot(10);
+        assert!(snapshot.bids.is_empty() && snapshot.asks.is_empty());
+    }
+
+    #[test]
+    fn test_book_checksum_matches_local_book() {
+        use std::collections::BTreeMap;
+
+        #[derive(Default)]
+        struct RecordingHandler {
+            events: parking_lot::Mutex<Vec<OrderEvent>>,
+        }
+        impl EventHandler for RecordingHandler {
+            fn on_event(&self, event: OrderEvent) {
+                self.events.lock().push(event);
+            }
         }
 
-        let snapshot = engine.get_snapshot(3);
-        assert_eq!(snapshot.bids.len(), 3);
-        assert_eq!(snapshot.asks.len(), 3);
-        assert!(snapshot.spread.is_some());
-        assert!(snapshot.mid_price.is_some());
+        let feed = Arc::new(RecordingHandler::default());
+        let engine = MatchingEngineBuilder::new("BTC-USD")
+            .price_time_matching(false)
+            .with_checksum_schedule(3, 1)
+            .build(feed.clone())
+            .unwrap();
+
+        let resting = limit_order(Side::Buy, 98, 1);
+        for order in [
+            limit_order(Side::Buy, 99, 2),
+            Arc::clone(&resting),
+            limit_order(Side::Sell, 101, 4),
+            limit_order(Side::Sell, 102, 1),
+            limit_order(Side::Buy, 97, 5),
+            // Outside the checksum depth
+            limit_order(Side::Buy, 96, 5),
+            // Trades against the best ask
+            limit_order(Side::Buy, 101, 1),
+        ] {
+            engine.submit_order(order);
+        }
+        engine.cancel_order(resting.id).unwrap();
+
+        // Consumer side: apply deltas, then verify each published checksum
+        let mut local: [BTreeMap<Price, Quantity>; 2] = Default::default();
+        let mut checksums = Vec::new();
+        for event in feed.events.lock().drain(..) {
+            match event {
+                OrderEvent::BookDelta {
+                    side,
+                    price,
+                    new_quantity,
+                    ..
+                } => {
+                    let book = &mut local[side as usize];
+                    if new_quantity.is_zero() {
+                        book.remove(&price);
+                    } else {
+                        book.insert(price, new_quantity);
+                    }
+                },
+                OrderEvent::BookChecksum { crc, depth, .. } => {
+                    let [bids, asks] = &local;
+                    let rebuilt = OrderBookSnapshot::with_depth(
+                        "BTC-USD".to_string(),
+                        bids.iter()
+                            .rev()
+                            .take(depth)
+                            .map(|(p, q)| (*p, *q))
+                            .collect(),
+                        asks.iter().take(depth).map(|(p, q)| (*p, *q)).collect(),
+                    );
+                    assert_eq!(rebuilt.checksum(), crc);
+                    checksums.push(crc);
+                },
+                _ => {},
+            }
+        }
+
+        // One checksum per book change, matching the engine's own view
+        assert_eq!(checksums.len(), 8);
+        assert_eq!(checksums[7], engine.book_checksum(3));
+        // Every change inside the top three levels moves the checksum...
+        for pair in checksums[..5].windows(2) {
+            assert_ne!(pair[0], pair[1]);
+        }
+        // ...while a change below them does not
+        assert_eq!(checksums[4], checksums[5]);
+        assert_ne!(checksums[5], checksums[6]);
+        assert_ne!(checksums[6], checksums[7]);
     }
 }
@@ -0,0 +1,271 @@
+// ============================================================================
+// Midpoint Match Algorithm
+// Dark pool execution at the mid of a lit reference market
+// ============================================================================
+
+use crate::domain::{Order, OrderBookSide, Side, Trade};
+use crate::interfaces::MatchingAlgorithm;
+use crate::numeric::{Price, Quantity};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Raw value marking "no reference mid yet"
+const NO_MID: i64 = i64::MIN;
+
+/// Midpoint matching algorithm
+///
+/// Every trade prints at the externally supplied reference mid. Limit prices
+/// only decide eligibility: a buy must be willing to pay the mid and a sell
+/// must be willing to accept it. Eligible resting orders are matched best
+/// price first, FIFO within a level. Without a reference mid nothing trades.
+///
+/// # Example
+/// ```text
+/// Reference mid: 100.50
+/// Book (asks):  A sell 1 @ 100.00, B sell 1 @ 101.00
+///
+/// Incoming: Buy 2 @ 101.00
+/// Result: 1 with A @ 100.50; B is not eligible (101.00 > mid)
+/// ```
+pub struct MidpointMatch {
+    /// Reference mid as a raw price (`NO_MID` until set)
+    reference_mid: AtomicI64,
+}
+
+impl MidpointMatch {
+    pub fn new() -> Self {
+        Self {
+            reference_mid: AtomicI64::new(NO_MID),
+        }
+    }
+
+    /// Get the current reference mid
+    pub fn reference_mid(&self) -> Option<Price> {
+        match self.reference_mid.load(Ordering::Acquire) {
+            NO_MID => None,
+            raw => Some(Price::from_raw(raw)),
+        }
+    }
+
+    /// Whether a resting order at `price` on `side` accepts a fill at `mid`
+    fn resting_accepts(side: Side, price: Price, mid: Price) -> bool {
+        match side {
+            Side::Buy => price >= mid,
+            Side::Sell => price <= mid,
+        }
+    }
+}
+
+impl Default for MidpointMatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MatchingAlgorithm for MidpointMatch {
+    fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
+        let mut trades = Vec::new();
+
+        let mid = match self.reference_mid() {
+            Some(mid) => mid,
+            None => return trades,
+        };
+
+        // The incoming limit must permit the mid
+        if !self.crosses_level(&incoming_order, opposite_side, mid) {
+            return trades;
+        }
+
+        while incoming_order.get_remaining_quantity() > Quantity::ZERO {
+            let level = match opposite_side.best_level() {
+                Some(level) => level,
+                None => break,
+            };
+            if !Self::resting_accepts(opposite_side.side, level.price, mid) {
+                break;
+            }
+
+            // Work the level front to back in place; a partially filled maker
+            // stays at the front and keeps its time priority
+            while let Some(maker_order) = level.orders.front() {
+                if maker_order.get_remaining_quantity() == Quantity::ZERO {
+                    level.orders.remove(maker_order.id);
+                    continue;
+                }
+
+                let trade_quantity = incoming_order
+                    .get_remaining_quantity()
+                    .min(maker_order.get_executable_quantity());
+                if trade_quantity == Quantity::ZERO
+                    || !maker_order.try_fill(trade_quantity)
+                    || !incoming_order.try_fill(trade_quantity)
+                {
+                    // Nothing more trades here; the maker keeps its place
+                    break;
+                }
+
+                trades.push(Trade::new(
+                    (*incoming_order.instrument).clone(),
+                    maker_order.id,
+                    incoming_order.id,
+                    mid,
+                    trade_quantity,
+                    incoming_order.side,
+                ));
+                level.record_fill(&maker_order, trade_quantity);
+
+                let slice_exhausted =
+                    maker_order.is_iceberg() && maker_order.consume_display(trade_quantity);
+                if maker_order.get_remaining_quantity() == Quantity::ZERO {
+                    level.orders.remove(maker_order.id);
+                } else if slice_exhausted {
+                    // Iceberg reloads a fresh slice and loses time priority
+                    maker_order.refresh_display();
+                    level.orders.remove(maker_order.id);
+                    level.orders.push(Arc::clone(&maker_order));
+                }
+
+                if incoming_order.get_remaining_quantity() == Quantity::ZERO {
+                    break;
+                }
+            }
+
+            if level.is_empty() {
+                opposite_side.remove_empty_levels();
+            } else {
+                // The level still has orders, so the incoming order is done
+                // (or nothing on the level could trade)
+                break;
+            }
+        }
+
+        trades
+    }
+
+    fn name(&self) -> &str {
+        "Midpoint"
+    }
+
+    fn set_reference_mid(&self, mid: Price) {
+        self.reference_mid.store(mid.raw_value(), Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{MatchingAlgorithmType, OrderBookConfig, OrderType, TimeInForce};
+    use crate::engine::create_from_config;
+    use crate::interfaces::{NoOpEventHandler, OrderEvent};
+
+    fn order(side: Side, price: &str, quantity: i64) -> Arc<Order> {
+        Arc::new(Order::new(
+            "user".to_string(),
+            "BTC-USD".to_string(),
+            side,
+            OrderType::Limit,
+            Some(price.parse().unwrap()),
+            Quantity::from_integer(quantity).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ))
+    }
+
+    #[test]
+    fn test_trades_print_at_mid() {
+        let algo = MidpointMatch::new();
+        algo.set_reference_mid("100.5".parse().unwrap());
+
+        let side = OrderBookSide::new(Side::Sell);
+        let cheap = order(Side::Sell, "100", 1);
+        let at_mid = order(Side::Sell, "100.5", 1);
+        side.add_order(Arc::clone(&cheap));
+        side.add_order(Arc::clone(&at_mid));
+        side.add_order(order(Side::Sell, "101", 1));
+
+        let trades = algo.match_order(order(Side::Buy, "102", 3), &side);
+
+        // The ask above the mid is not eligible even though the buy limit covers it
+        assert_eq!(trades.len(), 2);
+        assert_eq!(trades[0].maker_order_id, cheap.id);
+        assert_eq!(trades[1].maker_order_id, at_mid.id);
+        for trade in &trades {
+            assert_eq!(trade.price, "100.5".parse().unwrap());
+        }
+        assert_eq!(side.best_price(), Some("101".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_partial_fill_keeps_queue_position() {
+        let algo = MidpointMatch::new();
+        algo.set_reference_mid("100".parse().unwrap());
+
+        let side = OrderBookSide::new(Side::Sell);
+        let first = order(Side::Sell, "100", 5);
+        let second = order(Side::Sell, "100", 5);
+        side.add_order(Arc::clone(&first));
+        side.add_order(Arc::clone(&second));
+
+        algo.match_order(order(Side::Buy, "100", 2), &side);
+        let level = side.best_level().unwrap();
+        let queued: Vec<_> = level.snapshot_orders().iter().map(|o| o.id).collect();
+        assert_eq!(queued, vec![first.id, second.id]);
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(8).unwrap()
+        );
+
+        // The partially filled maker is still first in line
+        let trades = algo.match_order(order(Side::Buy, "100", 4), &side);
+        assert_eq!(trades[0].maker_order_id, first.id);
+        assert_eq!(trades[0].quantity, Quantity::from_integer(3).unwrap());
+        assert_eq!(trades[1].maker_order_id, second.id);
+        assert_eq!(level.snapshot_orders().len(), 1);
+    }
+
+    #[test]
+    fn test_limits_bound_eligibility() {
+        let algo = MidpointMatch::new();
+        let side = OrderBookSide::new(Side::Buy);
+        side.add_order(order(Side::Buy, "101", 2));
+
+        // No reference mid yet
+        assert!(algo
+            .match_order(order(Side::Sell, "99", 1), &side)
+            .is_empty());
+
+        algo.set_reference_mid("100".parse().unwrap());
+
+        // A sell limit above the mid will not accept it
+        assert!(algo
+            .match_order(order(Side::Sell, "100.01", 1), &side)
+            .is_empty());
+
+        // A limit exactly at the mid is accepted
+        let trades = algo.match_order(order(Side::Sell, "100", 1), &side);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].price, "100".parse().unwrap());
+
+        // A resting bid below the mid is not eligible
+        algo.set_reference_mid("102".parse().unwrap());
+        assert!(algo
+            .match_order(order(Side::Sell, "90", 1), &side)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_midpoint_engine_from_config() {
+        let mut config = OrderBookConfig::dark_pool("BTC-USD".to_string());
+        config.matching_algorithm = MatchingAlgorithmType::MidpointMatch;
+        let engine = create_from_config(config, Arc::new(NoOpEventHandler)).unwrap();
+        engine.set_reference_mid("100.25".parse().unwrap());
+
+        engine.submit_order(order(Side::Sell, "99", 1));
+        let events = engine.submit_order(order(Side::Buy, "101", 1));
+
+        let trade = events.iter().find_map(|e| match e {
+            OrderEvent::OrderMatched { trade, .. } => Some(trade),
+            _ => None,
+        });
+        assert_eq!(trade.unwrap().price, "100.25".parse().unwrap());
+    }
+}
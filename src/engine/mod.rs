@@ -3,19 +3,42 @@
 // Contains the core matching engine business logic
 // ============================================================================
 
+mod allocation;
+mod auction;
+mod client_order_ids;
+mod clock;
+mod last_look;
 mod lmm_priority;
 mod matching_engine;
+mod midpoint;
+mod position;
 mod price_time;
 mod pro_rata;
 mod pro_rata_tob_fifo;
+mod rate_limit;
+mod reaper;
+mod simulation;
+mod statistics;
 mod threshold_pro_rata;
+mod venue;
+mod weighted_pro_rata;
 
 pub mod factory;
 
+pub use allocation::{ProRataRounding, ShareRounding};
+pub use auction::AuctionResult;
+pub use clock::{Clock, MockClock, SystemClock};
 pub use factory::{create_from_config, MatchingEngineBuilder};
+pub use last_look::LastLook;
 pub use lmm_priority::LmmPriority;
 pub use matching_engine::MatchingEngine;
+pub use midpoint::MidpointMatch;
+pub use position::{Position, PositionBook};
 pub use price_time::PriceTimePriority;
 pub use pro_rata::ProRata;
 pub use pro_rata_tob_fifo::ProRataTobFifo;
+pub use reaper::ReaperHandle;
+pub use statistics::EngineStats;
 pub use threshold_pro_rata::ThresholdProRata;
+pub use venue::MatchingVenue;
+pub use weighted_pro_rata::WeightedProRata;
@@ -0,0 +1,191 @@
+// ============================================================================
+// Position Tracking
+// Per-user net position and realized P&L under average-cost accounting
+// ============================================================================
+
+use crate::domain::Side;
+use crate::numeric::{Price, Quantity, PRICE_DECIMALS};
+use parking_lot::RwLock;
+use rust_decimal::Decimal;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// One user's position built from fills
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Position {
+    /// Net quantity (long positive, short negative)
+    pub net: Quantity,
+    /// Average cost of the open position (None when flat)
+    pub avg_entry_price: Option<Price>,
+    /// Profit locked in by reducing or closing the position, in quote currency
+    pub realized_pnl: Decimal,
+}
+
+impl Position {
+    /// Fold one fill into the position
+    ///
+    /// Fills that open or grow the position move the average entry price;
+    /// fills against it realize `(price - avg) * closed` for a long (the
+    /// reverse for a short) and leave the average alone. A fill that flips
+    /// the position opens the new side at the fill price.
+    pub fn apply(&mut self, side: Side, price: Price, quantity: Quantity) {
+        let signed = match side {
+            Side::Buy => quantity,
+            Side::Sell => -quantity,
+        };
+        let growing =
+            self.net == Quantity::ZERO || (self.net.is_positive() == signed.is_positive());
+
+        if growing {
+            let held = self.net.to_decimal().abs();
+            let avg = self
+                .avg_entry_price
+                .map_or(Decimal::ZERO, Price::to_decimal);
+            let added = quantity.to_decimal();
+            self.net = self.net.saturating_add(signed);
+            if held + added > Decimal::ZERO {
+                let cost = held * avg + added * price.to_decimal();
+                self.avg_entry_price = to_price(cost / (held + added));
+            }
+            return;
+        }
+
+        let closed = self.net.abs().unwrap_or(Quantity::MAX).min(quantity);
+        if let Some(avg) = self.avg_entry_price {
+            let per_unit = price.to_decimal() - avg.to_decimal();
+            let per_unit = if self.net.is_positive() {
+                per_unit
+            } else {
+                -per_unit
+            };
+            self.realized_pnl += per_unit * closed.to_decimal();
+        }
+
+        let was_long = self.net.is_positive();
+        self.net = self.net.saturating_add(signed);
+        if self.net == Quantity::ZERO {
+            self.avg_entry_price = None;
+        } else if self.net.is_positive() != was_long {
+            self.avg_entry_price = Some(price);
+        }
+    }
+}
+
+/// Average prices carry more digits than a `Price`; round to the nearest
+fn to_price(value: Decimal) -> Option<Price> {
+    Price::from_decimal(value.round_dp(PRICE_DECIMALS as u32)).ok()
+}
+
+/// User id shared with the orders, so recording a fill never allocates;
+/// hashes like the `str` it holds and is looked up by one
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct UserKey(Arc<String>);
+
+impl Borrow<str> for UserKey {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Positions of every user that has traded on an engine
+#[derive(Debug, Default)]
+pub struct PositionBook {
+    positions: RwLock<HashMap<UserKey, Position>>,
+}
+
+impl PositionBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update `user_id`'s position with a fill on `side`
+    pub fn apply_fill(&self, user_id: &Arc<String>, side: Side, price: Price, quantity: Quantity) {
+        let mut positions = self.positions.write();
+        match positions.get_mut(user_id.as_str()) {
+            Some(position) => position.apply(side, price, quantity),
+            None => positions
+                .entry(UserKey(Arc::clone(user_id)))
+                .or_default()
+                .apply(side, price, quantity),
+        }
+    }
+
+    /// Full position of `user_id` (flat if they have never traded)
+    pub fn get(&self, user_id: &str) -> Position {
+        self.positions
+            .read()
+            .get(user_id)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Net quantity held by `user_id` (long positive)
+    pub fn net_position(&self, user_id: &str) -> Quantity {
+        self.positions
+            .read()
+            .get(user_id)
+            .map_or(Quantity::ZERO, |position| position.net)
+    }
+
+    /// Average entry price of `user_id`'s open position
+    pub fn avg_entry_price(&self, user_id: &str) -> Option<Price> {
+        self.positions
+            .read()
+            .get(user_id)
+            .and_then(|position| position.avg_entry_price)
+    }
+
+    /// Profit `user_id` has realized so far
+    pub fn realized_pnl(&self, user_id: &str) -> Decimal {
+        self.positions
+            .read()
+            .get(user_id)
+            .map_or(Decimal::ZERO, |position| position.realized_pnl)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn px(value: i64) -> Price {
+        Price::from_integer(value).unwrap()
+    }
+
+    fn qty(value: i64) -> Quantity {
+        Quantity::from_integer(value).unwrap()
+    }
+
+    #[test]
+    fn test_average_cost_across_adds_and_partial_close() {
+        let book = PositionBook::new();
+        let alice = Arc::new("alice".to_string());
+        book.apply_fill(&alice, Side::Buy, px(100), qty(2));
+        book.apply_fill(&alice, Side::Buy, px(110), qty(2));
+        assert_eq!(book.avg_entry_price("alice"), Some(px(105)));
+
+        // Selling half realizes (120 - 105) * 2 and keeps the average
+        book.apply_fill(&alice, Side::Sell, px(120), qty(2));
+        assert_eq!(book.net_position("alice"), qty(2));
+        assert_eq!(book.avg_entry_price("alice"), Some(px(105)));
+        assert_eq!(book.realized_pnl("alice"), Decimal::from(30));
+    }
+
+    #[test]
+    fn test_short_flip_opens_at_fill_price() {
+        let mut position = Position::default();
+        position.apply(Side::Sell, px(50), qty(3));
+        assert_eq!(position.net, qty(-3));
+
+        // Buying 5 covers the short at a 2 per unit loss and goes long 2 at 52
+        position.apply(Side::Buy, px(52), qty(5));
+        assert_eq!(position.net, qty(2));
+        assert_eq!(position.avg_entry_price, Some(px(52)));
+        assert_eq!(position.realized_pnl, Decimal::from(-6));
+    }
+}
@@ -3,11 +3,18 @@
 // Most common in traditional exchanges (NASDAQ, NYSE, etc.)
 // ============================================================================
 
-use crate::domain::{Order, OrderBookSide, Trade};
+use crate::domain::{Order, OrderBookSide, Side, Trade};
 use crate::interfaces::MatchingAlgorithm;
-use crate::numeric::Quantity;
+use crate::numeric::{Price, Quantity};
+use crate::platform::simd::{create_simd_matcher, SimdMatcher};
 use std::sync::Arc;
 
+/// Number of top-of-book levels inspected by the SIMD pre-check
+const PRECHECK_LEVELS: usize = 8;
+
+/// Default book depth below which the SIMD pre-check is skipped
+const DEFAULT_SIMD_MIN_LEVELS: usize = 16;
+
 /// Price/Time Priority (FIFO) matching algorithm
 ///
 /// Orders at the same price level are matched in time priority order.
@@ -21,25 +28,91 @@ use std::sync::Arc;
 /// Incoming: Buy 1.5 BTC @ 50000
 /// Result: Match 1.0 with A, then 0.5 with B
 /// ```
+///
+/// Iceberg orders only trade their display slice. Once the slice is
+/// exhausted the order is re-queued at the back of its level with a fresh
+/// slice, losing time priority to orders that arrived in the meantime.
 pub struct PriceTimePriority {
     use_simd: bool,
+    /// Vectorized crossing check, present when `use_simd` is set
+    simd_matcher: Option<Arc<dyn SimdMatcher>>,
+    /// Opposite sides with fewer levels than this take the scalar path
+    simd_min_levels: usize,
 }
 
 impl PriceTimePriority {
     pub fn new(use_simd: bool) -> Self {
-        Self { use_simd }
+        Self {
+            use_simd,
+            simd_matcher: use_simd.then(create_simd_matcher),
+            simd_min_levels: DEFAULT_SIMD_MIN_LEVELS,
+        }
+    }
+
+    /// Only run the SIMD pre-check when the opposite side has at least
+    /// `levels` price levels
+    ///
+    /// On small books gathering prices and dispatching costs more than just
+    /// looking at the best level. `0` always runs the pre-check.
+    pub fn with_simd_min_levels(mut self, levels: usize) -> Self {
+        self.simd_min_levels = levels;
+        self
+    }
+
+    /// Whether any of the top levels of `opposite_side` can cross the order
+    ///
+    /// Counts crossing prices over a stack buffer so the early exit does not
+    /// allocate.
+    fn any_crossing(
+        &self,
+        matcher: &dyn SimdMatcher,
+        incoming_order: &Order,
+        opposite_side: &OrderBookSide,
+    ) -> bool {
+        // Market orders cross every level, whatever price they carry
+        if incoming_order.is_market_order() {
+            return true;
+        }
+
+        let mut prices = [0i64; PRECHECK_LEVELS];
+        let len = opposite_side.best_prices_raw(&mut prices);
+        let prices = &prices[..len];
+
+        // On an inverted book a buy crosses the way a sell does on a normal one
+        let buying = (incoming_order.side == Side::Buy) != opposite_side.inverted;
+        let crossing = if buying {
+            let price = incoming_order.price().unwrap_or(Price::MAX);
+            matcher.count_crossing_buy_prices(price.raw_value(), prices)
+        } else {
+            let price = incoming_order.price().unwrap_or(Price::MIN);
+            matcher.count_crossing_sell_prices(price.raw_value(), prices)
+        };
+        crossing > 0
     }
 }
 
 impl MatchingAlgorithm for PriceTimePriority {
     fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
         let mut trades = Vec::new();
+        self.match_order_into(incoming_order, opposite_side, &mut trades);
+        trades
+    }
 
-        // TODO: Integrate SIMD into matching algorithm
-        // The SIMD infrastructure is complete (SimdMatcher trait, i64-based operations).
-        // Integration into this iterative matching loop is pending - currently processes
-        // one level at a time which doesn't benefit from SIMD vectorization.
-        let _ = self.use_simd;
+    fn match_order_into(
+        &self,
+        incoming_order: Arc<Order>,
+        opposite_side: &OrderBookSide,
+        trades: &mut Vec<Trade>,
+    ) {
+        // SIMD early exit: nothing to do if no top-of-book price crosses.
+        // The matching loop itself still walks one level at a time.
+        if let Some(matcher) = &self.simd_matcher {
+            if opposite_side.level_count() >= self.simd_min_levels
+                && !self.any_crossing(matcher.as_ref(), &incoming_order, opposite_side)
+            {
+                return;
+            }
+        }
 
         // Match orders in FIFO order
         while incoming_order.get_remaining_quantity() > Quantity::ZERO {
@@ -50,16 +123,19 @@ impl MatchingAlgorithm for PriceTimePriority {
             };
 
             // Check if prices cross
-            if !self.prices_cross(&incoming_order, best_level.price) {
+            if !self.crosses_level(&incoming_order, opposite_side, best_level.price) {
                 break;
             }
 
-            // Pop orders from the level (FIFO)
-            while let Some(maker_order) = best_level.orders.pop() {
-                let maker_remaining = maker_order.get_remaining_quantity();
+            // Work the level front to back; a partially filled maker stays
+            // at the front and keeps its time priority
+            while let Some(maker_order) = best_level.orders.front() {
+                // Icebergs only expose their current display slice
+                let maker_remaining = maker_order.get_executable_quantity();
                 let taker_remaining = incoming_order.get_remaining_quantity();
 
-                if maker_remaining == Quantity::ZERO {
+                if maker_order.get_remaining_quantity() == Quantity::ZERO {
+                    best_level.orders.remove(maker_order.id);
                     continue; // Skip already filled orders
                 }
 
@@ -72,22 +148,35 @@ impl MatchingAlgorithm for PriceTimePriority {
                         (*incoming_order.instrument).clone(),
                         maker_order.id,
                         incoming_order.id,
-                        maker_order.price.unwrap(),
+                        maker_order.price().unwrap(),
                         trade_quantity,
+                        incoming_order.side,
                     );
 
                     // Update level quantity
-                    best_level.subtract_quantity(trade_quantity);
+                    best_level.record_fill(&maker_order, trade_quantity);
 
                     trades.push(trade);
 
-                    // If maker still has quantity, put it back
+                    let slice_exhausted =
+                        maker_order.is_iceberg() && maker_order.consume_display(trade_quantity);
+
+                    // If maker still has quantity, it stays on the level
                     if maker_order.get_remaining_quantity() > Quantity::ZERO {
-                        best_level.orders.push(Arc::clone(&maker_order));
+                        if slice_exhausted {
+                            // Iceberg reloads a fresh slice and loses time priority
+                            maker_order.refresh_display();
+                            best_level.orders.remove(maker_order.id);
+                            best_level.orders.push(Arc::clone(&maker_order));
+                            continue;
+                        }
                         break; // Process next incoming order
                     }
                 }
 
+                // Filled, or could not be filled: the maker leaves the queue
+                best_level.orders.remove(maker_order.id);
+
                 if incoming_order.get_remaining_quantity() == Quantity::ZERO {
                     break;
                 }
@@ -102,8 +191,6 @@ impl MatchingAlgorithm for PriceTimePriority {
                 break;
             }
         }
-
-        trades
     }
 
     fn name(&self) -> &str {
@@ -168,6 +255,54 @@ mod tests {
         assert_eq!(trades[0].maker_order_id, sell1.id);
     }
 
+    #[test]
+    fn test_match_order_into_matches_allocating_version() {
+        let algo = PriceTimePriority::new(false);
+        let order = |side, price, quantity| {
+            Order::new(
+                "user".to_string(),
+                "BTC-USD".to_string(),
+                side,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(quantity).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )
+        };
+        let makers = [
+            order(Side::Sell, 101, 2),
+            order(Side::Sell, 100, 1),
+            order(Side::Sell, 100, 3),
+            order(Side::Sell, 102, 5),
+        ];
+        let taker = order(Side::Buy, 101, 5);
+
+        // Identical books and takers for each version
+        let book = || {
+            let side = OrderBookSide::new(Side::Sell);
+            for maker in &makers {
+                side.add_order(Arc::new(maker.clone()));
+            }
+            side
+        };
+        let key = |trade: &Trade| (trade.maker_order_id, trade.price, trade.quantity);
+
+        let allocating = algo.match_order(Arc::new(taker.clone()), &book());
+
+        // Appends after whatever the buffer already holds
+        let mut out = vec![allocating[0].clone()];
+        let side = book();
+        algo.match_order_into(Arc::new(taker.clone()), &side, &mut out);
+
+        assert_eq!(allocating.len(), 3);
+        assert_eq!(out.len(), 1 + allocating.len());
+        assert_eq!(
+            out[1..].iter().map(key).collect::<Vec<_>>(),
+            allocating.iter().map(key).collect::<Vec<_>>()
+        );
+        assert_eq!(side.total_quantity(), Quantity::from_integer(6).unwrap());
+    }
+
     #[test]
     fn test_price_time_partial_fill() {
         let algo = PriceTimePriority::new(false);
@@ -205,4 +340,278 @@ mod tests {
             Quantity::from_integer(1).unwrap()
         );
     }
+
+    #[test]
+    fn test_partially_filled_maker_keeps_priority() {
+        let algo = PriceTimePriority::new(false);
+        let side = OrderBookSide::new(Side::Sell);
+        let price = Price::from_integer(50000).unwrap();
+
+        let makers: Vec<_> = ["first", "second"]
+            .iter()
+            .map(|user| {
+                let sell = Arc::new(Order::new(
+                    user.to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(price),
+                    Quantity::from_integer(5).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ));
+                side.add_order(Arc::clone(&sell));
+                sell
+            })
+            .collect();
+
+        // Two small takers both hit the front order
+        for _ in 0..2 {
+            let buy = Arc::new(Order::new(
+                "buyer".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(price),
+                Quantity::from_integer(2).unwrap(),
+                TimeInForce::ImmediateOrCancel,
+            ));
+            let trades = algo.match_order(buy, &side);
+            assert_eq!(trades.len(), 1);
+            assert_eq!(trades[0].maker_order_id, makers[0].id);
+        }
+        assert_eq!(makers[1].get_filled_quantity(), Quantity::ZERO);
+
+        let level = side.best_level().unwrap();
+        assert_eq!(level.orders.front().unwrap().id, makers[0].id);
+        assert_eq!(level.order_count(), 2);
+        assert_eq!(
+            level.get_total_quantity(),
+            Quantity::from_integer(6).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_iceberg_worked_in_display_slices() {
+        let algo = PriceTimePriority::new(false);
+        let side = OrderBookSide::new(Side::Sell);
+        let price = Price::from_integer(50000).unwrap();
+        let two = Quantity::from_integer(2).unwrap();
+
+        // Iceberg: 10 total, 2 displayed, first in queue
+        let iceberg = Arc::new(Order::new_iceberg(
+            "iceberg".to_string(),
+            "BTC-USD".to_string(),
+            Side::Sell,
+            OrderType::Limit,
+            Some(price),
+            Quantity::from_integer(10).unwrap(),
+            two,
+            TimeInForce::GoodTillCancel,
+        ));
+        side.add_order(iceberg.clone());
+
+        let mut others = Vec::new();
+        for user in ["user1", "user2"] {
+            let sell = Arc::new(Order::new(
+                user.to_string(),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(price),
+                two,
+                TimeInForce::GoodTillCancel,
+            ));
+            side.add_order(sell.clone());
+            others.push(sell);
+        }
+
+        let buy = |qty: i64| {
+            Arc::new(Order::new(
+                "buyer".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(price),
+                Quantity::from_integer(qty).unwrap(),
+                TimeInForce::ImmediateOrCancel,
+            ))
+        };
+
+        // Iceberg slice trades first, then it goes behind the other two orders
+        let trades = algo.match_order(buy(6), &side);
+        let makers: Vec<_> = trades.iter().map(|t| t.maker_order_id).collect();
+        assert_eq!(makers, vec![iceberg.id, others[0].id, others[1].id]);
+        assert!(trades.iter().all(|t| t.quantity == two));
+        assert!(iceberg.take_display_refreshed());
+
+        // Only the iceberg is left: it is worked down slice by slice
+        let trades = algo.match_order(buy(8), &side);
+        assert_eq!(trades.len(), 4);
+        assert!(trades
+            .iter()
+            .all(|t| t.maker_order_id == iceberg.id && t.quantity == two));
+        assert_eq!(iceberg.get_remaining_quantity(), Quantity::ZERO);
+        assert!(side.best_level().is_none());
+    }
+
+    #[test]
+    fn test_simd_precheck() {
+        let algo = PriceTimePriority::new(true).with_simd_min_levels(0);
+        let side = OrderBookSide::new(Side::Sell);
+        for price in [50100, 50200] {
+            side.add_order(Arc::new(Order::new(
+                "maker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(1).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+
+        let buy = |price: i64| {
+            Arc::new(Order::new(
+                "taker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(price).unwrap()),
+                Quantity::from_integer(2).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+
+        // Below every ask: the pre-check exits without trading
+        assert!(algo.match_order(buy(50000), &side).is_empty());
+        assert_eq!(side.best_price(), Some(Price::from_integer(50100).unwrap()));
+
+        // Crossing both levels matches as usual
+        let trades = algo.match_order(buy(50200), &side);
+        assert_eq!(trades.len(), 2);
+        assert_eq!(side.best_price(), None);
+    }
+
+    #[test]
+    fn test_simd_precheck_passes_market_orders() {
+        // A market sell must reach a bid below zero
+        let algo = PriceTimePriority::new(true).with_simd_min_levels(0);
+        let side = OrderBookSide::new(Side::Buy);
+        side.add_order(Arc::new(Order::new(
+            "maker".to_string(),
+            "SPREAD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(-Price::from_integer(5).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        )));
+
+        let sell = Arc::new(Order::new(
+            "taker".to_string(),
+            "SPREAD".to_string(),
+            Side::Sell,
+            OrderType::Market,
+            None,
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::ImmediateOrCancel,
+        ));
+        let trades = algo.match_order(sell, &side);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].price, -Price::from_integer(5).unwrap());
+    }
+
+    #[test]
+    fn test_simd_and_scalar_trades_match() {
+        // (maker index, price, quantity) for every trade against the same book
+        let run = |use_simd: bool| -> Vec<(usize, Price, Quantity)> {
+            let algo = PriceTimePriority::new(use_simd);
+            let side = OrderBookSide::new(Side::Sell);
+            let makers: Vec<_> = [(50300, 2), (50100, 1), (50200, 3), (50100, 2), (50500, 4)]
+                .iter()
+                .map(|&(price, qty)| {
+                    let order = Arc::new(Order::new(
+                        "maker".to_string(),
+                        "BTC-USD".to_string(),
+                        Side::Sell,
+                        OrderType::Limit,
+                        Some(Price::from_integer(price).unwrap()),
+                        Quantity::from_integer(qty).unwrap(),
+                        TimeInForce::GoodTillCancel,
+                    ));
+                    side.add_order(Arc::clone(&order));
+                    order
+                })
+                .collect();
+
+            let mut fills = Vec::new();
+            for (price, qty) in [(50000, 1), (50150, 2), (50250, 4), (50600, 5)] {
+                let taker = Arc::new(Order::new(
+                    "taker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(qty).unwrap(),
+                    TimeInForce::ImmediateOrCancel,
+                ));
+                for trade in algo.match_order(taker, &side) {
+                    let maker = makers
+                        .iter()
+                        .position(|m| m.id == trade.maker_order_id)
+                        .unwrap();
+                    fills.push((maker, trade.price, trade.quantity));
+                }
+            }
+            fills
+        };
+
+        let scalar = run(false);
+        assert_eq!(scalar.len(), 6);
+        assert_eq!(run(true), scalar);
+    }
+
+    #[test]
+    fn test_simd_threshold_keeps_results() {
+        // Books of 3 and 4 levels against a threshold of 4: one takes the
+        // scalar path, the other runs the pre-check
+        let run = |algo: PriceTimePriority, levels: i64| -> Vec<(Price, Quantity)> {
+            let side = OrderBookSide::new(Side::Sell);
+            for level in 0..levels {
+                side.add_order(Arc::new(Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(Price::from_integer(50100 + level * 100).unwrap()),
+                    Quantity::from_integer(1).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )));
+            }
+
+            let mut fills = Vec::new();
+            for price in [50000, 50150, 50350] {
+                let taker = Arc::new(Order::new(
+                    "taker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Buy,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(2).unwrap(),
+                    TimeInForce::ImmediateOrCancel,
+                ));
+                for trade in algo.match_order(taker, &side) {
+                    fills.push((trade.price, trade.quantity));
+                }
+            }
+            fills
+        };
+
+        for levels in [3, 4] {
+            let scalar = run(PriceTimePriority::new(false), levels);
+            assert_eq!(scalar.len(), 3);
+            let threshold = PriceTimePriority::new(true).with_simd_min_levels(4);
+            assert_eq!(run(threshold, levels), scalar);
+        }
+    }
 }
@@ -3,6 +3,7 @@
 // Used in derivatives exchanges (CME, Eurex)
 // ============================================================================
 
+use super::allocation::{match_by_allocation, natural_first, ProRataRounding, ShareRounding};
 use crate::domain::{Order, OrderBookLevel, OrderBookSide, OrderId, Trade};
 use crate::interfaces::MatchingAlgorithm;
 use crate::numeric::Quantity;
@@ -29,8 +30,11 @@ use std::sync::Arc;
 pub struct ProRata {
     /// Minimum order size to participate in pro-rata allocation
     pub minimum_quantity: Quantity,
-    /// Whether to give FIFO priority to the top order
+    /// Whether the oldest order at each level is filled in full before the
+    /// rest is split pro-rata
     pub top_of_book_fifo: bool,
+    /// Rounding of the pro-rata shares
+    pub rounding: ShareRounding,
 }
 
 impl ProRata {
@@ -38,6 +42,7 @@ impl ProRata {
         Self {
             minimum_quantity,
             top_of_book_fifo,
+            rounding: ShareRounding::default(),
         }
     }
 
@@ -46,150 +51,83 @@ impl ProRata {
         &self,
         level: &OrderBookLevel,
         quantity_to_fill: Quantity,
+    ) -> Vec<(OrderId, Quantity)> {
+        // Sort by sequence number rather than queue position: rounding
+        // leftovers then land on the same orders on every run and on replay.
+        let mut orders = level.snapshot_orders();
+        orders.sort_by_key(|order| order.get_sequence_number());
+
+        natural_first(&orders, quantity_to_fill, |orders, quantity| {
+            self.allocate(orders, quantity)
+        })
+    }
+
+    /// Split `quantity_to_fill` across `orders` in proportion to size
+    ///
+    /// With `top_of_book_fifo` the oldest order is filled in full first and
+    /// only the rest is split.
+    fn allocate(
+        &self,
+        mut orders: &[Arc<Order>],
+        mut quantity_to_fill: Quantity,
     ) -> Vec<(OrderId, Quantity)> {
         let mut allocations = Vec::new();
-        let mut eligible_quantity = Quantity::ZERO;
 
-        // Collect eligible orders (above minimum size)
-        let mut eligible_orders = Vec::new();
-        let mut ineligible_orders = Vec::new();
-
-        // Drain orders to inspect them
-        while let Some(order) = level.orders.pop() {
-            let remaining = order.get_remaining_quantity();
-            if remaining >= self.minimum_quantity {
-                eligible_quantity = eligible_quantity + remaining;
-                eligible_orders.push((order.id, remaining, order));
-            } else {
-                // Save ineligible orders to put back later
-                ineligible_orders.push(order);
+        if self.top_of_book_fifo {
+            if let Some((first, rest)) = orders.split_first() {
+                let fifo_allocation = quantity_to_fill.min(first.get_remaining_quantity());
+                allocations.push((first.id, fifo_allocation));
+                quantity_to_fill = quantity_to_fill - fifo_allocation;
+                orders = rest;
+            }
+            if quantity_to_fill <= Quantity::ZERO {
+                return allocations;
             }
         }
 
-        // Put back ineligible orders
-        for order in ineligible_orders {
-            level.orders.push(order);
-        }
+        // Collect eligible orders (above minimum size)
+        let eligible_orders: Vec<(OrderId, Quantity)> = orders
+            .iter()
+            .map(|order| (order.id, order.get_remaining_quantity()))
+            .filter(|(_, remaining)| *remaining >= self.minimum_quantity)
+            .collect();
+        let eligible_quantity: i128 = eligible_orders
+            .iter()
+            .map(|(_, qty)| qty.raw_value() as i128)
+            .sum();
 
-        if eligible_quantity == Quantity::ZERO {
+        if eligible_quantity <= 0 {
             return allocations;
         }
 
         // Calculate pro-rata allocations
         // Pro-rata: allocation = (order_quantity / eligible_quantity) * quantity_to_fill
-        // Using raw values: allocation = (order_qty_raw * qty_to_fill_raw) / eligible_qty_raw
-        let mut total_allocated = Quantity::ZERO;
-        let eligible_raw = eligible_quantity.raw_value();
-        let fill_raw = quantity_to_fill.raw_value();
-
-        for (order_id, order_quantity, order) in eligible_orders.iter() {
-            let order_raw = order_quantity.raw_value();
-            // Calculate (order_quantity * quantity_to_fill) / eligible_quantity
-            // Use i128 to avoid overflow during multiplication
-            let allocation_raw =
-                ((order_raw as i128 * fill_raw as i128) / eligible_raw as i128) as i64;
-            let allocation = Quantity::from_raw(allocation_raw);
-
-            allocations.push((*order_id, allocation));
-            total_allocated = total_allocated + allocation;
-
-            // Put order back for later use
-            level.orders.push(Arc::clone(order));
-        }
+        // Truncation leftovers are distributed according to the remainder policy
+        let sizes: Vec<Quantity> = eligible_orders.iter().map(|(_, qty)| *qty).collect();
+        let shares = self.rounding.split(&sizes, quantity_to_fill);
 
-        // Handle remainder with FIFO (allocate to first order)
-        let remainder = quantity_to_fill - total_allocated;
-        if remainder > Quantity::ZERO && !allocations.is_empty() {
-            allocations[0].1 = allocations[0].1 + remainder;
-        }
+        allocations.extend(
+            eligible_orders
+                .iter()
+                .zip(shares)
+                .map(|((order_id, _), allocation)| (*order_id, allocation)),
+        );
 
         allocations
     }
 }
 
+impl ProRataRounding for ProRata {
+    fn rounding_mut(&mut self) -> &mut ShareRounding {
+        &mut self.rounding
+    }
+}
+
 impl MatchingAlgorithm for ProRata {
     fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
-        let mut trades = Vec::new();
-
-        while incoming_order.get_remaining_quantity() > Quantity::ZERO {
-            let best_level = match opposite_side.best_level() {
-                Some(level) => level,
-                None => break,
-            };
-
-            if !self.prices_cross(&incoming_order, best_level.price) {
-                break;
-            }
-
-            let remaining_to_fill = incoming_order.get_remaining_quantity();
-
-            // Calculate pro-rata allocation
-            let allocations = self.calculate_allocation(&best_level, remaining_to_fill);
-
-            if allocations.is_empty() {
-                break;
-            }
-
-            // Execute allocations
-            for (order_id, allocated_qty) in allocations {
-                if allocated_qty <= Quantity::ZERO {
-                    continue;
-                }
-
-                // Find the order in the level
-                let mut found_order: Option<Arc<Order>> = None;
-                while let Some(order) = best_level.orders.pop() {
-                    if order.id == order_id {
-                        found_order = Some(order);
-                        break;
-                    } else {
-                        // Put back other orders
-                        best_level.orders.push(order);
-                    }
-                }
-
-                if let Some(maker_order) = found_order {
-                    let trade_quantity = allocated_qty.min(maker_order.get_remaining_quantity());
-
-                    if trade_quantity > Quantity::ZERO
-                        && maker_order.try_fill(trade_quantity)
-                        && incoming_order.try_fill(trade_quantity)
-                    {
-                        let trade = Trade::new(
-                            (*incoming_order.instrument).clone(),
-                            maker_order.id,
-                            incoming_order.id,
-                            maker_order.price.unwrap(),
-                            trade_quantity,
-                        );
-
-                        best_level.subtract_quantity(trade_quantity);
-                        trades.push(trade);
-
-                        // Put maker order back if not fully filled
-                        if maker_order.get_remaining_quantity() > Quantity::ZERO {
-                            best_level.orders.push(maker_order);
-                        }
-                    }
-                }
-
-                if incoming_order.get_remaining_quantity() == Quantity::ZERO {
-                    break;
-                }
-            }
-
-            // Clean up empty levels
-            if best_level.is_empty() {
-                opposite_side.remove_empty_levels();
-            }
-
-            // Prevent infinite loop
-            if incoming_order.get_remaining_quantity() == remaining_to_fill {
-                break;
-            }
-        }
-
-        trades
+        match_by_allocation(self, &incoming_order, opposite_side, |level, quantity| {
+            self.calculate_allocation(level, quantity)
+        })
     }
 
     fn name(&self) -> &str {
@@ -200,7 +138,7 @@ impl MatchingAlgorithm for ProRata {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{OrderType, Side, TimeInForce};
+    use crate::domain::{OrderOrigin, OrderType, RemainderPolicy, Side, TimeInForce};
     use crate::numeric::Price;
 
     #[test]
@@ -302,4 +240,344 @@ mod tests {
         assert_eq!(trades.len(), 1);
         assert_eq!(trades[0].maker_order_id, sell_large.id);
     }
+
+    #[test]
+    fn test_remainder_policies() {
+        // Makers of 30, 30 and 50 units (raw) sharing a 10 unit fill get
+        // 2, 2 and 4 after truncation, leaving 2 units over
+        let fills_for = |policy: RemainderPolicy| -> Vec<i64> {
+            let algo = ProRata::new(Quantity::ZERO, false).with_remainder_policy(policy);
+            let side = OrderBookSide::new(Side::Sell);
+            let makers: Vec<_> = [30, 30, 50]
+                .iter()
+                .map(|&size| {
+                    let order = Arc::new(Order::new(
+                        "maker".to_string(),
+                        "BTC-USD".to_string(),
+                        Side::Sell,
+                        OrderType::Limit,
+                        Some(Price::from_integer(50000).unwrap()),
+                        Quantity::from_raw(size),
+                        TimeInForce::GoodTillCancel,
+                    ));
+                    side.add_order(Arc::clone(&order));
+                    order
+                })
+                .collect();
+
+            let buy = Arc::new(Order::new(
+                "taker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(50000).unwrap()),
+                Quantity::from_raw(10),
+                TimeInForce::ImmediateOrCancel,
+            ));
+            algo.match_order(buy, &side);
+
+            makers
+                .iter()
+                .map(|m| m.get_filled_quantity().raw_value())
+                .collect()
+        };
+
+        let first = fills_for(RemainderPolicy::FirstOrder);
+        let largest = fills_for(RemainderPolicy::LargestOrder);
+        let fraction = fills_for(RemainderPolicy::LargestFraction);
+        let round_robin = fills_for(RemainderPolicy::RoundRobin);
+
+        for fills in [&first, &largest, &fraction, &round_robin] {
+            assert_eq!(fills.iter().sum::<i64>(), 10);
+        }
+        assert_eq!(first, vec![4, 2, 4]);
+        assert_eq!(largest, vec![2, 2, 6]);
+        // Fractions are .73, .73, .55: the two equal-sized orders win
+        assert_eq!(fraction, vec![3, 3, 4]);
+        assert_ne!(first, largest);
+    }
+
+    #[test]
+    fn test_min_trade_increment() {
+        // Makers of 3, 3 and 5 lots split a 10.7 lot buy in whole lots:
+        // 2, 2 and 4 after snapping, two lots of rounding over and 0.7 carried
+        let algo = ProRata::new(Quantity::ZERO, false)
+            .with_remainder_policy(RemainderPolicy::LargestFraction)
+            .with_min_trade_increment(Quantity::ONE);
+        let side = OrderBookSide::new(Side::Sell);
+        for size in [3, 3, 5] {
+            side.add_order(Arc::new(Order::new(
+                "maker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(50000).unwrap()),
+                Quantity::from_integer(size).unwrap(),
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+
+        let buy = Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            "10.7".parse::<Quantity>().unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        let trades = algo.match_order(Arc::clone(&buy), &side);
+
+        let quantities: Vec<Quantity> = trades.iter().map(|t| t.quantity).collect();
+        assert_eq!(
+            quantities,
+            [3, 3, 4].map(|lots| Quantity::from_integer(lots).unwrap())
+        );
+        // The sub-lot leftover stays with the taker instead of trading as dust
+        assert_eq!(
+            buy.get_remaining_quantity(),
+            "0.7".parse::<Quantity>().unwrap()
+        );
+        assert_eq!(side.best_level().unwrap().order_count(), 1);
+    }
+
+    #[test]
+    fn test_max_sized_orders_do_not_overflow() {
+        // Twenty makers of Quantity::MAX sum far past i64; a 2000 unit buy
+        // still splits evenly
+        let algo = ProRata::new(Quantity::ZERO, false);
+        let side = OrderBookSide::new(Side::Sell);
+        for _ in 0..20 {
+            side.add_order(Arc::new(Order::new(
+                "maker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Sell,
+                OrderType::Limit,
+                Some(Price::from_integer(50000).unwrap()),
+                Quantity::MAX,
+                TimeInForce::GoodTillCancel,
+            )));
+        }
+
+        let buy = Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(2000).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        let trades = algo.match_order(Arc::clone(&buy), &side);
+
+        assert_eq!(trades.len(), 20);
+        assert!(trades
+            .iter()
+            .all(|t| t.quantity == Quantity::from_integer(100).unwrap()));
+        assert_eq!(buy.get_remaining_quantity(), Quantity::ZERO);
+    }
+
+    #[test]
+    fn test_allocation_follows_sequence_order() {
+        // Makers of 30, 30 and 50 units (raw) with sequence numbers 1..=3
+        // share a 10 unit fill, leaving 2 units of rounding over
+        let allocate = |insertion_order: [usize; 3]| -> Vec<(i64, i64)> {
+            let algo = ProRata::new(Quantity::ZERO, false);
+            let side = OrderBookSide::new(Side::Sell);
+            let makers: Vec<_> = [30, 30, 50]
+                .iter()
+                .zip(1..)
+                .map(|(&size, seq)| {
+                    let order = Arc::new(Order::new(
+                        "maker".to_string(),
+                        "BTC-USD".to_string(),
+                        Side::Sell,
+                        OrderType::Limit,
+                        Some(Price::from_integer(50000).unwrap()),
+                        Quantity::from_raw(size),
+                        TimeInForce::GoodTillCancel,
+                    ));
+                    order.set_sequence_number(seq);
+                    order
+                })
+                .collect();
+            for i in insertion_order {
+                side.add_order(Arc::clone(&makers[i]));
+            }
+
+            let level = side.best_level().unwrap();
+            let mut allocations: Vec<(i64, i64)> = algo
+                .calculate_allocation(&level, Quantity::from_raw(10))
+                .into_iter()
+                .map(|(id, qty)| {
+                    let maker = makers.iter().find(|m| m.id == id).unwrap();
+                    (maker.get_sequence_number(), qty.raw_value())
+                })
+                .collect();
+            allocations.sort();
+            allocations
+        };
+
+        let expected = allocate([0, 1, 2]);
+        // The leftover goes to the oldest order, whatever the queue order
+        assert_eq!(expected, vec![(1, 4), (2, 2), (3, 4)]);
+        for insertion_order in [[2, 1, 0], [1, 2, 0], [2, 0, 1]] {
+            assert_eq!(allocate(insertion_order), expected);
+        }
+    }
+
+    #[test]
+    fn test_top_of_book_fifo_flag() {
+        // Makers of 10, 20 and 70 at one price, oldest first, share a 50 lot
+        let fills = |top_of_book_fifo| -> Vec<Quantity> {
+            let algo = ProRata::new(Quantity::ZERO, top_of_book_fifo);
+            let side = OrderBookSide::new(Side::Sell);
+            let makers: Vec<_> = [10, 20, 70]
+                .iter()
+                .zip(1..)
+                .map(|(&size, seq)| {
+                    let order = Arc::new(Order::new(
+                        "maker".to_string(),
+                        "BTC-USD".to_string(),
+                        Side::Sell,
+                        OrderType::Limit,
+                        Some(Price::from_integer(50000).unwrap()),
+                        Quantity::from_integer(size).unwrap(),
+                        TimeInForce::GoodTillCancel,
+                    ));
+                    order.set_sequence_number(seq);
+                    side.add_order(Arc::clone(&order));
+                    order
+                })
+                .collect();
+
+            let buy = Arc::new(Order::new(
+                "taker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(50000).unwrap()),
+                Quantity::from_integer(50).unwrap(),
+                TimeInForce::GoodTillCancel,
+            ));
+            algo.match_order(buy, &side);
+            makers.iter().map(|m| m.get_filled_quantity()).collect()
+        };
+        let qty = |q| Quantity::from_integer(q).unwrap();
+
+        // Pure pro-rata: 50 split 10:20:70
+        assert_eq!(fills(false), vec![qty(5), qty(10), qty(35)]);
+        // Top order takes its full 10 first, then 40 is split 20:70
+        let fills = fills(true);
+        assert_eq!(fills[0], qty(10));
+        assert_eq!(fills.iter().fold(Quantity::ZERO, |a, b| a + *b), qty(50));
+        assert!(fills[1] > qty(8) && fills[1] < qty(9));
+    }
+
+    #[test]
+    fn test_naturals_fill_before_implied() {
+        let qty = |q| Quantity::from_integer(q).unwrap();
+        let maker = |size, origin| {
+            Arc::new(
+                Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(Price::from_integer(50000).unwrap()),
+                    qty(size),
+                    TimeInForce::GoodTillCancel,
+                )
+                .with_origin(origin),
+            )
+        };
+        let buy = |size| {
+            Arc::new(Order::new(
+                "taker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(Price::from_integer(50000).unwrap()),
+                qty(size),
+                TimeInForce::GoodTillCancel,
+            ))
+        };
+
+        let algo = ProRata::new(Quantity::ZERO, false);
+        let side = OrderBookSide::new(Side::Sell);
+        // The implied order is the oldest and the largest at the level
+        let implied = maker(40, OrderOrigin::Implied);
+        let naturals = [
+            maker(10, OrderOrigin::Natural),
+            maker(20, OrderOrigin::Natural),
+        ];
+        for (order, seq) in [&implied, &naturals[0], &naturals[1]].into_iter().zip(1..) {
+            order.set_sequence_number(seq);
+            side.add_order(Arc::clone(order));
+        }
+
+        // Naturals can absorb 30, so a 15 lot never reaches the implied order
+        let trades = algo.match_order(buy(15), &side);
+        assert!(trades.iter().all(|t| t.maker_order_id != implied.id));
+        assert_eq!(naturals[0].get_remaining_quantity(), qty(5));
+        assert_eq!(naturals[1].get_remaining_quantity(), qty(10));
+
+        // Only what the naturals leave over goes to the implied order
+        let trades = algo.match_order(buy(25), &side);
+        assert_eq!(trades.len(), 3);
+        assert!(naturals
+            .iter()
+            .all(|o| o.get_remaining_quantity() == Quantity::ZERO));
+        assert_eq!(implied.get_remaining_quantity(), qty(30));
+    }
+
+    #[test]
+    fn test_pro_rata_market_order_sweeps_levels() {
+        let algo = ProRata::new(Quantity::ZERO, false);
+        let side = OrderBookSide::new(Side::Sell);
+        let makers: Vec<_> = [(50000, 10), (50000, 20), (50100, 30)]
+            .iter()
+            .map(|&(price, qty)| {
+                let order = Arc::new(Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(Price::from_integer(price).unwrap()),
+                    Quantity::from_integer(qty).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ));
+                side.add_order(Arc::clone(&order));
+                order
+            })
+            .collect();
+
+        let buy = Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Market,
+            None,
+            Quantity::from_integer(40).unwrap(),
+            TimeInForce::ImmediateOrCancel,
+        ));
+        let trades = algo.match_order(Arc::clone(&buy), &side);
+
+        // The best level is taken in full, pro-rata, before the next one
+        assert_eq!(buy.get_remaining_quantity(), Quantity::ZERO);
+        let filled: Vec<Quantity> = makers.iter().map(|m| m.get_filled_quantity()).collect();
+        assert_eq!(
+            filled,
+            vec![
+                Quantity::from_integer(10).unwrap(),
+                Quantity::from_integer(20).unwrap(),
+                Quantity::from_integer(10).unwrap(),
+            ]
+        );
+        assert_eq!(
+            trades.last().unwrap().price,
+            Price::from_integer(50100).unwrap()
+        );
+        assert_eq!(side.best_price(), Some(Price::from_integer(50100).unwrap()));
+    }
 }
@@ -3,6 +3,7 @@
 // Used by Eurex, ICE Futures, and other major derivatives exchanges
 // ============================================================================
 
+use super::allocation::{match_by_allocation, natural_first, ProRataRounding, ShareRounding};
 use crate::domain::{Order, OrderBookLevel, OrderBookSide, OrderId, Trade};
 use crate::interfaces::MatchingAlgorithm;
 use crate::numeric::Quantity;
@@ -40,11 +41,16 @@ use std::sync::Arc;
 pub struct ProRataTobFifo {
     /// Minimum order size to participate in pro-rata allocation
     pub minimum_quantity: Quantity,
+    /// Rounding of the pro-rata shares
+    pub rounding: ShareRounding,
 }
 
 impl ProRataTobFifo {
     pub fn new(minimum_quantity: Quantity) -> Self {
-        Self { minimum_quantity }
+        Self {
+            minimum_quantity,
+            rounding: ShareRounding::default(),
+        }
     }
 
     /// Calculate allocation for a price level:
@@ -55,27 +61,32 @@ impl ProRataTobFifo {
         level: &OrderBookLevel,
         quantity_to_fill: Quantity,
     ) -> Vec<(OrderId, Quantity)> {
-        let mut allocations = Vec::new();
-
-        // Collect all orders from the level
-        let mut all_orders = Vec::new();
-        while let Some(order) = level.orders.pop() {
-            all_orders.push(order);
-        }
-
+        // Orders in queue order
+        let all_orders = level.snapshot_orders();
         if all_orders.is_empty() {
-            return allocations;
+            return Vec::new();
         }
 
-        // Put all orders back first (maintaining order)
-        for order in all_orders.iter().rev() {
-            level.orders.push(Arc::clone(order));
+        natural_first(&all_orders, quantity_to_fill, |orders, quantity| {
+            self.allocate(orders, quantity)
+        })
+    }
+
+    /// FIFO to the first order, pro-rata across the rest
+    fn allocate(
+        &self,
+        orders: &[Arc<Order>],
+        quantity_to_fill: Quantity,
+    ) -> Vec<(OrderId, Quantity)> {
+        let mut allocations = Vec::new();
+        if orders.is_empty() {
+            return allocations;
         }
 
         let mut remaining_to_allocate = quantity_to_fill;
 
         // Step 1: Give FIFO priority to the first order (top of book)
-        let first_order = &all_orders[0];
+        let first_order = &orders[0];
         let first_order_qty = first_order.get_remaining_quantity();
 
         if first_order_qty > Quantity::ZERO {
@@ -85,7 +96,7 @@ impl ProRataTobFifo {
         }
 
         // If everything was allocated to first order, we're done
-        if remaining_to_allocate <= Quantity::ZERO || all_orders.len() == 1 {
+        if remaining_to_allocate <= Quantity::ZERO || orders.len() == 1 {
             return allocations;
         }
 
@@ -93,7 +104,7 @@ impl ProRataTobFifo {
         let mut eligible_quantity = Quantity::ZERO;
         let mut eligible_orders = Vec::new();
 
-        for order in all_orders.iter().skip(1) {
+        for order in orders.iter().skip(1) {
             let remaining = order.get_remaining_quantity();
             if remaining >= self.minimum_quantity {
                 eligible_quantity = eligible_quantity + remaining;
@@ -105,121 +116,29 @@ impl ProRataTobFifo {
             return allocations;
         }
 
-        // Calculate pro-rata allocations
-        let mut total_allocated = Quantity::ZERO;
-        let eligible_raw = eligible_quantity.raw_value();
-        let remaining_raw = remaining_to_allocate.raw_value();
-
-        for (order_id, order_quantity) in eligible_orders.iter() {
-            let order_raw = order_quantity.raw_value();
-            let allocation_raw =
-                ((order_raw as i128 * remaining_raw as i128) / eligible_raw as i128) as i64;
-            let allocation = Quantity::from_raw(allocation_raw);
+        // Calculate pro-rata allocations (leftover per the remainder policy)
+        let sizes: Vec<Quantity> = eligible_orders.iter().map(|(_, qty)| *qty).collect();
+        let shares = self.rounding.split(&sizes, remaining_to_allocate);
 
+        for ((order_id, _), allocation) in eligible_orders.iter().zip(shares) {
             allocations.push((*order_id, allocation));
-            total_allocated = total_allocated + allocation;
-        }
-
-        // Handle remainder - give to first eligible pro-rata order
-        let remainder = remaining_to_allocate - total_allocated;
-        if remainder > Quantity::ZERO && allocations.len() > 1 {
-            // Find first pro-rata allocation (skip the FIFO one at index 0)
-            allocations[1].1 = allocations[1].1 + remainder;
         }
 
         allocations
     }
 }
 
+impl ProRataRounding for ProRataTobFifo {
+    fn rounding_mut(&mut self) -> &mut ShareRounding {
+        &mut self.rounding
+    }
+}
+
 impl MatchingAlgorithm for ProRataTobFifo {
     fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
-        let mut trades = Vec::new();
-
-        while incoming_order.get_remaining_quantity() > Quantity::ZERO {
-            let best_level = match opposite_side.best_level() {
-                Some(level) => level,
-                None => break,
-            };
-
-            if !self.prices_cross(&incoming_order, best_level.price) {
-                break;
-            }
-
-            let remaining_to_fill = incoming_order.get_remaining_quantity();
-
-            // Calculate allocation (FIFO for first, pro-rata for rest)
-            let allocations = self.calculate_allocation(&best_level, remaining_to_fill);
-
-            if allocations.is_empty() {
-                break;
-            }
-
-            // Execute allocations
-            for (order_id, allocated_qty) in allocations {
-                if allocated_qty <= Quantity::ZERO {
-                    continue;
-                }
-
-                // Find the order in the level
-                let mut found_order: Option<Arc<Order>> = None;
-                let mut temp_orders = Vec::new();
-
-                while let Some(order) = best_level.orders.pop() {
-                    if order.id == order_id {
-                        found_order = Some(order);
-                        break;
-                    } else {
-                        temp_orders.push(order);
-                    }
-                }
-
-                // Put back orders we didn't match
-                for order in temp_orders.into_iter().rev() {
-                    best_level.orders.push(order);
-                }
-
-                if let Some(maker_order) = found_order {
-                    let trade_quantity = allocated_qty.min(maker_order.get_remaining_quantity());
-
-                    if trade_quantity > Quantity::ZERO
-                        && maker_order.try_fill(trade_quantity)
-                        && incoming_order.try_fill(trade_quantity)
-                    {
-                        let trade = Trade::new(
-                            (*incoming_order.instrument).clone(),
-                            maker_order.id,
-                            incoming_order.id,
-                            maker_order.price.unwrap(),
-                            trade_quantity,
-                        );
-
-                        best_level.subtract_quantity(trade_quantity);
-                        trades.push(trade);
-
-                        // Put maker order back if not fully filled
-                        if maker_order.get_remaining_quantity() > Quantity::ZERO {
-                            best_level.orders.push(maker_order);
-                        }
-                    }
-                }
-
-                if incoming_order.get_remaining_quantity() == Quantity::ZERO {
-                    break;
-                }
-            }
-
-            // Clean up empty levels
-            if best_level.is_empty() {
-                opposite_side.remove_empty_levels();
-            }
-
-            // Prevent infinite loop
-            if incoming_order.get_remaining_quantity() == remaining_to_fill {
-                break;
-            }
-        }
-
-        trades
+        match_by_allocation(self, &incoming_order, opposite_side, |level, quantity| {
+            self.calculate_allocation(level, quantity)
+        })
     }
 
     fn name(&self) -> &str {
@@ -230,7 +149,7 @@ impl MatchingAlgorithm for ProRataTobFifo {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domain::{OrderType, Side, TimeInForce};
+    use crate::domain::{OrderOrigin, OrderType, Side, TimeInForce};
     use crate::numeric::Price;
 
     #[test]
@@ -363,6 +282,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tob_fifo_residual_keeps_priority() {
+        let algo = ProRataTobFifo::new(Quantity::ZERO);
+        let side = OrderBookSide::new(Side::Sell);
+        let price = Price::from_integer(50000).unwrap();
+
+        let makers: Vec<_> = ["user1", "user2"]
+            .iter()
+            .map(|user| {
+                let sell = Arc::new(Order::new(
+                    user.to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(price),
+                    Quantity::from_integer(100).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ));
+                side.add_order(Arc::clone(&sell));
+                sell
+            })
+            .collect();
+        let buy = |qty: i64| {
+            Arc::new(Order::new(
+                "buyer".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(price),
+                Quantity::from_integer(qty).unwrap(),
+                TimeInForce::ImmediateOrCancel,
+            ))
+        };
+
+        // First match leaves the TOB order with a residual, still at the front
+        let trades = algo.match_order(buy(60), &side);
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0].maker_order_id, makers[0].id);
+        let level = side.best_level().unwrap();
+        assert_eq!(level.orders.front().unwrap().id, makers[0].id);
+
+        // Second match: the residual 40 is still filled FIFO before pro-rata
+        let trades = algo.match_order(buy(70), &side);
+        let fills: Vec<_> = trades
+            .iter()
+            .map(|t| (t.maker_order_id, t.quantity))
+            .collect();
+        assert_eq!(
+            fills,
+            vec![
+                (makers[0].id, Quantity::from_integer(40).unwrap()),
+                (makers[1].id, Quantity::from_integer(30).unwrap()),
+            ]
+        );
+        assert_eq!(makers[0].get_remaining_quantity(), Quantity::ZERO);
+        assert_eq!(level.orders.front().unwrap().id, makers[1].id);
+    }
+
     #[test]
     fn test_tob_fifo_with_minimum_quantity() {
         let algo = ProRataTobFifo::new(Quantity::from_integer(50).unwrap()); // 50 BTC minimum
@@ -439,6 +416,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tob_fifo_implied_top_order_yields_to_naturals() {
+        let algo = ProRataTobFifo::new(Quantity::ZERO);
+        let side = OrderBookSide::new(Side::Sell);
+        let maker = |size, origin| {
+            Arc::new(
+                Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(Price::from_integer(50000).unwrap()),
+                    Quantity::from_integer(size).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                )
+                .with_origin(origin),
+            )
+        };
+
+        // The implied order is first in the queue but loses the FIFO slot
+        let implied = maker(50, OrderOrigin::Implied);
+        let first_natural = maker(10, OrderOrigin::Natural);
+        let second_natural = maker(30, OrderOrigin::Natural);
+        side.add_order(implied.clone());
+        side.add_order(first_natural.clone());
+        side.add_order(second_natural.clone());
+
+        let buy = Arc::new(Order::new(
+            "buyer".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(45).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        let trades = algo.match_order(buy, &side);
+
+        let filled = |order: &Arc<Order>| {
+            trades
+                .iter()
+                .filter(|t| t.maker_order_id == order.id)
+                .fold(Quantity::ZERO, |acc, t| acc + t.quantity)
+        };
+        assert_eq!(filled(&first_natural), Quantity::from_integer(10).unwrap());
+        assert_eq!(filled(&second_natural), Quantity::from_integer(30).unwrap());
+        assert_eq!(filled(&implied), Quantity::from_integer(5).unwrap());
+    }
+
     #[test]
     fn test_tob_fifo_empty_book() {
         let algo = ProRataTobFifo::new(Quantity::ZERO);
@@ -0,0 +1,90 @@
+// ============================================================================
+// Order Rate Limiting
+// Per-user token buckets guarding against quote stuffing
+// ============================================================================
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::collections::HashMap;
+
+/// Tokens available to one user and when they were last topped up
+struct TokenBucket {
+    tokens: f64,
+    refilled_at: DateTime<Utc>,
+}
+
+/// Token-bucket rate limiter keyed by user id
+///
+/// Each user may burst up to `max_orders_per_sec` orders; tokens refill
+/// continuously at the same rate, measured on the caller's clock.
+pub(crate) struct RateLimiter {
+    max_orders_per_sec: u32,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(max_orders_per_sec: u32) -> Self {
+        Self {
+            max_orders_per_sec,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take one token from `user_id`'s bucket, returning false if it is empty
+    pub(crate) fn try_acquire(&self, user_id: &str, now: DateTime<Utc>) -> bool {
+        let capacity = f64::from(self.max_orders_per_sec);
+        let mut buckets = self.buckets.lock();
+        // Only a user's first order allocates a key
+        if !buckets.contains_key(user_id) {
+            buckets.insert(
+                user_id.to_string(),
+                TokenBucket {
+                    tokens: capacity,
+                    refilled_at: now,
+                },
+            );
+        }
+        let Some(bucket) = buckets.get_mut(user_id) else {
+            return false;
+        };
+
+        // A clock set backwards refills nothing
+        let elapsed = (now - bucket.refilled_at).to_std().unwrap_or_default();
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * capacity).min(capacity);
+        bucket.refilled_at = bucket.refilled_at.max(now);
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let limiter = RateLimiter::new(2);
+        let start = Utc::now();
+
+        assert!(limiter.try_acquire("alice", start));
+        assert!(limiter.try_acquire("alice", start));
+        assert!(!limiter.try_acquire("alice", start));
+
+        // Half a second buys back one order at 2/sec
+        let later = start + Duration::milliseconds(500);
+        assert!(limiter.try_acquire("alice", later));
+        assert!(!limiter.try_acquire("alice", later));
+
+        // Idle time never refills beyond the burst size
+        let much_later = start + Duration::seconds(60);
+        assert!(limiter.try_acquire("alice", much_later));
+        assert!(limiter.try_acquire("alice", much_later));
+        assert!(!limiter.try_acquire("alice", much_later));
+    }
+}
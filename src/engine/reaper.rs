@@ -0,0 +1,128 @@
+// ============================================================================
+// Expiry Reaper
+// Background thread that expires GoodTillDate orders and last-look holds on a fixed tick
+// ============================================================================
+
+use super::MatchingEngine;
+use crossbeam::channel::{self, RecvTimeoutError, Sender};
+use std::io;
+use std::sync::Weak;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Handle to a running expiry reaper
+///
+/// Dropping the handle stops the reaper as well; `stop` only makes the
+/// shutdown explicit.
+pub struct ReaperHandle {
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ReaperHandle {
+    /// Spawn a thread calling `expire_stale_orders` and `expire_last_looks`
+    /// every `tick`
+    ///
+    /// The thread holds a weak reference, so it never keeps the engine
+    /// alive; it exits on its own once the engine is dropped. Fails if the
+    /// OS refuses to start the thread.
+    pub(crate) fn spawn(engine: Weak<MatchingEngine>, tick: Duration) -> io::Result<Self> {
+        let (stop, stopped) = channel::bounded::<()>(1);
+        let thread = std::thread::Builder::new()
+            .name("expiry-reaper".to_string())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(tick) {
+                    // Events reach the engine's handler through the expire calls
+                    match engine.upgrade() {
+                        Some(engine) => {
+                            engine.expire_stale_orders(engine.now());
+                            engine.expire_last_looks(engine.now());
+                        },
+                        None => break,
+                    };
+                }
+            })?;
+
+        Ok(Self {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+
+    /// Stop the reaper and wait for its thread to exit
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        // Dropping the sender wakes the thread immediately
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for ReaperHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::{Order, OrderType, Side, TimeInForce};
+    use crate::engine::{MatchingEngine, PriceTimePriority};
+    use crate::interfaces::{EventHandler, OrderEvent};
+    use crate::numeric::{Price, Quantity};
+    use chrono::Utc;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<OrderEvent>>);
+
+    impl EventHandler for Recorder {
+        fn on_event(&self, event: OrderEvent) {
+            self.0.lock().push(event);
+        }
+    }
+
+    #[test]
+    fn test_reaper_expires_gtd_order() {
+        let recorder = Arc::new(Recorder::default());
+        let engine = Arc::new(MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::clone(&recorder) as Arc<dyn EventHandler>,
+        ));
+        let order = Arc::new(Order::new(
+            "user".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(100).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillDate(Utc::now() + chrono::Duration::milliseconds(30)),
+        ));
+        engine.submit_order(Arc::clone(&order));
+
+        let reaper = engine
+            .start_expiry_reaper(Duration::from_millis(5))
+            .unwrap();
+        let expired = |recorder: &Recorder| {
+            recorder.0.lock().iter().any(|event| {
+                matches!(event, OrderEvent::OrderExpired { order_id, .. } if *order_id == order.id)
+            })
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !expired(&recorder) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        reaper.stop();
+
+        assert!(expired(&recorder));
+        assert!(engine.get_order(order.id).is_none());
+    }
+}
@@ -0,0 +1,77 @@
+// ============================================================================
+// Simulation
+// Orders buffered by injected arrival time for reproducible backtests
+// ============================================================================
+
+use crate::domain::Order;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::Arc;
+
+/// An order waiting for its simulated arrival
+struct Arrival {
+    at: DateTime<Utc>,
+    /// Submission order, breaking ties between equal arrival times
+    seq: u64,
+    order: Arc<Order>,
+}
+
+impl PartialEq for Arrival {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Arrival {}
+
+impl PartialOrd for Arrival {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Arrival {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.at, self.seq).cmp(&(other.at, other.seq))
+    }
+}
+
+/// Buffered orders, released earliest arrival first and in submission
+/// order among equal arrivals
+pub(crate) struct ArrivalQueue {
+    /// Min-heap of pending arrivals and the next submission number
+    pending: Mutex<(BinaryHeap<Reverse<Arrival>>, u64)>,
+}
+
+impl ArrivalQueue {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending: Mutex::new((BinaryHeap::new(), 0)),
+        }
+    }
+
+    pub(crate) fn push(&self, order: Arc<Order>, at: DateTime<Utc>) {
+        let mut pending = self.pending.lock();
+        let seq = pending.1;
+        pending.1 += 1;
+        pending.0.push(Reverse(Arrival { at, seq, order }));
+    }
+
+    /// Remove and return the earliest order arriving at or before `until`
+    pub(crate) fn pop_due(&self, until: DateTime<Utc>) -> Option<(DateTime<Utc>, Arc<Order>)> {
+        let mut pending = self.pending.lock();
+        if pending.0.peek()?.0.at > until {
+            return None;
+        }
+        pending
+            .0
+            .pop()
+            .map(|Reverse(arrival)| (arrival.at, arrival.order))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.pending.lock().0.len()
+    }
+}
@@ -0,0 +1,41 @@
+// ============================================================================
+// Engine Statistics
+// Running trade aggregates maintained by the matching engine
+// ============================================================================
+
+use crate::domain::Trade;
+use crate::numeric::{Price, Quantity};
+use rust_decimal::Decimal;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Running totals over every trade since the engine started or was last reset
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EngineStats {
+    /// Number of trades executed
+    pub total_trades: u64,
+    /// Sum of executed quantity (saturates instead of overflowing)
+    pub total_volume: Quantity,
+    /// Sum of price * quantity in quote currency
+    pub total_notional: Decimal,
+    /// Price of the most recent trade
+    pub last_trade_price: Option<Price>,
+    /// Highest trade price
+    pub high: Option<Price>,
+    /// Lowest trade price
+    pub low: Option<Price>,
+}
+
+impl EngineStats {
+    /// Fold one trade into the totals
+    pub fn record(&mut self, trade: &Trade) {
+        self.total_trades += 1;
+        self.total_volume = self.total_volume.saturating_add(trade.quantity);
+        self.total_notional += trade.price.to_decimal() * trade.quantity.to_decimal();
+        self.last_trade_price = Some(trade.price);
+        self.high = Some(self.high.map_or(trade.price, |high| high.max(trade.price)));
+        self.low = Some(self.low.map_or(trade.price, |low| low.min(trade.price)));
+    }
+}
@@ -3,6 +3,7 @@
 // Used by various derivatives exchanges to protect smaller orders
 // ============================================================================
 
+use super::allocation::{match_by_allocation, natural_first, ProRataRounding, ShareRounding};
 use crate::domain::{Order, OrderBookLevel, OrderBookSide, OrderId, Trade};
 use crate::interfaces::MatchingAlgorithm;
 use crate::numeric::Quantity;
@@ -22,6 +23,9 @@ pub struct ThresholdProRata {
 
     /// Minimum order size to participate in pro-rata allocation
     pub minimum_quantity: Quantity,
+
+    /// Rounding of the pro-rata shares
+    pub rounding: ShareRounding,
 }
 
 impl ThresholdProRata {
@@ -29,6 +33,7 @@ impl ThresholdProRata {
         Self {
             threshold,
             minimum_quantity,
+            rounding: ShareRounding::default(),
         }
     }
 
@@ -38,36 +43,41 @@ impl ThresholdProRata {
         level: &OrderBookLevel,
         quantity_to_fill: Quantity,
     ) -> Vec<(OrderId, Quantity)> {
-        let mut allocations = Vec::new();
-
-        // Collect all orders from the level
-        let mut all_orders = Vec::new();
-        while let Some(order) = level.orders.pop() {
-            all_orders.push(order);
-        }
-
+        // Orders in queue order
+        let all_orders = level.snapshot_orders();
         if all_orders.is_empty() {
-            return allocations;
+            return Vec::new();
         }
 
-        // Put all orders back (maintaining order)
-        for order in all_orders.iter().rev() {
-            level.orders.push(Arc::clone(order));
+        natural_first(&all_orders, quantity_to_fill, |orders, quantity| {
+            self.allocate(orders, quantity)
+        })
+    }
+
+    /// FIFO below the threshold, pro-rata at or above it
+    fn allocate(
+        &self,
+        orders: &[Arc<Order>],
+        quantity_to_fill: Quantity,
+    ) -> Vec<(OrderId, Quantity)> {
+        let mut allocations = Vec::new();
+        if orders.is_empty() {
+            return allocations;
         }
 
         let mut remaining_to_allocate = quantity_to_fill;
 
         // Separate orders into categories
-        let mut small_orders = Vec::new();
+        let mut smorders = Vec::new();
         let mut large_orders = Vec::new();
         let mut large_total_quantity = Quantity::ZERO;
 
-        for order in all_orders.iter() {
+        for order in orders.iter() {
             let remaining = order.get_remaining_quantity();
 
             if remaining < self.threshold {
                 // Small orders get FIFO treatment
-                small_orders.push((order.id, remaining));
+                smorders.push((order.id, remaining));
             } else {
                 // Large orders get pro-rata
                 if remaining >= self.minimum_quantity {
@@ -78,7 +88,7 @@ impl ThresholdProRata {
         }
 
         // Step 1: Allocate to small orders in FIFO order
-        for (order_id, order_quantity) in small_orders {
+        for (order_id, order_quantity) in smorders {
             if remaining_to_allocate <= Quantity::ZERO {
                 break;
             }
@@ -90,29 +100,12 @@ impl ThresholdProRata {
 
         // Step 2: Allocate to large orders pro-rata
         if remaining_to_allocate > Quantity::ZERO && large_total_quantity > Quantity::ZERO {
-            let mut prorata_allocated = Quantity::ZERO;
-            let large_total_raw = large_total_quantity.raw_value();
-            let remaining_raw = remaining_to_allocate.raw_value();
-
-            for (order_id, order_quantity) in large_orders.iter() {
-                let order_raw = order_quantity.raw_value();
-                let allocation_raw =
-                    ((order_raw as i128 * remaining_raw as i128) / large_total_raw as i128) as i64;
-                let allocation = Quantity::from_raw(allocation_raw);
+            // Leftover from truncation is distributed per the remainder policy
+            let sizes: Vec<Quantity> = large_orders.iter().map(|(_, qty)| *qty).collect();
+            let shares = self.rounding.split(&sizes, remaining_to_allocate);
 
+            for ((order_id, _), allocation) in large_orders.iter().zip(shares) {
                 allocations.push((*order_id, allocation));
-                prorata_allocated = prorata_allocated + allocation;
-            }
-
-            // Handle remainder - give to first large order
-            let remainder = remaining_to_allocate - prorata_allocated;
-            if remainder > Quantity::ZERO && !large_orders.is_empty() {
-                if let Some(first_large) = allocations
-                    .iter_mut()
-                    .find(|(id, _)| large_orders.iter().any(|(large_id, _)| large_id == id))
-                {
-                    first_large.1 = first_large.1 + remainder;
-                }
             }
         }
 
@@ -120,95 +113,17 @@ impl ThresholdProRata {
     }
 }
 
+impl ProRataRounding for ThresholdProRata {
+    fn rounding_mut(&mut self) -> &mut ShareRounding {
+        &mut self.rounding
+    }
+}
+
 impl MatchingAlgorithm for ThresholdProRata {
     fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
-        let mut trades = Vec::new();
-
-        while incoming_order.get_remaining_quantity() > Quantity::ZERO {
-            let best_level = match opposite_side.best_level() {
-                Some(level) => level,
-                None => break,
-            };
-
-            if !self.prices_cross(&incoming_order, best_level.price) {
-                break;
-            }
-
-            let remaining_to_fill = incoming_order.get_remaining_quantity();
-
-            // Calculate allocation (FIFO for small, pro-rata for large)
-            let allocations = self.calculate_allocation(&best_level, remaining_to_fill);
-
-            if allocations.is_empty() {
-                break;
-            }
-
-            // Execute allocations
-            for (order_id, allocated_qty) in allocations {
-                if allocated_qty <= Quantity::ZERO {
-                    continue;
-                }
-
-                // Find the order in the level
-                let mut found_order: Option<Arc<Order>> = None;
-                let mut temp_orders = Vec::new();
-
-                while let Some(order) = best_level.orders.pop() {
-                    if order.id == order_id {
-                        found_order = Some(order);
-                        break;
-                    } else {
-                        temp_orders.push(order);
-                    }
-                }
-
-                // Put back orders we didn't match
-                for order in temp_orders.into_iter().rev() {
-                    best_level.orders.push(order);
-                }
-
-                if let Some(maker_order) = found_order {
-                    let trade_quantity = allocated_qty.min(maker_order.get_remaining_quantity());
-
-                    if trade_quantity > Quantity::ZERO
-                        && maker_order.try_fill(trade_quantity)
-                        && incoming_order.try_fill(trade_quantity)
-                    {
-                        let trade = Trade::new(
-                            (*incoming_order.instrument).clone(),
-                            maker_order.id,
-                            incoming_order.id,
-                            maker_order.price.unwrap(),
-                            trade_quantity,
-                        );
-
-                        best_level.subtract_quantity(trade_quantity);
-                        trades.push(trade);
-
-                        // Put maker order back if not fully filled
-                        if maker_order.get_remaining_quantity() > Quantity::ZERO {
-                            best_level.orders.push(maker_order);
-                        }
-                    }
-                }
-
-                if incoming_order.get_remaining_quantity() == Quantity::ZERO {
-                    break;
-                }
-            }
-
-            // Clean up empty levels
-            if best_level.is_empty() {
-                opposite_side.remove_empty_levels();
-            }
-
-            // Prevent infinite loop
-            if incoming_order.get_remaining_quantity() == remaining_to_fill {
-                break;
-            }
-        }
-
-        trades
+        match_by_allocation(self, &incoming_order, opposite_side, |level, quantity| {
+            self.calculate_allocation(level, quantity)
+        })
     }
 
     fn name(&self) -> &str {
@@ -0,0 +1,234 @@
+// ============================================================================
+// Matching Venue
+// Routes orders across per-instrument matching engines
+// ============================================================================
+
+use crate::domain::config::OrderBookConfig;
+use crate::domain::order::state::OrderState;
+use crate::domain::{Order, OrderBookSnapshot, OrderId};
+use crate::engine::{create_from_config, Clock, MatchingEngine, SystemClock};
+use crate::interfaces::{EventHandler, OrderEvent, RejectReason};
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use std::sync::Arc;
+
+/// Multi-instrument venue holding one matching engine per instrument
+///
+/// Orders are routed on `order.instrument`. All engines share the venue's
+/// event handler and clock.
+///
+/// # Example
+/// ```
+/// use matching_engine::prelude::*;
+/// use matching_engine::engine::MatchingVenue;
+/// use std::sync::Arc;
+///
+/// let venue = MatchingVenue::new(Arc::new(NoOpEventHandler));
+/// venue
+///     .register_instrument(OrderBookConfig::nasdaq_style("AAPL".to_string()))
+///     .unwrap();
+/// assert!(venue.get_snapshot("AAPL", 10).is_some());
+/// ```
+pub struct MatchingVenue {
+    /// Engines keyed by instrument
+    engines: DashMap<String, Arc<MatchingEngine>>,
+
+    /// Event handler shared by all engines
+    event_handler: Arc<dyn EventHandler>,
+
+    /// Time source shared by all engines and venue-level rejections
+    clock: Arc<dyn Clock>,
+}
+
+impl MatchingVenue {
+    /// Create an empty venue
+    pub fn new(event_handler: Arc<dyn EventHandler>) -> Self {
+        Self {
+            engines: DashMap::new(),
+            event_handler,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Use `clock` for engines registered from now on and for the venue's own
+    /// rejections
+    ///
+    /// Defaults to [`SystemClock`].
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Create and register an engine for the configured instrument
+    ///
+    /// Fails if the configuration is invalid or the instrument is already
+    /// registered.
+    pub fn register_instrument(
+        &self,
+        config: OrderBookConfig,
+    ) -> Result<Arc<MatchingEngine>, String> {
+        match self.engines.entry(config.instrument.clone()) {
+            Entry::Occupied(entry) => {
+                Err(format!("Instrument already registered: {}", entry.key()))
+            },
+            Entry::Vacant(entry) => {
+                let engine = create_from_config(config, Arc::clone(&self.event_handler))?
+                    .with_clock(Arc::clone(&self.clock));
+                let engine = Arc::new(engine);
+                entry.insert(Arc::clone(&engine));
+                Ok(engine)
+            },
+        }
+    }
+
+    /// Submit an order to the engine for its instrument
+    ///
+    /// Orders for unregistered instruments are rejected.
+    pub fn submit_order(&self, order: Arc<Order>) -> Vec<OrderEvent> {
+        match self.get_engine(&order.instrument) {
+            Some(engine) => engine.submit_order(order),
+            None => {
+                order.set_state(OrderState::Rejected);
+                let events = vec![
+                    OrderEvent::OrderReceived {
+                        order_id: order.id,
+                        timestamp: self.clock.now(),
+                    },
+                    OrderEvent::OrderRejected {
+                        order_id: order.id,
+                        code: RejectReason::UnknownInstrument,
+                        reason: format!("Unknown instrument: {}", order.instrument),
+                        timestamp: self.clock.now(),
+                    },
+                ];
+                self.event_handler.on_events(events.clone());
+                events
+            },
+        }
+    }
+
+    /// Cancel an order on the given instrument
+    pub fn cancel_order(&self, instrument: &str, order_id: OrderId) -> Option<OrderEvent> {
+        self.get_engine(instrument)?.cancel_order(order_id)
+    }
+
+    /// Get a snapshot for the given instrument
+    pub fn get_snapshot(&self, instrument: &str, depth: usize) -> Option<OrderBookSnapshot> {
+        self.get_engine(instrument)
+            .map(|engine| engine.get_snapshot(depth))
+    }
+
+    /// Get the engine for an instrument
+    pub fn get_engine(&self, instrument: &str) -> Option<Arc<MatchingEngine>> {
+        self.engines
+            .get(instrument)
+            .map(|engine| Arc::clone(engine.value()))
+    }
+
+    /// Registered instruments (unordered)
+    pub fn instruments(&self) -> Vec<String> {
+        self.engines
+            .iter()
+            .map(|entry| entry.key().clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OrderType, Side, TimeInForce};
+    use crate::engine::MockClock;
+    use crate::interfaces::NoOpEventHandler;
+    use crate::numeric::{Price, Quantity};
+    use chrono::{DateTime, Utc};
+
+    fn limit(instrument: &str, side: Side, price: i64) -> Arc<Order> {
+        Arc::new(Order::new(
+            "user1".to_string(),
+            instrument.to_string(),
+            side,
+            OrderType::Limit,
+            Some(Price::from_integer(price).unwrap()),
+            Quantity::from_integer(1).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ))
+    }
+
+    #[test]
+    fn test_venue_routes_by_instrument() {
+        let venue = MatchingVenue::new(Arc::new(NoOpEventHandler));
+        venue
+            .register_instrument(OrderBookConfig::nasdaq_style("AAPL".to_string()))
+            .unwrap();
+        venue
+            .register_instrument(OrderBookConfig::nasdaq_style("MSFT".to_string()))
+            .unwrap();
+
+        venue.submit_order(limit("AAPL", Side::Sell, 150));
+        let bid = limit("MSFT", Side::Buy, 400);
+        venue.submit_order(Arc::clone(&bid));
+
+        let aapl = venue.get_snapshot("AAPL", 10).unwrap();
+        let msft = venue.get_snapshot("MSFT", 10).unwrap();
+        assert_eq!(aapl.best_ask(), Some(Price::from_integer(150).unwrap()));
+        assert!(aapl.bids.is_empty());
+        assert_eq!(msft.best_bid(), Some(Price::from_integer(400).unwrap()));
+        assert!(msft.asks.is_empty());
+
+        // A crossing price on the other instrument does not match
+        let events = venue.submit_order(limit("MSFT", Side::Buy, 150));
+        assert!(!events
+            .iter()
+            .any(|e| matches!(e, OrderEvent::OrderMatched { .. })));
+
+        assert!(venue.cancel_order("AAPL", bid.id).is_none());
+        assert!(venue.cancel_order("MSFT", bid.id).is_some());
+    }
+
+    #[test]
+    fn test_venue_rejects_unknown_instrument() {
+        let venue = MatchingVenue::new(Arc::new(NoOpEventHandler));
+        let order = limit("TSLA", Side::Buy, 200);
+
+        let events = venue.submit_order(Arc::clone(&order));
+        assert!(matches!(
+            events.last(),
+            Some(OrderEvent::OrderRejected { .. })
+        ));
+        assert_eq!(order.get_state(), OrderState::Rejected);
+        assert!(venue.get_snapshot("TSLA", 10).is_none());
+    }
+
+    #[test]
+    fn test_venue_clock_stamps_engines_and_rejections() {
+        let start = "2024-01-02T09:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let venue = MatchingVenue::new(Arc::new(NoOpEventHandler))
+            .with_clock(Arc::new(MockClock::new(start)));
+        venue
+            .register_instrument(OrderBookConfig::nasdaq_style("AAPL".to_string()))
+            .unwrap();
+        let received_at = |events: Vec<OrderEvent>| match events.first() {
+            Some(OrderEvent::OrderReceived { timestamp, .. }) => *timestamp,
+            other => panic!("unexpected event {:?}", other),
+        };
+
+        assert_eq!(
+            received_at(venue.submit_order(limit("AAPL", Side::Buy, 150))),
+            start
+        );
+        assert_eq!(
+            received_at(venue.submit_order(limit("TSLA", Side::Buy, 200))),
+            start
+        );
+    }
+
+    #[test]
+    fn test_venue_duplicate_registration() {
+        let venue = MatchingVenue::new(Arc::new(NoOpEventHandler));
+        let config = OrderBookConfig::nasdaq_style("AAPL".to_string());
+        assert!(venue.register_instrument(config.clone()).is_ok());
+        assert!(venue.register_instrument(config).is_err());
+        assert_eq!(venue.instruments(), vec!["AAPL".to_string()]);
+    }
+}
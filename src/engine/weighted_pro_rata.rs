@@ -0,0 +1,240 @@
+// ============================================================================
+// Weighted Pro-Rata Matching Algorithm
+// Size-dampened ("power") pro-rata allocation
+// ============================================================================
+
+use super::allocation::{match_by_allocation, natural_first, ProRataRounding, ShareRounding};
+use crate::domain::{Order, OrderBookLevel, OrderBookSide, OrderId, Trade};
+use crate::interfaces::MatchingAlgorithm;
+use crate::numeric::{Quantity, QTY_DECIMALS};
+use rust_decimal::{Decimal, MathematicalOps};
+use std::sync::Arc;
+
+/// Weighted (power) pro-rata matching algorithm
+///
+/// Each eligible order is weighted by `remaining ^ exponent` and fills are
+/// split in proportion to the weights. An exponent below 1 dampens the
+/// advantage of very large orders; 1 is plain pro-rata and 0 splits evenly.
+///
+/// # Example
+/// ```text
+/// Book at 50000, exponent 0.5:
+///   Order A: 100 BTC  (weight 10)
+///   Order B: 400 BTC  (weight 20)
+///
+/// Incoming: Sell 150 BTC @ 50000
+/// Allocation:
+///   A gets: 150 * (10/30) = 50 BTC   (plain pro-rata: 30 BTC)
+///   B gets: 150 * (20/30) = 100 BTC  (plain pro-rata: 120 BTC)
+/// ```
+pub struct WeightedProRata {
+    /// Power applied to each order's remaining size
+    pub exponent: Decimal,
+    /// Minimum order size to participate in the allocation
+    pub minimum_quantity: Quantity,
+    /// Rounding of the pro-rata shares
+    pub rounding: ShareRounding,
+}
+
+impl WeightedProRata {
+    pub fn new(exponent: Decimal, minimum_quantity: Quantity) -> Self {
+        Self {
+            exponent,
+            minimum_quantity,
+            rounding: ShareRounding::default(),
+        }
+    }
+
+    /// Calculate weighted allocation for orders at a price level
+    fn calculate_allocation(
+        &self,
+        level: &OrderBookLevel,
+        quantity_to_fill: Quantity,
+    ) -> Vec<(OrderId, Quantity)> {
+        // Oldest first, so rounding leftovers land the same way on replay
+        let mut orders = level.snapshot_orders();
+        orders.sort_by_key(|order| order.get_sequence_number());
+
+        natural_first(&orders, quantity_to_fill, |orders, quantity| {
+            self.allocate(orders, quantity)
+        })
+    }
+
+    /// Split `quantity_to_fill` across `orders` in proportion to their weights
+    fn allocate(
+        &self,
+        orders: &[Arc<Order>],
+        quantity_to_fill: Quantity,
+    ) -> Vec<(OrderId, Quantity)> {
+        let eligible_orders: Vec<(OrderId, Quantity)> = orders
+            .iter()
+            .map(|order| (order.id, order.get_remaining_quantity()))
+            .filter(|(_, remaining)| remaining.is_positive() && *remaining >= self.minimum_quantity)
+            .collect();
+        if eligible_orders.is_empty() {
+            return Vec::new();
+        }
+
+        let sizes: Vec<Quantity> = eligible_orders.iter().map(|(_, qty)| *qty).collect();
+        let weights = self.weights(&sizes);
+        let shares = self.rounding.split(&weights, quantity_to_fill);
+
+        eligible_orders
+            .iter()
+            .zip(shares)
+            .map(|((order_id, _), allocation)| (*order_id, allocation))
+            .collect()
+    }
+
+    /// `size ^ exponent` for each size, scaled so the largest weight is 1
+    ///
+    /// Scaling keeps large sizes raised to large exponents in range. An
+    /// exponent of exactly 1 uses the sizes unchanged, so it matches `ProRata`
+    /// to the unit.
+    fn weights(&self, sizes: &[Quantity]) -> Vec<Quantity> {
+        if self.exponent == Decimal::ONE {
+            return sizes.to_vec();
+        }
+
+        let largest = sizes.iter().copied().max().unwrap_or(Quantity::ZERO);
+        sizes
+            .iter()
+            .map(|size| {
+                (size.to_decimal() / largest.to_decimal())
+                    .checked_powd(self.exponent)
+                    .and_then(|weight| {
+                        Quantity::from_decimal(weight.round_dp(QTY_DECIMALS as u32)).ok()
+                    })
+                    .unwrap_or(Quantity::ZERO)
+            })
+            .collect()
+    }
+}
+
+impl ProRataRounding for WeightedProRata {
+    fn rounding_mut(&mut self) -> &mut ShareRounding {
+        &mut self.rounding
+    }
+}
+
+impl MatchingAlgorithm for WeightedProRata {
+    fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade> {
+        match_by_allocation(self, &incoming_order, opposite_side, |level, quantity| {
+            self.calculate_allocation(level, quantity)
+        })
+    }
+
+    fn name(&self) -> &str {
+        "Weighted-ProRata"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OrderType, Side, TimeInForce};
+    use crate::engine::ProRata;
+    use crate::numeric::Price;
+
+    /// Fill per maker when `algo` matches a 150 lot against makers of 100 and 400
+    fn fills(algo: &dyn MatchingAlgorithm) -> Vec<Quantity> {
+        let side = OrderBookSide::new(Side::Sell);
+        let makers: Vec<_> = [100, 400]
+            .iter()
+            .zip(1..)
+            .map(|(&size, seq)| {
+                let order = Arc::new(Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(Price::from_integer(50000).unwrap()),
+                    Quantity::from_integer(size).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ));
+                order.set_sequence_number(seq);
+                side.add_order(Arc::clone(&order));
+                order
+            })
+            .collect();
+
+        let buy = Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(150).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        algo.match_order(buy, &side);
+        makers.iter().map(|m| m.get_filled_quantity()).collect()
+    }
+
+    #[test]
+    fn test_unit_exponent_matches_pro_rata() {
+        let weighted = WeightedProRata::new(Decimal::ONE, Quantity::ZERO);
+        let plain = ProRata::new(Quantity::ZERO, false);
+
+        assert_eq!(fills(&weighted), fills(&plain));
+        assert_eq!(
+            fills(&weighted),
+            vec![
+                Quantity::from_integer(30).unwrap(),
+                Quantity::from_integer(120).unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_square_root_compresses_large_orders() {
+        let weighted = WeightedProRata::new(Decimal::new(5, 1), Quantity::ZERO);
+
+        // Weights 10 : 20 instead of 100 : 400
+        let fills = fills(&weighted);
+        assert_eq!(fills[0], Quantity::from_integer(50).unwrap());
+        assert_eq!(fills[1], Quantity::from_integer(100).unwrap());
+    }
+
+    #[test]
+    fn test_over_allocation_is_redistributed() {
+        // Makers of 1 and 100 with exponent 0: an even split offers the small
+        // order 40 of an 80 lot, far more than it holds
+        let algo = WeightedProRata::new(Decimal::ZERO, Quantity::ZERO);
+        let side = OrderBookSide::new(Side::Sell);
+        let makers: Vec<_> = [1, 100]
+            .iter()
+            .map(|&size| {
+                let order = Arc::new(Order::new(
+                    "maker".to_string(),
+                    "BTC-USD".to_string(),
+                    Side::Sell,
+                    OrderType::Limit,
+                    Some(Price::from_integer(50000).unwrap()),
+                    Quantity::from_integer(size).unwrap(),
+                    TimeInForce::GoodTillCancel,
+                ));
+                side.add_order(Arc::clone(&order));
+                order
+            })
+            .collect();
+
+        let buy = Arc::new(Order::new(
+            "taker".to_string(),
+            "BTC-USD".to_string(),
+            Side::Buy,
+            OrderType::Limit,
+            Some(Price::from_integer(50000).unwrap()),
+            Quantity::from_integer(80).unwrap(),
+            TimeInForce::GoodTillCancel,
+        ));
+        algo.match_order(Arc::clone(&buy), &side);
+
+        assert_eq!(buy.get_remaining_quantity(), Quantity::ZERO);
+        assert_eq!(makers[0].get_filled_quantity(), Quantity::ONE);
+        assert_eq!(
+            makers[1].get_filled_quantity(),
+            Quantity::from_integer(79).unwrap()
+        );
+    }
+}
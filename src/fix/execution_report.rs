@@ -0,0 +1,299 @@
+// ============================================================================
+// FIX Execution Reports
+// Builds ExecutionReport (35=8) messages from engine events
+// ============================================================================
+
+use super::message::{msg_types, tags, FixMessage};
+use crate::domain::{OrderId, Side};
+use crate::interfaces::OrderEvent;
+use crate::numeric::{Price, Quantity};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// ExecType (150)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecType {
+    New,
+    Canceled,
+    Rejected,
+    Expired,
+    Trade,
+    OrderStatus,
+}
+
+impl ExecType {
+    pub fn fix_value(self) -> &'static str {
+        match self {
+            ExecType::New => "0",
+            ExecType::Canceled => "4",
+            ExecType::Rejected => "8",
+            ExecType::Expired => "C",
+            ExecType::Trade => "F",
+            ExecType::OrderStatus => "I",
+        }
+    }
+}
+
+/// OrdStatus (39)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrdStatus {
+    New,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+    Expired,
+}
+
+impl OrdStatus {
+    pub fn fix_value(self) -> &'static str {
+        match self {
+            OrdStatus::New => "0",
+            OrdStatus::PartiallyFilled => "1",
+            OrdStatus::Filled => "2",
+            OrdStatus::Canceled => "4",
+            OrdStatus::Rejected => "8",
+            OrdStatus::Expired => "C",
+        }
+    }
+}
+
+/// Execution report for one order
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutionReport {
+    /// Engine order id (OrderID, 37)
+    pub order_id: OrderId,
+    /// Client's id for the order (ClOrdID, 11); the engine order id is sent
+    /// in its place when unset
+    pub client_order_id: Option<String>,
+    pub exec_id: String,
+    pub exec_type: ExecType,
+    pub ord_status: OrdStatus,
+    pub side: Option<Side>,
+    pub last_px: Option<Price>,
+    pub last_qty: Option<Quantity>,
+    pub cum_qty: Option<Quantity>,
+    pub leaves_qty: Option<Quantity>,
+    pub text: Option<String>,
+    pub transact_time: DateTime<Utc>,
+}
+
+impl ExecutionReport {
+    fn new(
+        order_id: OrderId,
+        exec_type: ExecType,
+        ord_status: OrdStatus,
+        transact_time: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            order_id,
+            client_order_id: None,
+            exec_id: Uuid::new_v4().to_string(),
+            exec_type,
+            ord_status,
+            side: None,
+            last_px: None,
+            last_qty: None,
+            cum_qty: None,
+            leaves_qty: None,
+            text: None,
+            transact_time,
+        }
+    }
+
+    /// Reports owed to clients for an engine event
+    ///
+    /// A trade produces one report per side, with LastPx/LastQty and
+    /// OrdStatus PartiallyFilled; the `OrderFilled` event that follows a
+    /// completing fill produces the terminal Filled status. Events with no
+    /// client-facing meaning (book deltas, halts, ...) produce nothing.
+    pub fn from_event(event: &OrderEvent) -> Vec<Self> {
+        match event {
+            OrderEvent::OrderAccepted {
+                order_id,
+                timestamp,
+            } => vec![Self::new(
+                *order_id,
+                ExecType::New,
+                OrdStatus::New,
+                *timestamp,
+            )],
+            OrderEvent::OrderRejected {
+                order_id,
+                reason,
+                timestamp,
+                ..
+            } => {
+                let mut report = Self::new(
+                    *order_id,
+                    ExecType::Rejected,
+                    OrdStatus::Rejected,
+                    *timestamp,
+                );
+                report.text = Some(reason.clone());
+                vec![report]
+            },
+            OrderEvent::OrderMatched { trade, timestamp } => {
+                let maker_side = trade.aggressor_side.opposite();
+                [
+                    (trade.taker_order_id, trade.aggressor_side),
+                    (trade.maker_order_id, maker_side),
+                ]
+                .into_iter()
+                .map(|(order_id, side)| {
+                    let mut report = Self::new(
+                        order_id,
+                        ExecType::Trade,
+                        OrdStatus::PartiallyFilled,
+                        *timestamp,
+                    );
+                    report.exec_id = trade.trade_id.to_string();
+                    report.side = Some(side);
+                    report.last_px = Some(trade.price);
+                    report.last_qty = Some(trade.quantity);
+                    report
+                })
+                .collect()
+            },
+            OrderEvent::OrderPartiallyFilled {
+                order_id,
+                filled_quantity,
+                remaining_quantity,
+                timestamp,
+            } => {
+                let mut report = Self::new(
+                    *order_id,
+                    ExecType::OrderStatus,
+                    OrdStatus::PartiallyFilled,
+                    *timestamp,
+                );
+                report.cum_qty = Some(*filled_quantity);
+                report.leaves_qty = Some(*remaining_quantity);
+                vec![report]
+            },
+            OrderEvent::OrderFilled {
+                order_id,
+                total_filled,
+                timestamp,
+            } => {
+                let mut report = Self::new(
+                    *order_id,
+                    ExecType::OrderStatus,
+                    OrdStatus::Filled,
+                    *timestamp,
+                );
+                report.cum_qty = Some(*total_filled);
+                report.leaves_qty = Some(Quantity::ZERO);
+                vec![report]
+            },
+            OrderEvent::OrderCancelled {
+                order_id,
+                timestamp,
+            } => vec![Self::new(
+                *order_id,
+                ExecType::Canceled,
+                OrdStatus::Canceled,
+                *timestamp,
+            )],
+            OrderEvent::OrderExpired {
+                order_id,
+                timestamp,
+            } => vec![Self::new(
+                *order_id,
+                ExecType::Expired,
+                OrdStatus::Expired,
+                *timestamp,
+            )],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Builder method: Report the client's ClOrdID for the order
+    pub fn with_client_order_id(mut self, client_order_id: impl Into<String>) -> Self {
+        self.client_order_id = Some(client_order_id.into());
+        self
+    }
+
+    /// Encode as an ExecutionReport message
+    pub fn to_message(&self) -> FixMessage {
+        let order_id = self.order_id.as_uuid().to_string();
+        let cl_ord_id = self.client_order_id.as_deref().unwrap_or(&order_id);
+        let mut msg = FixMessage::new(msg_types::EXECUTION_REPORT)
+            .with_field(tags::ORDER_ID, &order_id)
+            .with_field(tags::CL_ORD_ID, cl_ord_id)
+            .with_field(tags::EXEC_ID, &self.exec_id)
+            .with_field(tags::EXEC_TYPE, self.exec_type.fix_value())
+            .with_field(tags::ORD_STATUS, self.ord_status.fix_value());
+
+        if let Some(side) = self.side {
+            msg = msg.with_field(
+                tags::SIDE,
+                match side {
+                    Side::Buy => "1",
+                    Side::Sell => "2",
+                },
+            );
+        }
+        let decimals = [
+            (tags::LAST_PX, self.last_px.map(|px| px.to_string())),
+            (tags::LAST_QTY, self.last_qty.map(|qty| qty.to_string())),
+            (tags::CUM_QTY, self.cum_qty.map(|qty| qty.to_string())),
+            (tags::LEAVES_QTY, self.leaves_qty.map(|qty| qty.to_string())),
+        ];
+        for (tag, value) in decimals {
+            if let Some(value) = value {
+                msg = msg.with_field(tag, value);
+            }
+        }
+        if let Some(text) = &self.text {
+            msg = msg.with_field(tags::TEXT, text);
+        }
+        msg.with_field(
+            tags::TRANSACT_TIME,
+            self.transact_time.format("%Y%m%d-%H:%M:%S%.3f"),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::Trade;
+
+    #[test]
+    fn test_trade_reports_both_sides() {
+        let (maker, taker) = (OrderId::new(), OrderId::new());
+        let price = Price::from_integer(100).unwrap();
+        let quantity = Quantity::from_integer(3).unwrap();
+        let mut trade = Trade::new(
+            "BTC-USD".to_string(),
+            maker,
+            taker,
+            price,
+            quantity,
+            Side::Sell,
+        );
+        trade.trade_id = 7;
+
+        let reports = ExecutionReport::from_event(&OrderEvent::OrderMatched {
+            trade,
+            timestamp: Utc::now(),
+        });
+        assert_eq!(reports.len(), 2);
+        assert_eq!(
+            (reports[0].order_id, reports[0].side),
+            (taker, Some(Side::Sell))
+        );
+        assert_eq!(
+            (reports[1].order_id, reports[1].side),
+            (maker, Some(Side::Buy))
+        );
+
+        let msg = reports[1].to_message();
+        assert_eq!(msg.get(tags::EXEC_TYPE), Some("F"));
+        assert_eq!(msg.get(tags::EXEC_ID), Some("7"));
+        assert_eq!(msg.get(tags::SIDE), Some("1"));
+        assert_eq!(msg.get(tags::LAST_PX), Some(price.to_string().as_str()));
+        assert_eq!(msg.get(tags::LAST_QTY), Some(quantity.to_string().as_str()));
+    }
+}
@@ -0,0 +1,152 @@
+// ============================================================================
+// FIX Message
+// Tag/value container with SOH-delimited encoding
+// ============================================================================
+
+use std::fmt;
+
+/// FIX field delimiter
+pub const SOH: char = '\x01';
+
+/// Tag numbers used by the adapter
+pub mod tags {
+    pub const ACCOUNT: u32 = 1;
+    pub const CL_ORD_ID: u32 = 11;
+    pub const CUM_QTY: u32 = 14;
+    pub const EXEC_ID: u32 = 17;
+    pub const LAST_PX: u32 = 31;
+    pub const LAST_QTY: u32 = 32;
+    pub const MSG_TYPE: u32 = 35;
+    pub const ORDER_ID: u32 = 37;
+    pub const ORDER_QTY: u32 = 38;
+    pub const ORD_STATUS: u32 = 39;
+    pub const ORD_TYPE: u32 = 40;
+    pub const ORIG_CL_ORD_ID: u32 = 41;
+    pub const PRICE: u32 = 44;
+    pub const SIDE: u32 = 54;
+    pub const SYMBOL: u32 = 55;
+    pub const TEXT: u32 = 58;
+    pub const TIME_IN_FORCE: u32 = 59;
+    pub const TRANSACT_TIME: u32 = 60;
+    pub const STOP_PX: u32 = 99;
+    pub const EXPIRE_TIME: u32 = 126;
+    pub const EXEC_TYPE: u32 = 150;
+    pub const LEAVES_QTY: u32 = 151;
+}
+
+/// Message types handled by the adapter (tag 35)
+pub(crate) mod msg_types {
+    pub const EXECUTION_REPORT: &str = "8";
+    pub const NEW_ORDER_SINGLE: &str = "D";
+    pub const ORDER_CANCEL_REQUEST: &str = "F";
+}
+
+/// Reason a message could not be mapped
+///
+/// `ref_tag` names the offending field where there is one, matching FIX's
+/// RefTagID (371) so the session layer can build a Reject.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixReject {
+    pub ref_tag: Option<u32>,
+    pub reason: String,
+}
+
+impl FixReject {
+    pub fn new(ref_tag: Option<u32>, reason: impl Into<String>) -> Self {
+        Self {
+            ref_tag,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for FixReject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ref_tag {
+            Some(tag) => write!(f, "tag {}: {}", tag, self.reason),
+            None => write!(f, "{}", self.reason),
+        }
+    }
+}
+
+impl std::error::Error for FixReject {}
+
+/// FIX message body as an ordered list of tag/value fields
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FixMessage {
+    fields: Vec<(u32, String)>,
+}
+
+impl FixMessage {
+    /// Create a message with its MsgType (35) set
+    pub fn new(msg_type: &str) -> Self {
+        Self::default().with_field(tags::MSG_TYPE, msg_type)
+    }
+
+    /// Append a field
+    pub fn with_field(mut self, tag: u32, value: impl ToString) -> Self {
+        self.fields.push((tag, value.to_string()));
+        self
+    }
+
+    /// Value of the first occurrence of `tag`
+    pub fn get(&self, tag: u32) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(t, _)| *t == tag)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Value of `tag`, rejecting the message if it is missing
+    pub fn require(&self, tag: u32) -> Result<&str, FixReject> {
+        self.get(tag)
+            .ok_or_else(|| FixReject::new(Some(tag), "required tag missing"))
+    }
+
+    /// MsgType (35)
+    pub fn msg_type(&self) -> Option<&str> {
+        self.get(tags::MSG_TYPE)
+    }
+
+    /// Parse an SOH-delimited `tag=value` string
+    pub fn parse(raw: &str) -> Result<Self, FixReject> {
+        let mut fields = Vec::new();
+        for field in raw.split(SOH).filter(|f| !f.is_empty()) {
+            let (tag, value) = field
+                .split_once('=')
+                .ok_or_else(|| FixReject::new(None, format!("malformed field '{}'", field)))?;
+            let tag = tag
+                .parse::<u32>()
+                .map_err(|_| FixReject::new(None, format!("invalid tag '{}'", tag)))?;
+            fields.push((tag, value.to_string()));
+        }
+        Ok(Self { fields })
+    }
+
+    /// Encode as an SOH-delimited `tag=value` string
+    pub fn encode(&self) -> String {
+        self.fields
+            .iter()
+            .map(|(tag, value)| format!("{}={}{}", tag, value, SOH))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_encode() {
+        let raw = "35=D\x0155=BTC-USD\x0144=50000.5\x01";
+        let msg = FixMessage::parse(raw).unwrap();
+
+        assert_eq!(msg.msg_type(), Some("D"));
+        assert_eq!(msg.get(tags::PRICE), Some("50000.5"));
+        assert_eq!(msg.get(tags::SIDE), None);
+        assert_eq!(msg.encode(), raw);
+
+        let err = FixMessage::parse("35=D\x01garbage\x01").unwrap_err();
+        assert_eq!(err.ref_tag, None);
+    }
+}
@@ -0,0 +1,24 @@
+// ============================================================================
+// FIX Module
+// Maps FIX order entry messages onto engine orders and engine events back
+// onto execution reports
+// ============================================================================
+
+//! Application-level FIX adapter.
+//!
+//! Only the message body is handled here: session framing (BeginString,
+//! BodyLength, CheckSum), sequencing and logon belong to the FIX session
+//! layer in front of the engine.
+//!
+//! Orders get engine-assigned [`OrderId`](crate::domain::OrderId)s. The
+//! client's ClOrdID (tag 11) travels as the order's client order id: cancels
+//! resolve it with `MatchingEngine::resolve_client_order_id`, and execution
+//! reports echo it with `ExecutionReport::with_client_order_id`.
+
+mod execution_report;
+mod message;
+mod order_entry;
+
+pub use execution_report::{ExecType, ExecutionReport, OrdStatus};
+pub use message::{tags, FixMessage, FixReject, SOH};
+pub use order_entry::{parse_cancel_request, parse_new_order_single, CancelRequest};
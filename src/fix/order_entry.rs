@@ -0,0 +1,272 @@
+// ============================================================================
+// FIX Order Entry
+// NewOrderSingle (35=D) and OrderCancelRequest (35=F) mapping
+// ============================================================================
+
+use super::message::{msg_types, tags, FixMessage, FixReject};
+use crate::domain::{Order, OrderType, Side, TimeInForce};
+use crate::numeric::{FixedDecimal, Quantity};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::sync::Arc;
+
+/// Cancel request decoded from an OrderCancelRequest
+///
+/// Resolve the order with `MatchingEngine::resolve_client_order_id`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CancelRequest {
+    /// ClOrdID of the order to cancel (OrigClOrdID, 41)
+    pub orig_client_order_id: String,
+    /// Symbol (55), for routing through a venue
+    pub instrument: String,
+    /// Account (1) the order was entered under
+    pub user_id: String,
+}
+
+/// Map a NewOrderSingle onto an engine order
+///
+/// Supported values: Side (54) `1`/`2`; OrdType (40) `1` market, `2` limit,
+/// `4` stop-limit (with StopPx, 99); TimeInForce (59) `1` GTC, `3` IOC,
+/// `4` FOK, `6` GTD (with ExpireTime, 126). A missing TimeInForce means GTC,
+/// since the engine has no trading-day boundary to honour Day orders.
+///
+/// The order gets a fresh engine id; ClOrdID (11) is carried as its
+/// client order id.
+pub fn parse_new_order_single(msg: &FixMessage) -> Result<Arc<Order>, FixReject> {
+    expect_msg_type(msg, msg_types::NEW_ORDER_SINGLE)?;
+
+    let client_order_id = msg.require(tags::CL_ORD_ID)?.to_string();
+    let user_id = msg.require(tags::ACCOUNT)?.to_string();
+    let instrument = msg.require(tags::SYMBOL)?.to_string();
+    let side = match msg.require(tags::SIDE)? {
+        "1" => Side::Buy,
+        "2" => Side::Sell,
+        _ => return Err(FixReject::new(Some(tags::SIDE), "unsupported side")),
+    };
+    let quantity: Quantity = parse_decimal(msg, tags::ORDER_QTY)?;
+
+    let (order_type, price) = match msg.require(tags::ORD_TYPE)? {
+        "1" => (OrderType::Market, None),
+        "2" => (OrderType::Limit, Some(parse_decimal(msg, tags::PRICE)?)),
+        "4" => (
+            OrderType::StopLimit {
+                trigger_price: parse_decimal(msg, tags::STOP_PX)?,
+            },
+            Some(parse_decimal(msg, tags::PRICE)?),
+        ),
+        _ => {
+            return Err(FixReject::new(
+                Some(tags::ORD_TYPE),
+                "unsupported order type",
+            ))
+        },
+    };
+
+    let time_in_force = match msg.get(tags::TIME_IN_FORCE) {
+        None | Some("1") => TimeInForce::GoodTillCancel,
+        Some("3") => TimeInForce::ImmediateOrCancel,
+        Some("4") => TimeInForce::FillOrKill,
+        Some("6") => TimeInForce::GoodTillDate(parse_timestamp(msg, tags::EXPIRE_TIME)?),
+        Some(_) => {
+            return Err(FixReject::new(
+                Some(tags::TIME_IN_FORCE),
+                "unsupported time in force",
+            ))
+        },
+    };
+
+    let order = Order::new(
+        user_id,
+        instrument,
+        side,
+        order_type,
+        price,
+        quantity,
+        time_in_force,
+    )
+    .with_client_order_id(client_order_id);
+    Ok(Arc::new(order))
+}
+
+/// Map an OrderCancelRequest onto the order it cancels
+pub fn parse_cancel_request(msg: &FixMessage) -> Result<CancelRequest, FixReject> {
+    expect_msg_type(msg, msg_types::ORDER_CANCEL_REQUEST)?;
+
+    Ok(CancelRequest {
+        orig_client_order_id: msg.require(tags::ORIG_CL_ORD_ID)?.to_string(),
+        instrument: msg.require(tags::SYMBOL)?.to_string(),
+        user_id: msg.require(tags::ACCOUNT)?.to_string(),
+    })
+}
+
+fn expect_msg_type(msg: &FixMessage, expected: &str) -> Result<(), FixReject> {
+    match msg.require(tags::MSG_TYPE)? {
+        msg_type if msg_type == expected => Ok(()),
+        msg_type => Err(FixReject::new(
+            Some(tags::MSG_TYPE),
+            format!("expected message type {}, got {}", expected, msg_type),
+        )),
+    }
+}
+
+fn parse_decimal<const D: u8, U>(
+    msg: &FixMessage,
+    tag: u32,
+) -> Result<FixedDecimal<D, U>, FixReject> {
+    msg.require(tag)?
+        .parse()
+        .map_err(|e| FixReject::new(Some(tag), format!("{}", e)))
+}
+
+/// Parse a UTCTimestamp (`YYYYMMDD-HH:MM:SS[.sss]`)
+fn parse_timestamp(msg: &FixMessage, tag: u32) -> Result<DateTime<Utc>, FixReject> {
+    NaiveDateTime::parse_from_str(msg.require(tag)?, "%Y%m%d-%H:%M:%S%.f")
+        .map(|t| t.and_utc())
+        .map_err(|_| FixReject::new(Some(tag), "invalid UTC timestamp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{MatchingEngine, PriceTimePriority};
+    use crate::fix::{ExecutionReport, OrdStatus};
+    use crate::interfaces::NoOpEventHandler;
+    use crate::numeric::Price;
+
+    fn engine() -> MatchingEngine {
+        MatchingEngine::new(
+            "BTC-USD".to_string(),
+            Box::new(PriceTimePriority::new(false)),
+            Arc::new(NoOpEventHandler),
+        )
+    }
+
+    /// Run events through the adapter and back through the wire format,
+    /// echoing `cl_ord_id` as the gateway would
+    fn reports_for(events: &[crate::interfaces::OrderEvent], cl_ord_id: &str) -> Vec<FixMessage> {
+        events
+            .iter()
+            .flat_map(ExecutionReport::from_event)
+            .map(|report| report.with_client_order_id(cl_ord_id))
+            .map(|report| FixMessage::parse(&report.to_message().encode()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_limit_buy_round_trip() {
+        // ClOrdIDs are free-form strings, not engine ids
+        let cl_ord_id = "20240611-0001";
+        let raw = FixMessage::new("D")
+            .with_field(tags::CL_ORD_ID, cl_ord_id)
+            .with_field(tags::ACCOUNT, "acct-1")
+            .with_field(tags::SYMBOL, "BTC-USD")
+            .with_field(tags::SIDE, "1")
+            .with_field(tags::ORD_TYPE, "2")
+            .with_field(tags::PRICE, "50000.25")
+            .with_field(tags::ORDER_QTY, "1.5")
+            .with_field(tags::TIME_IN_FORCE, "1")
+            .encode();
+
+        let order = parse_new_order_single(&FixMessage::parse(&raw).unwrap()).unwrap();
+        assert_eq!(
+            order.client_order_id.as_deref().map(String::as_str),
+            Some(cl_ord_id)
+        );
+        assert_eq!(order.user_id.as_str(), "acct-1");
+        assert_eq!(order.side, Side::Buy);
+        assert_eq!(order.order_type, OrderType::Limit);
+        assert_eq!(order.price(), Some("50000.25".parse::<Price>().unwrap()));
+        assert_eq!(order.quantity, "1.5".parse::<Quantity>().unwrap());
+        assert_eq!(order.time_in_force, TimeInForce::GoodTillCancel);
+
+        let order_id = order.id.as_uuid().to_string();
+        let reports = reports_for(&engine().submit_order(order), cl_ord_id);
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].msg_type(), Some("8"));
+        assert_eq!(reports[0].get(tags::CL_ORD_ID), Some(cl_ord_id));
+        assert_eq!(reports[0].get(tags::ORDER_ID), Some(order_id.as_str()));
+        assert_eq!(reports[0].get(tags::ORD_STATUS), Some("0"));
+    }
+
+    #[test]
+    fn test_cancel_round_trip() {
+        let engine = engine();
+        let new_order = FixMessage::new("D")
+            .with_field(tags::CL_ORD_ID, "sell-1")
+            .with_field(tags::ACCOUNT, "acct-1")
+            .with_field(tags::SYMBOL, "BTC-USD")
+            .with_field(tags::SIDE, "2")
+            .with_field(tags::ORD_TYPE, "2")
+            .with_field(tags::PRICE, "51000")
+            .with_field(tags::ORDER_QTY, "2");
+        let order = parse_new_order_single(&new_order).unwrap();
+        engine.submit_order(Arc::clone(&order));
+
+        let raw = FixMessage::new("F")
+            .with_field(tags::ORIG_CL_ORD_ID, "sell-1")
+            .with_field(tags::CL_ORD_ID, "cancel-1")
+            .with_field(tags::ACCOUNT, "acct-1")
+            .with_field(tags::SYMBOL, "BTC-USD")
+            .encode();
+        let cancel = parse_cancel_request(&FixMessage::parse(&raw).unwrap()).unwrap();
+        assert_eq!(cancel.instrument, "BTC-USD");
+        assert_eq!(cancel.user_id, "acct-1");
+
+        // Another account's ClOrdID of the same value does not resolve
+        assert_eq!(engine.resolve_client_order_id("acct-2", "sell-1"), None);
+
+        let order_id = engine
+            .resolve_client_order_id(&cancel.user_id, &cancel.orig_client_order_id)
+            .unwrap();
+        assert_eq!(order_id, order.id);
+        let event = engine.cancel_order(order_id).unwrap();
+        let reports = reports_for(&[event], "cancel-1");
+        assert_eq!(
+            reports[0].get(tags::ORDER_ID),
+            Some(order.id.as_uuid().to_string().as_str())
+        );
+        assert_eq!(
+            reports[0].get(tags::ORD_STATUS),
+            Some(OrdStatus::Canceled.fix_value())
+        );
+        assert_eq!(engine.resolve_client_order_id("acct-1", "sell-1"), None);
+    }
+
+    #[test]
+    fn test_rejects_name_the_offending_tag() {
+        let base = FixMessage::new("D")
+            .with_field(tags::CL_ORD_ID, "buy-1")
+            .with_field(tags::ACCOUNT, "acct-1")
+            .with_field(tags::SYMBOL, "BTC-USD")
+            .with_field(tags::SIDE, "1")
+            .with_field(tags::ORDER_QTY, "1");
+
+        let missing_price = base.clone().with_field(tags::ORD_TYPE, "2");
+        assert_eq!(
+            parse_new_order_single(&missing_price).unwrap_err().ref_tag,
+            Some(tags::PRICE)
+        );
+
+        let day_order = base
+            .with_field(tags::ORD_TYPE, "1")
+            .with_field(tags::TIME_IN_FORCE, "0");
+        assert_eq!(
+            parse_new_order_single(&day_order).unwrap_err().ref_tag,
+            Some(tags::TIME_IN_FORCE)
+        );
+
+        // Cancels are resolved per account, so Account is required
+        let no_account = FixMessage::new("F")
+            .with_field(tags::ORIG_CL_ORD_ID, "buy-1")
+            .with_field(tags::SYMBOL, "BTC-USD");
+        assert_eq!(
+            parse_cancel_request(&no_account).unwrap_err().ref_tag,
+            Some(tags::ACCOUNT)
+        );
+
+        let wrong_type = FixMessage::new("F");
+        assert_eq!(
+            parse_new_order_single(&wrong_type).unwrap_err().ref_tag,
+            Some(tags::MSG_TYPE)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+// ============================================================================
+// Channel Event Handler
+// Moves event processing off the matching thread through a bounded channel
+// ============================================================================
+
+use super::event_handler::{EventHandler, OrderEvent};
+use crossbeam::channel::{self, Receiver, Sender, TrySendError};
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread::JoinHandle;
+
+/// What to do with a new event when the channel is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Wait for the consumer to make room (matching stalls, nothing is lost)
+    Block,
+    /// Evict the oldest queued event to make room for the new one
+    DropOldest,
+    /// Discard the new event
+    RejectNew,
+}
+
+/// Event handler that queues events on a bounded channel
+///
+/// Matching only pays for a channel send instead of running the handler
+/// inline, so a slow consumer (database writes, network fan-out) no longer
+/// stalls the hot path. The tradeoff is delivery latency: events reach the
+/// consumer asynchronously, and once the channel is full the
+/// [`BackpressurePolicy`] decides between stalling matching (`Block`) and
+/// losing events (`DropOldest`, `RejectNew`). Dropped events are counted.
+pub struct ChannelEventHandler {
+    sender: Option<Sender<OrderEvent>>,
+    /// Used to evict the oldest event under `DropOldest`
+    receiver: Receiver<OrderEvent>,
+    policy: BackpressurePolicy,
+    dropped: AtomicU64,
+    consumer: Option<JoinHandle<()>>,
+}
+
+impl ChannelEventHandler {
+    /// Create a handler and the receiving end of its channel
+    ///
+    /// The caller is responsible for draining the receiver.
+    pub fn bounded(capacity: usize, policy: BackpressurePolicy) -> (Self, Receiver<OrderEvent>) {
+        let (sender, receiver) = channel::bounded(capacity);
+        let handler = Self {
+            sender: Some(sender),
+            receiver: receiver.clone(),
+            policy,
+            dropped: AtomicU64::new(0),
+            consumer: None,
+        };
+        (handler, receiver)
+    }
+
+    /// Create a handler with a consumer thread that passes every event to `consume`
+    ///
+    /// Dropping the handler closes the channel and waits for the consumer to
+    /// finish the events already queued. Fails if the consumer thread cannot
+    /// be spawned.
+    pub fn with_consumer<F>(
+        capacity: usize,
+        policy: BackpressurePolicy,
+        mut consume: F,
+    ) -> io::Result<Self>
+    where
+        F: FnMut(OrderEvent) + Send + 'static,
+    {
+        let (mut handler, receiver) = Self::bounded(capacity, policy);
+        let consumer = std::thread::Builder::new()
+            .name("event-consumer".to_string())
+            .spawn(move || {
+                for event in receiver {
+                    consume(event);
+                }
+            })?;
+        handler.consumer = Some(consumer);
+        Ok(handler)
+    }
+
+    /// Number of events discarded because the channel was full
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Acquire)
+    }
+}
+
+impl EventHandler for ChannelEventHandler {
+    fn on_event(&self, event: OrderEvent) {
+        let sender = match &self.sender {
+            Some(sender) => sender,
+            None => return,
+        };
+
+        match self.policy {
+            BackpressurePolicy::Block => {
+                let _ = sender.send(event);
+            },
+            BackpressurePolicy::RejectNew => {
+                if let Err(TrySendError::Full(_)) = sender.try_send(event) {
+                    self.dropped.fetch_add(1, Ordering::AcqRel);
+                }
+            },
+            BackpressurePolicy::DropOldest => {
+                let mut event = event;
+                while let Err(TrySendError::Full(rejected)) = sender.try_send(event) {
+                    // The consumer may have freed a slot in the meantime
+                    if self.receiver.try_recv().is_ok() {
+                        self.dropped.fetch_add(1, Ordering::AcqRel);
+                    }
+                    event = rejected;
+                }
+            },
+        }
+    }
+}
+
+impl Drop for ChannelEventHandler {
+    fn drop(&mut self) {
+        // Closing the channel ends the consumer loop once it is drained
+        self.sender.take();
+        if let Some(consumer) = self.consumer.take() {
+            let _ = consumer.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::OrderId;
+    use chrono::Utc;
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    fn received(order_id: OrderId) -> OrderEvent {
+        OrderEvent::OrderReceived {
+            order_id,
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn order_id_of(event: &OrderEvent) -> OrderId {
+        match event {
+            OrderEvent::OrderReceived { order_id, .. } => *order_id,
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_consumer_receives_events_in_order() {
+        let ids: Vec<OrderId> = (0..100).map(|_| OrderId::new()).collect();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let sink = Arc::clone(&seen);
+        let handler = ChannelEventHandler::with_consumer(8, BackpressurePolicy::Block, move |e| {
+            sink.lock().push(order_id_of(&e))
+        })
+        .unwrap();
+        handler.on_events(ids.iter().map(|id| received(*id)).collect());
+        drop(handler);
+
+        assert_eq!(*seen.lock(), ids);
+    }
+
+    #[test]
+    fn test_backpressure_policies() {
+        let ids: Vec<OrderId> = (0..3).map(|_| OrderId::new()).collect();
+        let drain = |receiver: Receiver<OrderEvent>| -> Vec<OrderId> {
+            receiver.try_iter().map(|e| order_id_of(&e)).collect()
+        };
+
+        // Full at two events: the third is discarded
+        let (handler, receiver) = ChannelEventHandler::bounded(2, BackpressurePolicy::RejectNew);
+        ids.iter().for_each(|id| handler.on_event(received(*id)));
+        assert_eq!(handler.dropped_events(), 1);
+        assert_eq!(drain(receiver), ids[..2]);
+
+        // Full at two events: the first is evicted
+        let (handler, receiver) = ChannelEventHandler::bounded(2, BackpressurePolicy::DropOldest);
+        ids.iter().for_each(|id| handler.on_event(received(*id)));
+        assert_eq!(handler.dropped_events(), 1);
+        assert_eq!(drain(receiver), ids[1..]);
+
+        // Blocking waits for the consumer instead of losing anything
+        let (handler, receiver) = ChannelEventHandler::bounded(1, BackpressurePolicy::Block);
+        let consumer = std::thread::spawn(move || {
+            receiver.iter().map(|e| order_id_of(&e)).collect::<Vec<_>>()
+        });
+        ids.iter().for_each(|id| handler.on_event(received(*id)));
+        assert_eq!(handler.dropped_events(), 0);
+        drop(handler);
+        assert_eq!(consumer.join().unwrap(), ids);
+    }
+}
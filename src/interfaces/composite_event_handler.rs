@@ -0,0 +1,120 @@
+// ============================================================================
+// Composite Event Handler
+// Fans every event out to several handlers (logging, persistence, metrics)
+// ============================================================================
+
+use super::event_handler::{EventHandler, OrderEvent, SequencedEvent};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+/// Event handler that passes every event to each of its handlers in turn
+///
+/// Handlers are called in the order they were added, each with its own copy
+/// of the batch, and sequenced batches keep their sequence numbers. A handler
+/// that panics does not stop the others: the remaining handlers still run,
+/// then the first panic is resumed on the matching thread so it is not
+/// silently lost.
+#[derive(Default)]
+pub struct CompositeEventHandler {
+    handlers: Vec<Arc<dyn EventHandler>>,
+}
+
+impl CompositeEventHandler {
+    pub fn new(handlers: Vec<Arc<dyn EventHandler>>) -> Self {
+        Self { handlers }
+    }
+
+    /// Append a handler; it runs after the ones already added
+    pub fn with_handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Call `deliver` with each handler and a copy of `batch`, isolating panics
+    fn fan_out<T: Clone>(&self, batch: T, deliver: impl Fn(&dyn EventHandler, T)) {
+        let mut first_panic = None;
+        for handler in &self.handlers {
+            let batch = batch.clone();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| deliver(&**handler, batch)));
+            if let Err(payload) = result {
+                first_panic.get_or_insert(payload);
+            }
+        }
+        if let Some(payload) = first_panic {
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+impl EventHandler for CompositeEventHandler {
+    fn on_event(&self, event: OrderEvent) {
+        self.fan_out(event, |handler, event| handler.on_event(event));
+    }
+
+    fn on_events(&self, events: Vec<OrderEvent>) {
+        self.fan_out(events, |handler, events| handler.on_events(events));
+    }
+
+    fn on_sequenced_events(&self, events: Vec<SequencedEvent>) {
+        self.fan_out(events, |handler, events| {
+            handler.on_sequenced_events(events)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::OrderId;
+    use chrono::Utc;
+    use parking_lot::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<OrderId>>);
+
+    impl EventHandler for Recorder {
+        fn on_event(&self, event: OrderEvent) {
+            if let OrderEvent::OrderReceived { order_id, .. } = event {
+                self.0.lock().push(order_id);
+            }
+        }
+    }
+
+    struct Panics;
+
+    impl EventHandler for Panics {
+        fn on_event(&self, _event: OrderEvent) {
+            panic!("handler failed");
+        }
+    }
+
+    fn received() -> OrderEvent {
+        OrderEvent::OrderReceived {
+            order_id: OrderId::new(),
+            timestamp: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_panicking_handler_does_not_skip_others() {
+        let before = Arc::new(Recorder::default());
+        let after = Arc::new(Recorder::default());
+        let composite = CompositeEventHandler::default()
+            .with_handler(before.clone())
+            .with_handler(Arc::new(Panics))
+            .with_handler(after.clone());
+
+        let result = panic::catch_unwind(AssertUnwindSafe(|| composite.on_event(received())));
+        assert!(result.is_err());
+        assert_eq!(before.0.lock().len(), 1);
+        assert_eq!(after.0.lock().len(), 1);
+    }
+}
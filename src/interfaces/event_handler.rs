@@ -3,9 +3,10 @@
 // Defines the contract for handling order and trade events
 // ============================================================================
 
-use crate::domain::{OrderId, Trade};
+use crate::domain::{OrderId, Side, Trade};
 use crate::numeric::{Price, Quantity};
 use chrono::{DateTime, Utc};
+use std::fmt;
 
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -26,9 +27,10 @@ pub enum OrderEvent {
         timestamp: DateTime<Utc>,
     },
 
-    /// Order rejected with reason
+    /// Order rejected; `code` is the machine-readable form of `reason`
     OrderRejected {
         order_id: OrderId,
+        code: RejectReason,
         reason: String,
         timestamp: DateTime<Utc>,
     },
@@ -66,13 +68,218 @@ pub enum OrderEvent {
         timestamp: DateTime<Utc>,
     },
 
+    /// Order size changed by the engine (e.g. reduce-only capping)
+    OrderAmended {
+        order_id: OrderId,
+        new_quantity: Quantity,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Pegged order moved to a new price level after its reference moved
+    /// (the order lost its time priority)
+    OrderRepriced {
+        order_id: OrderId,
+        old_price: Price,
+        new_price: Price,
+        timestamp: DateTime<Utc>,
+    },
+
     /// Order added to book
     OrderAddedToBook {
         order_id: OrderId,
+        side: Side,
         price: Price,
         quantity: Quantity,
         timestamp: DateTime<Utc>,
     },
+
+    /// Iceberg order reloaded its display slice from the hidden reserve
+    /// (the order moved to the back of its price level)
+    IcebergRefreshed {
+        order_id: OrderId,
+        visible_quantity: Quantity,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Aggregate quantity at a price level changed
+    /// (`new_quantity` of zero means the level was removed)
+    BookDelta {
+        side: Side,
+        price: Price,
+        new_quantity: Quantity,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Best bid price or its aggregate quantity changed
+    /// (`price` of `None` means the bid side is now empty)
+    BestBidChanged {
+        price: Option<Price>,
+        quantity: Quantity,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Best ask price or its aggregate quantity changed
+    /// (`price` of `None` means the ask side is now empty)
+    BestAskChanged {
+        price: Option<Price>,
+        quantity: Quantity,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// CRC32 of the top `depth` levels per side (see
+    /// `OrderBookSnapshot::checksum`), published after the deltas it covers
+    BookChecksum {
+        crc: u32,
+        depth: usize,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// A crossed book was detected after matching and re-matched until it
+    /// uncrossed (`best_bid`/`best_ask` are the prices at detection)
+    BookCrossedResolved {
+        best_bid: Price,
+        best_ask: Price,
+        trade_count: usize,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// The circuit breaker tripped: an order would have traded at `price`,
+    /// too far from the recent trade at `reference_price`
+    TradingHalted {
+        reference_price: Price,
+        price: Price,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// Trading resumed after a halt
+    TradingResumed { timestamp: DateTime<Utc> },
+
+    /// The matching algorithm was swapped (names as reported by the algorithms)
+    AlgorithmChanged {
+        from: String,
+        to: String,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// One leg of an OCO pair traded and the other leg was cancelled
+    OcoTriggered {
+        filled_id: OrderId,
+        cancelled_id: OrderId,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// A prospective match is held for the maker's last look; resolve it with
+    /// `confirm_last_look` or `reject_last_look` before the hold runs out
+    LastLookPending {
+        match_id: u64,
+        maker_id: OrderId,
+        taker_id: OrderId,
+        price: Price,
+        quantity: Quantity,
+        timestamp: DateTime<Utc>,
+    },
+
+    /// A held match did not trade: the maker rejected it, its hold window
+    /// ran out, or the maker left the book in the meantime
+    LastLookRejected {
+        match_id: u64,
+        maker_id: OrderId,
+        taker_id: OrderId,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+/// Why an order was rejected
+///
+/// `Display` gives the text carried in `OrderEvent::OrderRejected::reason`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RejectReason {
+    /// Order quantity is zero or negative
+    NonPositiveQuantity,
+    /// Display (iceberg) quantity is zero or negative
+    NonPositiveDisplayQuantity,
+    /// Minimum fill is zero or negative
+    NonPositiveMinFill,
+    /// Minimum fill is larger than the order
+    MinFillExceedsQuantity,
+    /// Not enough crossing liquidity to meet the minimum fill
+    MinFillUnavailable,
+    /// Not enough crossing liquidity to fill a fill-or-kill order in full
+    FillOrKillUnavailable,
+    /// Limit order without a price
+    MissingLimitPrice,
+    /// Pegged order whose reference price is missing
+    PegUnavailable,
+    /// Price is zero or negative on a book that requires positive prices
+    NonPositivePrice,
+    /// Price is not a multiple of the tick size
+    TickViolation,
+    /// Quantity is not a multiple of the lot size
+    LotViolation,
+    /// Price outside the fat-finger band
+    OutsidePriceBand,
+    /// Post-only order would take liquidity
+    PostOnlyWouldCross,
+    /// Order would trade against its own account or account group
+    SelfTrade,
+    /// Reduce-only order would increase the position
+    ReduceOnlyIncreasesPosition,
+    /// User exceeded the order rate limit
+    RateLimited,
+    /// Order would open a level past the maximum book depth
+    DepthLimit,
+    /// Order would join a level already holding the maximum number of orders
+    LevelFull,
+    /// Trading is halted
+    Halted,
+    /// Engine is shutting down and takes no new orders
+    Draining,
+    /// Another live order of the same user carries this client order id
+    DuplicateClientOrderId,
+    /// Order type or time in force not accepted while in auction
+    NotAcceptedInAuction,
+    /// Matching algorithm cannot match market orders
+    MarketOrdersUnsupported,
+    /// Market-with-protection order on an engine without a tick size
+    ProtectionUnavailable,
+    /// No engine is registered for the order's instrument
+    UnknownInstrument,
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            RejectReason::NonPositiveQuantity => "Quantity must be positive",
+            RejectReason::NonPositiveDisplayQuantity => "Display quantity must be positive",
+            RejectReason::NonPositiveMinFill => "Minimum fill must be positive",
+            RejectReason::MinFillExceedsQuantity => "Minimum fill exceeds order quantity",
+            RejectReason::MinFillUnavailable => "minimum fill not available",
+            RejectReason::FillOrKillUnavailable => "fill-or-kill would not fully fill",
+            RejectReason::MissingLimitPrice => "Limit orders must have a price",
+            RejectReason::PegUnavailable => "peg reference unavailable",
+            RejectReason::NonPositivePrice => "Price must be positive",
+            RejectReason::TickViolation => "Price is not a multiple of the tick size",
+            RejectReason::LotViolation => "Quantity is not a multiple of the lot size",
+            RejectReason::OutsidePriceBand => "outside price band",
+            RejectReason::PostOnlyWouldCross => "post-only would cross",
+            RejectReason::SelfTrade => "self-trade prevented",
+            RejectReason::ReduceOnlyIncreasesPosition => {
+                "Reduce-only order would increase position"
+            },
+            RejectReason::RateLimited => "rate limit exceeded",
+            RejectReason::DepthLimit => "book depth limit reached",
+            RejectReason::LevelFull => "level full",
+            RejectReason::Halted => "market halted",
+            RejectReason::Draining => "engine draining",
+            RejectReason::DuplicateClientOrderId => "duplicate client order id",
+            RejectReason::NotAcceptedInAuction => "not accepted during auction",
+            RejectReason::MarketOrdersUnsupported => "market orders not supported",
+            RejectReason::ProtectionUnavailable => "market protection needs a tick size",
+            RejectReason::UnknownInstrument => "Unknown instrument",
+        };
+        f.write_str(text)
+    }
 }
 
 /// Event handler trait for processing matching engine events
@@ -87,6 +294,25 @@ pub trait EventHandler: Send + Sync {
             self.on_event(event);
         }
     }
+
+    /// Batch handler for events stamped with the engine's event sequence
+    ///
+    /// The matching engine delivers through this method. Override it to detect
+    /// gaps; the default drops the sequence numbers and calls `on_events`.
+    fn on_sequenced_events(&self, events: Vec<SequencedEvent>) {
+        self.on_events(events.into_iter().map(|e| e.event).collect());
+    }
+}
+
+/// Event with its position in an engine's event stream
+///
+/// Sequence numbers start at 1 and increase by one per event, per engine, so
+/// a consumer that sees a jump has missed events.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SequencedEvent {
+    pub seq: u64,
+    pub event: OrderEvent,
 }
 
 /// No-op event handler for testing
@@ -6,6 +6,7 @@
 use crate::domain::{Order, OrderBookSide, Trade};
 use crate::numeric::{Price, Quantity};
 use std::sync::Arc;
+use std::time::Duration;
 
 /// Strategy pattern interface for matching algorithms
 /// Implementations: PriceTime (FIFO), ProRata, SizeProRata, LMM Priority, etc.
@@ -20,22 +21,71 @@ pub trait MatchingAlgorithm: Send + Sync {
     /// Vector of trades generated from matching
     fn match_order(&self, incoming_order: Arc<Order>, opposite_side: &OrderBookSide) -> Vec<Trade>;
 
+    /// Match like `match_order`, appending the trades to `out`
+    ///
+    /// Lets hot loops reuse one buffer instead of allocating per call; `out`
+    /// is not cleared first. Default implementation calls `match_order`, so
+    /// only algorithms that override it avoid the allocation.
+    fn match_order_into(
+        &self,
+        incoming_order: Arc<Order>,
+        opposite_side: &OrderBookSide,
+        out: &mut Vec<Trade>,
+    ) {
+        out.extend(self.match_order(incoming_order, opposite_side));
+    }
+
     /// Get the algorithm name for logging/metrics
     fn name(&self) -> &str;
 
+    /// Optional: Update the reference mid price for algorithms that trade at it
+    /// Default implementation ignores the update
+    fn set_reference_mid(&self, _mid: Price) {}
+
+    /// Whether the algorithm can match market orders
+    /// The engine rejects market orders for algorithms that return false
+    fn supports_market_orders(&self) -> bool {
+        true
+    }
+
+    /// Optional: Window makers get to confirm or reject each prospective match
+    /// Default implementation executes matches immediately
+    fn last_look_hold(&self) -> Option<Duration> {
+        None
+    }
+
     /// Optional: Check if two prices can cross
-    /// Default implementation handles buy/sell logic
+    /// Default implementation: a limit buy crosses asks at or below its price,
+    /// a limit sell crosses bids at or above it, and market orders cross every
+    /// level (a market buy sweeps asks from the best price upward). Override
+    /// only for algorithms that trade away from the book's prices.
     fn prices_cross(&self, incoming: &Order, book_price: Price) -> bool {
         use crate::domain::Side;
 
-        let incoming_price = incoming.price.unwrap_or(match incoming.side {
-            Side::Buy => Price::MAX,
-            Side::Sell => Price::ZERO,
-        });
+        if incoming.is_market_order() {
+            return true;
+        }
+
+        match (incoming.side, incoming.price()) {
+            (Side::Buy, Some(limit)) => limit >= book_price,
+            (Side::Sell, Some(limit)) => limit <= book_price,
+            // Validation rejects priced order types without a price
+            (_, None) => true,
+        }
+    }
 
-        match incoming.side {
-            Side::Buy => incoming_price >= book_price,
-            Side::Sell => incoming_price <= book_price,
+    /// Check if `incoming` can trade against the level of `book` at `level_price`
+    /// Default implementation: `prices_cross` on a normal book. On an inverted
+    /// book the comparison flips, so a limit buy crosses asks at or above
+    /// its price.
+    fn crosses_level(&self, incoming: &Order, book: &OrderBookSide, level_price: Price) -> bool {
+        if !book.inverted {
+            return self.prices_cross(incoming, level_price);
+        }
+
+        match incoming.price() {
+            Some(limit) if !incoming.is_market_order() => book.is_at_or_better(level_price, limit),
+            _ => true,
         }
     }
 }
@@ -57,6 +107,10 @@ pub struct MatchingConfig {
 
     /// LMM allocation percentage (e.g., 0.4 for 40%)
     pub lmm_allocation_pct: Quantity,
+
+    /// Pro-rata shares are rounded down to multiples of this increment
+    /// (None allows any size)
+    pub min_trade_increment: Option<Quantity>,
 }
 
 impl Default for MatchingConfig {
@@ -67,6 +121,134 @@ impl Default for MatchingConfig {
             top_of_book_fifo: false,
             lmm_accounts: Vec::new(),
             lmm_allocation_pct: Quantity::ZERO,
+            min_trade_increment: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{OrderType, Side, TimeInForce};
+    use crate::engine::{
+        LmmPriority, MidpointMatch, PriceTimePriority, ProRata, ProRataTobFifo, ThresholdProRata,
+        WeightedProRata,
+    };
+    use rust_decimal::Decimal;
+
+    fn algorithms() -> Vec<Box<dyn MatchingAlgorithm>> {
+        vec![
+            Box::new(PriceTimePriority::new(false)),
+            Box::new(PriceTimePriority::new(true)),
+            Box::new(ProRata::new(Quantity::ZERO, false)),
+            Box::new(ProRataTobFifo::new(Quantity::ZERO)),
+            Box::new(ThresholdProRata::new(Quantity::ONE, Quantity::ZERO)),
+            Box::new(WeightedProRata::new(Decimal::ONE, Quantity::ZERO)),
+            Box::new(LmmPriority::new(Vec::new(), Quantity::ZERO, Quantity::ZERO)),
+            Box::new(MidpointMatch::new()),
+        ]
+    }
+
+    fn order(side: Side, order_type: OrderType, price: Option<i64>) -> Order {
+        Order::new(
+            "user".to_string(),
+            "BTC-USD".to_string(),
+            side,
+            order_type,
+            price.map(|p| Price::from_integer(p).unwrap()),
+            Quantity::ONE,
+            TimeInForce::ImmediateOrCancel,
+        )
+    }
+
+    #[test]
+    fn test_market_orders_cross_any_level() {
+        let market_buy = order(Side::Buy, OrderType::Market, None);
+        let market_sell = order(Side::Sell, OrderType::Market, None);
+        for algorithm in algorithms() {
+            assert!(
+                algorithm.prices_cross(&market_buy, Price::MAX),
+                "{}",
+                algorithm.name()
+            );
+            assert!(
+                algorithm.prices_cross(&market_sell, Price::MIN),
+                "{}",
+                algorithm.name()
+            );
+            assert!(
+                algorithm.prices_cross(&market_sell, -Price::from_integer(5).unwrap()),
+                "{}",
+                algorithm.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_limit_orders_cross_at_or_through_their_price() {
+        let buy = order(Side::Buy, OrderType::Limit, Some(100));
+        let sell = order(Side::Sell, OrderType::Limit, Some(100));
+        let price = |p| Price::from_integer(p).unwrap();
+        for algorithm in algorithms() {
+            assert!(algorithm.prices_cross(&buy, price(100)));
+            assert!(algorithm.prices_cross(&buy, price(99)));
+            assert!(!algorithm.prices_cross(&buy, price(101)));
+            assert!(algorithm.prices_cross(&sell, price(100)));
+            assert!(algorithm.prices_cross(&sell, price(101)));
+            assert!(!algorithm.prices_cross(&sell, price(99)));
+        }
+    }
+
+    #[test]
+    fn test_sweep_takes_levels_in_strict_price_order() {
+        let price = |p| Price::from_integer(p).unwrap();
+        // Midpoint trades at the mid, not at book levels
+        for algorithm in algorithms()
+            .into_iter()
+            .filter(|algorithm| algorithm.name() != "Midpoint")
+        {
+            // Two makers per level, levels inserted out of price order
+            let asks = OrderBookSide::new(Side::Sell);
+            for p in [50020, 50000, 50010] {
+                for size in [1, 2] {
+                    asks.add_order(Arc::new(Order::new(
+                        "maker".to_string(),
+                        "BTC-USD".to_string(),
+                        Side::Sell,
+                        OrderType::Limit,
+                        Some(price(p)),
+                        Quantity::from_integer(size).unwrap(),
+                        TimeInForce::GoodTillCancel,
+                    )));
+                }
+            }
+
+            let buy = Arc::new(Order::new(
+                "taker".to_string(),
+                "BTC-USD".to_string(),
+                Side::Buy,
+                OrderType::Limit,
+                Some(price(50020)),
+                Quantity::from_integer(8).unwrap(),
+                TimeInForce::ImmediateOrCancel,
+            ));
+            let trades = algorithm.match_order(buy, &asks);
+
+            let mut levels: Vec<Price> = trades.iter().map(|t| t.price).collect();
+            levels.dedup();
+            assert_eq!(
+                levels,
+                vec![price(50000), price(50010), price(50020)],
+                "{}",
+                algorithm.name()
+            );
+            // The last level is only partly taken
+            assert_eq!(
+                asks.best_price(),
+                Some(price(50020)),
+                "{}",
+                algorithm.name()
+            );
         }
     }
 }
@@ -3,8 +3,14 @@
 // Contains all trait definitions and contracts
 // ============================================================================
 
+mod channel_event_handler;
+mod composite_event_handler;
 mod event_handler;
 mod matching_algorithm;
 
-pub use event_handler::{EventHandler, LoggingEventHandler, NoOpEventHandler, OrderEvent};
+pub use channel_event_handler::{BackpressurePolicy, ChannelEventHandler};
+pub use composite_event_handler::CompositeEventHandler;
+pub use event_handler::{
+    EventHandler, LoggingEventHandler, NoOpEventHandler, OrderEvent, RejectReason, SequencedEvent,
+};
 pub use matching_algorithm::{MatchingAlgorithm, MatchingConfig};
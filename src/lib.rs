@@ -51,7 +51,10 @@
 
 pub mod domain;
 pub mod engine;
+pub mod fix;
 pub mod interfaces;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod numeric;
 pub mod platform;
 
@@ -62,8 +65,8 @@ pub mod simd {
 }
 pub mod utils {
     pub use crate::platform::{
-        get_available_cores, pin_current_thread_to_core, pin_current_thread_to_node, NumaNode,
-        NumaTopology,
+        get_available_cores, pin_current_thread_to_core, pin_current_thread_to_node,
+        recommended_core, NumaNode, NumaTopology,
     };
 }
 
@@ -71,16 +74,22 @@ pub mod utils {
 pub mod prelude {
     pub use crate::domain::order::state::{OrderState, OrderStateTransition};
     pub use crate::domain::{
-        MatchingAlgorithmType, Order, OrderBookConfig, OrderBookSide, OrderBookSnapshot,
-        OrderBookType, OrderId, OrderType, Side, TimeInForce, Trade,
+        ChecksumSchedule, CircuitBreaker, FeeSchedule, FillEstimate, FillReport, L3Level,
+        L3Snapshot, MatchingAlgorithmType, Order, OrderBookConfig, OrderBookEntry, OrderBookSide,
+        OrderBookSnapshot, OrderBookType, OrderId, OrderOrigin, OrderStatus, OrderType,
+        PegReference, PriceBand, RemainderPolicy, Side, SnapshotDiff, TimeInForce, Trade,
+        TradePriceRule,
     };
     pub use crate::engine::{
-        create_from_config, LmmPriority, MatchingEngine, MatchingEngineBuilder, PriceTimePriority,
-        ProRata, ProRataTobFifo, ThresholdProRata,
+        create_from_config, AuctionResult, Clock, EngineStats, LastLook, LmmPriority,
+        MatchingEngine, MatchingEngineBuilder, MatchingVenue, MidpointMatch, MockClock, Position,
+        PositionBook, PriceTimePriority, ProRata, ProRataRounding, ProRataTobFifo, ReaperHandle,
+        ShareRounding, SystemClock, ThresholdProRata, WeightedProRata,
     };
     pub use crate::interfaces::{
-        EventHandler, LoggingEventHandler, MatchingAlgorithm, MatchingConfig, NoOpEventHandler,
-        OrderEvent,
+        BackpressurePolicy, ChannelEventHandler, CompositeEventHandler, EventHandler,
+        LoggingEventHandler, MatchingAlgorithm, MatchingConfig, NoOpEventHandler, OrderEvent,
+        RejectReason,
     };
     pub use crate::platform::{create_simd_matcher, SimdMatcher};
 }
@@ -0,0 +1,227 @@
+// ============================================================================
+// Metrics
+// Lock-free counters, gauges and histograms with Prometheus text rendering
+// ============================================================================
+
+use std::fmt::{Display, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Upper bounds of the match latency buckets, in nanoseconds
+const LATENCY_BUCKETS_NS: [u64; 13] = [
+    1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000, 500_000, 1_000_000, 2_500_000,
+    5_000_000, 10_000_000,
+];
+
+/// Monotonically increasing count
+///
+/// Updates use relaxed atomics: totals are exact, but a reader racing a
+/// writer may see counters that are momentarily out of step with each other.
+#[derive(Debug, Default)]
+pub struct Counter(AtomicU64);
+
+impl Counter {
+    #[inline]
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    #[inline]
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Latency histogram with fixed buckets from 1µs to 10ms
+#[derive(Debug)]
+pub struct Histogram {
+    /// Non-cumulative count per bucket; the last slot is the overflow (+Inf)
+    buckets: [AtomicU64; LATENCY_BUCKETS_NS.len() + 1],
+    sum_ns: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            sum_ns: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    /// Record one observation
+    #[inline]
+    pub fn observe(&self, elapsed: Duration) {
+        let ns = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        let bucket = LATENCY_BUCKETS_NS.partition_point(|&bound| bound < ns);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.sum_ns.fetch_add(ns, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Start timing; the elapsed time is observed when the timer is dropped
+    #[inline]
+    pub fn start_timer(&self) -> HistogramTimer<'_> {
+        HistogramTimer {
+            histogram: self,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+}
+
+/// Observes the time since its creation into a histogram on drop
+pub struct HistogramTimer<'a> {
+    histogram: &'a Histogram,
+    started: Instant,
+}
+
+impl Drop for HistogramTimer<'_> {
+    fn drop(&mut self) {
+        self.histogram.observe(self.started.elapsed());
+    }
+}
+
+/// Counters the matching engine updates on its hot path
+#[derive(Debug, Default)]
+pub struct EngineMetrics {
+    /// Orders handed to the engine, accepted or not
+    pub orders_submitted: Counter,
+    /// Orders rejected on arrival
+    pub orders_rejected: Counter,
+    /// Trades executed
+    pub trades: Counter,
+    /// Time to process one submitted order
+    pub match_latency: Histogram,
+}
+
+// ============================================================================
+// Prometheus Text Format
+// ============================================================================
+
+/// Builder for the Prometheus text exposition format
+///
+/// Every sample carries the `labels` given to [`new`](Self::new), e.g. the
+/// instrument, followed by any per-sample labels.
+pub struct PrometheusWriter {
+    out: String,
+    labels: String,
+}
+
+impl PrometheusWriter {
+    pub fn new(labels: &[(&str, &str)]) -> Self {
+        Self {
+            out: String::new(),
+            labels: format_labels(labels),
+        }
+    }
+
+    /// Write a counter with its HELP and TYPE lines
+    pub fn counter(&mut self, name: &str, help: &str, value: u64) {
+        self.header(name, help, "counter");
+        self.sample(name, &[], value);
+    }
+
+    /// Write a gauge with one sample per label set
+    pub fn gauge<V: Display>(&mut self, name: &str, help: &str, samples: &[(&[(&str, &str)], V)]) {
+        self.header(name, help, "gauge");
+        for (labels, value) in samples {
+            self.sample(name, labels, value);
+        }
+    }
+
+    /// Write a latency histogram in seconds
+    pub fn histogram(&mut self, name: &str, help: &str, histogram: &Histogram) {
+        self.header(name, help, "histogram");
+        let bucket_name = format!("{}_bucket", name);
+        let mut cumulative = 0;
+        for (bound, count) in LATENCY_BUCKETS_NS.iter().zip(&histogram.buckets) {
+            cumulative += count.load(Ordering::Relaxed);
+            let le = seconds(*bound).to_string();
+            self.sample(&bucket_name, &[("le", &le)], cumulative);
+        }
+        cumulative += histogram.buckets[LATENCY_BUCKETS_NS.len()].load(Ordering::Relaxed);
+        self.sample(&bucket_name, &[("le", "+Inf")], cumulative);
+        let sum = seconds(histogram.sum_ns.load(Ordering::Relaxed));
+        self.sample(&format!("{}_sum", name), &[], sum);
+        self.sample(&format!("{}_count", name), &[], histogram.count());
+    }
+
+    pub fn finish(self) -> String {
+        self.out
+    }
+
+    fn header(&mut self, name: &str, help: &str, kind: &str) {
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind);
+    }
+
+    fn sample<V: Display>(&mut self, name: &str, labels: &[(&str, &str)], value: V) {
+        let extra = format_labels(labels);
+        let labels = match (self.labels.is_empty(), extra.is_empty()) {
+            (true, true) => String::new(),
+            (false, true) => format!("{{{}}}", self.labels),
+            (true, false) => format!("{{{}}}", extra),
+            (false, false) => format!("{{{},{}}}", self.labels, extra),
+        };
+        let _ = writeln!(self.out, "{}{} {}", name, labels, value);
+    }
+}
+
+/// `a="x",b="y"` with label values escaped
+fn format_labels(labels: &[(&str, &str)]) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| {
+            let escaped = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, escaped)
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn seconds(ns: u64) -> f64 {
+    ns as f64 / 1e9
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets_are_cumulative() {
+        let histogram = Histogram::default();
+        histogram.observe(Duration::from_nanos(500));
+        histogram.observe(Duration::from_micros(3));
+        histogram.observe(Duration::from_secs(1));
+
+        let mut writer = PrometheusWriter::new(&[("instrument", "BTC-USD")]);
+        writer.histogram("latency_seconds", "Latency", &histogram);
+        let text = writer.finish();
+
+        assert!(text.contains("# TYPE latency_seconds histogram"));
+        assert!(text.contains("latency_seconds_bucket{instrument=\"BTC-USD\",le=\"0.000001\"} 1"));
+        assert!(text.contains("latency_seconds_bucket{instrument=\"BTC-USD\",le=\"0.000005\"} 2"));
+        assert!(text.contains("latency_seconds_bucket{instrument=\"BTC-USD\",le=\"0.01\"} 2"));
+        assert!(text.contains("latency_seconds_bucket{instrument=\"BTC-USD\",le=\"+Inf\"} 3"));
+        assert!(text.contains("latency_seconds_count{instrument=\"BTC-USD\"} 3"));
+    }
+
+    #[test]
+    fn test_label_values_are_escaped() {
+        assert_eq!(format_labels(&[("a", "x\"y\\z")]), "a=\"x\\\"y\\\\z\"");
+    }
+}
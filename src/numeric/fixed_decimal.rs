@@ -7,6 +7,7 @@ use super::errors::{NumericError, NumericResult};
 use std::cmp::Ordering;
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
 use std::ops::{Add, Neg, Sub};
 
 #[cfg(feature = "serde")]
@@ -33,9 +34,85 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 /// let qty = FixedDecimal::<9>::from_str("2.5")?;      // 2.500000000
 /// let total = price.checked_mul(qty)?;                 // 250.000000000
 /// ```
-#[derive(Clone, Copy)]
+///
+/// # Units
+/// `U` is a zero-sized unit tag. Values with different tags are distinct
+/// types, so a [`Price`] cannot be added to or compared with a [`Quantity`]
+/// by accident; crossing units takes an explicit [`with_unit`](Self::with_unit)
+/// and the price-times-quantity product goes through [`Price::notional`].
 #[repr(transparent)]
-pub struct FixedDecimal<const DECIMALS: u8 = 9>(i64);
+pub struct FixedDecimal<const DECIMALS: u8 = 9, U = Unitless>(i64, PhantomData<U>);
+
+impl<const D: u8, U> Clone for FixedDecimal<D, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const D: u8, U> Copy for FixedDecimal<D, U> {}
+
+// ============================================================================
+// Units
+// ============================================================================
+
+/// Unit tag for plain numbers (the default)
+#[derive(Debug)]
+pub enum Unitless {}
+
+/// Unit tag for prices
+#[derive(Debug)]
+pub enum PriceUnit {}
+
+/// Unit tag for quantities
+#[derive(Debug)]
+pub enum QuantityUnit {}
+
+/// Unit tag for notionals (price times quantity)
+#[derive(Debug)]
+pub enum NotionalUnit {}
+
+// ============================================================================
+// Rounding
+// ============================================================================
+
+/// How a result that falls between two representable values is rounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundingMode {
+    /// Nearest value, ties away from zero
+    #[default]
+    HalfUp,
+    /// Nearest value, ties to the even neighbour (banker's rounding)
+    HalfEven,
+    /// Toward zero (truncation)
+    Down,
+    /// Away from zero
+    Up,
+}
+
+impl RoundingMode {
+    /// Round the truncated division `quotient`, `remainder` of some `n / den`
+    #[inline]
+    fn round(self, quotient: i128, remainder: i128, den: i128) -> i128 {
+        if remainder == 0 {
+            return quotient;
+        }
+
+        let away = if (remainder < 0) == (den < 0) {
+            quotient + 1
+        } else {
+            quotient - 1
+        };
+        let twice = 2 * remainder.abs();
+        match self {
+            RoundingMode::HalfUp if twice >= den.abs() => away,
+            RoundingMode::HalfEven if twice > den.abs() => away,
+            RoundingMode::HalfEven if twice == den.abs() && quotient % 2 != 0 => away,
+            RoundingMode::Up => away,
+            _ => quotient,
+        }
+    }
+}
 
 // ============================================================================
 // Scale Constants
@@ -52,24 +129,21 @@ const fn pow10(n: u8) -> i64 {
     result
 }
 
-impl<const D: u8> FixedDecimal<D> {
+impl<const D: u8, U> FixedDecimal<D, U> {
     /// The scale factor (10^DECIMALS)
     pub const SCALE: i64 = pow10(D);
 
-    /// Half scale for rounding (SCALE / 2)
-    const HALF_SCALE: i64 = pow10(D) / 2;
-
     /// Zero value
-    pub const ZERO: Self = Self(0);
+    pub const ZERO: Self = Self::from_raw(0);
 
     /// One (1.0)
-    pub const ONE: Self = Self(pow10(D));
+    pub const ONE: Self = Self::from_raw(pow10(D));
 
     /// Maximum representable value
-    pub const MAX: Self = Self(i64::MAX);
+    pub const MAX: Self = Self::from_raw(i64::MAX);
 
     /// Minimum representable value
-    pub const MIN: Self = Self(i64::MIN);
+    pub const MIN: Self = Self::from_raw(i64::MIN);
 
     // ========================================================================
     // Construction
@@ -80,7 +154,7 @@ impl<const D: u8> FixedDecimal<D> {
     /// Use this when you already have a scaled value (e.g., from SIMD operations).
     #[inline]
     pub const fn from_raw(raw: i64) -> Self {
-        Self(raw)
+        Self(raw, PhantomData)
     }
 
     /// Create from an integer value.
@@ -91,7 +165,7 @@ impl<const D: u8> FixedDecimal<D> {
     pub fn from_integer(value: i64) -> NumericResult<Self> {
         value
             .checked_mul(Self::SCALE)
-            .map(Self)
+            .map(Self::from_raw)
             .ok_or(NumericError::Overflow)
     }
 
@@ -124,7 +198,7 @@ impl<const D: u8> FixedDecimal<D> {
 
         int_scaled
             .checked_add(frac_signed)
-            .map(Self)
+            .map(Self::from_raw)
             .ok_or(NumericError::Overflow)
     }
 
@@ -176,7 +250,7 @@ impl<const D: u8> FixedDecimal<D> {
         if self.0 == i64::MIN {
             Err(NumericError::Overflow)
         } else {
-            Ok(Self(self.0.abs()))
+            Ok(Self::from_raw(self.0.abs()))
         }
     }
 
@@ -190,7 +264,7 @@ impl<const D: u8> FixedDecimal<D> {
     /// Returns `Overflow` or `Underflow` if the result is out of range.
     #[inline]
     pub fn checked_add(self, rhs: Self) -> NumericResult<Self> {
-        self.0.checked_add(rhs.0).map(Self).ok_or({
+        self.0.checked_add(rhs.0).map(Self::from_raw).ok_or({
             if rhs.0 > 0 {
                 NumericError::Overflow
             } else {
@@ -205,7 +279,7 @@ impl<const D: u8> FixedDecimal<D> {
     /// Returns `Overflow` or `Underflow` if the result is out of range.
     #[inline]
     pub fn checked_sub(self, rhs: Self) -> NumericResult<Self> {
-        self.0.checked_sub(rhs.0).map(Self).ok_or({
+        self.0.checked_sub(rhs.0).map(Self::from_raw).ok_or({
             if rhs.0 < 0 {
                 NumericError::Overflow
             } else {
@@ -223,37 +297,226 @@ impl<const D: u8> FixedDecimal<D> {
     /// Returns `Overflow` or `Underflow` if the result is out of range.
     #[inline]
     pub fn checked_mul(self, rhs: Self) -> NumericResult<Self> {
-        let scale = Self::SCALE as i128;
-        let half_scale = Self::HALF_SCALE as i128;
+        self.checked_mul_with(rhs, RoundingMode::HalfUp)
+    }
+
+    /// Checked multiplication with an explicit rounding mode.
+    ///
+    /// The i128 product is rounded according to `mode` while scaling back.
+    ///
+    /// # Errors
+    /// Returns `Overflow` or `Underflow` if the result is out of range.
+    #[inline]
+    pub fn checked_mul_with(self, rhs: Self, mode: RoundingMode) -> NumericResult<Self> {
         let product = (self.0 as i128) * (rhs.0 as i128);
+        let scale = Self::SCALE as i128;
+        Self::from_i128(mode.round(product / scale, product % scale, scale))
+    }
 
-        // Round half-up: add half scale before dividing (adjust sign for negative)
-        let rounded = if product >= 0 {
-            product + half_scale
-        } else {
-            product - half_scale
-        };
+    /// Multiply by an integer (no scaling needed).
+    ///
+    /// More efficient than `checked_mul` when multiplying by a whole number.
+    #[inline]
+    pub fn checked_mul_int(self, rhs: i64) -> NumericResult<Self> {
+        self.0
+            .checked_mul(rhs)
+            .map(Self::from_raw)
+            .ok_or(NumericError::Overflow)
+    }
+
+    /// `self + ticks * tick_size`, e.g. a price moved a number of ticks.
+    ///
+    /// Negative `ticks` move down. The product is kept in i128, so only the
+    /// final result has to fit.
+    ///
+    /// # Errors
+    /// Returns `Overflow` or `Underflow` if the result is out of range.
+    #[inline]
+    pub fn add_ticks(self, ticks: i64, tick_size: Self) -> NumericResult<Self> {
+        Self::from_i128(self.0 as i128 + ticks as i128 * tick_size.0 as i128)
+    }
+
+    /// Compute `self * num / den` with round half-up (away from zero).
+    ///
+    /// The product is kept in i128 and only the final quotient is rounded,
+    /// so proportional splits like `size * fill / total` stay exact.
+    ///
+    /// # Errors
+    /// - `DivisionByZero` if `den` is zero
+    /// - `Overflow`/`Underflow` if the result is out of range
+    #[inline]
+    pub fn mul_div(self, num: Self, den: Self) -> NumericResult<Self> {
+        let (quotient, remainder, den) = self.mul_div_parts(num, den)?;
+        Self::from_i128(RoundingMode::HalfUp.round(quotient, remainder, den))
+    }
 
-        let result = rounded / scale;
+    /// Compute `self * num / den`, truncating toward zero.
+    ///
+    /// # Errors
+    /// - `DivisionByZero` if `den` is zero
+    /// - `Overflow`/`Underflow` if the result is out of range
+    #[inline]
+    pub fn mul_div_trunc(self, num: Self, den: Self) -> NumericResult<Self> {
+        let (quotient, _, _) = self.mul_div_parts(num, den)?;
+        Self::from_i128(quotient)
+    }
 
-        if result > i64::MAX as i128 {
+    /// Checked remainder of `self / rhs`.
+    ///
+    /// Both operands share the scale, so the remainder is exact. Like `%` on
+    /// integers it truncates toward zero: the result takes the sign of `self`.
+    ///
+    /// # Errors
+    /// Returns `DivisionByZero` if `rhs` is zero.
+    #[inline]
+    pub fn checked_rem(self, rhs: Self) -> NumericResult<Self> {
+        if rhs.is_zero() {
+            return Err(NumericError::DivisionByZero);
+        }
+        // i128 so that `MIN % -1` cannot overflow
+        Self::from_i128(self.0 as i128 % rhs.0 as i128)
+    }
+
+    /// Whether `self` is a whole multiple of `rhs` (e.g. a price on the tick grid).
+    ///
+    /// Signs are ignored. Zero is the only multiple of zero.
+    #[inline]
+    pub fn is_multiple_of(self, rhs: Self) -> bool {
+        match self.checked_rem(rhs) {
+            Ok(remainder) => remainder.is_zero(),
+            Err(_) => self.is_zero(),
+        }
+    }
+
+    /// `percent` percent of `self` (e.g. 5 percent of 200 is 10), rounded half-up.
+    ///
+    /// # Errors
+    /// Returns `Overflow` or `Underflow` if the result is out of range.
+    #[inline]
+    pub fn percent_of(self, percent: Self) -> NumericResult<Self> {
+        self.mul_div(percent, Self::from_integer(100)?)
+    }
+
+    /// Quotient, remainder and divisor of `self * num / den` in i128
+    #[inline]
+    fn mul_div_parts(self, num: Self, den: Self) -> NumericResult<(i128, i128, i128)> {
+        if den.is_zero() {
+            return Err(NumericError::DivisionByZero);
+        }
+        let product = (self.0 as i128) * (num.0 as i128);
+        let den = den.0 as i128;
+        Ok((product / den, product % den, den))
+    }
+
+    /// Narrow an i128 raw value, reporting the direction of any overflow
+    #[inline]
+    fn from_i128(raw: i128) -> NumericResult<Self> {
+        if raw > i64::MAX as i128 {
             Err(NumericError::Overflow)
-        } else if result < i64::MIN as i128 {
+        } else if raw < i64::MIN as i128 {
             Err(NumericError::Underflow)
         } else {
-            Ok(Self(result as i64))
+            Ok(Self::from_raw(raw as i64))
         }
     }
 
-    /// Multiply by an integer (no scaling needed).
+    /// Saturating addition. Clamps to `MAX`/`MIN` instead of erroring.
+    #[inline]
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self::from_raw(self.0.saturating_add(rhs.0))
+    }
+
+    /// Saturating subtraction. Clamps to `MAX`/`MIN` instead of erroring.
+    #[inline]
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self::from_raw(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Saturating multiplication with round half-up.
     ///
-    /// More efficient than `checked_mul` when multiplying by a whole number.
+    /// Clamps to `MAX`/`MIN` instead of erroring.
     #[inline]
-    pub fn checked_mul_int(self, rhs: i64) -> NumericResult<Self> {
+    pub fn saturating_mul(self, rhs: Self) -> Self {
+        match self.checked_mul(rhs) {
+            Ok(result) => result,
+            Err(NumericError::Underflow) => Self::MIN,
+            Err(_) => Self::MAX,
+        }
+    }
+
+    // ========================================================================
+    // Precision Conversion
+    // ========================================================================
+
+    /// Convert to a different number of decimal places.
+    ///
+    /// Widening multiplies the raw value; narrowing divides it and requires
+    /// the dropped digits to be zero.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let price = FixedDecimal::<9>::from_str("12.34")?;
+    /// let cents: FixedDecimal<2> = price.convert()?;
+    /// ```
+    ///
+    /// # Errors
+    /// - `PrecisionLoss` if narrowing would drop nonzero digits
+    /// - `Overflow`/`Underflow` if widening goes out of range
+    #[inline]
+    pub fn convert<const E: u8>(self) -> NumericResult<FixedDecimal<E, U>> {
+        if E >= D {
+            return self.widen();
+        }
+
+        let divisor = pow10(D - E);
+        if self.0 % divisor != 0 {
+            return Err(NumericError::PrecisionLoss);
+        }
+        Ok(FixedDecimal::from_raw(self.0 / divisor))
+    }
+
+    /// Reinterpret the value under another unit tag, keeping the raw value
+    /// and precision.
+    ///
+    /// This is the explicit escape hatch for crossing units, e.g. treating a
+    /// quantity as a plain ratio. Combine with [`convert`](Self::convert) when
+    /// the two units carry different precisions.
+    #[inline]
+    pub const fn with_unit<V>(self) -> FixedDecimal<D, V> {
+        FixedDecimal::from_raw(self.0)
+    }
+
+    /// Convert to a different number of decimal places, rounding half-up
+    /// (away from zero) when narrowing.
+    ///
+    /// # Errors
+    /// Returns `Overflow` or `Underflow` if widening goes out of range.
+    #[inline]
+    pub fn convert_rounding<const E: u8>(self) -> NumericResult<FixedDecimal<E, U>> {
+        if E >= D {
+            return self.widen();
+        }
+
+        let divisor = pow10(D - E) as i128;
+        let half = divisor / 2;
+        let raw = self.0 as i128;
+        let rounded = if raw >= 0 { raw + half } else { raw - half };
+
+        // Narrowing only shrinks the magnitude, so the result always fits
+        Ok(FixedDecimal::from_raw((rounded / divisor) as i64))
+    }
+
+    /// Rescale to at least as many decimal places.
+    #[inline]
+    fn widen<const E: u8>(self) -> NumericResult<FixedDecimal<E, U>> {
         self.0
-            .checked_mul(rhs)
-            .map(Self)
-            .ok_or(NumericError::Overflow)
+            .checked_mul(pow10(E - D))
+            .map(FixedDecimal::from_raw)
+            .ok_or(if self.0 > 0 {
+                NumericError::Overflow
+            } else {
+                NumericError::Underflow
+            })
     }
 
     // ========================================================================
@@ -263,13 +526,68 @@ impl<const D: u8> FixedDecimal<D> {
     /// Returns the minimum of two values.
     #[inline]
     pub fn min(self, other: Self) -> Self {
-        Self(self.0.min(other.0))
+        Self::from_raw(self.0.min(other.0))
     }
 
     /// Returns the maximum of two values.
     #[inline]
     pub fn max(self, other: Self) -> Self {
-        Self(self.0.max(other.0))
+        Self::from_raw(self.0.max(other.0))
+    }
+}
+
+// ============================================================================
+// Notional
+// ============================================================================
+
+impl<const D: u8> FixedDecimal<D, PriceUnit> {
+    /// Notional value `price * qty`, rounded half-up to `N` decimals.
+    ///
+    /// The product is formed exactly in i128 at `D + Q` decimals and only
+    /// then rescaled, so price and quantity may carry different precisions.
+    ///
+    /// # Errors
+    /// `Overflow`/`Underflow` if the notional does not fit at `N` decimals.
+    #[inline]
+    pub fn notional<const Q: u8, const N: u8>(
+        self,
+        qty: FixedDecimal<Q, QuantityUnit>,
+    ) -> NumericResult<FixedDecimal<N, NotionalUnit>> {
+        let product = (self.0 as i128) * (qty.0 as i128);
+        let raw = if N >= D + Q {
+            product
+                .checked_mul(10i128.pow((N - D - Q) as u32))
+                .ok_or(if product > 0 {
+                    NumericError::Overflow
+                } else {
+                    NumericError::Underflow
+                })?
+        } else {
+            let scale = 10i128.pow((D + Q - N) as u32);
+            RoundingMode::HalfUp.round(product / scale, product % scale, scale)
+        };
+        FixedDecimal::from_i128(raw)
+    }
+
+    /// Notional value `price * qty` as an i128 raw value at the price scale.
+    ///
+    /// Same rounding as `checked_mul` (half-up at `D` decimals), but the
+    /// result is not narrowed to i64, so notionals far beyond `MAX` (for
+    /// example 50000 * 1e9) are representable. Intended for fee and risk
+    /// accumulation; use `notional_to_decimal` to display the result.
+    ///
+    /// # Errors
+    /// None today: the i128 intermediate cannot overflow for any pair of
+    /// i64 operands. The `Result` keeps the signature in line with the
+    /// other checked operations.
+    #[inline]
+    pub fn checked_notional<const Q: u8>(
+        price: Self,
+        qty: FixedDecimal<Q, QuantityUnit>,
+    ) -> NumericResult<i128> {
+        let product = (price.0 as i128) * (qty.0 as i128);
+        let scale = pow10(Q) as i128;
+        Ok(RoundingMode::HalfUp.round(product / scale, product % scale, scale))
     }
 }
 
@@ -277,54 +595,54 @@ impl<const D: u8> FixedDecimal<D> {
 // Trait Implementations
 // ============================================================================
 
-impl<const D: u8> Default for FixedDecimal<D> {
+impl<const D: u8, U> Default for FixedDecimal<D, U> {
     #[inline]
     fn default() -> Self {
         Self::ZERO
     }
 }
 
-impl<const D: u8> PartialEq for FixedDecimal<D> {
+impl<const D: u8, U> PartialEq for FixedDecimal<D, U> {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
         self.0 == other.0
     }
 }
 
-impl<const D: u8> Eq for FixedDecimal<D> {}
+impl<const D: u8, U> Eq for FixedDecimal<D, U> {}
 
-impl<const D: u8> PartialOrd for FixedDecimal<D> {
+impl<const D: u8, U> PartialOrd for FixedDecimal<D, U> {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl<const D: u8> Ord for FixedDecimal<D> {
+impl<const D: u8, U> Ord for FixedDecimal<D, U> {
     #[inline]
     fn cmp(&self, other: &Self) -> Ordering {
         self.0.cmp(&other.0)
     }
 }
 
-impl<const D: u8> Hash for FixedDecimal<D> {
+impl<const D: u8, U> Hash for FixedDecimal<D, U> {
     #[inline]
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.hash(state);
     }
 }
 
-impl<const D: u8> Neg for FixedDecimal<D> {
+impl<const D: u8, U> Neg for FixedDecimal<D, U> {
     type Output = Self;
 
     #[inline]
     fn neg(self) -> Self::Output {
-        Self(-self.0)
+        Self::from_raw(-self.0)
     }
 }
 
 // Infallible Add/Sub for ergonomics (panics on overflow - use checked_* in production)
-impl<const D: u8> Add for FixedDecimal<D> {
+impl<const D: u8, U> Add for FixedDecimal<D, U> {
     type Output = Self;
 
     #[inline]
@@ -334,7 +652,7 @@ impl<const D: u8> Add for FixedDecimal<D> {
     }
 }
 
-impl<const D: u8> Sub for FixedDecimal<D> {
+impl<const D: u8, U> Sub for FixedDecimal<D, U> {
     type Output = Self;
 
     #[inline]
@@ -348,13 +666,13 @@ impl<const D: u8> Sub for FixedDecimal<D> {
 // Display and Debug
 // ============================================================================
 
-impl<const D: u8> fmt::Debug for FixedDecimal<D> {
+impl<const D: u8, U> fmt::Debug for FixedDecimal<D, U> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "FixedDecimal<{}>({}, raw={})", D, self, self.0)
     }
 }
 
-impl<const D: u8> fmt::Display for FixedDecimal<D> {
+impl<const D: u8, U> fmt::Display for FixedDecimal<D, U> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let int_part = self.integer_part();
         let frac_part = self.fractional_part();
@@ -374,7 +692,7 @@ impl<const D: u8> fmt::Display for FixedDecimal<D> {
 // Conversion from rust_decimal (for API boundaries)
 // ============================================================================
 
-impl<const D: u8> FixedDecimal<D> {
+impl<const D: u8, U> FixedDecimal<D, U> {
     /// Convert from rust_decimal::Decimal.
     ///
     /// This is intended for API boundaries only (parsing user input).
@@ -407,7 +725,7 @@ impl<const D: u8> FixedDecimal<D> {
             }
         }
 
-        Ok(Self(raw))
+        Ok(Self::from_raw(raw))
     }
 
     /// Convert to rust_decimal::Decimal.
@@ -418,71 +736,167 @@ impl<const D: u8> FixedDecimal<D> {
         d.set_scale(D as u32).expect("valid scale");
         d
     }
+
+    /// Convert a notional from `checked_notional` to a Decimal for display.
+    ///
+    /// # Errors
+    /// `Overflow` if the value needs more than Decimal's 96-bit mantissa.
+    pub fn notional_to_decimal(notional: i128) -> NumericResult<rust_decimal::Decimal> {
+        rust_decimal::Decimal::try_from_i128_with_scale(notional, D as u32)
+            .map_err(|_| NumericError::Overflow)
+    }
+}
+
+// ============================================================================
+// Conversion from f64 (for API boundaries)
+// ============================================================================
+
+impl<const D: u8, U> FixedDecimal<D, U> {
+    /// Convert from f64, rounding half-to-even at the last decimal place.
+    ///
+    /// Like [`from_decimal`](Self::from_decimal) this is a boundary API for
+    /// ingesting external feeds. It is lossy: the f64 is scaled by `SCALE` in
+    /// floating point before rounding, so values that are not exactly
+    /// representable in binary may land one unit away from their decimal form.
+    ///
+    /// # Errors
+    /// - `InvalidInput` if the value is NaN or infinite
+    /// - `Overflow` if the value is too large
+    pub fn from_f64(value: f64) -> NumericResult<Self> {
+        if !value.is_finite() {
+            return Err(NumericError::InvalidInput);
+        }
+
+        let scaled = (value * Self::SCALE as f64).round_ties_even();
+        // i64::MIN is -2^63 and exactly representable; i64::MAX is not
+        if scaled < i64::MIN as f64 || scaled >= -(i64::MIN as f64) {
+            return Err(NumericError::Overflow);
+        }
+
+        Ok(Self::from_raw(scaled as i64))
+    }
+
+    /// Convert to f64.
+    ///
+    /// This is lossy for values with more than ~15 significant digits.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::SCALE as f64
+    }
 }
 
 // ============================================================================
 // String Parsing
 // ============================================================================
 
-impl<const D: u8> std::str::FromStr for FixedDecimal<D> {
+impl<const D: u8, U> std::str::FromStr for FixedDecimal<D, U> {
     type Err = NumericError;
 
     /// Parse from a decimal string.
     ///
+    /// Accepts an optional sign, `_` between digits for grouping and an
+    /// `e`/`E` exponent. The value is scaled exactly: extra decimals are
+    /// accepted as long as they are zeros, otherwise `PrecisionLoss`.
+    ///
     /// # Examples
     /// - "123" -> 123.000000000
     /// - "123.456" -> 123.456000000
     /// - "-0.001" -> -0.001000000
+    /// - "+1_000.5" -> 1000.500000000
+    /// - "1.5e-3" -> 0.001500000
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let s = s.trim();
-        if s.is_empty() {
-            return Err(NumericError::InvalidInput);
-        }
 
-        // Check for negative
-        let (is_negative, s) = if let Some(rest) = s.strip_prefix('-') {
-            (true, rest)
-        } else {
-            (false, s)
+        // Sign
+        let (is_negative, s) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
         };
 
-        // Split on decimal point
-        let (int_str, frac_str) = if let Some(pos) = s.find('.') {
-            (&s[..pos], Some(&s[pos + 1..]))
-        } else {
-            (s, None)
+        // Exponent
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(pos) => (&s[..pos], parse_exponent(&s[pos + 1..])?),
+            None => (s, 0),
         };
 
-        // Parse integer part
-        let int_val: i64 = if int_str.is_empty() {
-            0
-        } else {
-            int_str.parse().map_err(|_| NumericError::InvalidInput)?
+        // Split on decimal point
+        let (int_str, frac_str) = match mantissa.split_once('.') {
+            Some((int_str, frac_str)) => (int_str, frac_str),
+            None => (mantissa, ""),
         };
+        let int_digits = grouped_digits(int_str).ok_or(NumericError::InvalidInput)?;
+        let frac_digits = grouped_digits(frac_str).ok_or(NumericError::InvalidInput)?;
+        if int_digits.is_empty() && frac_digits.is_empty() {
+            return Err(NumericError::InvalidInput);
+        }
 
-        // Parse fractional part
-        let frac_val: u64 = if let Some(frac) = frac_str {
-            if frac.is_empty() {
-                0
-            } else if frac.len() > D as usize {
-                return Err(NumericError::PrecisionLoss);
-            } else {
-                // Pad with zeros to reach DECIMALS length
-                let padded = format!("{:0<width$}", frac, width = D as usize);
-                padded.parse().map_err(|_| NumericError::InvalidInput)?
-            }
-        } else {
-            0
+        // value = digits * 10^(exponent - fraction digits), raw = value * 10^D
+        let mut digits = int_digits;
+        digits.extend(frac_digits.iter());
+        let mut shift = exponent
+            .saturating_sub(frac_digits.len() as i64)
+            .saturating_add(D as i64);
+
+        // Drop trailing zeros the scale cannot hold; any other digit is lost precision
+        while shift < 0 && digits.last() == Some(&0) {
+            digits.pop();
+            shift += 1;
+        }
+        let first_significant = digits.iter().position(|&d| d != 0);
+        let Some(first_significant) = first_significant else {
+            return Ok(Self::ZERO);
         };
+        if shift < 0 {
+            return Err(NumericError::PrecisionLoss);
+        }
+
+        // Combine in i128 so that MIN (whose magnitude exceeds MAX) still parses
+        let magnitude = digits[first_significant..]
+            .iter()
+            .try_fold(0i128, |acc, &d| acc.checked_mul(10)?.checked_add(d as i128))
+            .and_then(|m| m.checked_mul(10i128.checked_pow(u32::try_from(shift).ok()?)?))
+            .ok_or(NumericError::Overflow)?;
+        let raw = if is_negative { -magnitude } else { magnitude };
 
-        // Combine
-        let mut result = Self::from_parts(int_val, frac_val)?;
-        if is_negative {
-            result = -result;
+        i64::try_from(raw)
+            .map(Self::from_raw)
+            .map_err(|_| NumericError::Overflow)
+    }
+}
+
+/// Digits of a run like `1_000`, with `_` allowed only between two digits
+///
+/// An empty run yields no digits; `None` marks an invalid character or
+/// misplaced separator.
+fn grouped_digits(s: &str) -> Option<Vec<u8>> {
+    let bytes = s.as_bytes();
+    let mut digits = Vec::with_capacity(bytes.len());
+    for (i, &b) in bytes.iter().enumerate() {
+        match b {
+            b'0'..=b'9' => digits.push(b - b'0'),
+            b'_' if i > 0
+                && bytes[i - 1].is_ascii_digit()
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_digit) => {},
+            _ => return None,
         }
+    }
+    Some(digits)
+}
 
-        Ok(result)
+/// Signed decimal exponent, saturating far outside any representable range
+fn parse_exponent(s: &str) -> NumericResult<i64> {
+    let (is_negative, digits) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(NumericError::InvalidInput);
     }
+    let magnitude = digits.bytes().fold(0i64, |acc, b| {
+        acc.saturating_mul(10).saturating_add((b - b'0') as i64)
+    });
+    Ok(if is_negative { -magnitude } else { magnitude })
 }
 
 // ============================================================================
@@ -490,27 +904,36 @@ impl<const D: u8> std::str::FromStr for FixedDecimal<D> {
 // ============================================================================
 
 #[cfg(feature = "serde")]
-impl<const D: u8> Serialize for FixedDecimal<D> {
+impl<const D: u8, U> Serialize for FixedDecimal<D, U> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        // Serialize as string to preserve precision and readability
-        serializer.serialize_str(&self.to_string())
+        if serializer.is_human_readable() {
+            // Serialize as string to preserve precision and readability
+            serializer.serialize_str(&self.to_string())
+        } else {
+            // Binary formats carry the raw scaled value
+            serializer.serialize_i64(self.0)
+        }
     }
 }
 
 #[cfg(feature = "serde")]
-impl<'de, const D: u8> Deserialize<'de> for FixedDecimal<D> {
+impl<'de, const D: u8, U> Deserialize<'de> for FixedDecimal<D, U> {
     fn deserialize<De>(deserializer: De) -> Result<Self, De::Error>
     where
         De: Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            return i64::deserialize(deserializer).map(FixedDecimal::from_raw);
+        }
+
         // Support both string and numeric formats
-        struct FixedDecimalVisitor<const D: u8>;
+        struct FixedDecimalVisitor<const D: u8, U>(PhantomData<U>);
 
-        impl<const D: u8> serde::de::Visitor<'_> for FixedDecimalVisitor<D> {
-            type Value = FixedDecimal<D>;
+        impl<const D: u8, U> serde::de::Visitor<'_> for FixedDecimalVisitor<D, U> {
+            type Value = FixedDecimal<D, U>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("a decimal number as string or number")
@@ -550,7 +973,7 @@ impl<'de, const D: u8> Deserialize<'de> for FixedDecimal<D> {
             }
         }
 
-        deserializer.deserialize_any(FixedDecimalVisitor::<D>)
+        deserializer.deserialize_any(FixedDecimalVisitor::<D, U>(PhantomData))
     }
 }
 
@@ -558,11 +981,47 @@ impl<'de, const D: u8> Deserialize<'de> for FixedDecimal<D> {
 // Type Aliases for Common Use Cases
 // ============================================================================
 
-/// Price with 9 decimal places (nano-precision)
-pub type Price = FixedDecimal<9>;
+/// Decimal places carried by [`Price`]
+///
+/// 9 by default; the `price-decimals-2`, `-4` and `-6` features select a
+/// coarser tick. If several are enabled the coarsest wins.
+pub const PRICE_DECIMALS: u8 = if cfg!(feature = "price-decimals-2") {
+    2
+} else if cfg!(feature = "price-decimals-4") {
+    4
+} else if cfg!(feature = "price-decimals-6") {
+    6
+} else {
+    9
+};
+
+/// Decimal places carried by [`Quantity`]
+///
+/// 9 by default; the `qty-decimals-4`, `-6` and `-8` features select a
+/// coarser lot. If several are enabled the coarsest wins.
+pub const QTY_DECIMALS: u8 = if cfg!(feature = "qty-decimals-4") {
+    4
+} else if cfg!(feature = "qty-decimals-6") {
+    6
+} else if cfg!(feature = "qty-decimals-8") {
+    8
+} else {
+    9
+};
+
+/// Decimal places carried by [`Notional`]
+///
+/// Fixed at 9 whatever the price and quantity scales.
+pub const NOTIONAL_DECIMALS: u8 = 9;
 
-/// Quantity with 9 decimal places
-pub type Quantity = FixedDecimal<9>;
+/// Price with `PRICE_DECIMALS` decimal places (nano-precision by default)
+pub type Price = FixedDecimal<PRICE_DECIMALS, PriceUnit>;
+
+/// Quantity with `QTY_DECIMALS` decimal places
+pub type Quantity = FixedDecimal<QTY_DECIMALS, QuantityUnit>;
+
+/// Price times quantity with `NOTIONAL_DECIMALS` decimal places
+pub type Notional = FixedDecimal<NOTIONAL_DECIMALS, NotionalUnit>;
 
 // ============================================================================
 // Tests
@@ -667,6 +1126,50 @@ mod tests {
         assert_eq!(result.raw_value(), 999_999_999);
     }
 
+    #[test]
+    fn test_checked_mul_with_rounding_modes() {
+        type FD1 = FixedDecimal<1>;
+        let mul = |a: i64, b: i64, mode| FD1::from_raw(a).checked_mul_with(FD1::from_raw(b), mode);
+
+        // 0.5 * 0.5 = 0.25 and 0.5 * 1.5 = 0.75 are ties at one decimal place
+        for (a, b, half_up, half_even, down, up) in [
+            (5, 5, 3, 2, 2, 3),
+            (5, 15, 8, 8, 7, 8),
+            (-5, 5, -3, -2, -2, -3),
+            (3, 3, 1, 1, 0, 1),
+            (2, 5, 1, 1, 1, 1),
+        ] {
+            assert_eq!(
+                mul(a, b, RoundingMode::HalfUp).unwrap().raw_value(),
+                half_up
+            );
+            assert_eq!(
+                mul(a, b, RoundingMode::HalfEven).unwrap().raw_value(),
+                half_even
+            );
+            assert_eq!(mul(a, b, RoundingMode::Down).unwrap().raw_value(), down);
+            assert_eq!(mul(a, b, RoundingMode::Up).unwrap().raw_value(), up);
+        }
+
+        // checked_mul keeps rounding half-up
+        let nano = FD9::from_raw(1);
+        let half = FD9::from_raw(500_000_000);
+        assert_eq!(nano.checked_mul(half).unwrap().raw_value(), 1);
+        assert_eq!(
+            nano.checked_mul_with(half, RoundingMode::HalfEven)
+                .unwrap()
+                .raw_value(),
+            0
+        );
+        assert_eq!(
+            FD9::from_raw(3)
+                .checked_mul_with(half, RoundingMode::HalfEven)
+                .unwrap()
+                .raw_value(),
+            2
+        );
+    }
+
     #[test]
     fn test_checked_mul_overflow() {
         let large = FD9::from_integer(1_000_000_000).unwrap();
@@ -674,6 +1177,345 @@ mod tests {
         assert_eq!(result, Err(NumericError::Overflow));
     }
 
+    #[test]
+    fn test_checked_notional() {
+        type Price = FixedDecimal<9, PriceUnit>;
+        type Quantity = FixedDecimal<9, QuantityUnit>;
+
+        // 50000 * 1e9 = 5e13 is far beyond MAX (~9.2e9 at 9 decimals)
+        let price = Price::from_integer(50_000).unwrap();
+        let qty = Quantity::from_integer(1_000_000_000).unwrap();
+        assert_eq!(
+            price.checked_mul(qty.with_unit()),
+            Err(NumericError::Overflow)
+        );
+
+        let notional = Price::checked_notional(price, qty).unwrap();
+        assert_eq!(notional, 50_000_000_000_000 * Price::SCALE as i128);
+        assert_eq!(
+            Price::notional_to_decimal(notional).unwrap().to_string(),
+            "50000000000000.000000000"
+        );
+
+        // Extremes of the operand range still fit in i128, though not in a Decimal
+        let notional = Price::checked_notional(Price::MAX, Quantity::MIN).unwrap();
+        assert!(notional < i64::MIN as i128);
+        assert_eq!(
+            Price::notional_to_decimal(notional),
+            Err(NumericError::Overflow)
+        );
+
+        // Rounds like checked_mul where both succeed
+        let a = Price::from_raw(1_500_000_001);
+        let b = Quantity::from_raw(333_333_333);
+        assert_eq!(
+            Price::checked_notional(a, b).unwrap(),
+            a.checked_mul(b.with_unit()).unwrap().raw_value() as i128
+        );
+        assert_eq!(
+            Price::checked_notional(-a, b).unwrap(),
+            (-a).checked_mul(b.with_unit()).unwrap().raw_value() as i128
+        );
+    }
+
+    #[test]
+    fn test_notional_scales() {
+        // 2-decimal price, 8-decimal quantity, 4-decimal notional
+        let price: FixedDecimal<2, PriceUnit> = "123.45".parse().unwrap();
+        let qty: FixedDecimal<8, QuantityUnit> = "0.00012345".parse().unwrap();
+        let notional: FixedDecimal<4, NotionalUnit> = price.notional(qty).unwrap();
+        // 123.45 * 0.00012345 = 0.0152399025, half-up at 4 decimals
+        assert_eq!(notional.to_string(), "0.0152");
+
+        let exact: FixedDecimal<10, NotionalUnit> = price.notional(qty).unwrap();
+        assert_eq!(exact.to_string(), "0.0152399025");
+
+        // Widening past the product scale is exact; overflow is reported
+        let wide: FixedDecimal<12, NotionalUnit> = price.notional(qty).unwrap();
+        assert_eq!(wide.to_string(), "0.015239902500");
+        let big = FixedDecimal::<2, PriceUnit>::from_integer(1_000_000_000).unwrap();
+        assert_eq!(
+            big.notional::<8, 12>(FixedDecimal::ONE),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(
+            (-big).notional::<8, 12>(FixedDecimal::ONE),
+            Err(NumericError::Underflow)
+        );
+
+        // The aliases carry the configured scales
+        let notional: Notional = "100.5"
+            .parse::<Price>()
+            .unwrap()
+            .notional(Quantity::from_integer(2).unwrap())
+            .unwrap();
+        assert_eq!(notional, Notional::from_integer(201).unwrap());
+    }
+
+    #[test]
+    fn test_units_cross_explicitly() {
+        let price = Price::from_integer(5).unwrap();
+        let qty = Quantity::from_integer(5).unwrap();
+
+        // `price == qty` and `price + qty` do not compile; crossing units is
+        // spelled out and keeps the raw value
+        let as_qty: FixedDecimal<PRICE_DECIMALS, QuantityUnit> = price.with_unit();
+        assert_eq!(as_qty.raw_value(), price.raw_value());
+        assert_eq!(as_qty.convert::<QTY_DECIMALS>().unwrap(), qty);
+
+        // Different precisions need a conversion as well
+        let cents: FixedDecimal<2, QuantityUnit> = qty.convert().unwrap();
+        assert_eq!(cents.raw_value(), 500);
+        let back: Price = cents.convert::<PRICE_DECIMALS>().unwrap().with_unit();
+        assert_eq!(back, price);
+    }
+
+    #[test]
+    fn test_aliases_follow_configured_precision() {
+        assert_eq!(Price::SCALE, pow10(PRICE_DECIMALS));
+        assert_eq!(Quantity::SCALE, pow10(QTY_DECIMALS));
+        assert_eq!(Notional::SCALE, pow10(NOTIONAL_DECIMALS));
+    }
+
+    #[cfg(all(
+        feature = "price-decimals-2",
+        feature = "qty-decimals-8",
+        not(any(feature = "qty-decimals-4", feature = "qty-decimals-6"))
+    ))]
+    #[test]
+    fn test_two_decimal_prices_eight_decimal_quantities() {
+        let price: Price = "50000.25".parse().unwrap();
+        assert_eq!(price.raw_value(), 5_000_025);
+        assert_eq!(price.to_string(), "50000.25");
+        assert_eq!(
+            "50000.255".parse::<Price>(),
+            Err(NumericError::PrecisionLoss)
+        );
+
+        let qty: Quantity = "0.00000001".parse().unwrap();
+        assert_eq!(qty.raw_value(), 1);
+        assert_eq!(
+            "0.000000001".parse::<Quantity>(),
+            Err(NumericError::PrecisionLoss)
+        );
+
+        // 50000.25 * 0.5 = 25000.125 at the 9-decimal notional scale
+        let notional: Notional = price
+            .notional(Quantity::from_parts(0, 50_000_000).unwrap())
+            .unwrap();
+        assert_eq!(notional.to_string(), "25000.125000000");
+    }
+
+    #[test]
+    fn test_mul_div_exact() {
+        // 150 * 100 / 300 = 50
+        let fill = FD9::from_integer(150).unwrap();
+        let size = FD9::from_integer(100).unwrap();
+        let total = FD9::from_integer(300).unwrap();
+        assert_eq!(
+            size.mul_div(fill, total),
+            Ok(FD9::from_integer(50).unwrap())
+        );
+        assert_eq!(
+            size.mul_div_trunc(fill, total),
+            Ok(FD9::from_integer(50).unwrap())
+        );
+
+        // Intermediate product far beyond i64 still divides back into range
+        assert_eq!(FD9::MAX.mul_div(FD9::MAX, FD9::MAX), Ok(FD9::MAX));
+    }
+
+    #[test]
+    fn test_mul_div_rounding() {
+        // 2 * 1 / 3 raw units = 0.67 -> 1 (half-up), 0 (truncated)
+        let two = FD9::from_raw(2);
+        let one = FD9::from_raw(1);
+        let three = FD9::from_raw(3);
+        assert_eq!(two.mul_div(one, three).unwrap().raw_value(), 1);
+        assert_eq!(two.mul_div_trunc(one, three).unwrap().raw_value(), 0);
+
+        // Exactly half rounds away from zero in both directions
+        assert_eq!(one.mul_div(one, two).unwrap().raw_value(), 1);
+        assert_eq!((-one).mul_div(one, two).unwrap().raw_value(), -1);
+        assert_eq!(one.mul_div(one, -two).unwrap().raw_value(), -1);
+
+        // Below half rounds toward zero
+        assert_eq!(one.mul_div(one, three).unwrap().raw_value(), 0);
+        assert_eq!((-one).mul_div(one, three).unwrap().raw_value(), 0);
+    }
+
+    #[test]
+    fn test_mul_div_errors() {
+        let one = FD9::ONE;
+        assert_eq!(
+            one.mul_div(one, FD9::ZERO),
+            Err(NumericError::DivisionByZero)
+        );
+        assert_eq!(
+            one.mul_div_trunc(one, FD9::ZERO),
+            Err(NumericError::DivisionByZero)
+        );
+        assert_eq!(
+            FD9::MAX.mul_div(FD9::from_integer(2).unwrap(), one),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(
+            FD9::MAX.mul_div(FD9::from_integer(-2).unwrap(), one),
+            Err(NumericError::Underflow)
+        );
+    }
+
+    #[test]
+    fn test_checked_rem() {
+        let d = |s: &str| s.parse::<FD9>().unwrap();
+        let tick = d("0.25");
+        assert_eq!(d("10.75").checked_rem(tick), Ok(FD9::ZERO));
+        assert_eq!(d("10.80").checked_rem(tick), Ok(d("0.05")));
+
+        // The remainder follows the sign of the dividend
+        assert_eq!(d("-10.80").checked_rem(tick), Ok(d("-0.05")));
+        assert_eq!(d("10.80").checked_rem(-tick), Ok(d("0.05")));
+        assert_eq!(FD9::MIN.checked_rem(FD9::from_raw(-1)), Ok(FD9::ZERO));
+
+        assert_eq!(
+            FD9::ONE.checked_rem(FD9::ZERO),
+            Err(NumericError::DivisionByZero)
+        );
+    }
+
+    #[test]
+    fn test_add_ticks() {
+        let d = |s: &str| s.parse::<FD9>().unwrap();
+        let tick = d("0.25");
+        assert_eq!(d("10").add_ticks(3, tick), Ok(d("10.75")));
+        assert_eq!(d("10").add_ticks(-3, tick), Ok(d("9.25")));
+        assert_eq!(d("10").add_ticks(0, tick), Ok(d("10")));
+
+        // Crossing zero either way
+        assert_eq!(d("0.5").add_ticks(-4, tick), Ok(d("-0.5")));
+        assert_eq!(d("-0.5").add_ticks(4, tick), Ok(d("0.5")));
+
+        // Near the ends of the range: the product alone would overflow i64,
+        // but the sum fits
+        let raw = |r: i64| FD9::from_raw(r);
+        assert_eq!(FD9::MAX.add_ticks(-1, raw(1)), Ok(raw(i64::MAX - 1)));
+        assert_eq!(FD9::MIN.add_ticks(i64::MAX, raw(2)), Ok(raw(i64::MAX - 1)));
+        assert_eq!(FD9::MAX.add_ticks(1, raw(1)), Err(NumericError::Overflow));
+        assert_eq!(FD9::MIN.add_ticks(-1, raw(1)), Err(NumericError::Underflow));
+        assert_eq!(
+            FD9::ZERO.add_ticks(i64::MIN, tick),
+            Err(NumericError::Underflow)
+        );
+    }
+
+    #[test]
+    fn test_is_multiple_of() {
+        let d = |s: &str| s.parse::<FD9>().unwrap();
+        let lot = d("0.001");
+        assert!(d("1.234").is_multiple_of(lot));
+        assert!(!d("1.2345").is_multiple_of(lot));
+        assert!(d("-1.234").is_multiple_of(lot));
+        assert!(d("1.234").is_multiple_of(-lot));
+        assert!(FD9::ZERO.is_multiple_of(lot));
+
+        assert!(FD9::ZERO.is_multiple_of(FD9::ZERO));
+        assert!(!FD9::ONE.is_multiple_of(FD9::ZERO));
+    }
+
+    #[test]
+    fn test_percent_of() {
+        let notional = FD9::from_integer(200).unwrap();
+        assert_eq!(
+            notional.percent_of(FD9::from_integer(5).unwrap()),
+            Ok(FD9::from_integer(10).unwrap())
+        );
+        // 0.25% of 200 = 0.5
+        assert_eq!(
+            notional.percent_of(FD9::from_parts(0, 250_000_000).unwrap()),
+            Ok(FD9::from_parts(0, 500_000_000).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_saturating_add() {
+        let one = FD9::from_raw(1);
+        assert_eq!(FD9::MAX.saturating_add(one), FD9::MAX);
+        assert_eq!(FD9::MIN.saturating_add(-one), FD9::MIN);
+        assert_eq!(FD9::from_raw(i64::MAX - 1).saturating_add(one), FD9::MAX);
+        assert_eq!(one.saturating_add(one), FD9::from_raw(2));
+    }
+
+    #[test]
+    fn test_saturating_sub() {
+        let one = FD9::from_raw(1);
+        assert_eq!(FD9::MIN.saturating_sub(one), FD9::MIN);
+        assert_eq!(FD9::MAX.saturating_sub(-one), FD9::MAX);
+        assert_eq!(FD9::from_raw(i64::MIN + 1).saturating_sub(one), FD9::MIN);
+        assert_eq!(one.saturating_sub(one), FD9::ZERO);
+    }
+
+    #[test]
+    fn test_saturating_mul() {
+        let large = FD9::from_integer(1_000_000_000).unwrap();
+        assert_eq!(large.saturating_mul(large), FD9::MAX);
+        assert_eq!(large.saturating_mul(-large), FD9::MIN);
+        assert_eq!((-large).saturating_mul(-large), FD9::MAX);
+
+        // Largest in-range product is returned unchanged
+        assert_eq!(FD9::MAX.saturating_mul(FD9::ONE), FD9::MAX);
+        assert_eq!(FD9::MIN.saturating_mul(FD9::ONE), FD9::MIN);
+
+        let x = FD9::from_parts(1, 500_000_000).unwrap();
+        assert_eq!(x.saturating_mul(x), x.checked_mul(x).unwrap());
+    }
+
+    #[test]
+    fn test_convert_narrowing() {
+        // 12.34 fits in two decimals exactly
+        let exact = FD9::from_parts(12, 340_000_000).unwrap();
+        let cents: FixedDecimal<2> = exact.convert().unwrap();
+        assert_eq!(cents.raw_value(), 1234);
+        assert_eq!(exact.convert_rounding::<2>().unwrap(), cents);
+
+        // 12.345 would lose a digit
+        let lossy = FD9::from_parts(12, 345_000_000).unwrap();
+        assert_eq!(lossy.convert::<2>(), Err(NumericError::PrecisionLoss));
+        assert_eq!(lossy.convert_rounding::<2>().unwrap().raw_value(), 1235);
+        assert_eq!((-lossy).convert_rounding::<2>().unwrap().raw_value(), -1235);
+
+        let below_half = FD9::from_parts(12, 344_999_999).unwrap();
+        assert_eq!(
+            below_half.convert_rounding::<2>().unwrap().raw_value(),
+            1234
+        );
+
+        // Rounding at the edge of the range stays in range
+        assert_eq!(
+            FD9::MAX.convert_rounding::<2>().unwrap().raw_value(),
+            i64::MAX / 10_000_000
+        );
+    }
+
+    #[test]
+    fn test_convert_widening() {
+        let cents = FixedDecimal::<2>::from_raw(1234);
+        let price: FD9 = cents.convert().unwrap();
+        assert_eq!(price, FD9::from_parts(12, 340_000_000).unwrap());
+        assert_eq!(cents.convert_rounding::<9>().unwrap(), price);
+
+        // Same precision is the identity
+        assert_eq!(price.convert::<9>().unwrap(), price);
+
+        assert_eq!(
+            FixedDecimal::<2>::MAX.convert::<9>(),
+            Err(NumericError::Overflow)
+        );
+        assert_eq!(
+            FixedDecimal::<2>::MIN.convert::<9>(),
+            Err(NumericError::Underflow)
+        );
+    }
+
     #[test]
     fn test_comparison() {
         let a = FD9::from_integer(100).unwrap();
@@ -723,10 +1565,51 @@ mod tests {
         assert_eq!(result, Err(NumericError::InvalidInput));
 
         // Too many decimals
-        let result: Result<FD9, _> = "1.1234567890".parse(); // 10 decimals
+        let result: Result<FD9, _> = "1.1234567891".parse(); // 10 decimals
         assert_eq!(result, Err(NumericError::PrecisionLoss));
     }
 
+    #[test]
+    fn test_from_str_sign_grouping_and_exponent() {
+        let parse = |s: &str| s.parse::<FD9>();
+
+        assert_eq!(parse("+42"), Ok(FD9::from_integer(42).unwrap()));
+        assert_eq!(
+            parse("1_000.5"),
+            Ok(FD9::from_parts(1000, 500_000_000).unwrap())
+        );
+        assert_eq!(parse("1.5e3"), Ok(FD9::from_integer(1500).unwrap()));
+        assert_eq!(parse("2.5E-2"), Ok(FD9::from_parts(0, 25_000_000).unwrap()));
+        assert_eq!(parse("-1.5e-3"), Ok(FD9::from_raw(-1_500_000)));
+        assert_eq!(parse("1e+2"), Ok(FD9::from_integer(100).unwrap()));
+        assert_eq!(parse("0.000_001"), Ok(FD9::from_raw(1_000)));
+
+        // Exact scaling: zeros past the precision are fine, anything else is not
+        assert_eq!(parse("1.1234567890"), Ok(FD9::from_raw(1_123_456_789)));
+        assert_eq!(parse("100_000_000e-17"), Ok(FD9::from_raw(1)));
+        assert_eq!(parse("1e-10"), Err(NumericError::PrecisionLoss));
+        assert_eq!(parse("1.5e-9"), Err(NumericError::PrecisionLoss));
+        assert_eq!(parse("0e-50"), Ok(FD9::ZERO));
+        assert_eq!(parse("1e10"), Err(NumericError::Overflow));
+        assert_eq!(parse("1e99999999999999999999"), Err(NumericError::Overflow));
+        assert_eq!(parse(&FD9::MIN.to_string()), Ok(FD9::MIN));
+    }
+
+    #[test]
+    fn test_from_str_malformed() {
+        for input in [
+            "", "+", "-", ".", "e3", "1e", "1e+", "1e1.5", "1e_3", "_1", "1_", "1__0", "1_.5",
+            "1._5", "+-1", "--1", "1.2.3", "1 000", "0x10", "1e3e3", "١",
+        ] {
+            assert_eq!(
+                input.parse::<FD9>(),
+                Err(NumericError::InvalidInput),
+                "{:?}",
+                input
+            );
+        }
+    }
+
     #[test]
     fn test_from_decimal() {
         use rust_decimal::Decimal;
@@ -744,6 +1627,36 @@ mod tests {
         assert_eq!(d.to_string(), "123.456000000");
     }
 
+    #[test]
+    fn test_from_f64() {
+        let x = FD9::from_f64(50000.25).unwrap();
+        assert_eq!(x, FD9::from_parts(50000, 250_000_000).unwrap());
+        assert_eq!(x.to_f64(), 50000.25);
+        assert_eq!(
+            FD9::from_f64(-0.5).unwrap(),
+            -FD9::from_parts(0, 500_000_000).unwrap()
+        );
+        assert_eq!(
+            FD9::from_f64(100.123456789).unwrap().raw_value(),
+            100_123_456_789
+        );
+
+        // Binary-exact ties at the last place round to even
+        assert_eq!(FixedDecimal::<1>::from_f64(0.25).unwrap().raw_value(), 2);
+        assert_eq!(FixedDecimal::<1>::from_f64(0.75).unwrap().raw_value(), 8);
+        assert_eq!(FixedDecimal::<1>::from_f64(-0.25).unwrap().raw_value(), -2);
+        assert_eq!(FixedDecimal::<0>::from_f64(2.5).unwrap().raw_value(), 2);
+        assert_eq!(FixedDecimal::<0>::from_f64(3.5).unwrap().raw_value(), 4);
+
+        assert_eq!(FD9::from_f64(f64::NAN), Err(NumericError::InvalidInput));
+        assert_eq!(
+            FD9::from_f64(f64::INFINITY),
+            Err(NumericError::InvalidInput)
+        );
+        assert_eq!(FD9::from_f64(1e10), Err(NumericError::Overflow));
+        assert_eq!(FD9::from_f64(-1e10), Err(NumericError::Overflow));
+    }
+
     #[test]
     fn test_negation() {
         let x = FD9::from_integer(100).unwrap();
@@ -780,4 +1693,43 @@ mod tests {
         assert_eq!(one.checked_sub(one).unwrap(), zero);
         assert_eq!(zero.checked_mul(one).unwrap(), zero);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_json_roundtrip() {
+        for value in [
+            FD9::from_parts(123, 456_000_000).unwrap(),
+            FD9::from_parts(-7, 1).unwrap(),
+            FD9::ZERO,
+            FD9::MAX,
+            FD9::MIN,
+        ] {
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(json, format!("\"{}\"", value));
+            let decoded: FD9 = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, value);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_binary_roundtrip() {
+        for value in [
+            FD9::from_parts(123, 456_000_000).unwrap(),
+            FD9::from_parts(-7, 1).unwrap(),
+            FD9::ZERO,
+            FD9::MAX,
+            FD9::MIN,
+        ] {
+            let mut bytes = Vec::new();
+            ciborium::into_writer(&value, &mut bytes).unwrap();
+
+            // Encoded as the raw i64, not as a string
+            let raw: i64 = ciborium::from_reader(bytes.as_slice()).unwrap();
+            assert_eq!(raw, value.raw_value());
+
+            let decoded: FD9 = ciborium::from_reader(bytes.as_slice()).unwrap();
+            assert_eq!(decoded, value);
+        }
+    }
 }
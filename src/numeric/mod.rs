@@ -6,7 +6,8 @@
 // This module provides:
 // - FixedDecimal<D>: Fixed-point decimal with compile-time precision
 // - NumericError: Error types for arithmetic operations
-// - Price/Quantity type aliases for common use cases
+// - RoundingMode: Rounding rules for arithmetic that drops precision
+// - Price/Quantity/Notional aliases: unit-tagged FixedDecimals that do not mix
 //
 // Design principles:
 // - No floating-point operations
@@ -18,4 +19,7 @@ mod errors;
 mod fixed_decimal;
 
 pub use errors::{NumericError, NumericResult};
-pub use fixed_decimal::{FixedDecimal, Price, Quantity};
+pub use fixed_decimal::{
+    FixedDecimal, Notional, NotionalUnit, Price, PriceUnit, Quantity, QuantityUnit, RoundingMode,
+    Unitless, NOTIONAL_DECIMALS, PRICE_DECIMALS, QTY_DECIMALS,
+};
@@ -37,5 +37,7 @@ pub use simd::Avx2Matcher;
 pub use simd::Avx512Matcher;
 
 // Re-export NUMA types at platform level
-pub use numa::{get_available_cores, pin_current_thread_to_core, pin_current_thread_to_node};
+pub use numa::{
+    get_available_cores, pin_current_thread_to_core, pin_current_thread_to_node, recommended_core,
+};
 pub use numa::{NumaNode, NumaTopology};
@@ -264,6 +264,16 @@ impl fmt::Display for NumaTopology {
     }
 }
 
+/// Pick a core for a single matching engine thread.
+///
+/// Uses the second CPU of the first node, leaving CPU 0 to the OS
+/// (interrupts and housekeeping tend to land there). Single-CPU nodes
+/// get their only CPU.
+pub fn recommended_core(topology: &NumaTopology) -> Option<usize> {
+    let node = topology.nodes().first()?;
+    node.cpu_ids.get(1).or(node.cpu_ids.first()).copied()
+}
+
 /// Parse a CPU list string like "0-3,8-11" into a Vec of CPU IDs.
 #[cfg(target_os = "linux")]
 fn parse_cpu_list(s: &str) -> Vec<usize> {
@@ -442,6 +452,16 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_recommended_core() {
+        let topology = NumaTopology::detect();
+        let core = recommended_core(&topology).unwrap();
+        assert!(topology.node_for_cpu(core).is_some());
+        if topology.total_cpus() > 1 {
+            assert_ne!(core, 0);
+        }
+    }
+
     #[test]
     fn test_recommend_zero_workers() {
         let topology = NumaTopology::detect();
@@ -40,6 +40,14 @@ impl SimdMatcher for Avx2Matcher {
         unsafe { avx2_find_crossing_sell(sell_price, bid_prices) }
     }
 
+    fn count_crossing_buy_prices(&self, buy_price: i64, ask_prices: &[i64]) -> usize {
+        unsafe { avx2_count_crossing_buy(buy_price, ask_prices) }
+    }
+
+    fn count_crossing_sell_prices(&self, sell_price: i64, bid_prices: &[i64]) -> usize {
+        unsafe { avx2_count_crossing_sell(sell_price, bid_prices) }
+    }
+
     fn name(&self) -> &'static str {
         "AVX2"
     }
@@ -160,6 +168,68 @@ unsafe fn avx2_find_crossing_sell(sell_price: i64, bid_prices: &[i64]) -> Vec<us
     crossing_indices
 }
 
+/// AVX2-accelerated count of asks a buy order crosses.
+///
+/// Popcounts the comparison mask instead of collecting indices.
+///
+/// # Safety
+/// Caller must ensure AVX2 is available.
+#[target_feature(enable = "avx2")]
+unsafe fn avx2_count_crossing_buy(buy_price: i64, ask_prices: &[i64]) -> usize {
+    use std::arch::x86_64::*;
+
+    let mut count = 0;
+    let buy_vec = _mm256_set1_epi64x(buy_price);
+
+    let chunks = ask_prices.chunks_exact(4);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let ask_vec = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+
+        // Lanes where ask > buy do NOT cross
+        let gt = _mm256_cmpgt_epi64(ask_vec, buy_vec);
+        let mask = _mm256_movemask_pd(_mm256_castsi256_pd(gt)) as u32;
+        count += 4 - mask.count_ones() as usize;
+    }
+
+    count
+        + remainder
+            .iter()
+            .filter(|&&ask_price| buy_price >= ask_price)
+            .count()
+}
+
+/// AVX2-accelerated count of bids a sell order crosses.
+///
+/// # Safety
+/// Caller must ensure AVX2 is available.
+#[target_feature(enable = "avx2")]
+unsafe fn avx2_count_crossing_sell(sell_price: i64, bid_prices: &[i64]) -> usize {
+    use std::arch::x86_64::*;
+
+    let mut count = 0;
+    let sell_vec = _mm256_set1_epi64x(sell_price);
+
+    let chunks = bid_prices.chunks_exact(4);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let bid_vec = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+
+        // Lanes where sell > bid do NOT cross
+        let gt = _mm256_cmpgt_epi64(sell_vec, bid_vec);
+        let mask = _mm256_movemask_pd(_mm256_castsi256_pd(gt)) as u32;
+        count += 4 - mask.count_ones() as usize;
+    }
+
+    count
+        + remainder
+            .iter()
+            .filter(|&&bid_price| sell_price <= bid_price)
+            .count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,6 +354,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_avx2_count_matches_find() {
+        if skip_if_no_avx2() {
+            return;
+        }
+
+        let matcher = Avx2Matcher::new();
+
+        for size in [0, 1, 3, 4, 5, 8, 9, 17] {
+            let prices: Vec<i64> = (0..size).map(|i| 100 + i * 10).collect();
+            for price in [0, 100, 155, 1000] {
+                assert_eq!(
+                    matcher.count_crossing_buy_prices(price, &prices),
+                    matcher.find_crossing_buy_prices(price, &prices).len(),
+                    "buy mismatch for size {} price {}",
+                    size,
+                    price
+                );
+                assert_eq!(
+                    matcher.count_crossing_sell_prices(price, &prices),
+                    matcher.find_crossing_sell_prices(price, &prices).len(),
+                    "sell mismatch for size {} price {}",
+                    size,
+                    price
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_avx2_name() {
         if skip_if_no_avx2() {
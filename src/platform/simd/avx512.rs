@@ -49,6 +49,14 @@ impl SimdMatcher for Avx512Matcher {
         unsafe { avx512_find_crossing_sell(sell_price, bid_prices) }
     }
 
+    fn count_crossing_buy_prices(&self, buy_price: i64, ask_prices: &[i64]) -> usize {
+        unsafe { avx512_count_crossing_buy(buy_price, ask_prices) }
+    }
+
+    fn count_crossing_sell_prices(&self, sell_price: i64, bid_prices: &[i64]) -> usize {
+        unsafe { avx512_count_crossing_sell(sell_price, bid_prices) }
+    }
+
     fn name(&self) -> &'static str {
         "AVX-512"
     }
@@ -74,7 +82,7 @@ unsafe fn avx512_find_crossing_buy(buy_price: i64, ask_prices: &[i64]) -> Vec<us
 
     for (chunk_idx, chunk) in chunks.enumerate() {
         // Load 8 ask prices
-        let ask_vec = _mm512_loadu_si512(chunk.as_ptr() as *const i32);
+        let ask_vec = _mm512_loadu_si512(chunk.as_ptr() as *const __m512i);
 
         // AVX-512 has native >= comparison returning a mask
         // _mm512_cmpge_epi64_mask: returns 8-bit mask where bit i is set if a[i] >= b[i]
@@ -120,7 +128,7 @@ unsafe fn avx512_find_crossing_sell(sell_price: i64, bid_prices: &[i64]) -> Vec<
 
     for (chunk_idx, chunk) in chunks.enumerate() {
         // Load 8 bid prices
-        let bid_vec = _mm512_loadu_si512(chunk.as_ptr() as *const i32);
+        let bid_vec = _mm512_loadu_si512(chunk.as_ptr() as *const __m512i);
 
         // _mm512_cmple_epi64_mask: returns 8-bit mask where bit i is set if a[i] <= b[i]
         let mask = _mm512_cmple_epi64_mask(sell_vec, bid_vec);
@@ -145,6 +153,62 @@ unsafe fn avx512_find_crossing_sell(sell_price: i64, bid_prices: &[i64]) -> Vec<
     crossing_indices
 }
 
+/// AVX-512 accelerated count of asks a buy order crosses.
+///
+/// Popcounts the comparison mask instead of collecting indices.
+///
+/// # Safety
+/// Caller must ensure AVX-512F is available.
+#[target_feature(enable = "avx512f")]
+unsafe fn avx512_count_crossing_buy(buy_price: i64, ask_prices: &[i64]) -> usize {
+    use std::arch::x86_64::*;
+
+    let mut count = 0;
+    let buy_vec = _mm512_set1_epi64(buy_price);
+
+    let chunks = ask_prices.chunks_exact(8);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let ask_vec = _mm512_loadu_si512(chunk.as_ptr() as *const __m512i);
+        let mask = _mm512_cmpge_epi64_mask(buy_vec, ask_vec);
+        count += mask.count_ones() as usize;
+    }
+
+    count
+        + remainder
+            .iter()
+            .filter(|&&ask_price| buy_price >= ask_price)
+            .count()
+}
+
+/// AVX-512 accelerated count of bids a sell order crosses.
+///
+/// # Safety
+/// Caller must ensure AVX-512F is available.
+#[target_feature(enable = "avx512f")]
+unsafe fn avx512_count_crossing_sell(sell_price: i64, bid_prices: &[i64]) -> usize {
+    use std::arch::x86_64::*;
+
+    let mut count = 0;
+    let sell_vec = _mm512_set1_epi64(sell_price);
+
+    let chunks = bid_prices.chunks_exact(8);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let bid_vec = _mm512_loadu_si512(chunk.as_ptr() as *const __m512i);
+        let mask = _mm512_cmple_epi64_mask(sell_vec, bid_vec);
+        count += mask.count_ones() as usize;
+    }
+
+    count
+        + remainder
+            .iter()
+            .filter(|&&bid_price| sell_price <= bid_price)
+            .count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,6 +336,35 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_avx512_count_matches_find() {
+        if skip_if_no_avx512() {
+            return;
+        }
+
+        let matcher = Avx512Matcher::new();
+
+        for size in [0, 1, 7, 8, 9, 16, 17, 25] {
+            let prices: Vec<i64> = (0..size).map(|i| 100 + i * 10).collect();
+            for price in [0, 100, 155, 1000] {
+                assert_eq!(
+                    matcher.count_crossing_buy_prices(price, &prices),
+                    matcher.find_crossing_buy_prices(price, &prices).len(),
+                    "buy mismatch for size {} price {}",
+                    size,
+                    price
+                );
+                assert_eq!(
+                    matcher.count_crossing_sell_prices(price, &prices),
+                    matcher.find_crossing_sell_prices(price, &prices).len(),
+                    "sell mismatch for size {} price {}",
+                    size,
+                    price
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_avx512_name() {
         if skip_if_no_avx512() {
@@ -35,6 +35,9 @@ mod avx2;
 #[cfg(all(target_arch = "x86_64", feature = "avx512"))]
 mod avx512;
 
+#[cfg(test)]
+mod parity;
+
 // Public exports
 pub use detector::{
     create_scalar_matcher, create_simd_matcher, Architecture, CpuCapabilities, SimdLevel,
@@ -29,6 +29,14 @@ impl SimdMatcher for NeonMatcher {
         unsafe { neon_find_crossing_sell(sell_price, bid_prices) }
     }
 
+    fn count_crossing_buy_prices(&self, buy_price: i64, ask_prices: &[i64]) -> usize {
+        unsafe { neon_count_crossing_buy(buy_price, ask_prices) }
+    }
+
+    fn count_crossing_sell_prices(&self, sell_price: i64, bid_prices: &[i64]) -> usize {
+        unsafe { neon_count_crossing_sell(sell_price, bid_prices) }
+    }
+
     fn name(&self) -> &'static str {
         "NEON"
     }
@@ -131,6 +139,63 @@ unsafe fn neon_find_crossing_sell(sell_price: i64, bid_prices: &[i64]) -> Vec<us
     crossing_indices
 }
 
+/// NEON-accelerated count of asks a buy order crosses.
+///
+/// Each all-ones comparison lane is shifted down to 1 and summed across
+/// iterations; the two lanes are added once at the end.
+///
+/// # Safety
+/// This function uses NEON intrinsics which are always available on aarch64.
+#[inline]
+unsafe fn neon_count_crossing_buy(buy_price: i64, ask_prices: &[i64]) -> usize {
+    use std::arch::aarch64::*;
+
+    let buy_vec = vdupq_n_s64(buy_price);
+    let mut lane_counts = vdupq_n_u64(0);
+
+    let chunks = ask_prices.chunks_exact(2);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let ask_vec = vld1q_s64(chunk.as_ptr());
+        let cmp = vcgeq_s64(buy_vec, ask_vec);
+        lane_counts = vaddq_u64(lane_counts, vshrq_n_u64::<63>(cmp));
+    }
+
+    vaddvq_u64(lane_counts) as usize
+        + remainder
+            .iter()
+            .filter(|&&ask_price| buy_price >= ask_price)
+            .count()
+}
+
+/// NEON-accelerated count of bids a sell order crosses.
+///
+/// # Safety
+/// This function uses NEON intrinsics which are always available on aarch64.
+#[inline]
+unsafe fn neon_count_crossing_sell(sell_price: i64, bid_prices: &[i64]) -> usize {
+    use std::arch::aarch64::*;
+
+    let sell_vec = vdupq_n_s64(sell_price);
+    let mut lane_counts = vdupq_n_u64(0);
+
+    let chunks = bid_prices.chunks_exact(2);
+    let remainder = chunks.remainder();
+
+    for chunk in chunks {
+        let bid_vec = vld1q_s64(chunk.as_ptr());
+        let cmp = vcleq_s64(sell_vec, bid_vec);
+        lane_counts = vaddq_u64(lane_counts, vshrq_n_u64::<63>(cmp));
+    }
+
+    vaddvq_u64(lane_counts) as usize
+        + remainder
+            .iter()
+            .filter(|&&bid_price| sell_price <= bid_price)
+            .count()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -219,6 +284,31 @@ mod tests {
         assert_eq!(result, vec![0, 1]);
     }
 
+    #[test]
+    fn test_neon_count_matches_find() {
+        let matcher = NeonMatcher::new();
+
+        for size in [0, 1, 2, 3, 4, 5, 17] {
+            let prices: Vec<i64> = (0..size).map(|i| 100 + i * 10).collect();
+            for price in [0, 100, 155, 1000] {
+                assert_eq!(
+                    matcher.count_crossing_buy_prices(price, &prices),
+                    matcher.find_crossing_buy_prices(price, &prices).len(),
+                    "buy mismatch for size {} price {}",
+                    size,
+                    price
+                );
+                assert_eq!(
+                    matcher.count_crossing_sell_prices(price, &prices),
+                    matcher.find_crossing_sell_prices(price, &prices).len(),
+                    "sell mismatch for size {} price {}",
+                    size,
+                    price
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_neon_name() {
         let matcher = NeonMatcher::new();
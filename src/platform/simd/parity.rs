@@ -0,0 +1,102 @@
+// ============================================================================
+// Cross-Backend Parity
+// Every SIMD backend available here must agree with the scalar matcher
+// ============================================================================
+
+use super::scalar::ScalarMatcher;
+use super::traits::SimdMatcher;
+
+/// Widest vector is AVX-512 with 8 lanes; sizes around each lane count
+/// exercise the remainder loops of every backend
+const EDGE_SIZES: [usize; 12] = [0, 1, 2, 3, 4, 5, 7, 8, 9, 15, 16, 17];
+
+/// Values where a signed comparison done with the wrong width or sign trips
+const EXTREMES: [i64; 7] = [i64::MIN, i64::MIN + 1, -1, 0, 1, i64::MAX - 1, i64::MAX];
+
+/// Vector backends usable on this CPU (scalar is the reference, not listed)
+fn backends() -> Vec<Box<dyn SimdMatcher>> {
+    #[allow(unused_mut)]
+    let mut backends: Vec<Box<dyn SimdMatcher>> = Vec::new();
+
+    #[cfg(target_arch = "x86_64")]
+    if super::avx2::Avx2Matcher::is_available() {
+        backends.push(Box::new(super::avx2::Avx2Matcher::new()));
+    }
+
+    #[cfg(all(target_arch = "x86_64", feature = "avx512"))]
+    if super::avx512::Avx512Matcher::is_available() {
+        backends.push(Box::new(super::avx512::Avx512Matcher::new()));
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    backends.push(Box::new(super::neon::NeonMatcher::new()));
+
+    backends
+}
+
+/// Assert every backend answers all four queries exactly like scalar
+fn assert_parity(price: i64, prices: &[i64]) {
+    let scalar = ScalarMatcher::new();
+    let buy = scalar.find_crossing_buy_prices(price, prices);
+    let sell = scalar.find_crossing_sell_prices(price, prices);
+
+    for backend in backends() {
+        let name = backend.name();
+        assert_eq!(
+            backend.find_crossing_buy_prices(price, prices),
+            buy,
+            "{name} buy @ {price} over {prices:?}"
+        );
+        assert_eq!(
+            backend.find_crossing_sell_prices(price, prices),
+            sell,
+            "{name} sell @ {price} over {prices:?}"
+        );
+        assert_eq!(
+            backend.count_crossing_buy_prices(price, prices),
+            buy.len(),
+            "{name} buy count @ {price} over {prices:?}"
+        );
+        assert_eq!(
+            backend.count_crossing_sell_prices(price, prices),
+            sell.len(),
+            "{name} sell count @ {price} over {prices:?}"
+        );
+    }
+}
+
+#[test]
+fn test_edge_sizes_and_extremes() {
+    for size in EDGE_SIZES {
+        // Cycle the extremes so each lane position sees each value
+        for offset in 0..EXTREMES.len() {
+            let prices: Vec<i64> = (0..size)
+                .map(|i| EXTREMES[(i + offset) % EXTREMES.len()])
+                .collect();
+            for price in EXTREMES {
+                assert_parity(price, &prices);
+            }
+        }
+    }
+}
+
+proptest::proptest! {
+    #[test]
+    fn prop_backends_match_scalar(
+        prices in proptest::collection::vec(
+            proptest::prop_oneof![
+                proptest::sample::select(EXTREMES.to_vec()),
+                proptest::num::i64::ANY,
+                -1_000i64..1_000,
+            ],
+            0..40,
+        ),
+        price in proptest::prop_oneof![
+            proptest::sample::select(EXTREMES.to_vec()),
+            proptest::num::i64::ANY,
+            -1_000i64..1_000,
+        ],
+    ) {
+        assert_parity(price, &prices);
+    }
+}
@@ -48,6 +48,20 @@ impl SimdMatcher for ScalarMatcher {
             .collect()
     }
 
+    fn count_crossing_buy_prices(&self, buy_price: i64, ask_prices: &[i64]) -> usize {
+        ask_prices
+            .iter()
+            .filter(|&&ask_price| buy_price >= ask_price)
+            .count()
+    }
+
+    fn count_crossing_sell_prices(&self, sell_price: i64, bid_prices: &[i64]) -> usize {
+        bid_prices
+            .iter()
+            .filter(|&&bid_price| sell_price <= bid_price)
+            .count()
+    }
+
     fn name(&self) -> &'static str {
         "Scalar"
     }
@@ -114,6 +128,24 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_scalar_count_matches_find() {
+        let matcher = ScalarMatcher::new();
+        let prices: Vec<i64> = (0..17).map(|i| 100 + i * 10).collect();
+
+        for price in [0, 100, 155, 260, 1000] {
+            assert_eq!(
+                matcher.count_crossing_buy_prices(price, &prices),
+                matcher.find_crossing_buy_prices(price, &prices).len()
+            );
+            assert_eq!(
+                matcher.count_crossing_sell_prices(price, &prices),
+                matcher.find_crossing_sell_prices(price, &prices).len()
+            );
+        }
+        assert_eq!(matcher.count_crossing_buy_prices(100, &[]), 0);
+    }
+
     #[test]
     fn test_scalar_name() {
         let matcher = ScalarMatcher::new();
@@ -43,6 +43,16 @@ pub trait SimdMatcher: Send + Sync {
     /// Vector of indices where crossing is possible
     fn find_crossing_sell_prices(&self, sell_price: i64, bid_prices: &[i64]) -> Vec<usize>;
 
+    /// Count prices where buy_price >= ask_prices[i], without allocating.
+    ///
+    /// Equivalent to `find_crossing_buy_prices(..).len()`.
+    fn count_crossing_buy_prices(&self, buy_price: i64, ask_prices: &[i64]) -> usize;
+
+    /// Count prices where sell_price <= bid_prices[i], without allocating.
+    ///
+    /// Equivalent to `find_crossing_sell_prices(..).len()`.
+    fn count_crossing_sell_prices(&self, sell_price: i64, bid_prices: &[i64]) -> usize;
+
     /// Get the name of this SIMD implementation.
     ///
     /// Used for logging, debugging, and benchmarking.
@@ -73,6 +83,14 @@ mod tests {
                 .collect()
         }
 
+        fn count_crossing_buy_prices(&self, buy_price: i64, ask_prices: &[i64]) -> usize {
+            ask_prices.iter().filter(|&&ask| buy_price >= ask).count()
+        }
+
+        fn count_crossing_sell_prices(&self, sell_price: i64, bid_prices: &[i64]) -> usize {
+            bid_prices.iter().filter(|&&bid| sell_price <= bid).count()
+        }
+
         fn name(&self) -> &'static str {
             "Mock"
         }